@@ -0,0 +1,139 @@
+//! Encoding of full-size **SJOG** and **IJOG** frames.
+//!
+//! `build` measures the whole builder. `insert` replays the former encoding, which pushed the data
+//! before inserting both checksums at indices 5 and 6, and `reserved` the current one, which
+//! writes the checksums right after the command, so that both are compared on the same payload.
+
+extern crate criterion;
+extern crate drs_0x01;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use drs_0x01::builder::{HerkulexMessage, MessageBuilder};
+use drs_0x01::checksum::{checksum1, checksum2};
+use drs_0x01::{JogColor, JogMode};
+
+const JOGS: u8 = 10;
+
+fn full_sjog() -> HerkulexMessage {
+    let mut message = MessageBuilder::new_with_id(0xFE).s_jog(
+        60,
+        JogMode::Normal { position: 512 },
+        JogColor::Green,
+        0,
+    );
+    for id in 1..JOGS {
+        message
+            .s_jog(JogMode::Normal { position: 512 }, JogColor::Blue, id)
+            .unwrap();
+    }
+    message.build()
+}
+
+fn full_ijog() -> HerkulexMessage {
+    let mut message = MessageBuilder::new_with_id(0xFE).i_jog(
+        60,
+        JogMode::Normal { position: 512 },
+        JogColor::Green,
+        0,
+    );
+    for id in 1..JOGS {
+        message
+            .s_jog(JogMode::Normal { position: 512 }, JogColor::Blue, 60, id)
+            .unwrap();
+    }
+    message.build()
+}
+
+fn encode_with_insert(size: u8, pid: u8, cmd: u8, data: &[u8]) -> HerkulexMessage {
+    let chk1 = checksum1(&[size, pid, cmd]) ^ checksum1(data);
+    let mut result = HerkulexMessage::new();
+    result.push(0xFF);
+    result.push(0xFF);
+    result.push(size);
+    result.push(pid);
+    result.push(cmd);
+    for byte in data {
+        result.push(*byte);
+    }
+    result.insert(5, chk1);
+    result.insert(6, checksum2(chk1));
+    result
+}
+
+fn encode_reserved(size: u8, pid: u8, cmd: u8, data: &[u8]) -> HerkulexMessage {
+    let chk1 = checksum1(&[size, pid, cmd]) ^ checksum1(data);
+    let mut result = HerkulexMessage::new();
+    result.extend(
+        [0xFF, 0xFF, size, pid, cmd, chk1, checksum2(chk1)]
+            .iter()
+            .cloned(),
+    );
+    result.extend(data.iter().cloned());
+    result
+}
+
+fn encode(c: &mut Criterion) {
+    let sjog = full_sjog();
+    let ijog = full_ijog();
+    assert_eq!(
+        encode_with_insert(sjog[2], sjog[3], sjog[4], &sjog[7..]),
+        sjog
+    );
+    assert_eq!(encode_reserved(sjog[2], sjog[3], sjog[4], &sjog[7..]), sjog);
+    assert_eq!(
+        encode_with_insert(ijog[2], ijog[3], ijog[4], &ijog[7..]),
+        ijog
+    );
+    assert_eq!(encode_reserved(ijog[2], ijog[3], ijog[4], &ijog[7..]), ijog);
+
+    let mut group = c.benchmark_group("sjog");
+    group.bench_function("build", |b| b.iter(|| black_box(full_sjog())));
+    group.bench_function("reserved", |b| {
+        b.iter(|| {
+            encode_reserved(
+                black_box(sjog[2]),
+                black_box(sjog[3]),
+                black_box(sjog[4]),
+                black_box(&sjog[7..]),
+            )
+        })
+    });
+    group.bench_function("insert", |b| {
+        b.iter(|| {
+            encode_with_insert(
+                black_box(sjog[2]),
+                black_box(sjog[3]),
+                black_box(sjog[4]),
+                black_box(&sjog[7..]),
+            )
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("ijog");
+    group.bench_function("build", |b| b.iter(|| black_box(full_ijog())));
+    group.bench_function("reserved", |b| {
+        b.iter(|| {
+            encode_reserved(
+                black_box(ijog[2]),
+                black_box(ijog[3]),
+                black_box(ijog[4]),
+                black_box(&ijog[7..]),
+            )
+        })
+    });
+    group.bench_function("insert", |b| {
+        b.iter(|| {
+            encode_with_insert(
+                black_box(ijog[2]),
+                black_box(ijog[3]),
+                black_box(ijog[4]),
+                black_box(&ijog[7..]),
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, encode);
+criterion_main!(benches);
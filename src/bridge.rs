@@ -0,0 +1,318 @@
+//! A transport-agnostic container to tunnel the servomotor bus over another link.
+//!
+//! Gateways which carry the bus over UDP, CAN or USB bulk transfers wrap every request and every
+//! answer in an envelope, laid out as `[len][version][kind][payload...][crc_lo][crc_hi]` :
+//!
+//! * `len` is the length of the payload,
+//! * `version` is [`VERSION`](constant.VERSION.html),
+//! * `kind` is 0x01 for a request, whose payload is the Herkulex frame, and 0x02 for an answer,
+//!   whose payload is `[pid][cmd][status_error][status_detail]` followed by
+//!   `[addr][data_len][data...]` for the answers to reads,
+//! * the CRC is the CRC-16/CCITT-FALSE of every previous byte of the envelope.
+//!
+//! The envelopes are self-delimited, several of them can be sent in the same datagram.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::bridge::{self, Payload};
+//! use drs_0x01::Servo;
+//!
+//! let message = Servo::new(0x40).reboot();
+//! let envelope = bridge::encode_message(&message);
+//! // Send the envelope over UDP, then on the other side of the link ...
+//! let (payload, len) = bridge::decode(&envelope).unwrap();
+//! assert_eq!(payload, Payload::Message(message));
+//! assert_eq!(len, envelope.len());
+//! ```
+
+use arrayvec::ArrayVec;
+use try_from::TryFrom;
+
+use addr::{EEPReadData, ReadableEEPAddr};
+use addr::{RamReadData, ReadableRamAddr};
+use builder::HerkulexMessage;
+use frame::MAX_READ_LEN;
+use reader::{ACKPacket, Command, StatusDetail, StatusError};
+
+/// The version of the envelopes produced by this module.
+pub const VERSION: u8 = 1;
+
+/// The number of bytes of an envelope around its payload.
+pub const ENVELOPE_OVERHEAD: usize = 5;
+
+const KIND_MESSAGE: u8 = 0x01;
+const KIND_ACK: u8 = 0x02;
+
+/// An envelope, ready to be sent over the link.
+pub type Envelope = ArrayVec<[u8; 160]>;
+
+/// The content of an envelope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Payload {
+    /// A request sent to the servomotors
+    Message(HerkulexMessage),
+    /// An answer of a servomotor
+    Ack(ACKPacket),
+}
+
+/// The error returned when decoding an envelope.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BridgeError {
+    /// The bytes end before the envelope.
+    Incomplete,
+    /// The envelope was produced by an unsupported version of this module.
+    UnsupportedVersion(u8),
+    /// The envelope holds an unknown kind of payload.
+    UnknownKind(u8),
+    /// The CRC of the envelope is invalid.
+    InvalidCrc,
+    /// The payload can not be decoded.
+    InvalidPayload,
+}
+
+/// A link over which envelopes are published.
+pub trait ByteSink {
+    /// The error returned by the underlying link
+    type Error;
+
+    /// Send every byte of `bytes`.
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl<S: ByteSink + ?Sized> ByteSink for &mut S {
+    type Error = S::Error;
+
+    fn send(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        (**self).send(bytes)
+    }
+}
+
+/// Compute the CRC-16/CCITT-FALSE of `bytes`.
+pub fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xFFFF, |crc, &byte| {
+        (0..8).fold(crc ^ u16::from(byte) << 8, |crc, _| {
+            if crc & 0x8000 != 0 {
+                crc << 1 ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+fn envelope(kind: u8, payload: &[u8]) -> Envelope {
+    let mut envelope = Envelope::new();
+    envelope.extend([payload.len() as u8, VERSION, kind].iter().cloned());
+    envelope.extend(payload.iter().cloned());
+    let crc = crc16(&envelope);
+    envelope.extend([crc as u8, (crc >> 8) as u8].iter().cloned());
+    envelope
+}
+
+/// Wrap the request `message` in an envelope.
+pub fn encode_message(message: &HerkulexMessage) -> Envelope {
+    envelope(KIND_MESSAGE, message)
+}
+
+/// Wrap the answer `packet` in an envelope.
+pub fn encode_ack(packet: &ACKPacket) -> Envelope {
+    let mut payload = ArrayVec::<[u8; 8]>::new();
+    payload.extend(
+        [
+            packet.pid,
+            u8::from(packet.cmd),
+            packet.error_raw,
+            packet.detail_raw,
+        ]
+        .iter()
+        .cloned(),
+    );
+    let read = match packet.cmd {
+        Command::EEPRead { data } => Some((u8::from(data.addr), data.data_len, data.data)),
+        Command::RamRead { data } => Some((u8::from(data.addr), data.data_len, data.data)),
+        _ => None,
+    };
+    if let Some((addr, data_len, data)) = read {
+        payload.extend([addr, data_len].iter().cloned());
+        payload.extend(data.iter().take(usize::from(data_len)).cloned());
+    }
+    envelope(KIND_ACK, &payload)
+}
+
+fn decode_ack(payload: &[u8]) -> Option<ACKPacket> {
+    if payload.len() < 4 {
+        return None;
+    }
+    let (status, read) = payload.split_at(4);
+    let (pid, cmd, error_raw, detail_raw) = (status[0], status[1], status[2], status[3]);
+    let mut data = [0; MAX_READ_LEN];
+    let read = match *read {
+        [] => None,
+        [addr, data_len, ref bytes @ ..] if bytes.len() == usize::from(data_len) => {
+            data.get_mut(..bytes.len())?.copy_from_slice(bytes);
+            Some((addr, data_len))
+        }
+        _ => return None,
+    };
+    let cmd = match (cmd, read) {
+        (0x41, None) => Command::EEPWrite,
+        (0x42, Some((addr, data_len))) => Command::EEPRead {
+            data: EEPReadData {
+                addr: ReadableEEPAddr::try_from(addr).ok()?,
+                data_len,
+                data,
+            },
+        },
+        (0x43, None) => Command::RamWrite,
+        (0x44, Some((addr, data_len))) => Command::RamRead {
+            data: RamReadData {
+                addr: ReadableRamAddr::try_from(addr).ok()?,
+                data_len,
+                data,
+            },
+        },
+        (0x45, None) => Command::IJog,
+        (0x46, None) => Command::SJog,
+        (0x47, None) => Command::Stat,
+        (0x48, None) => Command::Rollback,
+        (0x49, None) => Command::Reboot,
+        _ => return None,
+    };
+    Some(ACKPacket {
+        pid,
+        cmd,
+        error: StatusError::from_bits(error_raw),
+        detail: StatusDetail::from_bits(detail_raw),
+        error_raw,
+        detail_raw,
+    })
+}
+
+/// Decode the envelope at the start of `bytes`, and return its payload with the length of the
+/// envelope, so that the next envelope starts right after it.
+///
+/// # Errors
+///
+/// Return [`BridgeError::Incomplete`](enum.BridgeError.html) if `bytes` is shorter than the
+/// envelope, and the other errors if the envelope is invalid.
+pub fn decode(bytes: &[u8]) -> Result<(Payload, usize), BridgeError> {
+    let len = match bytes.first() {
+        Some(&len) => usize::from(len) + ENVELOPE_OVERHEAD,
+        None => return Err(BridgeError::Incomplete),
+    };
+    let envelope = bytes.get(..len).ok_or(BridgeError::Incomplete)?;
+    let (content, crc) = envelope.split_at(len - 2);
+    if crc16(content) != u16::from(crc[0]) | u16::from(crc[1]) << 8 {
+        return Err(BridgeError::InvalidCrc);
+    }
+    if content[1] != VERSION {
+        return Err(BridgeError::UnsupportedVersion(content[1]));
+    }
+    let payload = &content[3..];
+    let payload = match content[2] {
+        KIND_MESSAGE => {
+            let mut message = HerkulexMessage::new();
+            if payload.len() > message.capacity() {
+                return Err(BridgeError::InvalidPayload);
+            }
+            message.extend(payload.iter().cloned());
+            Payload::Message(message)
+        }
+        KIND_ACK => Payload::Ack(decode_ack(payload).ok_or(BridgeError::InvalidPayload)?),
+        kind => return Err(BridgeError::UnknownKind(kind)),
+    };
+    Ok((payload, len))
+}
+
+/// Publish the request `message` over `sink`.
+pub fn publish_message<S: ByteSink>(
+    sink: &mut S,
+    message: &HerkulexMessage,
+) -> Result<(), S::Error> {
+    sink.send(&encode_message(message))
+}
+
+/// Publish the answer `packet` over `sink`.
+pub fn publish_ack<S: ByteSink>(sink: &mut S, packet: &ACKPacket) -> Result<(), S::Error> {
+    sink.send(&encode_ack(packet))
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{RamReadData, ReadableRamAddr};
+    use bridge::{self, crc16, BridgeError, ByteSink, Payload};
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+    use servo::Servo;
+    use std::vec::Vec;
+
+    impl ByteSink for Vec<u8> {
+        type Error = ();
+
+        fn send(&mut self, bytes: &[u8]) -> Result<(), ()> {
+            self.extend_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn envelopes() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+
+        let message = Servo::new(0x40).reboot();
+        let mut packet = ACKPacket::new(
+            0x40,
+            Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::CalibratedPosition,
+                    data_len: 2,
+                    data: [0x00, 0x02],
+                },
+            },
+            StatusError::NoError,
+            StatusDetail::MotorOnFlag,
+        );
+        packet.error_raw |= 0x80;
+
+        let mut link = Vec::new();
+        bridge::publish_message(&mut link, &message).unwrap();
+        bridge::publish_ack(&mut link, &packet).unwrap();
+        bridge::publish_ack(
+            &mut link,
+            &ACKPacket {
+                cmd: Command::Stat,
+                ..packet
+            },
+        )
+        .unwrap();
+        let (payload, len) = bridge::decode(&link).unwrap();
+        assert_eq!(payload, Payload::Message(message));
+        let (payload, second) = bridge::decode(&link[len..]).unwrap();
+        assert_eq!(payload, Payload::Ack(packet));
+        let (payload, _) = bridge::decode(&link[len + second..]).unwrap();
+        assert_eq!(
+            payload,
+            Payload::Ack(ACKPacket {
+                cmd: Command::Stat,
+                ..packet
+            })
+        );
+
+        assert_eq!(
+            bridge::decode(&link[..len - 1]),
+            Err(BridgeError::Incomplete)
+        );
+        link[3] ^= 0x01;
+        assert_eq!(bridge::decode(&link), Err(BridgeError::InvalidCrc));
+        let mut envelope = bridge::encode_ack(&packet);
+        envelope[1] = 2;
+        let crc = crc16(&envelope[..envelope.len() - 2]);
+        let end = envelope.len();
+        envelope[end - 2..].copy_from_slice(&[crc as u8, (crc >> 8) as u8]);
+        assert_eq!(
+            bridge::decode(&envelope),
+            Err(BridgeError::UnsupportedVersion(2))
+        );
+    }
+}
@@ -1,64 +1,110 @@
 use addr::*;
+use checksum::{checksum1, checksum2};
+#[cfg(feature = "eep")]
+use config::BaudRate;
+use frame::{RequestCommand, MAX_REQUEST_DATA_LEN, MAX_REQUEST_LEN, MIN_FRAME_LEN};
 use message::*;
+use servo::{Servo, BROADCAST_ID};
 
 use arrayvec::ArrayVec;
+use core::slice::Iter;
+#[cfg(feature = "eep")]
+use try_from::TryFrom;
 
 /// The error returned by [`MessageBuilder`](struct.MessageBuilder.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum MessageBuilderError {
     /// The maximum number of data has been reached for this message.
     MaximumDataReached,
+    /// The value is not a valid code for the `BaudRate` register.
+    InvalidBaudRate(u8),
+    /// A read of `size` bytes at `addr` is empty or goes past the end of the register map.
+    InvalidReadSize {
+        /// The address of the first byte read
+        addr: u8,
+        /// The number of bytes read
+        size: u8,
+    },
+    /// The register at this address, `ID` or `BaudRate`, would be written on every servomotor at
+    /// once through the broadcast ID.
+    BroadcastWrite(u8),
+    /// A write of `size` bytes at `addr` is empty or goes past the end of the register map.
+    InvalidWriteSize {
+        /// The address of the first byte written
+        addr: u8,
+        /// The number of bytes written
+        size: u8,
+    },
+    /// The register has no address in the memory accessed, see
+    /// [`Servo::write`](../struct.Servo.html#method.write).
+    UnmappedRegister(&'static str),
 }
 
+// Check that `size` bytes can be read at `addr` in a register map of `map_size` bytes.
+fn check_read(addr: u8, size: u8, map_size: u8) -> Result<(), MessageBuilderError> {
+    if size == 0 || u16::from(addr) + u16::from(size) > u16::from(map_size) {
+        Err(MessageBuilderError::InvalidReadSize { addr, size })
+    } else {
+        Ok(())
+    }
+}
+
+// The largest number of bytes written by a single request, after the address and the size.
+const MAX_WRITE_DATA_LEN: usize = MAX_REQUEST_DATA_LEN - 2;
+
 struct Packet {
     pid: u8,
-    cmd: u8,
-    data: [u8; 16],
+    cmd: RequestCommand,
+    data: [u8; MAX_REQUEST_DATA_LEN],
     data_size: usize,
 }
 
-impl Default for Packet {
-    fn default() -> Packet {
+impl Packet {
+    fn new(pid: u8, cmd: RequestCommand) -> Packet {
         Packet {
-            pid: 0,
-            cmd: 0,
-            data: [0; 16],
+            pid,
+            cmd,
+            data: [0; MAX_REQUEST_DATA_LEN],
             data_size: 0,
         }
     }
-}
 
-impl Packet {
     fn build(self) -> HerkulexMessage {
         let mut result = HerkulexMessage::new();
-        let size: u8 = self.data_size as u8 + 7;
-        let mut checksum1: u8 = size ^ self.pid ^ self.cmd;
-        result.push(0xFF);
-        result.push(0xFF);
-        result.push(size);
-        result.push(self.pid);
-        result.push(self.cmd);
-        for i in 0..self.data_size {
-            result.push(self.data[i]);
-            checksum1 ^= self.data[i];
-        }
-        checksum1 &= 0xFE;
-        let checksum2: u8 = (!checksum1) & 0xFE;
-        result.insert(5, checksum1);
-        result.insert(6, checksum2);
+        let size: u8 = (self.data_size + MIN_FRAME_LEN) as u8;
+        let data = &self.data[0..self.data_size];
+        let cmd = u8::from(self.cmd);
+        // Both checksums are known before anything is written, so they take their slot right
+        // after the command instead of being inserted in front of the data afterwards.
+        let chk1 = checksum1(&[size, self.pid, cmd]) ^ checksum1(data);
+        result.extend(
+            [0xFF, 0xFF, size, self.pid, cmd, chk1, checksum2(chk1)]
+                .iter()
+                .cloned(),
+        );
+        result.extend(data.iter().cloned());
+        log_trace!("encoded frame {:02X?}", result.as_slice());
         result
     }
 
+    // The builders never push more than `MAX_REQUEST_DATA_LEN` bytes, the extra ones would be
+    // dropped.
     fn push_data(&mut self, data: u8) {
-        self.data[self.data_size] = data;
-        self.data_size += 1;
+        if let Some(slot) = self.data.get_mut(self.data_size) {
+            *slot = data;
+            self.data_size += 1;
+        }
     }
 }
 
 /// This is the type of all the message provided by this crate.
 pub type HerkulexMessage = ArrayVec<[u8; 128]>;
 
+// The messages must be able to hold the largest request.
+const _: () = assert!(MAX_REQUEST_LEN <= 128);
+
 /// This struct allows you to build message to directly speak to the herkulex servomotors.
-#[derive(Default)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
 pub struct MessageBuilder {}
 
 /// This is a specialized version of the [`MessageBuilder`](struct.MessageBuilder.html) which
@@ -68,12 +114,14 @@ pub struct MessageBuilder {}
 /// * [MessageBuilderMem](struct.MessageBuilderMem.html)
 /// * [MessageBuilderPosition](struct.MessageBuilderPosition.html)
 /// * [MessageBuilderSpecial](struct.MessageBuilderSpecial.html)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct MessageBuilderCmd {
     pid: u8,
 }
 
 /// This is a specialized version of the [`MessageBuilder`](struct.MessageBuilder.html) which contains an ID and a memory
 /// request (read or write, and where).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct MessageBuilderMem {
     pid: u8,
     addr: RegisterRequest,
@@ -82,6 +130,7 @@ pub struct MessageBuilderMem {
 
 /// This is a specialized version of the [`MessageBuilder`](struct.MessageBuilder.html) which contains an ID and a position
 /// request.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MessageBuilderPositionSJOG {
     pid: u8,
     pos: SJogRequest,
@@ -89,6 +138,7 @@ pub struct MessageBuilderPositionSJOG {
 
 /// This is a specialized version of the [`MessageBuilder`](struct.MessageBuilder.html) which contains an ID and a position
 /// request.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MessageBuilderPositionIJOG {
     pid: u8,
     pos: IJogRequest,
@@ -96,11 +146,23 @@ pub struct MessageBuilderPositionIJOG {
 
 /// This is a specialized version of the [`MessageBuilder`](struct.MessageBuilder.html) which contains an ID and a special
 /// request (reboot, reset or stat).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct MessageBuilderSpecial {
     pid: u8,
     kind: SpecialRequest,
 }
 
+/// This is a specialized version of the [`MessageBuilder`](struct.MessageBuilder.html) which
+/// contains an ID and a write of raw bytes to consecutive registers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MessageBuilderBytes {
+    pid: u8,
+    cmd: RequestCommand,
+    addr: u8,
+    data: [u8; MAX_WRITE_DATA_LEN],
+    size: u8,
+}
+
 impl MessageBuilder {
     /// Create a new message builder
     pub fn new() -> MessageBuilder {
@@ -116,10 +178,86 @@ impl MessageBuilder {
     pub fn id(self, id: u8) -> MessageBuilderCmd {
         MessageBuilderCmd { pid: id }
     }
+
+    /// Create a new message builder addressed to `servo`, to build the messages which have no
+    /// helper in [`Servo`](../struct.Servo.html).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate drs_0x01;
+    /// use drs_0x01::builder::MessageBuilder;
+    /// use drs_0x01::{ReadableRamAddr, Servo};
+    ///
+    /// let servo = Servo::new(0x40);
+    /// let message = MessageBuilder::for_servo(&servo)
+    ///     .read_ram(ReadableRamAddr::Temperature, None)
+    ///     .build();
+    /// ```
+    pub fn for_servo(servo: &Servo) -> MessageBuilderCmd {
+        MessageBuilderCmd::from(*servo)
+    }
+
+    /// Build the same message for every ID of `ids`, for the commands which can not be
+    /// broadcasted such as reads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate drs_0x01;
+    /// use drs_0x01::builder::MessageBuilder;
+    /// use drs_0x01::ReadableRamAddr;
+    ///
+    /// let messages = MessageBuilder::for_each_id(&[1, 2, 3], |builder| {
+    ///     builder.read_ram(ReadableRamAddr::Temperature, None).build()
+    /// });
+    /// for message in messages {
+    ///     // Send the message ...
+    /// }
+    /// ```
+    pub fn for_each_id<'a, F>(ids: &'a [u8], build: F) -> ForEachId<'a, F>
+    where
+        F: FnMut(MessageBuilderCmd) -> HerkulexMessage,
+    {
+        ForEachId {
+            ids: ids.iter(),
+            build,
+        }
+    }
+}
+
+/// An iterator over the same message built for several IDs, created by
+/// [`MessageBuilder::for_each_id`](struct.MessageBuilder.html#method.for_each_id).
+pub struct ForEachId<'a, F> {
+    ids: Iter<'a, u8>,
+    build: F,
+}
+
+impl<'a, F> Iterator for ForEachId<'a, F>
+where
+    F: FnMut(MessageBuilderCmd) -> HerkulexMessage,
+{
+    type Item = HerkulexMessage;
+
+    fn next(&mut self) -> Option<HerkulexMessage> {
+        let id = *self.ids.next()?;
+        Some((self.build)(MessageBuilderCmd { pid: id }))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.ids.size_hint()
+    }
+}
+
+impl From<Servo> for MessageBuilderCmd {
+    fn from(servo: Servo) -> MessageBuilderCmd {
+        MessageBuilderCmd { pid: servo.id() }
+    }
 }
 
 impl MessageBuilderCmd {
     /// Create a message of type **RAM_READ** (read from the temporary memory)
+    /// The size is not checked, see [`try_read_ram`](#method.try_read_ram).
     pub fn read_ram<T: Into<Option<u8>>>(
         self,
         ram_addr: ReadableRamAddr,
@@ -135,9 +273,43 @@ impl MessageBuilderCmd {
         }
     }
 
+    /// Create a message of type **RAM_READ**, refusing the sizes which do not fit in the RAM.
+    ///
+    /// # Errors
+    ///
+    /// Return [MessageBuilderError::InvalidReadSize](enum.MessageBuilderError.html) if the read
+    /// is empty or goes past the end of the RAM.
+    pub fn try_read_ram<T: Into<Option<u8>>>(
+        self,
+        ram_addr: ReadableRamAddr,
+        size: T,
+    ) -> Result<MessageBuilderMem, MessageBuilderError> {
+        let message = self.read_ram(ram_addr, size);
+        check_read(ram_addr.into(), message.size, RAM_MAP_SIZE)?;
+        Ok(message)
+    }
+
     /// Create a message of type **RAM_WRITE** (write to the temporary memory, last until the servo
-    /// is restarted)
-    pub fn write_ram(self, ram_addr: WritableRamAddr) -> MessageBuilderMem {
+    /// is restarted), refusing to give the same ID to every servomotor.
+    ///
+    /// # Errors
+    ///
+    /// Return [MessageBuilderError::BroadcastWrite](enum.MessageBuilderError.html) if the `ID`
+    /// register is written through the broadcast ID.
+    pub fn write_ram(
+        self,
+        ram_addr: WritableRamAddr,
+    ) -> Result<MessageBuilderMem, MessageBuilderError> {
+        if let WritableRamAddr::ID(_) = ram_addr {
+            self.check_not_broadcast(ram_addr.into())?;
+        }
+        Ok(self.write_ram_unchecked(ram_addr))
+    }
+
+    /// Create a message of type **RAM_WRITE** like [`write_ram`](#method.write_ram), without
+    /// refusing the `ID` register through the broadcast ID. The other registers are never
+    /// refused, so this is the same as `write_ram` for them.
+    pub fn write_ram_unchecked(self, ram_addr: WritableRamAddr) -> MessageBuilderMem {
         MessageBuilderMem {
             pid: self.pid,
             addr: RegisterRequest::RamWrite(ram_addr),
@@ -145,7 +317,18 @@ impl MessageBuilderCmd {
         }
     }
 
+    // Refuse to write the register at `addr` on every servomotor at once.
+    fn check_not_broadcast(self, addr: u8) -> Result<(), MessageBuilderError> {
+        if self.pid == BROADCAST_ID {
+            Err(MessageBuilderError::BroadcastWrite(addr))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Create a message of type **READ_EEP** (read the permanent memory)
+    /// The size is not checked, see [`try_read_eep`](#method.try_read_eep).
+    #[cfg(feature = "eep")]
     pub fn read_eep<T: Into<Option<u8>>>(
         self,
         eep_addr: ReadableEEPAddr,
@@ -161,9 +344,55 @@ impl MessageBuilderCmd {
         }
     }
 
+    /// Create a message of type **READ_EEP**, refusing the sizes which do not fit in the EEP
+    /// memory.
+    ///
+    /// # Errors
+    ///
+    /// Return [MessageBuilderError::InvalidReadSize](enum.MessageBuilderError.html) if the read
+    /// is empty or goes past the end of the EEP memory.
+    #[cfg(feature = "eep")]
+    pub fn try_read_eep<T: Into<Option<u8>>>(
+        self,
+        eep_addr: ReadableEEPAddr,
+        size: T,
+    ) -> Result<MessageBuilderMem, MessageBuilderError> {
+        let message = self.read_eep(eep_addr, size);
+        check_read(eep_addr.into(), message.size, EEP_MAP_SIZE)?;
+        Ok(message)
+    }
+
     /// Create a message of type **WRITE_EEP** (write to the permanent memory, require a reboot to
-    /// take effect).
-    pub fn write_eep(self, eep_addr: WritableEEPAddr) -> MessageBuilderMem {
+    /// take effect), refusing the values which would make the servomotors unreachable.
+    ///
+    /// # Errors
+    ///
+    /// Return [MessageBuilderError::InvalidBaudRate](enum.MessageBuilderError.html) if a
+    /// `BaudRate` code is not one of [`BaudRate`](../config/enum.BaudRate.html), and
+    /// [MessageBuilderError::BroadcastWrite](enum.MessageBuilderError.html) if the `ID` or
+    /// `BaudRate` register is written through the broadcast ID.
+    #[cfg(feature = "eep")]
+    pub fn write_eep(
+        self,
+        eep_addr: WritableEEPAddr,
+    ) -> Result<MessageBuilderMem, MessageBuilderError> {
+        match eep_addr {
+            WritableEEPAddr::BaudRate(code) => {
+                BaudRate::try_from(code).map_err(|_| MessageBuilderError::InvalidBaudRate(code))?;
+                self.check_not_broadcast(eep_addr.into())?;
+            }
+            WritableEEPAddr::ID(_) => self.check_not_broadcast(eep_addr.into())?,
+            _ => (),
+        }
+        Ok(self.write_eep_unchecked(eep_addr))
+    }
+
+    /// Create a message of type **WRITE_EEP** like [`write_eep`](#method.write_eep), without
+    /// checking the value written nor the ID. An invalid `BaudRate` code makes the servomotor
+    /// unreachable until its EEP memory is rolled back, and the `ID` or `BaudRate` register
+    /// written through the broadcast ID changes every servomotor of the bus at once.
+    #[cfg(feature = "eep")]
+    pub fn write_eep_unchecked(self, eep_addr: WritableEEPAddr) -> MessageBuilderMem {
         MessageBuilderMem {
             pid: self.pid,
             addr: RegisterRequest::EEPWrite(eep_addr),
@@ -171,6 +400,84 @@ impl MessageBuilderCmd {
         }
     }
 
+    /// Create a message of type **RAM_WRITE** writing the bytes of `data` to the consecutive
+    /// registers starting at `addr`, for example a payload computed from a slice.
+    ///
+    /// # Errors
+    ///
+    /// Return [MessageBuilderError::MaximumDataReached](enum.MessageBuilderError.html) if `data`
+    /// does not fit in a single request,
+    /// [MessageBuilderError::InvalidWriteSize](enum.MessageBuilderError.html) if it is empty or
+    /// goes past the end of the RAM, and
+    /// [MessageBuilderError::BroadcastWrite](enum.MessageBuilderError.html) if the `ID` register
+    /// is written through the broadcast ID.
+    pub fn write_ram_bytes<I: IntoIterator<Item = u8>>(
+        self,
+        addr: u8,
+        data: I,
+    ) -> Result<MessageBuilderBytes, MessageBuilderError> {
+        let message = self.write_bytes(RequestCommand::RamWrite, addr, data, RAM_MAP_SIZE)?;
+        if message.covers(WritableRamAddr::ID(0).into()) {
+            self.check_not_broadcast(WritableRamAddr::ID(0).into())?;
+        }
+        Ok(message)
+    }
+
+    /// Create a message of type **WRITE_EEP** writing the bytes of `data` to the consecutive
+    /// registers starting at `addr`.
+    ///
+    /// # Errors
+    ///
+    /// Return [MessageBuilderError::MaximumDataReached](enum.MessageBuilderError.html) if `data`
+    /// does not fit in a single request,
+    /// [MessageBuilderError::InvalidWriteSize](enum.MessageBuilderError.html) if it is empty or
+    /// goes past the end of the EEP memory, and the errors of
+    /// [`write_eep`](#method.write_eep) if the `ID` or `BaudRate` register is written.
+    #[cfg(feature = "eep")]
+    pub fn write_eep_bytes<I: IntoIterator<Item = u8>>(
+        self,
+        addr: u8,
+        data: I,
+    ) -> Result<MessageBuilderBytes, MessageBuilderError> {
+        let message = self.write_bytes(RequestCommand::EEPWrite, addr, data, EEP_MAP_SIZE)?;
+        if let Some(code) = message.byte_at(WritableEEPAddr::BaudRate(0).into()) {
+            self.write_eep(WritableEEPAddr::BaudRate(code))?;
+        }
+        if let Some(id) = message.byte_at(WritableEEPAddr::ID(0).into()) {
+            self.write_eep(WritableEEPAddr::ID(id))?;
+        }
+        Ok(message)
+    }
+
+    // Collect the bytes written at `addr` in a register map of `map_size` bytes.
+    fn write_bytes<I: IntoIterator<Item = u8>>(
+        self,
+        cmd: RequestCommand,
+        addr: u8,
+        data: I,
+        map_size: u8,
+    ) -> Result<MessageBuilderBytes, MessageBuilderError> {
+        let mut message = MessageBuilderBytes {
+            pid: self.pid,
+            cmd,
+            addr,
+            data: [0; MAX_WRITE_DATA_LEN],
+            size: 0,
+        };
+        for byte in data {
+            if usize::from(message.size) == MAX_WRITE_DATA_LEN {
+                return Err(MessageBuilderError::MaximumDataReached);
+            }
+            message.data[usize::from(message.size)] = byte;
+            message.size += 1;
+        }
+        let size = message.size;
+        if size == 0 || u16::from(addr) + u16::from(size) > u16::from(map_size) {
+            return Err(MessageBuilderError::InvalidWriteSize { addr, size });
+        }
+        Ok(message)
+    }
+
     /// Create a message of type **REBOOT** (reboot the designed servos)
     pub fn reboot(self) -> MessageBuilderSpecial {
         MessageBuilderSpecial {
@@ -265,21 +572,22 @@ impl MessageBuilderCmd {
 
 impl MessageBuilderMem {
     /// Build the final message to be sent to the servomotor through a serial connection.
-    pub fn build(self) -> HerkulexMessage {
-        let pid = self.pid;
+    /// The builder is left untouched, so the same message can be built again.
+    pub fn build(&self) -> HerkulexMessage {
         let cmd = match self.addr {
-            RegisterRequest::EEPWrite(_) => 0x01,
-            RegisterRequest::EEPRead(_) => 0x02,
-            RegisterRequest::RamWrite(_) => 0x03,
-            RegisterRequest::RamRead(_) => 0x04,
+            #[cfg(feature = "eep")]
+            RegisterRequest::EEPWrite(_) => RequestCommand::EEPWrite,
+            #[cfg(feature = "eep")]
+            RegisterRequest::EEPRead(_) => RequestCommand::EEPRead,
+            RegisterRequest::RamWrite(_) => RequestCommand::RamWrite,
+            RegisterRequest::RamRead(_) => RequestCommand::RamRead,
         };
-        let mut packet = Packet::default();
-        packet.pid = pid;
-        packet.cmd = cmd;
+        let mut packet = Packet::new(self.pid, cmd);
 
         // TODO : Check write data sizes
         match self.addr {
             // EEP Write packet
+            #[cfg(feature = "eep")]
             RegisterRequest::EEPWrite(addr) => {
                 packet.push_data(addr.into());
                 packet.push_data(self.size);
@@ -302,6 +610,7 @@ impl MessageBuilderMem {
             }
 
             // EEP Read packet
+            #[cfg(feature = "eep")]
             RegisterRequest::EEPRead(addr) => {
                 packet.push_data(addr.into());
                 packet.push_data(self.size);
@@ -317,17 +626,46 @@ impl MessageBuilderMem {
     }
 }
 
+impl MessageBuilderBytes {
+    /// Return the bytes written, starting at the first register.
+    pub fn data(&self) -> &[u8] {
+        &self.data[..usize::from(self.size)]
+    }
+
+    // Return true if the register at `addr` is written.
+    fn covers(&self, addr: u8) -> bool {
+        self.byte_at(addr).is_some()
+    }
+
+    // Return the byte written to the register at `addr`, if any.
+    fn byte_at(&self, addr: u8) -> Option<u8> {
+        addr.checked_sub(self.addr)
+            .and_then(|offset| self.data().get(usize::from(offset)).cloned())
+    }
+
+    /// Build the final message to be sent to the servomotor through a serial connection.
+    /// The builder is left untouched, so the same message can be built again.
+    pub fn build(&self) -> HerkulexMessage {
+        let mut packet = Packet::new(self.pid, self.cmd);
+        packet.push_data(self.addr);
+        packet.push_data(self.size);
+        for byte in self.data() {
+            packet.push_data(*byte);
+        }
+        packet.build()
+    }
+}
+
 impl MessageBuilderSpecial {
     /// Build the final message to be sent to the servomotor through a serial connection.
-    pub fn build(self) -> HerkulexMessage {
+    /// The builder is left untouched, so the same message can be built again.
+    pub fn build(&self) -> HerkulexMessage {
         let cmd = match self.kind {
-            SpecialRequest::Stat => 0x07,
-            SpecialRequest::Rollback { .. } => 0x08,
-            SpecialRequest::Reboot => 0x09,
+            SpecialRequest::Stat => RequestCommand::Stat,
+            SpecialRequest::Rollback { .. } => RequestCommand::Rollback,
+            SpecialRequest::Reboot => RequestCommand::Reboot,
         };
-        let mut packet = Packet::default();
-        packet.pid = self.pid;
-        packet.cmd = cmd;
+        let mut packet = Packet::new(self.pid, cmd);
         if let SpecialRequest::Rollback {
             skip_id: id_bit,
             skip_baud: baud_bit,
@@ -367,12 +705,11 @@ impl MessageBuilderPositionSJOG {
     }
 
     /// Build the final message to be sent to the servomotor through a serial connection.
-    pub fn build(self) -> HerkulexMessage {
-        let mut packet = Packet::default();
-        packet.pid = self.pid;
-        packet.cmd = 6;
+    /// The builder is left untouched, so the same message can be built again.
+    pub fn build(&self) -> HerkulexMessage {
+        let mut packet = Packet::new(self.pid, RequestCommand::SJog);
         packet.push_data(self.pos.playtime);
-        for data in self.pos.data {
+        for data in &self.pos.data {
             let d = data.mode.associated_data();
             let lsb = (d) as u8;
             let msb = (d >> 8) as u8;
@@ -383,6 +720,7 @@ impl MessageBuilderPositionSJOG {
             match data.mode {
                 JogMode::Normal { .. } => set |= 0b0000_0000,
                 JogMode::Continuous { .. } => set |= 0b0000_0010,
+                JogMode::NoMove => set |= 0b0010_0000,
             }
             match data.color {
                 JogColor::Blue => set |= 0b0000_1000,
@@ -423,11 +761,10 @@ impl MessageBuilderPositionIJOG {
     }
 
     /// Build the final message to be sent to the servomotor through a serial connection.
-    pub fn build(self) -> HerkulexMessage {
-        let mut packet = Packet::default();
-        packet.pid = self.pid;
-        packet.cmd = 5;
-        for data in self.pos {
+    /// The builder is left untouched, so the same message can be built again.
+    pub fn build(&self) -> HerkulexMessage {
+        let mut packet = Packet::new(self.pid, RequestCommand::IJog);
+        for data in &self.pos {
             let d = data.mode.associated_data();
             let lsb = (d) as u8;
             let msb = (d >> 8) as u8;
@@ -438,6 +775,7 @@ impl MessageBuilderPositionIJOG {
             match data.mode {
                 JogMode::Normal { .. } => set |= 0b0000_0000,
                 JogMode::Continuous { .. } => set |= 0b0000_0010,
+                JogMode::NoMove => set |= 0b0010_0000,
             }
             match data.color {
                 JogColor::Blue => set |= 0b0000_1000,
@@ -455,6 +793,7 @@ impl MessageBuilderPositionIJOG {
 #[cfg(test)]
 mod test {
 
+    #[cfg(feature = "eep")]
     use addr::ReadableEEPAddr;
     use builder::*;
 
@@ -486,6 +825,7 @@ mod test {
         let message = MessageBuilder::new()
             .id(0xFD)
             .write_ram(WritableRamAddr::LEDControl(0x01))
+            .unwrap()
             .build();
         assert_eq!(
             message.as_slice(),
@@ -495,6 +835,7 @@ mod test {
         let message = MessageBuilder::new()
             .id(0xFD)
             .write_ram(WritableRamAddr::TorqueControl(0x60))
+            .unwrap()
             .build();
         assert_eq!(
             message.as_slice(),
@@ -523,6 +864,7 @@ mod test {
         );
     }
 
+    #[cfg(feature = "eep")]
     #[test]
     fn eep_read() {
         let message = MessageBuilder::new()
@@ -549,7 +891,15 @@ mod test {
 
         let message = MessageBuilder::new()
             .id(0xFD)
-            .s_jog(60, JogMode::Continuous { speed: 320, rotation: Rotation::CounterClockwise }, JogColor::Blue, 0xFD)
+            .s_jog(
+                60,
+                JogMode::Continuous {
+                    speed: 320,
+                    rotation: Rotation::CounterClockwise,
+                },
+                JogColor::Blue,
+                0xFD,
+            )
             .build();
 
         assert_eq!(
@@ -559,7 +909,15 @@ mod test {
 
         let message = MessageBuilder::new()
             .id(0xFD)
-            .s_jog(60, JogMode::Continuous { speed: 320, rotation: Rotation::Clockwise }, JogColor::Blue, 0xFD)
+            .s_jog(
+                60,
+                JogMode::Continuous {
+                    speed: 320,
+                    rotation: Rotation::Clockwise,
+                },
+                JogColor::Blue,
+                0xFD,
+            )
             .build();
 
         assert_eq!(
@@ -581,7 +939,15 @@ mod test {
 
         let message = MessageBuilder::new()
             .id(0xFD)
-            .i_jog(60, JogMode::Continuous { speed: 320, rotation: Rotation::CounterClockwise }, JogColor::Blue, 0xFD)
+            .i_jog(
+                60,
+                JogMode::Continuous {
+                    speed: 320,
+                    rotation: Rotation::CounterClockwise,
+                },
+                JogColor::Blue,
+                0xFD,
+            )
             .build();
         assert_eq!(
             message.as_slice(),
@@ -590,7 +956,15 @@ mod test {
 
         let message = MessageBuilder::new()
             .id(0xFD)
-            .i_jog(60, JogMode::Continuous { speed: 320, rotation: Rotation::Clockwise }, JogColor::Blue, 0xFD)
+            .i_jog(
+                60,
+                JogMode::Continuous {
+                    speed: 320,
+                    rotation: Rotation::Clockwise,
+                },
+                JogColor::Blue,
+                0xFD,
+            )
             .build();
         assert_eq!(
             message.as_slice(),
@@ -598,4 +972,249 @@ mod test {
         )
     }
 
+    #[test]
+    fn no_move_jog() {
+        let message = MessageBuilder::new()
+            .id(0xFD)
+            .s_jog(60, JogMode::NoMove, JogColor::Red, 0xFD)
+            .build();
+        assert_eq!(
+            message.as_slice(),
+            &[0xFF, 0xFF, 0x0C, 0xFD, 0x06, 0x06, 0xF8, 0x3C, 0x00, 0x00, 0x30, 0xFD]
+        );
+
+        let message = MessageBuilder::new()
+            .id(0xFD)
+            .i_jog(60, JogMode::NoMove, JogColor::Red, 0xFD)
+            .build();
+        assert_eq!(
+            message.as_slice(),
+            &[0xFF, 0xFF, 0x0C, 0xFD, 0x05, 0x04, 0xFA, 0x00, 0x00, 0x30, 0xFD, 0x3C]
+        );
+    }
+
+    #[test]
+    fn for_each_id() {
+        let messages: std::vec::Vec<_> =
+            MessageBuilder::for_each_id(&[1, 2], |builder| builder.stat().build()).collect();
+        assert_eq!(
+            messages,
+            [
+                MessageBuilder::new_with_id(1).stat().build(),
+                MessageBuilder::new_with_id(2).stat().build(),
+            ]
+        );
+    }
+
+    #[test]
+    fn reusable_builders() {
+        let cmd = MessageBuilder::new_with_id(0xFD);
+        assert_eq!(cmd.reboot().build(), cmd.reboot().build());
+
+        let mut sjog = cmd.s_jog(60, JogMode::Normal { position: 512 }, JogColor::Green, 0xFD);
+        let first = sjog.build();
+        assert_eq!(sjog.build(), first);
+
+        let single = sjog.clone();
+        sjog.s_jog(JogMode::Normal { position: 256 }, JogColor::Red, 0x01)
+            .ok()
+            .unwrap();
+        assert_eq!(single.build(), first);
+        assert_eq!(sjog.build().len(), first.len() + 4);
+    }
+
+    #[test]
+    fn byte_writes() {
+        let builder = MessageBuilder::new_with_id(0xFD);
+        let limits = [200u16, 800].iter().flat_map(|limit| limit.to_le_bytes());
+        assert_eq!(
+            builder
+                .write_ram_bytes(20, limits)
+                .map(|message| message.build()),
+            Ok(
+                [0xFF, 0xFF, 0x0D, 0xFD, 0x03, 0x08, 0xF6, 0x14, 0x04, 0xC8, 0x00, 0x20, 0x03]
+                    .iter()
+                    .cloned()
+                    .collect()
+            )
+        );
+        assert_eq!(
+            builder
+                .write_ram_bytes(22, [0u8; 2].iter().cloned())
+                .map(|m| m.build()),
+            builder
+                .write_ram(WritableRamAddr::MaxPosition(0, 0))
+                .map(|m| m.build())
+        );
+        assert_eq!(
+            builder.write_ram_bytes(72, [0u8; 3].iter().cloned()),
+            Err(MessageBuilderError::InvalidWriteSize { addr: 72, size: 3 })
+        );
+        assert_eq!(
+            builder.write_ram_bytes(0, core::iter::empty()),
+            Err(MessageBuilderError::InvalidWriteSize { addr: 0, size: 0 })
+        );
+        assert_eq!(
+            builder.write_ram_bytes(0, core::iter::repeat(0)),
+            Err(MessageBuilderError::MaximumDataReached)
+        );
+        #[cfg(feature = "eep")]
+        assert_eq!(
+            MessageBuilder::new_with_id(0xFE).write_eep_bytes(3, [0u8; 5].iter().cloned()),
+            Err(MessageBuilderError::InvalidBaudRate(0))
+        );
+        #[cfg(feature = "eep")]
+        assert_eq!(
+            MessageBuilder::new_with_id(0xFE).write_eep_bytes(5, [0u8; 2].iter().cloned()),
+            Err(MessageBuilderError::BroadcastWrite(6))
+        );
+    }
+
+    #[cfg(feature = "eep")]
+    #[test]
+    fn baud_rate_writes() {
+        let builder = MessageBuilder::new_with_id(0xFD);
+        assert_eq!(
+            builder
+                .write_eep(WritableEEPAddr::BaudRate(0x10))
+                .map(|message| message.build()),
+            Ok(builder
+                .write_eep_unchecked(WritableEEPAddr::BaudRate(0x10))
+                .build())
+        );
+        assert_eq!(
+            builder
+                .write_eep(WritableEEPAddr::BaudRate(0x11))
+                .map(|message| message.build()),
+            Err(MessageBuilderError::InvalidBaudRate(0x11))
+        );
+        assert_eq!(
+            builder
+                .write_eep_unchecked(WritableEEPAddr::BaudRate(0x11))
+                .build()[9],
+            0x11
+        );
+        assert!(builder.write_eep(WritableEEPAddr::ID(0x11)).is_ok());
+    }
+
+    #[test]
+    fn hashable_messages() {
+        use std::collections::HashSet;
+
+        let modes: HashSet<_> = [
+            JogMode::Normal { position: 512 },
+            JogMode::Continuous {
+                speed: 320,
+                rotation: Rotation::Clockwise,
+            },
+            JogMode::Normal { position: 512 },
+            JogMode::NoMove,
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        assert_eq!(modes.len(), 3);
+
+        let builder = MessageBuilder::new_with_id(0xFD);
+        assert_eq!(builder, MessageBuilder::new().id(0xFD));
+        assert_eq!(
+            builder.read_ram(ReadableRamAddr::Temperature, None),
+            builder.read_ram(ReadableRamAddr::Temperature, 1)
+        );
+        assert_ne!(
+            builder.s_jog(60, JogMode::NoMove, JogColor::Red, 0xFD),
+            builder.s_jog(60, JogMode::NoMove, JogColor::Blue, 0xFD)
+        );
+    }
+
+    #[test]
+    fn from_servo() {
+        let servo = Servo::new(0x40);
+        assert_eq!(MessageBuilderCmd::from(servo).stat().build(), servo.stat());
+        assert_eq!(
+            MessageBuilder::for_servo(&servo)
+                .write_ram(WritableRamAddr::TorqueControl(0x60))
+                .map(|message| message.build()),
+            Ok(servo.enable_torque())
+        );
+    }
+
+    #[test]
+    fn broadcast_writes() {
+        let builder = MessageBuilder::new_with_id(0xFE);
+        #[cfg(feature = "eep")]
+        assert_eq!(
+            builder
+                .write_eep(WritableEEPAddr::ID(0x11))
+                .map(|message| message.build()),
+            Err(MessageBuilderError::BroadcastWrite(6))
+        );
+        #[cfg(feature = "eep")]
+        assert_eq!(
+            builder
+                .write_eep(WritableEEPAddr::BaudRate(0x10))
+                .map(|message| message.build()),
+            Err(MessageBuilderError::BroadcastWrite(4))
+        );
+        assert_eq!(
+            builder
+                .write_ram(WritableRamAddr::ID(0x11))
+                .map(|message| message.build()),
+            Err(MessageBuilderError::BroadcastWrite(0))
+        );
+        #[cfg(feature = "eep")]
+        assert!(builder.write_eep(WritableEEPAddr::AckPolicy(0x01)).is_ok());
+        assert!(builder.write_ram(WritableRamAddr::LEDControl(0x01)).is_ok());
+        assert!(MessageBuilder::new_with_id(0xFD)
+            .write_ram(WritableRamAddr::ID(0x11))
+            .is_ok());
+
+        // The explicit opt-in still writes every servomotor at once.
+        assert_eq!(
+            builder
+                .write_ram_unchecked(WritableRamAddr::ID(0x11))
+                .build()
+                .as_slice(),
+            &[0xFF, 0xFF, 0x0A, 0xFE, 0x03, 0xE6, 0x18, 0x00, 0x01, 0x11]
+        );
+        #[cfg(feature = "eep")]
+        assert_eq!(
+            builder
+                .write_eep_unchecked(WritableEEPAddr::BaudRate(0x10))
+                .build()[3],
+            BROADCAST_ID
+        );
+    }
+
+    #[test]
+    fn read_sizes() {
+        let builder = MessageBuilder::new_with_id(0xFD);
+        #[cfg(feature = "eep")]
+        assert_eq!(
+            builder
+                .try_read_eep(ReadableEEPAddr::PositionKp, 4)
+                .map(|message| message.build()),
+            Ok(builder.read_eep(ReadableEEPAddr::PositionKp, 4).build())
+        );
+        assert!(builder
+            .try_read_ram(ReadableRamAddr::DesiredVelocity, None)
+            .is_ok());
+        assert_eq!(
+            builder
+                .try_read_ram(ReadableRamAddr::DesiredVelocity, 3)
+                .map(|message| message.build()),
+            Err(MessageBuilderError::InvalidReadSize { addr: 72, size: 3 })
+        );
+        #[cfg(feature = "eep")]
+        assert_eq!(
+            builder
+                .try_read_eep(ReadableEEPAddr::PositionKp, 0)
+                .map(|message| message.build()),
+            Err(MessageBuilderError::InvalidReadSize { addr: 30, size: 0 })
+        );
+        #[cfg(feature = "eep")]
+        assert!(builder
+            .try_read_eep(ReadableEEPAddr::CalibrationDifference, 2)
+            .is_err());
+    }
 }
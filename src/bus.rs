@@ -0,0 +1,181 @@
+//! A bus shared between several tasks.
+//!
+//! A request and its ACK must not be interleaved with the frames of another task, otherwise the
+//! ACKs can not be matched with their requests. A [`SharedBus`](struct.SharedBus.html) runs every
+//! transaction with the bus locked, using any mutex implementing
+//! [`BusMutex`](trait.BusMutex.html).
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use std::cell::RefCell;
+//! use drs_0x01::bus::SharedBus;
+//! use drs_0x01::transport::Transport;
+//! use drs_0x01::Servo;
+//!
+//! # struct Uart;
+//! # impl Transport for Uart {
+//! #     type Error = ();
+//! #     fn write(&mut self, _frame: &[u8]) -> Result<(), ()> { Ok(()) }
+//! #     fn read(&mut self, _buf: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+//! # }
+//! let bus = SharedBus::new(RefCell::new(Uart));
+//! bus.send(&Servo::new(0x40).enable_torque()).unwrap();
+//! let read = bus.transaction(|uart| {
+//!     uart.write(&Servo::new(0x40).stat())?;
+//!     let mut buf = [0; 16];
+//!     uart.read(&mut buf)
+//! });
+//! ```
+
+use core::cell::RefCell;
+
+use transport::Transport;
+
+/// A mutex protecting a bus, to be implemented for the mutex of your platform (an interrupt-free
+/// critical section, an RTOS mutex ...).
+pub trait BusMutex {
+    /// The bus protected by the mutex
+    type Bus;
+
+    /// Run `f` with exclusive access to the bus.
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R;
+}
+
+/// A `RefCell` can be used when every task runs in the same execution context. Locking the bus
+/// twice panics.
+impl<T> BusMutex for RefCell<T> {
+    type Bus = T;
+
+    fn lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        f(&mut self.borrow_mut())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> BusMutex for ::std::sync::Mutex<T> {
+    type Bus = T;
+
+    fn lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+        let mut bus = match ::std::sync::Mutex::lock(self) {
+            Ok(bus) => bus,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        f(&mut bus)
+    }
+}
+
+/// A bus which serializes the transactions of several tasks.
+#[derive(Debug, Default)]
+pub struct SharedBus<M> {
+    mutex: M,
+}
+
+impl<M: BusMutex> SharedBus<M> {
+    /// Share the bus protected by `mutex`.
+    pub fn new(mutex: M) -> SharedBus<M> {
+        SharedBus { mutex }
+    }
+
+    /// Run `f` with the bus locked, no other task can send a frame until it returns.
+    pub fn transaction<R, F: FnOnce(&mut M::Bus) -> R>(&self, f: F) -> R {
+        self.mutex.lock(f)
+    }
+
+    /// Return the mutex protecting the bus.
+    pub fn into_inner(self) -> M {
+        self.mutex
+    }
+}
+
+impl<M: BusMutex> SharedBus<M>
+where
+    M::Bus: Transport,
+{
+    /// Send a single frame, which does not expect any answer.
+    pub fn send(&self, frame: &[u8]) -> Result<(), <M::Bus as Transport>::Error> {
+        self.transaction(|bus| bus.write(frame))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bus::{BusMutex, SharedBus};
+    use transport::Transport;
+
+    use core::cell::RefCell;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::vec::Vec;
+
+    #[derive(Default)]
+    struct Recorder {
+        frames: Vec<Vec<u8>>,
+    }
+
+    impl Transport for Recorder {
+        type Error = ();
+
+        fn write(&mut self, frame: &[u8]) -> Result<(), ()> {
+            self.frames.push(Vec::from(frame));
+            Ok(())
+        }
+
+        fn read(&mut self, _buf: &mut [u8]) -> Result<usize, ()> {
+            Ok(0)
+        }
+    }
+
+    struct StdMutex<T>(Mutex<T>);
+
+    impl<T> BusMutex for StdMutex<T> {
+        type Bus = T;
+
+        fn lock<R, F: FnOnce(&mut T) -> R>(&self, f: F) -> R {
+            f(&mut self.0.lock().unwrap())
+        }
+    }
+
+    #[test]
+    fn ref_cell_bus() {
+        let bus = SharedBus::new(RefCell::new(Recorder::default()));
+        bus.send(&[1, 2]).unwrap();
+        bus.transaction(|recorder| {
+            recorder.write(&[3]).unwrap();
+            recorder.write(&[4]).unwrap();
+        });
+        assert_eq!(
+            bus.into_inner().into_inner().frames,
+            vec![vec![1, 2], vec![3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn transactions_are_not_interleaved() {
+        let bus = Arc::new(SharedBus::new(StdMutex(Mutex::new(Recorder::default()))));
+        let handles: Vec<_> = (0..4u8)
+            .map(|task| {
+                let bus = bus.clone();
+                thread::spawn(move || {
+                    for _ in 0..50 {
+                        bus.transaction(|recorder| {
+                            recorder.write(&[task]).unwrap();
+                            thread::yield_now();
+                            recorder.write(&[task]).unwrap();
+                        });
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        bus.transaction(|recorder| {
+            assert_eq!(recorder.frames.len(), 400);
+            for pair in recorder.frames.chunks(2) {
+                assert_eq!(pair[0], pair[1]);
+            }
+        });
+    }
+}
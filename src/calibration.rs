@@ -0,0 +1,254 @@
+//! Calibration of the position of a servomotor.
+//!
+//! The `CalibrationDifference` register shifts the position reported and commanded by a
+//! servomotor : the calibrated position is its absolute position minus this difference. Setting
+//! it by hand through the raw registers is error-prone, [`calibrate`](fn.calibrate.html) measures
+//! and writes it for a joint held at a known reference position.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # extern crate drs_0x01;
+//! use drs_0x01::calibration::calibrate;
+//! use drs_0x01::transport::Transport;
+//! use drs_0x01::Servo;
+//!
+//! # struct Uart;
+//! # impl Transport for Uart {
+//! #     type Error = ();
+//! #     fn write(&mut self, _frame: &[u8]) -> Result<(), ()> { Ok(()) }
+//! #     fn read(&mut self, _buf: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+//! # }
+//! # let millis = || 0;
+//! let mut uart = Uart;
+//! let servo = Servo::new(0x40);
+//! uart.write(&servo.disable_torque()).unwrap();
+//! // Hold the joint against its mechanical reference, at position 512 ...
+//! let difference = calibrate(&mut uart, &millis, servo, 512, 10).unwrap();
+//! ```
+
+#[cfg(feature = "eep")]
+use addr::WritableEEPAddr;
+use addr::{ReadableRamAddr, WritableRamAddr};
+use clock::Clock;
+use reader::ACKReader;
+use reg;
+use servo::Servo;
+use transaction::{decode_register, Transaction};
+use transport::{bytes_read, Transport};
+
+/// The largest difference between the calibrated position read back and the reference for the
+/// calibration to be considered successful.
+pub const CALIBRATION_TOLERANCE: u16 = 1;
+
+/// The error returned by [`calibrate`](fn.calibrate.html).
+#[derive(Debug, PartialEq, Eq)]
+pub enum CalibrationError<E> {
+    /// The transport failed.
+    Transport(E),
+    /// The servomotor did not answer in time.
+    Timeout,
+    /// The difference between the absolute position and the reference does not fit in the
+    /// `CalibrationDifference` register.
+    OutOfRange(i16),
+    /// The calibrated position read back after the calibration is not the reference.
+    Verification {
+        /// The reference position
+        expected: u16,
+        /// The calibrated position read back
+        read: u16,
+    },
+}
+
+/// Send the request of `transaction` and busy-wait its answer for `timeout` ticks.
+fn exchange<T, C, V>(
+    transport: &mut T,
+    clock: &C,
+    transaction: &Transaction<V>,
+    timeout: u32,
+) -> Result<V, CalibrationError<T::Error>>
+where
+    T: Transport + ?Sized,
+    C: Clock + ?Sized,
+{
+    let message = transaction.message();
+    let _span = span!("exchange", id = message[3], cmd = message[4]);
+    let mut reader = ACKReader::new();
+    let mut buf = [0; 32];
+    let start = clock.now();
+    transport
+        .write(message)
+        .map_err(CalibrationError::Transport)?;
+    loop {
+        let read = transport
+            .read(&mut buf)
+            .map_err(CalibrationError::Transport)?;
+        let mut value = None;
+        reader.parse_with(bytes_read(&buf, read), &mut |packet: &_| {
+            if value.is_none() {
+                value = transaction.complete(packet);
+            }
+        });
+        if let Some(value) = value {
+            return Ok(value);
+        } else if clock.now().wrapping_sub(start) > timeout {
+            log_debug!(
+                "no answer from 0x{:02X} to the calibration read",
+                message[3]
+            );
+            return Err(CalibrationError::Timeout);
+        }
+    }
+}
+
+/// Calibrate `servo` so that its current position reads as `reference`, and return the
+/// `CalibrationDifference` applied.
+///
+/// The joint must be held at its reference position, with the torque released, during the
+/// calibration. The absolute position is read, the difference is written to the RAM and to the
+/// EEP memory so that it survives a reboot (only to the RAM without the `eep` feature), and the
+/// calibrated position is read back to verify it. Every read busy-waits for `timeout` ticks at
+/// most.
+///
+/// # Errors
+///
+/// * `Transport` and `Timeout` if the servomotor could not be read,
+/// * `OutOfRange` if the joint is too far from the reference to be calibrated, in which case
+///   nothing is written,
+/// * `Verification` if the servomotor does not report the reference after the calibration.
+pub fn calibrate<T, C>(
+    transport: &mut T,
+    clock: &C,
+    servo: Servo,
+    reference: u16,
+    timeout: u32,
+) -> Result<i8, CalibrationError<T::Error>>
+where
+    T: Transport + ?Sized,
+    C: Clock + ?Sized,
+{
+    let _span = span!("calibrate", id = servo.id(), reference);
+    let absolute = exchange(
+        transport,
+        clock,
+        &servo.ram_transaction(
+            ReadableRamAddr::AbsolutePosition,
+            decode_register::<reg::AbsolutePosition>,
+        ),
+        timeout,
+    )?;
+    let difference = absolute as i16 - reference as i16;
+    if difference < i16::from(i8::MIN) || difference > i16::from(i8::MAX) {
+        return Err(CalibrationError::OutOfRange(difference));
+    }
+    let difference = difference as i8;
+    transport
+        .write(&servo.ram_write_unchecked(WritableRamAddr::CalibrationDifference(difference as u8)))
+        .map_err(CalibrationError::Transport)?;
+    #[cfg(feature = "eep")]
+    transport
+        .write(&servo.eep_write_unchecked(WritableEEPAddr::CalibrationDifference(difference as u8)))
+        .map_err(CalibrationError::Transport)?;
+    let read = exchange(
+        transport,
+        clock,
+        &servo.ram_transaction(
+            ReadableRamAddr::CalibratedPosition,
+            decode_register::<reg::CalibratedPosition>,
+        ),
+        timeout,
+    )?;
+    if read.abs_diff(reference) > CALIBRATION_TOLERANCE {
+        return Err(CalibrationError::Verification {
+            expected: reference,
+            read,
+        });
+    }
+    Ok(difference)
+}
+
+#[cfg(test)]
+mod test {
+    use calibration::{calibrate, CalibrationError};
+    use servo::Servo;
+    use test_support::AckFrameBuilder;
+    use transport::Transport;
+
+    use core::cell::Cell;
+    use std::vec::Vec;
+
+    /// A servomotor 0x40 at the absolute position `absolute`, answering its position reads.
+    struct Joint {
+        absolute: u16,
+        difference: i8,
+        eep_difference: Option<i8>,
+        pending: Vec<u8>,
+    }
+
+    impl Transport for Joint {
+        type Error = ();
+
+        fn write(&mut self, frame: &[u8]) -> Result<(), ()> {
+            match (frame[4], frame[7]) {
+                (0x03, 47) => self.difference = frame[9] as i8,
+                (0x01, 53) => self.eep_difference = Some(frame[9] as i8),
+                (0x04, addr) => {
+                    let position = match addr {
+                        60 => self.absolute,
+                        _ => (self.absolute as i16 - i16::from(self.difference)) as u16,
+                    };
+                    let data = [position as u8, (position >> 8) as u8];
+                    self.pending = AckFrameBuilder::ram_read(0x40, addr, &data)
+                        .build()
+                        .to_vec();
+                }
+                _ => (),
+            }
+            Ok(())
+        }
+
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+            let len = self.pending.len();
+            buf[..len].copy_from_slice(&self.pending);
+            self.pending.clear();
+            Ok(len)
+        }
+    }
+
+    fn joint(absolute: u16) -> Joint {
+        Joint {
+            absolute,
+            difference: 0,
+            eep_difference: None,
+            pending: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn calibration() {
+        let tick = Cell::new(0u32);
+        let clock = || {
+            tick.set(tick.get() + 1);
+            tick.get()
+        };
+        let servo = Servo::new(0x40);
+        let mut right = joint(520);
+        assert_eq!(calibrate(&mut right, &clock, servo, 512, 10), Ok(8));
+        // Without the `eep` feature, the difference is only written to the RAM.
+        let eep_difference = if cfg!(feature = "eep") { Some(8) } else { None };
+        assert_eq!(right.eep_difference, eep_difference);
+        let mut left = joint(400);
+        assert_eq!(calibrate(&mut left, &clock, servo, 512, 10), Ok(-112));
+
+        let mut far = joint(100);
+        assert_eq!(
+            calibrate(&mut far, &clock, servo, 512, 10),
+            Err(CalibrationError::OutOfRange(-412))
+        );
+        assert_eq!(far.eep_difference, None);
+        assert_eq!(
+            calibrate(&mut far, &clock, Servo::new(0x41), 512, 10),
+            Err(CalibrationError::Timeout)
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! The checksums used by every Herkulex frame, as described page 18 of the datasheet.
+//!
+//! A frame is laid out as `[0xFF][0xFF][psize][pid][cmd][checksum1][checksum2][data...]`.
+//! Both checksums are computed from every byte of the frame except the header and the checksums
+//! themselves.
+
+/// Compute the first checksum of a frame from its covered bytes (`psize`, `pid`, `cmd` and the
+/// data, in any order).
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::checksum::{checksum1, checksum2};
+///
+/// // Reboot message for servo 0xFD
+/// let chk1 = checksum1(&[0x07, 0xFD, 0x09]);
+/// assert_eq!(chk1, 0xF2);
+/// assert_eq!(checksum2(chk1), 0x0C);
+/// ```
+pub fn checksum1(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |chk, byte| chk ^ byte) & 0xFE
+}
+
+/// Compute the second checksum of a frame from its first checksum.
+pub fn checksum2(checksum1: u8) -> u8 {
+    !checksum1 & 0xFE
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(all(feature = "runtime", feature = "eep"))]
+    use addr::ReadableEEPAddr;
+    use addr::{ReadableRamAddr, RAM_MAP_SIZE};
+    use builder::MessageBuilder;
+    use frame::ValidateFrame;
+    use message::{JogColor, JogMode, Rotation};
+    #[cfg(feature = "runtime")]
+    use reader::ACKReader;
+    #[cfg(all(feature = "runtime", feature = "eep"))]
+    use reader::Command;
+    #[cfg(feature = "runtime")]
+    use test_support::AckFrameBuilder;
+
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use try_from::TryFrom;
+
+    fn color() -> impl Strategy<Value = JogColor> {
+        prop_oneof![
+            Just(JogColor::Red),
+            Just(JogColor::Green),
+            Just(JogColor::Blue)
+        ]
+    }
+
+    fn mode() -> impl Strategy<Value = JogMode> {
+        prop_oneof![
+            (0u16..1024).prop_map(|position| JogMode::Normal { position }),
+            (0u16..1024, any::<bool>()).prop_map(|(speed, clockwise)| JogMode::Continuous {
+                speed,
+                rotation: if clockwise {
+                    Rotation::Clockwise
+                } else {
+                    Rotation::CounterClockwise
+                },
+            }),
+            Just(JogMode::NoMove),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn ram_read_checksums(id in any::<u8>(), addr in any::<u8>(), len in 1u8..3) {
+            if let Ok(addr) = ReadableRamAddr::try_from(addr) {
+                let message = MessageBuilder::new_with_id(id).read_ram(addr, len).build();
+                prop_assert_eq!(message.validate(), Ok(()));
+            }
+        }
+
+        #[test]
+        fn ram_write_checksums(
+            id in 0u8..0xFE,
+            addr in 0..RAM_MAP_SIZE,
+            data in vec(any::<u8>(), 1..5),
+        ) {
+            let builder = MessageBuilder::new_with_id(id);
+            if let Ok(message) = builder.write_ram_bytes(addr, data.iter().cloned()) {
+                prop_assert_eq!(message.build().validate(), Ok(()));
+            }
+        }
+
+        #[test]
+        fn sjog_checksums(
+            id in any::<u8>(),
+            playtime in any::<u8>(),
+            entries in vec((mode(), color(), any::<u8>()), 1..11),
+        ) {
+            let (first, rest) = entries.split_first().unwrap();
+            let mut message =
+                MessageBuilder::new_with_id(id).s_jog(playtime, first.0, first.1, first.2);
+            for &(mode, color, id) in rest {
+                message.s_jog(mode, color, id).unwrap();
+            }
+            prop_assert_eq!(message.build().validate(), Ok(()));
+        }
+
+        #[test]
+        fn ijog_checksums(
+            id in any::<u8>(),
+            entries in vec((mode(), color(), any::<u8>(), any::<u8>()), 1..11),
+        ) {
+            let (first, rest) = entries.split_first().unwrap();
+            let mut message =
+                MessageBuilder::new_with_id(id).i_jog(first.2, first.0, first.1, first.3);
+            for &(mode, color, playtime, id) in rest {
+                message.s_jog(mode, color, playtime, id).unwrap();
+            }
+            prop_assert_eq!(message.build().validate(), Ok(()));
+        }
+
+        #[cfg(feature = "runtime")]
+        #[test]
+        fn ack_checksums(
+            pid in any::<u8>(),
+            cmd in 0x45u8..0x4A,
+            error in any::<u8>(),
+            detail in any::<u8>(),
+        ) {
+            let frame = AckFrameBuilder::new(pid, cmd - 0x40).status(error, detail).build();
+            let mut reader = ACKReader::new();
+            reader.parse(&frame);
+            let packet = reader.pop_ack_packet();
+            prop_assert_eq!(packet.map(|packet| u8::from(packet.cmd)), Some(cmd));
+        }
+
+        #[cfg(all(feature = "runtime", feature = "eep"))]
+        #[test]
+        fn read_ack_checksums(
+            pid in any::<u8>(),
+            addr in any::<u8>(),
+            data in vec(any::<u8>(), 1..3),
+            eep in any::<bool>(),
+            error in any::<u8>(),
+            detail in any::<u8>(),
+        ) {
+            let frame = if eep {
+                ReadableEEPAddr::try_from(addr).map(|_| AckFrameBuilder::eep_read(pid, addr, &data))
+            } else {
+                ReadableRamAddr::try_from(addr).map(|_| AckFrameBuilder::ram_read(pid, addr, &data))
+            };
+            if let Ok(frame) = frame {
+                let mut reader = ACKReader::new();
+                reader.parse(&frame.status(error, detail).build());
+                let read = match reader.pop_ack_packet().map(|packet| packet.cmd) {
+                    Some(Command::RamRead { data }) => {
+                        Some((u8::from(data.addr), data.data_len, data.data))
+                    }
+                    Some(Command::EEPRead { data }) => {
+                        Some((u8::from(data.addr), data.data_len, data.data))
+                    }
+                    _ => None,
+                };
+                let (read_addr, len, bytes) = read.unwrap();
+                prop_assert_eq!(read_addr, addr);
+                prop_assert_eq!(&bytes[..usize::from(len)], &data[..]);
+            }
+        }
+    }
+}
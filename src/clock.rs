@@ -0,0 +1,533 @@
+//! Timestamps for the packets received, given by a clock of the application.
+//!
+//! Knowing when a packet was received allows measuring the latency of the servomotors and
+//! rejecting stale data. Timestamps are expressed in ticks of the clock, and are allowed to wrap
+//! around.
+//!
+//! The clock of the application can also be described by a [`DelayTick`](trait.DelayTick.html),
+//! which tells its frequency along with its ticks, so that durations are converted without
+//! relying on any HAL. [`TickClock`](struct.TickClock.html) turns it into a `Clock`, and adapters
+//! are provided for `std::time::Instant` (with the `std` feature) and for the `DelayNs` trait of
+//! `embedded-hal` (with the `embedded-hal` feature).
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::clock::TimestampedReader;
+//!
+//! // Any function returning the current tick can be used as a clock.
+//! let mut reader = TimestampedReader::new(|| 1000);
+//! reader.parse(&[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01]);
+//! let packet = reader.pop_ack_packet().unwrap();
+//! assert_eq!(packet.timestamp, 1000);
+//! assert!(packet.is_stale(1100, 50));
+//! ```
+
+use arrayvec::ArrayVec;
+
+use addr::ReadableRamAddr;
+use reader::{ACKPacket, ACKReader, Command, TRAME_READER_INTERNAL_BUFFER_SIZE};
+
+/// A monotonic clock, counting ticks of any period.
+pub trait Clock {
+    /// Return the current tick.
+    fn now(&self) -> u32;
+}
+
+impl<F: Fn() -> u32> Clock for F {
+    fn now(&self) -> u32 {
+        self()
+    }
+}
+
+/// A monotonic tick counter of known frequency.
+pub trait DelayTick {
+    /// Return the number of ticks elapsed since an arbitrary origin.
+    fn ticks(&self) -> u64;
+
+    /// Return the number of ticks per second.
+    fn tick_hz(&self) -> u32;
+
+    /// Return the number of ticks lasting `us` microseconds, rounded up.
+    fn ticks_from_us(&self, us: u32) -> u64 {
+        (u64::from(us) * u64::from(self.tick_hz())).div_ceil(1_000_000)
+    }
+
+    /// Return the number of microseconds elapsed since the tick `since`.
+    fn elapsed_us(&self, since: u64) -> u64 {
+        let elapsed = u128::from(self.ticks().wrapping_sub(since)) * 1_000_000;
+        (elapsed / u128::from(self.tick_hz().max(1))) as u64
+    }
+}
+
+impl<D: DelayTick + ?Sized> DelayTick for &D {
+    fn ticks(&self) -> u64 {
+        (**self).ticks()
+    }
+
+    fn tick_hz(&self) -> u32 {
+        (**self).tick_hz()
+    }
+}
+
+/// A [`Clock`](trait.Clock.html) counting the ticks of a [`DelayTick`](trait.DelayTick.html),
+/// wrapping around after 32 bits.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::clock::{DelayTick, TickClock};
+/// use drs_0x01::timings;
+///
+/// struct Timer;
+/// impl DelayTick for Timer {
+///     fn ticks(&self) -> u64 { 1000 }
+///     fn tick_hz(&self) -> u32 { 32_768 }
+/// }
+///
+/// let clock = TickClock(Timer);
+/// let timeout = timings::for_baud(115_200).reply_timeout_ticks(clock.0.tick_hz());
+/// // Use clock and timeout with ping, read_region ...
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TickClock<D>(pub D);
+
+impl<D: DelayTick> Clock for TickClock<D> {
+    fn now(&self) -> u32 {
+        self.0.ticks() as u32
+    }
+}
+
+/// A [`DelayTick`](trait.DelayTick.html) counting the microseconds elapsed since its creation.
+///
+/// This struct is available with the `std` feature.
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug)]
+pub struct InstantTicks {
+    origin: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl InstantTicks {
+    /// Start counting from now.
+    pub fn new() -> InstantTicks {
+        InstantTicks {
+            origin: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for InstantTicks {
+    fn default() -> InstantTicks {
+        InstantTicks::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl DelayTick for InstantTicks {
+    fn ticks(&self) -> u64 {
+        self.origin.elapsed().as_micros() as u64
+    }
+
+    fn tick_hz(&self) -> u32 {
+        1_000_000
+    }
+}
+
+/// A [`DelayTick`](trait.DelayTick.html) counting the time spent in the delays of an
+/// `embedded-hal` `DelayNs`, for the platforms without any timer.
+///
+/// The count only advances in the delays : an application busy-waiting on it must delay in the
+/// loop, through this struct. It implements `DelayNs` itself, so the drivers sharing the delay
+/// keep the count up to date.
+///
+/// This struct is available with the `embedded-hal` feature.
+#[cfg(feature = "embedded-hal")]
+#[derive(Debug)]
+pub struct DelayTicks<D> {
+    delay: D,
+    tick_hz: u32,
+    ticks: u64,
+    // The nanoseconds spent in the delays, not counted in a whole tick yet, times tick_hz
+    remainder: u64,
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<D: embedded_hal::delay::DelayNs> DelayTicks<D> {
+    /// Count the delays of `delay` in ticks of `tick_hz` per second.
+    pub fn new(delay: D, tick_hz: u32) -> DelayTicks<D> {
+        DelayTicks {
+            delay,
+            tick_hz: tick_hz.max(1),
+            ticks: 0,
+            remainder: 0,
+        }
+    }
+
+    /// Wait for `ticks` ticks.
+    pub fn delay_ticks(&mut self, ticks: u32) {
+        let ns = u64::from(ticks) * 1_000_000_000 / u64::from(self.tick_hz);
+        embedded_hal::delay::DelayNs::delay_ns(self, ns.min(u64::from(u32::MAX)) as u32);
+    }
+
+    /// Return the wrapped delay.
+    pub fn into_inner(self) -> D {
+        self.delay
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<D: embedded_hal::delay::DelayNs> embedded_hal::delay::DelayNs for DelayTicks<D> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns);
+        self.remainder += u64::from(ns) * u64::from(self.tick_hz);
+        self.ticks += self.remainder / 1_000_000_000;
+        self.remainder %= 1_000_000_000;
+    }
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<D> DelayTick for DelayTicks<D> {
+    fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    fn tick_hz(&self) -> u32 {
+        self.tick_hz
+    }
+}
+
+/// Return true if `deadline` is in the past at `now`.
+pub(crate) fn expired(deadline: u32, now: u32) -> bool {
+    let late = now.wrapping_sub(deadline);
+    late != 0 && late < 1 << 31
+}
+
+/// An `ACKPacket` and the tick at which it was received.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TimestampedPacket {
+    /// The packet received
+    pub packet: ACKPacket,
+    /// The tick at which the last byte of the packet was parsed
+    pub timestamp: u32,
+}
+
+impl TimestampedPacket {
+    /// Return the number of ticks elapsed between the reception of the packet and `now`.
+    pub fn age(&self, now: u32) -> u32 {
+        now.wrapping_sub(self.timestamp)
+    }
+
+    /// Return true if the packet was received more than `max_age` ticks before `now`.
+    pub fn is_stale(&self, now: u32, max_age: u32) -> bool {
+        self.age(now) > max_age
+    }
+}
+
+/// An [`ACKReader`](../reader/struct.ACKReader.html) which timestamps the packets it decodes.
+pub struct TimestampedReader<C> {
+    reader: ACKReader,
+    clock: C,
+    buffer: ArrayVec<[TimestampedPacket; TRAME_READER_INTERNAL_BUFFER_SIZE]>,
+}
+
+impl<C: Clock> TimestampedReader<C> {
+    /// Create a reader timestamping the packets with `clock`.
+    pub fn new(clock: C) -> TimestampedReader<C> {
+        TimestampedReader {
+            reader: ACKReader::new(),
+            clock,
+            buffer: ArrayVec::new(),
+        }
+    }
+
+    /// Return the oldest packet read. When the internal buffer is full, the newest packets are
+    /// dropped.
+    pub fn pop_ack_packet(&mut self) -> Option<TimestampedPacket> {
+        if self.buffer.is_empty() {
+            None
+        } else {
+            Some(self.buffer.remove(0))
+        }
+    }
+
+    /// Get the number of available messages in the internal buffer
+    pub fn available_messages(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Parse a buffer of bytes, adding sucessfully decoded messages to the internal buffer
+    pub fn parse(&mut self, buf: &[u8]) {
+        let clock = &self.clock;
+        let buffer = &mut self.buffer;
+        self.reader.parse_with(buf, &mut |packet: &ACKPacket| {
+            let _ = buffer.try_push(TimestampedPacket {
+                packet: ACKPacket {
+                    pid: packet.pid,
+                    cmd: packet.cmd,
+                    error: packet.error,
+                    detail: packet.detail,
+                    error_raw: packet.error_raw,
+                    detail_raw: packet.detail_raw,
+                },
+                timestamp: clock.now(),
+            });
+        });
+    }
+
+    /// Return the clock of the reader.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+}
+
+/// The period of the `Tick` register of the servomotors, in milliseconds.
+pub const SERVO_TICK_MS: f32 = 11.2;
+
+/// The time base of a servomotor, given by its `Tick` register, mapped to the clock of the host.
+///
+/// The register counts periods of 11.2 ms and wraps around after 8 bits (16 bits with
+/// [`with_wide_tick`](#method.with_wide_tick)). Feeding the clock with the register regularly,
+/// more often than once per wrap (2.8 s for 8 bits), it keeps a continuous count of the ticks of
+/// the servomotor and measures how many host ticks each of them lasts. Playtimes can then be
+/// computed with the actual pace of the servomotor instead of the nominal one.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::clock::ServoClock;
+///
+/// // The host clock counts milliseconds.
+/// let mut clock = ServoClock::new(11.2);
+/// // Send Servo::new(0x40).request_tick() and feed the answers to the clock ...
+/// clock.update(250, 1000);
+/// // The register wrapped around : 26 ticks in 310 ms, the servomotor is 6% slow.
+/// clock.update(20, 1310);
+/// // 1 second from now, in ticks of the servomotor.
+/// assert_eq!(clock.playtime_until(1310, 2310), 84);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct ServoClock {
+    nominal: f32,
+    mask: u16,
+    last_tick: Option<u16>,
+    origin: (u32, u32),
+    latest: (u32, u32),
+}
+
+impl ServoClock {
+    /// Create a clock for a servomotor whose tick lasts `host_ticks_per_tick` ticks of the host
+    /// clock (11.2 for a host clock counting milliseconds), until it is measured.
+    pub fn new(host_ticks_per_tick: f32) -> ServoClock {
+        ServoClock {
+            nominal: host_ticks_per_tick,
+            mask: 0xFF,
+            last_tick: None,
+            origin: (0, 0),
+            latest: (0, 0),
+        }
+    }
+
+    /// Use a `Tick` register of 16 bits.
+    pub fn with_wide_tick(mut self) -> ServoClock {
+        self.mask = 0xFFFF;
+        self
+    }
+
+    /// Record that the `Tick` register was `tick` at the host time `now`.
+    pub fn update(&mut self, tick: u16, now: u32) {
+        let tick = tick & self.mask;
+        match self.last_tick {
+            Some(last) => {
+                let elapsed = u32::from(tick.wrapping_sub(last) & self.mask);
+                self.latest = (now, self.latest.1.wrapping_add(elapsed));
+            }
+            None => {
+                self.origin = (now, 0);
+                self.latest = (now, 0);
+            }
+        }
+        self.last_tick = Some(tick);
+    }
+
+    /// Record the `Tick` register carried by `packet`, received at the host time `now`. Return
+    /// false if `packet` is not the answer to a read of `Tick`.
+    pub fn update_from_packet(&mut self, packet: &ACKPacket, now: u32) -> bool {
+        match packet.cmd {
+            Command::RamRead { data } if data.addr == ReadableRamAddr::Tick => {
+                let tick = match data.data_len {
+                    1 => u16::from(data.data[0]),
+                    _ => match data.as_u16() {
+                        Some(tick) => tick,
+                        None => return false,
+                    },
+                };
+                self.update(tick, now);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Return the number of host ticks per tick of the servomotor, measured since the first
+    /// update, or the nominal value until the servomotor ticked.
+    pub fn host_ticks_per_tick(&self) -> f32 {
+        let ticks = self.latest.1.wrapping_sub(self.origin.1);
+        if ticks == 0 {
+            self.nominal
+        } else {
+            self.latest.0.wrapping_sub(self.origin.0) as f32 / ticks as f32
+        }
+    }
+
+    /// Return the ratio of the measured duration of a tick to its nominal duration. A servomotor
+    /// whose clock runs slow has a drift above 1.
+    pub fn drift(&self) -> f32 {
+        self.host_ticks_per_tick() / self.nominal
+    }
+
+    /// Return the number of ticks of the servomotor since the first update, estimated at the
+    /// host time `now`, or `None` before the first update.
+    pub fn servo_ticks(&self, now: u32) -> Option<u32> {
+        self.last_tick?;
+        let elapsed = now.wrapping_sub(self.latest.0) as i32 as f32 / self.host_ticks_per_tick();
+        Some(self.latest.1.wrapping_add(elapsed as i32 as u32))
+    }
+
+    /// Return the host time at which the servomotor reaches `ticks` ticks since the first update,
+    /// or `None` before the first update.
+    pub fn host_time(&self, ticks: u32) -> Option<u32> {
+        self.last_tick?;
+        let elapsed = ticks.wrapping_sub(self.latest.1) as i32 as f32 * self.host_ticks_per_tick();
+        Some(self.latest.0.wrapping_add(elapsed as i32 as u32))
+    }
+
+    /// Return the playtime making a motion started at the host time `now` end at `deadline`,
+    /// rounded to the closest tick of the servomotor and clamped to the largest playtime (254).
+    pub fn playtime_until(&self, now: u32, deadline: u32) -> u8 {
+        let remaining = deadline.wrapping_sub(now) as i32;
+        if remaining <= 0 {
+            return 0;
+        }
+        let ticks = remaining as f32 / self.host_ticks_per_tick() + 0.5;
+        ticks.clamp(0.0, 254.0) as u8
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "std")]
+    use clock::InstantTicks;
+    use clock::{Clock, DelayTick, ServoClock, TickClock, TimestampedReader};
+    use reader::Command;
+
+    use core::cell::Cell;
+
+    struct FakeClock(Cell<u32>);
+
+    impl Clock for FakeClock {
+        fn now(&self) -> u32 {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn timestamps() {
+        let mut reader = TimestampedReader::new(FakeClock(Cell::new(10)));
+        let sjog = [0xFF, 0xFF, 0x09, 0xFD, 0x46, 0xB2, 0x4C, 0x08, 0x08];
+        let stat = [0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01];
+        reader.parse(&sjog);
+        reader.parse(&stat[..4]);
+        reader.clock().0.set(u32::MAX);
+        reader.parse(&stat[4..]);
+        assert_eq!(reader.available_messages(), 2);
+
+        let first = reader.pop_ack_packet().unwrap();
+        assert_eq!((first.packet.cmd, first.timestamp), (Command::SJog, 10));
+        let second = reader.pop_ack_packet().unwrap();
+        assert_eq!(
+            (second.packet.cmd, second.timestamp),
+            (Command::Stat, u32::MAX)
+        );
+        assert_eq!(second.age(4), 5);
+        assert!(!second.is_stale(4, 5));
+        assert!(second.is_stale(5, 5));
+        assert_eq!(reader.pop_ack_packet(), None);
+    }
+
+    #[test]
+    fn servo_clock() {
+        let mut clock = ServoClock::new(10.0);
+        assert_eq!(clock.servo_ticks(0), None);
+        clock.update(250, u32::MAX - 99);
+        assert_eq!(clock.host_ticks_per_tick(), 10.0);
+        assert_eq!(clock.playtime_until(0, 100), 10);
+
+        // The register wrapped around : 30 ticks in 330 host ticks.
+        clock.update(24, 230);
+        assert_eq!(clock.host_ticks_per_tick(), 11.0);
+        assert_eq!(clock.drift(), 1.1);
+        assert_eq!(clock.servo_ticks(340), Some(40));
+        assert_eq!(clock.host_time(40), Some(340));
+        assert_eq!(clock.host_time(20), Some(120));
+        assert_eq!(clock.playtime_until(230, 340), 10);
+        assert_eq!(clock.playtime_until(230, 100_000), 254);
+        assert_eq!(clock.playtime_until(230, 200), 0);
+
+        let mut wide = ServoClock::new(10.0).with_wide_tick();
+        wide.update(0xFFFF, 0);
+        wide.update(0x0100, 2570);
+        assert_eq!(wide.servo_ticks(2570), Some(257));
+    }
+
+    struct Timer(Cell<u64>);
+
+    impl DelayTick for Timer {
+        fn ticks(&self) -> u64 {
+            self.0.get()
+        }
+
+        fn tick_hz(&self) -> u32 {
+            32_768
+        }
+    }
+
+    #[test]
+    fn delay_ticks() {
+        let timer = Timer(Cell::new(u64::from(u32::MAX) + 10));
+        assert_eq!(timer.ticks_from_us(1000), 33);
+        assert_eq!(timer.elapsed_us(u64::from(u32::MAX) - 23), 1007);
+        let clock = TickClock(&timer);
+        assert_eq!(clock.now(), 9);
+
+        #[cfg(feature = "std")]
+        {
+            let instant = InstantTicks::new();
+            let start = instant.ticks();
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            assert!(instant.elapsed_us(start) >= 2000);
+        }
+
+        #[cfg(feature = "embedded-hal")]
+        {
+            use clock::DelayTicks;
+            use embedded_hal::delay::DelayNs;
+
+            struct Spin;
+            impl DelayNs for Spin {
+                fn delay_ns(&mut self, _ns: u32) {}
+            }
+
+            let mut delay = DelayTicks::new(Spin, 1000);
+            delay.delay_us(1500);
+            assert_eq!(delay.ticks(), 1);
+            delay.delay_us(500);
+            delay.delay_ticks(3);
+            assert_eq!(delay.ticks(), 5);
+        }
+    }
+}
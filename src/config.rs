@@ -0,0 +1,1179 @@
+//! Typed values for the configuration registers of the servomotors.
+
+#[cfg(feature = "eep")]
+use addr::EEPReadData;
+use addr::RamReadData;
+#[cfg(feature = "eep")]
+use addr::ReadableEEPAddr;
+use addr::ReadableRamAddr;
+#[cfg(feature = "eep")]
+use addr::WritableEEPAddr;
+use addr::WritableRamAddr;
+#[cfg(feature = "eep")]
+use addr::EEP_MAP_SIZE;
+#[cfg(feature = "eep")]
+use model::Model;
+use model::DEGREES_PER_POSITION;
+use reader::StatusError;
+
+#[cfg(feature = "eep")]
+use core::slice::Iter;
+use try_from::TryFrom;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The maximum PWM applied by the servomotors.
+pub const MAX_PWM: u16 = 1023;
+
+/// The largest value allowed by the datasheet for the registers counting periods of 11.2 ms, like
+/// `LedBlinkPeriod` and `PacketGarbageDetectionPeriod` : 2.845 s.
+pub const MAX_PERIOD_TICKS: u8 = 254;
+
+/// The error returned when a configuration value is invalid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConfigError {
+    /// The value of the register with this name is out of the range allowed by the datasheet.
+    OutOfRange(&'static str),
+    /// A byte of the register with this name is missing from a register table.
+    MissingRegister(&'static str),
+    /// This address is not one of the registers of a register table.
+    UnknownAddress(u8),
+}
+
+/// The value of the `OverloadPWMThreshold` register.
+///
+/// The servomotor raises an `OverloadDetected` error when the PWM applied to its motor stays above
+/// this threshold for longer than the `OverloadDetectionPeriod` register (11.2 ms per tick).
+/// Depending on the `TorquePolicy` register, the torque is then released to protect the motor.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum OverloadThreshold {
+    /// A percentage of the maximum PWM, clamped to 100.
+    Percent(u8),
+    /// A raw PWM value, clamped to `MAX_PWM`.
+    Pwm(u16),
+}
+
+impl OverloadThreshold {
+    /// Return the PWM threshold.
+    pub fn pwm(self) -> u16 {
+        match self {
+            OverloadThreshold::Percent(percent) => {
+                (u32::from(percent.min(100)) * u32::from(MAX_PWM) / 100) as u16
+            }
+            OverloadThreshold::Pwm(pwm) => pwm.min(MAX_PWM),
+        }
+    }
+
+    /// Return the little endian bytes of the register.
+    pub fn to_bytes(self) -> (u8, u8) {
+        let pwm = self.pwm();
+        (pwm as u8, (pwm >> 8) as u8)
+    }
+
+    /// Decode the answer to a RAM read of `OverloadPWMThreshold`.
+    pub fn from_read(data: &RamReadData) -> Option<OverloadThreshold> {
+        if data.addr == ReadableRamAddr::OverloadPWMThreshold {
+            data.as_u16().map(OverloadThreshold::Pwm)
+        } else {
+            None
+        }
+    }
+}
+
+/// The registers controlling the compliance of the servomotor around its goal position.
+///
+/// * Inside the dead zone around the goal position, no PWM is applied.
+/// * Outside of the dead zone, the PWM starts at the saturator offset and then grows with the
+///   position error following the saturator slope.
+///
+/// Those registers work together, so they are set and read together.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ComplianceSettings {
+    /// The `DeadZone` register, between 0 and 254
+    pub dead_zone: u8,
+    /// The `SaturatorOffset` register, between 0 and 254
+    pub saturator_offset: u8,
+    /// The `SaturatorSlope` register, between 0 and 32767
+    pub saturator_slope: u16,
+}
+
+impl ComplianceSettings {
+    /// The RAM addresses holding the compliance settings.
+    pub const ADDRESSES: [ReadableRamAddr; 3] = [
+        ReadableRamAddr::DeadZone,
+        ReadableRamAddr::SaturatorOffset,
+        ReadableRamAddr::SaturatorSlope,
+    ];
+
+    /// Check the values against the ranges given by the datasheet.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.dead_zone > 254 {
+            Err(ConfigError::OutOfRange(ReadableRamAddr::DeadZone.name()))
+        } else if self.saturator_offset > 254 {
+            Err(ConfigError::OutOfRange(
+                ReadableRamAddr::SaturatorOffset.name(),
+            ))
+        } else if self.saturator_slope > 0x7FFF {
+            Err(ConfigError::OutOfRange(
+                ReadableRamAddr::SaturatorSlope.name(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Return the RAM writes applying those settings.
+    pub fn ram_writes(&self) -> Result<[WritableRamAddr; 3], ConfigError> {
+        self.validate()?;
+        Ok([
+            WritableRamAddr::DeadZone(self.dead_zone),
+            WritableRamAddr::SaturatorOffset(self.saturator_offset),
+            WritableRamAddr::SaturatorSlope(
+                self.saturator_slope as u8,
+                (self.saturator_slope >> 8) as u8,
+            ),
+        ])
+    }
+
+    /// Return the EEP writes applying those settings after the next reboot.
+    #[cfg(feature = "eep")]
+    pub fn eep_writes(&self) -> Result<[WritableEEPAddr; 3], ConfigError> {
+        self.validate()?;
+        Ok([
+            WritableEEPAddr::DeadZone(self.dead_zone),
+            WritableEEPAddr::SaturatorOffset(self.saturator_offset),
+            WritableEEPAddr::SaturatorSlope(
+                self.saturator_slope as u8,
+                (self.saturator_slope >> 8) as u8,
+            ),
+        ])
+    }
+
+    /// Update the settings from the answer to a RAM read of one of the `ADDRESSES`.
+    /// Return false if `data` is not one of them.
+    pub fn update_from_read(&mut self, data: &RamReadData) -> bool {
+        match (data.addr, data.data_len) {
+            (ReadableRamAddr::DeadZone, 1) => self.dead_zone = data.data[0],
+            (ReadableRamAddr::SaturatorOffset, 1) => self.saturator_offset = data.data[0],
+            (ReadableRamAddr::SaturatorSlope, _) => match data.as_u16() {
+                Some(slope) => self.saturator_slope = slope,
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// How the LED shows the alarms : the errors which make it blink, and the blink period.
+///
+/// The `AlarmLEDPolicy` register holds the errors, with the bits of the `StatusError` register,
+/// and the `LedBlinkPeriod` register holds the period in units of 11.2 ms. They are set and read
+/// together.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::config::BlinkPattern;
+/// use drs_0x01::reader::StatusError;
+///
+/// let pattern = BlinkPattern::new(504, &[StatusError::OverloadDetected]);
+/// assert_eq!(pattern.period_ticks(), 0x2D);
+/// assert!(pattern.blinks_on(StatusError::OverloadDetected));
+/// assert!(!pattern.blinks_on(StatusError::InvalidPacket));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BlinkPattern {
+    period: u8,
+    policy: u8,
+}
+
+impl BlinkPattern {
+    /// The RAM addresses holding the blink pattern.
+    pub const ADDRESSES: [ReadableRamAddr; 2] = [
+        ReadableRamAddr::AlarmLEDPolicy,
+        ReadableRamAddr::LedBlinkPeriod,
+    ];
+
+    /// Create a pattern blinking every `period_ms` milliseconds, rounded to a multiple of 11.2 ms
+    /// between 11.2 ms and 2.845 s, on any of `errors`.
+    pub fn new(period_ms: u16, errors: &[StatusError]) -> BlinkPattern {
+        let ticks = (u32::from(period_ms) * 10 + 56) / 112;
+        BlinkPattern {
+            period: ticks.clamp(1, u32::from(MAX_PERIOD_TICKS)) as u8,
+            policy: errors.iter().fold(0, |policy, error| policy | error.bits()),
+        }
+    }
+
+    /// Create a pattern from the values of the `LedBlinkPeriod` and `AlarmLEDPolicy` registers.
+    pub fn from_raw(period_ticks: u8, policy: u8) -> BlinkPattern {
+        BlinkPattern {
+            period: period_ticks,
+            policy,
+        }
+    }
+
+    /// Return the value of the `LedBlinkPeriod` register.
+    pub fn period_ticks(self) -> u8 {
+        self.period
+    }
+
+    /// Return the blink period in milliseconds.
+    pub fn period_ms(self) -> u16 {
+        (u32::from(self.period) * 112 / 10) as u16
+    }
+
+    /// Return the value of the `AlarmLEDPolicy` register.
+    pub fn policy(self) -> u8 {
+        self.policy
+    }
+
+    /// Return true if the LED blinks when the servomotor reports `error`.
+    pub fn blinks_on(self, error: StatusError) -> bool {
+        self.policy & error.bits() != 0
+    }
+
+    /// Return the RAM writes applying this pattern.
+    pub fn ram_writes(self) -> [WritableRamAddr; 2] {
+        [
+            WritableRamAddr::AlarmLEDPolicy(self.policy),
+            WritableRamAddr::LedBlinkPeriod(self.period),
+        ]
+    }
+
+    /// Return the EEP writes applying this pattern after the next reboot.
+    #[cfg(feature = "eep")]
+    pub fn eep_writes(self) -> [WritableEEPAddr; 2] {
+        [
+            WritableEEPAddr::AlarmLEDPolicy(self.policy),
+            WritableEEPAddr::LedBlinkPeriod(self.period),
+        ]
+    }
+
+    /// Update the pattern from the answer to a RAM read of one of the `ADDRESSES`.
+    /// Return false if `data` is not one of them.
+    pub fn update_from_read(&mut self, data: &RamReadData) -> bool {
+        match (data.addr, data.data_len) {
+            (ReadableRamAddr::AlarmLEDPolicy, 1) => self.policy = data.data[0],
+            (ReadableRamAddr::LedBlinkPeriod, 1) => self.period = data.data[0],
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// The baudrates supported by the servomotors, stored in the `BaudRate` EEP register.
+///
+/// Writing any other value to the register prevents any communication with the servomotor until
+/// its EEP memory is rolled back.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BaudRate {
+    /// 57 600 bps
+    Baud57600,
+    /// 115 200 bps, the factory default
+    #[default]
+    Baud115200,
+    /// 200 000 bps
+    Baud200000,
+    /// 250 000 bps
+    Baud250000,
+    /// 400 000 bps
+    Baud400000,
+    /// 500 000 bps
+    Baud500000,
+    /// 666 666 bps
+    Baud666666,
+}
+
+impl BaudRate {
+    /// Every supported baudrate, from the slowest to the fastest.
+    pub const ALL: [BaudRate; 7] = [
+        BaudRate::Baud57600,
+        BaudRate::Baud115200,
+        BaudRate::Baud200000,
+        BaudRate::Baud250000,
+        BaudRate::Baud400000,
+        BaudRate::Baud500000,
+        BaudRate::Baud666666,
+    ];
+
+    /// Return the value of the `BaudRate` register.
+    pub fn code(self) -> u8 {
+        match self {
+            BaudRate::Baud57600 => 0x22,
+            BaudRate::Baud115200 => 0x10,
+            BaudRate::Baud200000 => 0x09,
+            BaudRate::Baud250000 => 0x07,
+            BaudRate::Baud400000 => 0x04,
+            BaudRate::Baud500000 => 0x03,
+            BaudRate::Baud666666 => 0x02,
+        }
+    }
+
+    /// Return the baudrate in bits per second.
+    pub fn bps(self) -> u32 {
+        match self {
+            BaudRate::Baud57600 => 57_600,
+            BaudRate::Baud115200 => 115_200,
+            BaudRate::Baud200000 => 200_000,
+            BaudRate::Baud250000 => 250_000,
+            BaudRate::Baud400000 => 400_000,
+            BaudRate::Baud500000 => 500_000,
+            BaudRate::Baud666666 => 666_666,
+        }
+    }
+
+    /// Return the supported baudrate of `bps` bits per second, if any.
+    pub fn from_bps(bps: u32) -> Option<BaudRate> {
+        BaudRate::ALL.iter().cloned().find(|baud| baud.bps() == bps)
+    }
+
+    /// Decode the answer to an EEP read of `BaudRate`.
+    #[cfg(feature = "eep")]
+    pub fn from_read(data: &EEPReadData) -> Option<BaudRate> {
+        if data.addr == ReadableEEPAddr::BaudRate && data.data_len == 1 {
+            BaudRate::try_from(data.data[0]).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl TryFrom<u8> for BaudRate {
+    type Err = ConfigError;
+
+    fn try_from(code: u8) -> Result<BaudRate, ConfigError> {
+        BaudRate::ALL
+            .iter()
+            .cloned()
+            .find(|baud| baud.code() == code)
+            .ok_or(ConfigError::OutOfRange("BaudRate"))
+    }
+}
+
+#[cfg(feature = "eep")]
+impl From<BaudRate> for WritableEEPAddr {
+    fn from(baud: BaudRate) -> WritableEEPAddr {
+        WritableEEPAddr::BaudRate(baud.code())
+    }
+}
+
+/// The states of the `TorqueControl` register.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TorqueState {
+    /// The motor is not powered, the output shaft turns freely
+    Free,
+    /// The motor is shorted, the output shaft is hard to turn
+    Brake,
+    /// The motor is powered and follows the commands
+    On,
+}
+
+impl TorqueState {
+    /// Return the value of the `TorqueControl` register.
+    pub fn code(self) -> u8 {
+        match self {
+            TorqueState::Free => 0x00,
+            TorqueState::Brake => 0x40,
+            TorqueState::On => 0x60,
+        }
+    }
+}
+
+impl TryFrom<u8> for TorqueState {
+    type Err = ConfigError;
+
+    fn try_from(code: u8) -> Result<TorqueState, ConfigError> {
+        match code {
+            0x00 => Ok(TorqueState::Free),
+            0x40 => Ok(TorqueState::Brake),
+            0x60 => Ok(TorqueState::On),
+            _ => Err(ConfigError::OutOfRange(
+                ReadableRamAddr::TorqueControl.name(),
+            )),
+        }
+    }
+}
+
+/// The values of the `AckPolicy` register : which requests the servomotor answers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AckPolicy {
+    /// No request is answered
+    NoReply,
+    /// Only the **EEP_READ**, **RAM_READ** and **STAT** requests are answered
+    #[default]
+    ReplyToRead,
+    /// Every request is answered
+    ReplyToAll,
+}
+
+impl AckPolicy {
+    /// Return the value of the `AckPolicy` register.
+    pub fn code(self) -> u8 {
+        match self {
+            AckPolicy::NoReply => 0x00,
+            AckPolicy::ReplyToRead => 0x01,
+            AckPolicy::ReplyToAll => 0x02,
+        }
+    }
+}
+
+impl TryFrom<u8> for AckPolicy {
+    type Err = ConfigError;
+
+    fn try_from(code: u8) -> Result<AckPolicy, ConfigError> {
+        match code {
+            0x00 => Ok(AckPolicy::NoReply),
+            0x01 => Ok(AckPolicy::ReplyToRead),
+            0x02 => Ok(AckPolicy::ReplyToAll),
+            _ => Err(ConfigError::OutOfRange(ReadableRamAddr::AckPolicy.name())),
+        }
+    }
+}
+
+/// The value of the `InpositionMargin` register : the largest position error, in raw position
+/// values, for which the servomotor reports to be in position.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct InpositionMargin(u8);
+
+impl InpositionMargin {
+    /// The largest value allowed by the datasheet.
+    pub const MAX: u8 = 254;
+
+    /// Create a margin of `raw` position values, clamped to `MAX`.
+    pub const fn new(raw: u8) -> InpositionMargin {
+        if raw > InpositionMargin::MAX {
+            InpositionMargin(InpositionMargin::MAX)
+        } else {
+            InpositionMargin(raw)
+        }
+    }
+
+    /// Create a margin of `degrees`, rounded to the closest raw position value (0.325° each) and
+    /// clamped between 0 and `MAX`.
+    pub fn from_degrees(degrees: f32) -> InpositionMargin {
+        let raw = degrees / DEGREES_PER_POSITION + 0.5;
+        InpositionMargin::new(raw.clamp(0.0, f32::from(InpositionMargin::MAX)) as u8)
+    }
+
+    /// Return the value of the register.
+    pub fn raw(self) -> u8 {
+        self.0
+    }
+
+    /// Return the margin in degrees.
+    pub fn degrees(self) -> f32 {
+        f32::from(self.0) * DEGREES_PER_POSITION
+    }
+
+    /// Return true if `position` is within the margin around `goal`, as the servomotor does.
+    pub fn contains(self, goal: u16, position: u16) -> bool {
+        goal.abs_diff(position) <= u16::from(self.0)
+    }
+
+    /// Decode the answer to a RAM read of `InpositionMargin`.
+    pub fn from_read(data: &RamReadData) -> Option<InpositionMargin> {
+        if data.addr == ReadableRamAddr::InpositionMargin && data.data_len == 1 {
+            Some(InpositionMargin(data.data[0]))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<InpositionMargin> for WritableRamAddr {
+    fn from(margin: InpositionMargin) -> WritableRamAddr {
+        WritableRamAddr::InpositionMargin(margin.raw())
+    }
+}
+
+#[cfg(feature = "eep")]
+impl From<InpositionMargin> for WritableEEPAddr {
+    fn from(margin: InpositionMargin) -> WritableEEPAddr {
+        WritableEEPAddr::InpositionMargin(margin.raw())
+    }
+}
+
+/// The number of entries of the table of a [`ServoConfig`](struct.ServoConfig.html) : one per
+/// byte of the EEP registers it holds.
+#[cfg(feature = "eep")]
+pub const CONFIG_TABLE_LEN: usize = 42;
+
+/// The EEP registers held by a [`ServoConfig`](struct.ServoConfig.html), in address order.
+#[cfg(feature = "eep")]
+const CONFIG_REGISTERS: [ReadableEEPAddr; 32] = [
+    ReadableEEPAddr::BaudRate,
+    ReadableEEPAddr::ID,
+    ReadableEEPAddr::AckPolicy,
+    ReadableEEPAddr::AlarmLEDPolicy,
+    ReadableEEPAddr::TorquePolicy,
+    ReadableEEPAddr::MaxTemperature,
+    ReadableEEPAddr::MinVoltage,
+    ReadableEEPAddr::MaxVoltage,
+    ReadableEEPAddr::AccelerationRatio,
+    ReadableEEPAddr::MaxAccelerationTime,
+    ReadableEEPAddr::DeadZone,
+    ReadableEEPAddr::SaturatorOffset,
+    ReadableEEPAddr::SaturatorSlope,
+    ReadableEEPAddr::PWMOffset,
+    ReadableEEPAddr::MinPWM,
+    ReadableEEPAddr::MaxPWM,
+    ReadableEEPAddr::OverloadPWMThreshold,
+    ReadableEEPAddr::MinPosition,
+    ReadableEEPAddr::MaxPosition,
+    ReadableEEPAddr::PositionKp,
+    ReadableEEPAddr::PositionKd,
+    ReadableEEPAddr::PositionKi,
+    ReadableEEPAddr::PositionFFFirstGain,
+    ReadableEEPAddr::PositionFFSecondGain,
+    ReadableEEPAddr::LedBlinkPeriod,
+    ReadableEEPAddr::ADCFaultCheckPeriod,
+    ReadableEEPAddr::PacketGarbageDetectionPeriod,
+    ReadableEEPAddr::StopDetectionPeriod,
+    ReadableEEPAddr::OverloadDetectionPeriod,
+    ReadableEEPAddr::StopThreshold,
+    ReadableEEPAddr::InpositionMargin,
+    ReadableEEPAddr::CalibrationDifference,
+];
+
+/// The configuration stored in the EEP memory of a servomotor.
+///
+/// The registers are exposed with their typed values. For the tools speaking generic register
+/// tables, [`to_table`](#method.to_table) and [`from_table`](#method.from_table) convert the
+/// configuration to and from a list of `(address, byte)` pairs.
+#[cfg(feature = "eep")]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ServoConfig {
+    /// The `BaudRate` register
+    pub baud_rate: BaudRate,
+    /// The `ID` register
+    pub id: u8,
+    /// The `AckPolicy` register
+    pub ack_policy: u8,
+    /// The `AlarmLEDPolicy` register
+    pub alarm_led_policy: u8,
+    /// The `TorquePolicy` register
+    pub torque_policy: u8,
+    /// The `MaxTemperature` register
+    pub max_temperature: u8,
+    /// The `MinVoltage` register
+    pub min_voltage: u8,
+    /// The `MaxVoltage` register
+    pub max_voltage: u8,
+    /// The `AccelerationRatio` register
+    pub acceleration_ratio: u8,
+    /// The `MaxAccelerationTime` register
+    pub max_acceleration_time: u8,
+    /// The `DeadZone`, `SaturatorOffset` and `SaturatorSlope` registers
+    pub compliance: ComplianceSettings,
+    /// The `PWMOffset` register
+    pub pwm_offset: i8,
+    /// The `MinPWM` register
+    pub min_pwm: u8,
+    /// The `MaxPWM` register
+    pub max_pwm: u16,
+    /// The `OverloadPWMThreshold` register
+    pub overload_threshold: OverloadThreshold,
+    /// The `MinPosition` register
+    pub min_position: u16,
+    /// The `MaxPosition` register
+    pub max_position: u16,
+    /// The `PositionKp` register
+    pub position_kp: u16,
+    /// The `PositionKd` register
+    pub position_kd: u16,
+    /// The `PositionKi` register
+    pub position_ki: u16,
+    /// The `PositionFFFirstGain` register
+    pub position_ff_first_gain: u16,
+    /// The `PositionFFSecondGain` register
+    pub position_ff_second_gain: u16,
+    /// The `LedBlinkPeriod` register
+    pub led_blink_period: u8,
+    /// The `ADCFaultCheckPeriod` register
+    pub adc_fault_check_period: u8,
+    /// The `PacketGarbageDetectionPeriod` register
+    pub packet_garbage_detection_period: u8,
+    /// The `StopDetectionPeriod` register
+    pub stop_detection_period: u8,
+    /// The `OverloadDetectionPeriod` register
+    pub overload_detection_period: u8,
+    /// The `StopThreshold` register
+    pub stop_threshold: u8,
+    /// The `InpositionMargin` register
+    pub inposition_margin: InpositionMargin,
+    /// The `CalibrationDifference` register
+    pub calibration_difference: u8,
+}
+
+#[cfg(feature = "eep")]
+impl ServoConfig {
+    /// Return the configuration of a servomotor leaving the factory, as given by the datasheet.
+    /// The DRS-0101 and the DRS-0201 share the same defaults.
+    pub const fn factory_default(model: Model) -> ServoConfig {
+        match model {
+            Model::Drs0101 | Model::Drs0201 => ServoConfig {
+                baud_rate: BaudRate::Baud115200,
+                id: 0xDB,
+                ack_policy: 0x01,
+                alarm_led_policy: 0x7F,
+                torque_policy: 0x35,
+                max_temperature: 0xDF,
+                min_voltage: 0x5B,
+                max_voltage: 0x89,
+                acceleration_ratio: 0x19,
+                max_acceleration_time: 0x2D,
+                compliance: ComplianceSettings {
+                    dead_zone: 0x00,
+                    saturator_offset: 0x00,
+                    saturator_slope: 0x0000,
+                },
+                pwm_offset: 0x00,
+                min_pwm: 0x00,
+                max_pwm: 0x03FF,
+                overload_threshold: OverloadThreshold::Pwm(0x03FF),
+                min_position: 0x0015,
+                max_position: 0x03EA,
+                position_kp: 0x01B8,
+                position_kd: 0x1F40,
+                position_ki: 0x0000,
+                position_ff_first_gain: 0x0000,
+                position_ff_second_gain: 0x0000,
+                led_blink_period: 0x2D,
+                adc_fault_check_period: 0x2D,
+                packet_garbage_detection_period: 0x12,
+                stop_detection_period: 0x1B,
+                overload_detection_period: 0x96,
+                stop_threshold: 0x03,
+                inposition_margin: InpositionMargin(0x03),
+                calibration_difference: 0x00,
+            },
+        }
+    }
+
+    /// Return the registers whose value differs from `reference`, in address order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate drs_0x01;
+    /// use drs_0x01::config::ServoConfig;
+    /// use drs_0x01::{Model, ReadableEEPAddr};
+    ///
+    /// let factory = ServoConfig::factory_default(Model::Drs0101);
+    /// let mut scanned = factory;
+    /// scanned.id = 0x40;
+    /// let deviation = scanned.deviations(&factory).next().unwrap();
+    /// assert_eq!(deviation.register, ReadableEEPAddr::ID);
+    /// assert_eq!((deviation.reference, deviation.value), (0xDB, 0x40));
+    /// ```
+    pub fn deviations(&self, reference: &ServoConfig) -> Deviations {
+        Deviations {
+            memory: self.memory(),
+            reference: reference.memory(),
+            registers: CONFIG_REGISTERS.iter(),
+        }
+    }
+
+    /// Return the EEP writes turning `current` into this configuration, in address order. Only the
+    /// registers which differ are written. The `ID` and `BaudRate` registers are left as they
+    /// are : changing them makes the servomotor unreachable at its current address, so they must
+    /// be written on their own, see
+    /// [`Servo::set_baud_rate`](../struct.Servo.html#method.set_baud_rate).
+    ///
+    /// The writes take effect after the next reboot.
+    pub fn eep_writes(&self, current: &ServoConfig) -> impl Iterator<Item = WritableEEPAddr> {
+        self.deviations(current)
+            .filter(|deviation| {
+                !matches!(
+                    deviation.register,
+                    ReadableEEPAddr::ID | ReadableEEPAddr::BaudRate
+                )
+            })
+            .filter_map(|deviation| deviation.eep_write())
+    }
+
+    /// Return the configuration as `(address, byte)` pairs, sorted by EEP address. The registers
+    /// of two bytes are split in two entries, the low byte first.
+    pub fn to_table(self) -> [(u8, u8); CONFIG_TABLE_LEN] {
+        let memory = self.memory();
+        let mut table = [(0, 0); CONFIG_TABLE_LEN];
+        let addresses = CONFIG_REGISTERS.iter().flat_map(|register| {
+            let addr = u8::from(*register);
+            addr..addr + register.bytes()
+        });
+        for (entry, addr) in table.iter_mut().zip(addresses) {
+            *entry = (addr, memory[usize::from(addr)]);
+        }
+        table
+    }
+
+    /// Build a configuration from `(address, byte)` pairs, in any order. When an address appears
+    /// several times, the last byte is kept.
+    ///
+    /// # Errors
+    ///
+    /// * `UnknownAddress` if an address is not one of the registers of the configuration,
+    /// * `MissingRegister` if a byte of a register is not in the table,
+    /// * `OutOfRange` if the baudrate or the compliance settings are invalid.
+    pub fn from_table(table: &[(u8, u8)]) -> Result<ServoConfig, ConfigError> {
+        let mut memory = [None; EEP_MAP_SIZE as usize];
+        for &(addr, byte) in table {
+            let known = CONFIG_REGISTERS.iter().any(|register| {
+                let start = u8::from(*register);
+                (start..start + register.bytes()).contains(&addr)
+            });
+            if !known {
+                return Err(ConfigError::UnknownAddress(addr));
+            }
+            memory[usize::from(addr)] = Some(byte);
+        }
+        for register in CONFIG_REGISTERS.iter() {
+            let addr = usize::from(u8::from(*register));
+            if memory[addr..addr + usize::from(register.bytes())]
+                .iter()
+                .any(Option::is_none)
+            {
+                return Err(ConfigError::MissingRegister(register.name()));
+            }
+        }
+        let byte = |addr: usize| memory[addr].unwrap_or(0);
+        let word = |addr: usize| u16::from(byte(addr)) | u16::from(byte(addr + 1)) << 8;
+        let config = ServoConfig {
+            baud_rate: BaudRate::try_from(byte(4))?,
+            id: byte(6),
+            ack_policy: byte(7),
+            alarm_led_policy: byte(8),
+            torque_policy: byte(9),
+            max_temperature: byte(11),
+            min_voltage: byte(12),
+            max_voltage: byte(13),
+            acceleration_ratio: byte(14),
+            max_acceleration_time: byte(15),
+            compliance: ComplianceSettings {
+                dead_zone: byte(16),
+                saturator_offset: byte(17),
+                saturator_slope: word(18),
+            },
+            pwm_offset: byte(20) as i8,
+            min_pwm: byte(21),
+            max_pwm: word(22),
+            overload_threshold: OverloadThreshold::Pwm(word(24)),
+            min_position: word(26),
+            max_position: word(28),
+            position_kp: word(30),
+            position_kd: word(32),
+            position_ki: word(34),
+            position_ff_first_gain: word(36),
+            position_ff_second_gain: word(38),
+            led_blink_period: byte(44),
+            adc_fault_check_period: byte(45),
+            packet_garbage_detection_period: byte(46),
+            stop_detection_period: byte(47),
+            overload_detection_period: byte(48),
+            stop_threshold: byte(49),
+            inposition_margin: InpositionMargin(byte(50)),
+            calibration_difference: byte(53),
+        };
+        config.compliance.validate()?;
+        Ok(config)
+    }
+
+    /// Return the EEP memory holding the configuration, the other bytes being zero.
+    pub(crate) fn memory(self) -> [u8; EEP_MAP_SIZE as usize] {
+        let mut memory = [0; EEP_MAP_SIZE as usize];
+        {
+            let mut word = |addr: usize, value: u16| {
+                memory[addr] = value as u8;
+                memory[addr + 1] = (value >> 8) as u8;
+            };
+            word(18, self.compliance.saturator_slope);
+            word(22, self.max_pwm);
+            word(24, self.overload_threshold.pwm());
+            word(26, self.min_position);
+            word(28, self.max_position);
+            word(30, self.position_kp);
+            word(32, self.position_kd);
+            word(34, self.position_ki);
+            word(36, self.position_ff_first_gain);
+            word(38, self.position_ff_second_gain);
+        }
+        memory[4] = self.baud_rate.code();
+        memory[6] = self.id;
+        memory[7] = self.ack_policy;
+        memory[8] = self.alarm_led_policy;
+        memory[9] = self.torque_policy;
+        memory[11] = self.max_temperature;
+        memory[12] = self.min_voltage;
+        memory[13] = self.max_voltage;
+        memory[14] = self.acceleration_ratio;
+        memory[15] = self.max_acceleration_time;
+        memory[16] = self.compliance.dead_zone;
+        memory[17] = self.compliance.saturator_offset;
+        memory[20] = self.pwm_offset as u8;
+        memory[21] = self.min_pwm;
+        memory[44] = self.led_blink_period;
+        memory[45] = self.adc_fault_check_period;
+        memory[46] = self.packet_garbage_detection_period;
+        memory[47] = self.stop_detection_period;
+        memory[48] = self.overload_detection_period;
+        memory[49] = self.stop_threshold;
+        memory[50] = self.inposition_margin.raw();
+        memory[53] = self.calibration_difference;
+        memory
+    }
+}
+
+/// A register of a [`ServoConfig`](struct.ServoConfig.html) which differs from a reference.
+#[cfg(feature = "eep")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Deviation {
+    /// The register
+    pub register: ReadableEEPAddr,
+    /// The value of the register in the reference
+    pub reference: u16,
+    /// The value of the register in the configuration
+    pub value: u16,
+}
+
+#[cfg(feature = "eep")]
+impl Deviation {
+    /// Return the EEP write setting the register to the value of the configuration, or `None`
+    /// if the register can not be written.
+    pub fn eep_write(&self) -> Option<WritableEEPAddr> {
+        WritableEEPAddr::try_from(u8::from(self.register))
+            .ok()
+            .map(|addr| addr.with_data([self.value as u8, (self.value >> 8) as u8]))
+    }
+}
+
+/// The iterator returned by [`ServoConfig::deviations`](struct.ServoConfig.html#method.deviations).
+#[cfg(feature = "eep")]
+#[derive(Clone, Debug)]
+pub struct Deviations {
+    memory: [u8; EEP_MAP_SIZE as usize],
+    reference: [u8; EEP_MAP_SIZE as usize],
+    registers: Iter<'static, ReadableEEPAddr>,
+}
+
+#[cfg(feature = "eep")]
+impl Iterator for Deviations {
+    type Item = Deviation;
+
+    fn next(&mut self) -> Option<Deviation> {
+        let memory = &self.memory;
+        let reference = &self.reference;
+        self.registers.by_ref().find_map(|register| {
+            let addr = usize::from(u8::from(*register));
+            let read = |memory: &[u8; EEP_MAP_SIZE as usize]| {
+                let bytes = &memory[addr..addr + usize::from(register.bytes())];
+                bytes
+                    .iter()
+                    .rev()
+                    .fold(0, |value, byte| value << 8 | u16::from(*byte))
+            };
+            let (reference, value) = (read(reference), read(memory));
+            if reference == value {
+                None
+            } else {
+                Some(Deviation {
+                    register: *register,
+                    reference,
+                    value,
+                })
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "eep")]
+    use addr::{EEPReadData, ReadableEEPAddr};
+    use addr::{RamReadData, ReadableRamAddr, WritableRamAddr};
+    use config::{
+        BaudRate, BlinkPattern, ComplianceSettings, ConfigError, InpositionMargin,
+        OverloadThreshold, TorqueState, MAX_PERIOD_TICKS,
+    };
+    #[cfg(feature = "eep")]
+    use config::{Deviation, ServoConfig, CONFIG_REGISTERS, CONFIG_TABLE_LEN};
+    #[cfg(feature = "eep")]
+    use model::Model;
+    #[cfg(feature = "postcard")]
+    use postcard;
+    use reader::StatusError;
+    use try_from::TryFrom;
+
+    #[test]
+    fn overload_threshold() {
+        assert_eq!(OverloadThreshold::Percent(50).to_bytes(), (0xFF, 0x01));
+        assert_eq!(OverloadThreshold::Percent(200).pwm(), 1023);
+        assert_eq!(OverloadThreshold::Pwm(0x1234).pwm(), 1023);
+        let data = RamReadData {
+            addr: ReadableRamAddr::OverloadPWMThreshold,
+            data_len: 2,
+            data: [0xFF, 0x01],
+        };
+        assert_eq!(
+            OverloadThreshold::from_read(&data),
+            Some(OverloadThreshold::Pwm(511))
+        );
+    }
+
+    #[test]
+    fn compliance_settings() {
+        let mut settings = ComplianceSettings {
+            dead_zone: 2,
+            saturator_offset: 10,
+            saturator_slope: 0x0102,
+        };
+        assert_eq!(
+            settings.ram_writes(),
+            Ok([
+                WritableRamAddr::DeadZone(2),
+                WritableRamAddr::SaturatorOffset(10),
+                WritableRamAddr::SaturatorSlope(0x02, 0x01),
+            ])
+        );
+
+        let mut read = ComplianceSettings::default();
+        assert!(read.update_from_read(&RamReadData {
+            addr: ReadableRamAddr::SaturatorSlope,
+            data_len: 2,
+            data: [0x02, 0x01],
+        }));
+        assert!(!read.update_from_read(&RamReadData {
+            addr: ReadableRamAddr::MaxPWM,
+            data_len: 2,
+            data: [0x02, 0x01],
+        }));
+        assert_eq!(read.saturator_slope, 0x0102);
+
+        settings.saturator_offset = 255;
+        assert_eq!(
+            settings.ram_writes(),
+            Err(ConfigError::OutOfRange("SaturatorOffset"))
+        );
+    }
+
+    #[test]
+    fn baud_rate() {
+        for baud in BaudRate::ALL.iter() {
+            assert_eq!(BaudRate::try_from(baud.code()), Ok(*baud));
+            assert_eq!(BaudRate::from_bps(baud.bps()), Some(*baud));
+        }
+        assert_eq!(
+            BaudRate::try_from(0x11),
+            Err(ConfigError::OutOfRange("BaudRate"))
+        );
+        assert_eq!(BaudRate::from_bps(9600), None);
+    }
+
+    #[cfg(feature = "eep")]
+    #[test]
+    fn baud_rate_from_read() {
+        let data = EEPReadData {
+            addr: ReadableEEPAddr::BaudRate,
+            data_len: 1,
+            data: [0x10, 0x00],
+        };
+        assert_eq!(BaudRate::from_read(&data), Some(BaudRate::Baud115200));
+    }
+
+    #[test]
+    fn torque_state() {
+        for state in [TorqueState::Free, TorqueState::Brake, TorqueState::On].iter() {
+            assert_eq!(TorqueState::try_from(state.code()), Ok(*state));
+        }
+        assert_eq!(
+            TorqueState::try_from(0x20),
+            Err(ConfigError::OutOfRange("TorqueControl"))
+        );
+    }
+
+    #[test]
+    fn inposition_margin() {
+        assert_eq!(InpositionMargin::from_degrees(1.0).raw(), 3);
+        assert_eq!(InpositionMargin::from_degrees(0.9).raw(), 3);
+        assert_eq!(InpositionMargin::from_degrees(-5.0).raw(), 0);
+        assert_eq!(InpositionMargin::from_degrees(1000.0).raw(), 254);
+        assert_eq!(InpositionMargin::new(4).degrees(), 1.3);
+        let margin = InpositionMargin::new(3);
+        assert!(margin.contains(512, 515));
+        assert!(margin.contains(512, 509));
+        assert!(!margin.contains(512, 516));
+        assert_eq!(
+            WritableRamAddr::from(margin),
+            WritableRamAddr::InpositionMargin(3)
+        );
+        let data = RamReadData {
+            addr: ReadableRamAddr::InpositionMargin,
+            data_len: 1,
+            data: [3, 0],
+        };
+        assert_eq!(InpositionMargin::from_read(&data), Some(margin));
+    }
+
+    #[cfg(feature = "eep")]
+    #[test]
+    fn servo_config_table() {
+        let config = ServoConfig {
+            baud_rate: BaudRate::Baud666666,
+            id: 0xFD,
+            ack_policy: 1,
+            alarm_led_policy: 0x7F,
+            torque_policy: 0x35,
+            max_temperature: 0xDF,
+            min_voltage: 0x5B,
+            max_voltage: 0x89,
+            acceleration_ratio: 25,
+            max_acceleration_time: 45,
+            compliance: ComplianceSettings {
+                dead_zone: 0,
+                saturator_offset: 0,
+                saturator_slope: 0,
+            },
+            pwm_offset: -2,
+            min_pwm: 0,
+            max_pwm: 1023,
+            overload_threshold: OverloadThreshold::Pwm(1023),
+            min_position: 21,
+            max_position: 1002,
+            position_kp: 0x01B8,
+            position_kd: 0x1F40,
+            position_ki: 0,
+            position_ff_first_gain: 0,
+            position_ff_second_gain: 0,
+            led_blink_period: 45,
+            adc_fault_check_period: 45,
+            packet_garbage_detection_period: 18,
+            stop_detection_period: 27,
+            overload_detection_period: 150,
+            stop_threshold: 3,
+            inposition_margin: InpositionMargin::new(3),
+            calibration_difference: 0,
+        };
+        let bytes: u8 = CONFIG_REGISTERS
+            .iter()
+            .map(|register| register.bytes())
+            .sum();
+        assert_eq!(usize::from(bytes), CONFIG_TABLE_LEN);
+        let table = config.to_table();
+        assert_eq!(&table[..3], &[(4, 0x02), (6, 0xFD), (7, 1)]);
+        assert!(table.contains(&(20, 0xFE)));
+        assert!(table.contains(&(30, 0xB8)));
+        assert!(table.contains(&(31, 0x01)));
+        assert_eq!(table[CONFIG_TABLE_LEN - 1], (53, 0));
+        assert_eq!(ServoConfig::from_table(&table), Ok(config));
+
+        let mut reversed = table;
+        reversed.reverse();
+        assert_eq!(ServoConfig::from_table(&reversed), Ok(config));
+        assert_eq!(
+            ServoConfig::from_table(&table[1..]),
+            Err(ConfigError::MissingRegister("BaudRate"))
+        );
+        assert_eq!(
+            ServoConfig::from_table(&[(5, 0)]),
+            Err(ConfigError::UnknownAddress(5))
+        );
+        let mut invalid = table;
+        invalid[0].1 = 0x11;
+        assert_eq!(
+            ServoConfig::from_table(&invalid),
+            Err(ConfigError::OutOfRange("BaudRate"))
+        );
+    }
+
+    #[test]
+    fn blink_pattern() {
+        let pattern = BlinkPattern::new(
+            1000,
+            &[
+                StatusError::ExceedTemperatureLimit,
+                StatusError::OverloadDetected,
+            ],
+        );
+        assert_eq!(pattern.period_ticks(), 89);
+        assert_eq!(pattern.period_ms(), 996);
+        assert_eq!(pattern.policy(), 0x14);
+        assert_eq!(
+            pattern.ram_writes(),
+            [
+                WritableRamAddr::AlarmLEDPolicy(0x14),
+                WritableRamAddr::LedBlinkPeriod(89)
+            ]
+        );
+        assert_eq!(BlinkPattern::new(0, &[]).period_ticks(), 1);
+        assert_eq!(BlinkPattern::new(2845, &[]).period_ticks(), 254);
+        assert_eq!(
+            BlinkPattern::new(u16::MAX, &[]).period_ticks(),
+            MAX_PERIOD_TICKS
+        );
+
+        let mut read = BlinkPattern::default();
+        assert!(read.update_from_read(&RamReadData {
+            addr: ReadableRamAddr::AlarmLEDPolicy,
+            data_len: 1,
+            data: [0x14, 0],
+        }));
+        assert!(read.update_from_read(&RamReadData {
+            addr: ReadableRamAddr::LedBlinkPeriod,
+            data_len: 1,
+            data: [89, 0],
+        }));
+        assert!(!read.update_from_read(&RamReadData {
+            addr: ReadableRamAddr::ID,
+            data_len: 1,
+            data: [0x40, 0],
+        }));
+        assert_eq!(read, pattern);
+    }
+
+    #[cfg(feature = "eep")]
+    #[test]
+    fn deviations() {
+        let factory = ServoConfig::factory_default(Model::Drs0201);
+        assert_eq!(factory.deviations(&factory).next(), None);
+        assert_eq!(ServoConfig::from_table(&factory.to_table()), Ok(factory));
+
+        let mut scanned = factory;
+        scanned.pwm_offset = -2;
+        scanned.position_kd = 0x1F41;
+        let mut deviations = scanned.deviations(&factory);
+        assert_eq!(
+            deviations.next(),
+            Some(Deviation {
+                register: ReadableEEPAddr::PWMOffset,
+                reference: 0x00,
+                value: 0xFE,
+            })
+        );
+        assert_eq!(
+            deviations.next(),
+            Some(Deviation {
+                register: ReadableEEPAddr::PositionKd,
+                reference: 0x1F40,
+                value: 0x1F41,
+            })
+        );
+        assert_eq!(deviations.next(), None);
+    }
+
+    #[cfg(all(feature = "eep", feature = "postcard"))]
+    #[test]
+    fn postcard_round_trip() {
+        let mut config = ServoConfig::factory_default(Model::Drs0201);
+        config.overload_threshold = OverloadThreshold::Percent(80);
+        let mut buf = [0u8; 96];
+        let bytes = postcard::to_slice(&config, &mut buf).unwrap();
+        assert_eq!(postcard::from_bytes::<ServoConfig>(bytes), Ok(config));
+    }
+}
@@ -0,0 +1,207 @@
+//! Detection of servomotors sharing the same ID.
+//!
+//! When two servomotors share an ID, both answer to every request sent to this ID. Their answers
+//! either arrive one after the other, or collide on the bus and are corrupted. An
+//! [`IdConflictDetector`](struct.IdConflictDetector.html) watches the answers to each request and
+//! reports [`Error::IdConflict`](enum.Error.html) when one of those patterns shows up.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::conflict::{Error, IdConflictDetector};
+//! use drs_0x01::reader::ACKReader;
+//!
+//! let mut reader = ACKReader::new();
+//! let mut detector = IdConflictDetector::new();
+//! detector.request(0xFD);
+//! // Send a STAT request to 0xFD, and receive two answers.
+//! let ack = [0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01];
+//! reader.parse_with(&ack, &mut detector);
+//! reader.parse_with(&ack, &mut detector);
+//! assert_eq!(detector.take_error(), Err(Error::IdConflict(0xFD)));
+//! ```
+
+use reader::{ACKHandler, ACKPacket};
+
+/// The number of consecutive requests to an ID answered by corrupted frames before a conflict is
+/// reported.
+pub const CHECKSUM_STORM_THRESHOLD: u8 = 3;
+
+/// The errors detected by an [`IdConflictDetector`](struct.IdConflictDetector.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Several servomotors answer to this ID.
+    IdConflict(u8),
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Pending {
+    id: u8,
+    answers: u8,
+    corrupted: bool,
+}
+
+/// This struct watches the answers to the requests sent, one request at a time.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct IdConflictDetector {
+    pending: Option<Pending>,
+    // The ID and the number of consecutive requests answered by corrupted frames
+    storm: Option<(u8, u8)>,
+    error: Option<Error>,
+}
+
+impl IdConflictDetector {
+    /// Create a detector.
+    pub fn new() -> IdConflictDetector {
+        IdConflictDetector::default()
+    }
+
+    /// Tell the detector a request expecting a single answer is sent to the servomotor `id`.
+    /// The answers to the previous request are not expected anymore.
+    pub fn request(&mut self, id: u8) {
+        self.close();
+        self.pending = Some(Pending {
+            id,
+            answers: 0,
+            corrupted: false,
+        });
+    }
+
+    // Update the checksum storm counter with the outcome of the pending request.
+    fn close(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            if pending.corrupted && pending.answers == 0 {
+                let count = match self.storm {
+                    Some((id, count)) if id == pending.id => count + 1,
+                    _ => 1,
+                };
+                self.storm = Some((pending.id, count));
+                if count >= CHECKSUM_STORM_THRESHOLD {
+                    self.error = self.error.or(Some(Error::IdConflict(pending.id)));
+                }
+            } else if pending.answers > 0 {
+                self.storm = None;
+            }
+        }
+    }
+
+    /// Check a valid packet.
+    ///
+    /// # Errors
+    ///
+    /// Return [Error::IdConflict](enum.Error.html) if the pending request was already answered.
+    pub fn check_packet(&mut self, packet: &ACKPacket) -> Result<(), Error> {
+        if let Some(ref mut pending) = self.pending {
+            if pending.id == packet.pid {
+                pending.answers = pending.answers.saturating_add(1);
+                if pending.answers > 1 {
+                    return Err(Error::IdConflict(pending.id));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a corrupted frame.
+    ///
+    /// # Errors
+    ///
+    /// Return [Error::IdConflict](enum.Error.html) if the last `CHECKSUM_STORM_THRESHOLD` requests
+    /// sent to the same ID were answered by corrupted frames only.
+    pub fn check_checksum_error(&mut self) -> Result<(), Error> {
+        if let Some(ref mut pending) = self.pending {
+            pending.corrupted = true;
+            let count = match self.storm {
+                Some((id, count)) if id == pending.id => count + 1,
+                _ => 1,
+            };
+            if pending.answers == 0 && count >= CHECKSUM_STORM_THRESHOLD {
+                return Err(Error::IdConflict(pending.id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the first conflict detected while used as an
+    /// [`ACKHandler`](../reader/trait.ACKHandler.html), and forget it.
+    pub fn take_error(&mut self) -> Result<(), Error> {
+        match self.error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ACKHandler for IdConflictDetector {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        if let Err(error) = self.check_packet(packet) {
+            self.error = self.error.or(Some(error));
+        }
+    }
+
+    fn on_checksum_error(&mut self, _pid: u8) {
+        if let Err(error) = self.check_checksum_error() {
+            self.error = self.error.or(Some(error));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use conflict::{Error, IdConflictDetector, CHECKSUM_STORM_THRESHOLD};
+    use reader::{stat_ack, ACKPacket, StatusError};
+
+    fn stat(pid: u8) -> ACKPacket {
+        stat_ack(pid, StatusError::NoError)
+    }
+
+    #[test]
+    fn several_answers() {
+        let mut detector = IdConflictDetector::new();
+        for _ in 0..3 {
+            detector.request(0x40);
+            assert_eq!(detector.check_packet(&stat(0x40)), Ok(()));
+            assert_eq!(detector.check_packet(&stat(0x41)), Ok(()));
+        }
+        assert_eq!(
+            detector.check_packet(&stat(0x40)),
+            Err(Error::IdConflict(0x40))
+        );
+    }
+
+    #[test]
+    fn checksum_storm() {
+        let mut detector = IdConflictDetector::new();
+        for _ in 1..CHECKSUM_STORM_THRESHOLD {
+            detector.request(0x40);
+            assert_eq!(detector.check_checksum_error(), Ok(()));
+        }
+        // A clean answer ends the storm.
+        detector.request(0x40);
+        assert_eq!(detector.check_packet(&stat(0x40)), Ok(()));
+        for _ in 1..CHECKSUM_STORM_THRESHOLD {
+            detector.request(0x40);
+            assert_eq!(detector.check_checksum_error(), Ok(()));
+        }
+        detector.request(0x40);
+        assert_eq!(
+            detector.check_checksum_error(),
+            Err(Error::IdConflict(0x40))
+        );
+    }
+
+    #[test]
+    fn handler() {
+        use reader::ACKHandler;
+
+        let mut detector = IdConflictDetector::new();
+        for _ in 0..CHECKSUM_STORM_THRESHOLD {
+            detector.request(0x40);
+            detector.on_checksum_error(0x12);
+        }
+        detector.request(0x41);
+        assert_eq!(detector.take_error(), Err(Error::IdConflict(0x40)));
+        assert_eq!(detector.take_error(), Ok(()));
+    }
+}
@@ -0,0 +1,164 @@
+//! An outer position loop running on the host, for when the gains of the servomotor can not be
+//! changed or when a static offset (gravity compensation for example) must be applied.
+//!
+//! This module is available with the `control` feature.
+
+use builder::HerkulexMessage;
+use config::MAX_PWM;
+use servo::Servo;
+
+/// The gains of a [`PositionController`](struct.PositionController.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PidGains {
+    /// Proportional gain, in PWM per raw position
+    pub kp: f32,
+    /// Integral gain, in PWM per raw position and per second
+    pub ki: f32,
+    /// Derivative gain, in PWM per raw position per second
+    pub kd: f32,
+}
+
+/// A PID position controller which outputs continuous rotation (PWM) jogs.
+///
+/// The controller must be updated at the fixed rate given at its creation. The integral term is
+/// clamped and is not accumulated while the output is saturated, to avoid wind-up.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::Servo;
+/// use drs_0x01::control::{PidGains, PositionController};
+///
+/// let gains = PidGains { kp: 4.0, ki: 1.0, kd: 0.0 };
+/// let mut controller = PositionController::new(Servo::new(0x40), gains, 100.0)
+///     .with_feedforward(50.0);
+/// // Every 10 ms :
+/// let message = controller.update(512, 500);
+/// // Send the message ...
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct PositionController {
+    servo: Servo,
+    gains: PidGains,
+    period: f32,
+    integral: f32,
+    integral_limit: f32,
+    output_limit: f32,
+    feedforward: f32,
+    last_error: Option<f32>,
+}
+
+impl PositionController {
+    /// Create a controller for `servo`, updated `rate_hz` times per second.
+    pub fn new(servo: Servo, gains: PidGains, rate_hz: f32) -> PositionController {
+        PositionController {
+            servo,
+            gains,
+            period: 1.0 / rate_hz,
+            integral: 0.0,
+            integral_limit: f32::from(MAX_PWM),
+            output_limit: f32::from(MAX_PWM),
+            feedforward: 0.0,
+            last_error: None,
+        }
+    }
+
+    /// Limit the contribution of the integral term to `limit` PWM.
+    pub fn with_integral_limit(mut self, limit: f32) -> PositionController {
+        self.integral_limit = limit;
+        self
+    }
+
+    /// Limit the PWM sent to the servo to `limit`, which can not be more than 1023.
+    pub fn with_output_limit(mut self, limit: f32) -> PositionController {
+        self.output_limit = limit.min(f32::from(MAX_PWM));
+        self
+    }
+
+    /// Add a constant PWM offset to every output, for example to compensate gravity.
+    pub fn with_feedforward(mut self, feedforward: f32) -> PositionController {
+        self.feedforward = feedforward;
+        self
+    }
+
+    /// Compute the PWM to apply to reach `target` from `current` (both raw positions).
+    pub fn output(&mut self, target: u16, current: u16) -> i16 {
+        let error = f32::from(target) - f32::from(current);
+        let derivative = match self.last_error {
+            Some(last) => (error - last) / self.period,
+            None => 0.0,
+        };
+        self.last_error = Some(error);
+
+        let integral = clamp(
+            self.integral + self.gains.ki * error * self.period,
+            self.integral_limit,
+        );
+        let unclamped =
+            self.feedforward + self.gains.kp * error + integral + self.gains.kd * derivative;
+        let output = clamp(unclamped, self.output_limit);
+        // Only integrate when the output is not saturated or when it helps leaving saturation.
+        if output == unclamped || (unclamped > output) != (error > 0.0) {
+            self.integral = integral;
+        }
+        output as i16
+    }
+
+    /// Compute the jog to send to reach `target` from `current` (both raw positions).
+    pub fn update(&mut self, target: u16, current: u16) -> HerkulexMessage {
+        let pwm = self.output(target, current);
+        self.servo.set_pwm(pwm)
+    }
+
+    /// Reset the integral and derivative terms.
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.last_error = None;
+    }
+}
+
+fn clamp(value: f32, limit: f32) -> f32 {
+    value.max(-limit).min(limit)
+}
+
+#[cfg(test)]
+mod test {
+    use control::{PidGains, PositionController};
+    use servo::Servo;
+
+    fn controller(kp: f32, ki: f32, kd: f32) -> PositionController {
+        PositionController::new(Servo::new(0x40), PidGains { kp, ki, kd }, 10.0)
+    }
+
+    #[test]
+    fn proportional_and_feedforward() {
+        let mut controller = controller(2.0, 0.0, 0.0).with_feedforward(10.0);
+        assert_eq!(controller.output(520, 500), 50);
+        assert_eq!(controller.output(480, 500), -30);
+        assert_eq!(controller.output(1000, 0), 1023);
+    }
+
+    #[test]
+    fn integral_anti_windup() {
+        let mut controller = controller(1.0, 10.0, 0.0).with_output_limit(100.0);
+        for _ in 0..100 {
+            assert_eq!(controller.output(1000, 0), 100);
+        }
+        // The integral did not accumulate while saturated.
+        assert_eq!(controller.output(500, 500), 0);
+    }
+
+    #[test]
+    fn derivative() {
+        let mut controller = controller(0.0, 0.0, 1.0);
+        assert_eq!(controller.output(500, 500), 0);
+        assert_eq!(controller.output(510, 500), 100);
+    }
+
+    #[test]
+    fn jog_message() {
+        let mut controller = controller(1.0, 0.0, 0.0);
+        assert_eq!(controller.update(400, 500), Servo::new(0x40).set_pwm(-100));
+    }
+}
@@ -0,0 +1,158 @@
+//! Filtering of the position commands which would not move the servomotors.
+//!
+//! A teleoperation loop sends targets at a high rate, most of them being almost the same as the
+//! previous one. A [`PositionDeadband`](struct.PositionDeadband.html) only lets through the
+//! **S_JOG** whose target differs enough from the last position commanded, or reported by the
+//! servomotor if none was commanded yet, which reduces the traffic on the bus.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::deadband::PositionDeadband;
+//! use drs_0x01::Servo;
+//!
+//! let servo = Servo::new(0x40);
+//! let mut deadband = PositionDeadband::new(3);
+//! assert_eq!(deadband.set_position(servo, 512), Some(servo.set_position(512)));
+//! assert_eq!(deadband.set_position(servo, 514), None);
+//! assert_eq!(deadband.set_position(servo, 515), Some(servo.set_position(515)));
+//! ```
+
+use arrayvec::ArrayVec;
+
+use builder::HerkulexMessage;
+use motion::position_of;
+use reader::{ACKHandler, ACKPacket};
+use servo::Servo;
+
+/// The maximum number of servomotors a [`PositionDeadband`](struct.PositionDeadband.html) can
+/// follow. The commands to the other servomotors are never filtered.
+pub const MAX_DEADBAND_SERVOS: usize = 32;
+
+#[derive(Copy, Clone, Debug)]
+struct Positions {
+    id: u8,
+    commanded: Option<u16>,
+    reported: Option<u16>,
+}
+
+/// This struct remembers the positions of the servomotors, and drops the commands within a
+/// threshold of them.
+#[derive(Clone, Debug)]
+pub struct PositionDeadband {
+    threshold: u16,
+    servos: ArrayVec<[Positions; MAX_DEADBAND_SERVOS]>,
+}
+
+impl PositionDeadband {
+    /// Create a filter dropping the targets less than `threshold` raw positions away from the
+    /// last position. A threshold of 0 lets every command through.
+    pub fn new(threshold: u16) -> PositionDeadband {
+        PositionDeadband {
+            threshold,
+            servos: ArrayVec::new(),
+        }
+    }
+
+    /// Change the threshold.
+    pub fn set_threshold(&mut self, threshold: u16) {
+        self.threshold = threshold;
+    }
+
+    fn entry(&mut self, id: u8) -> Option<&mut Positions> {
+        if !self.servos.iter().any(|servo| servo.id == id) {
+            self.servos
+                .try_push(Positions {
+                    id,
+                    commanded: None,
+                    reported: None,
+                })
+                .ok()?;
+        }
+        self.servos.iter_mut().find(|servo| servo.id == id)
+    }
+
+    /// Return the message moving `servo` to `position`, or `None` if it is too close to the last
+    /// position of the servomotor.
+    pub fn set_position(&mut self, servo: Servo, position: u16) -> Option<HerkulexMessage> {
+        let threshold = self.threshold;
+        if let Some(entry) = self.entry(servo.id()) {
+            if let Some(last) = entry.commanded.or(entry.reported) {
+                if last.abs_diff(position) < threshold {
+                    return None;
+                }
+            }
+            entry.commanded = Some(position);
+        }
+        Some(servo.set_position(position))
+    }
+
+    /// Forget the positions of the servomotor `id`, so that its next command is sent. This must be
+    /// called when the servomotor was moved by another message, or rebooted.
+    pub fn forget(&mut self, id: u8) {
+        self.servos.retain(|servo| servo.id != id);
+    }
+
+    /// Record the position reported in `packet`, if any.
+    pub fn update(&mut self, packet: &ACKPacket) {
+        if let Some(position) = position_of(packet) {
+            if let Some(entry) = self.entry(packet.pid) {
+                entry.reported = Some(position);
+            }
+        }
+    }
+}
+
+impl ACKHandler for PositionDeadband {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        self.update(packet);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{RamReadData, ReadableRamAddr};
+    use deadband::PositionDeadband;
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+    use servo::Servo;
+
+    #[test]
+    fn deadband() {
+        let servo = Servo::new(0x40);
+        let mut deadband = PositionDeadband::new(4);
+        deadband.update(&ACKPacket::new(
+            0x40,
+            Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::CalibratedPosition,
+                    data_len: 2,
+                    data: [0x00, 0x02],
+                },
+            },
+            StatusError::NoError,
+            StatusDetail::NoDetail,
+        ));
+        assert_eq!(deadband.set_position(servo, 509), None);
+        assert_eq!(
+            deadband.set_position(servo, 520),
+            Some(servo.set_position(520))
+        );
+        assert_eq!(deadband.set_position(servo, 517), None);
+        assert_eq!(
+            deadband.set_position(Servo::new(0x41), 517),
+            Some(Servo::new(0x41).set_position(517))
+        );
+
+        deadband.forget(0x40);
+        assert_eq!(
+            deadband.set_position(servo, 517),
+            Some(servo.set_position(517))
+        );
+        deadband.set_threshold(0);
+        assert_eq!(
+            deadband.set_position(servo, 517),
+            Some(servo.set_position(517))
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! A health report of the bus, telling apart wiring, firmware and ID problems.
+//!
+//! A [`BusDiagnostics`](struct.BusDiagnostics.html) counts, for every servomotor, the requests
+//! sent, the answers received, the timeouts, the corrupted frames and the errors reported by the
+//! servomotor itself. [`report`](struct.BusDiagnostics.html#method.report) turns those counters
+//! into a [`DiagnosticsReport`](struct.DiagnosticsReport.html) flagging the likely cause of the
+//! problems :
+//!
+//! * corrupted frames in both directions point to the wiring (noise, missing termination, wrong
+//!   baudrate),
+//! * rejected commands point to the firmware of the host or of the servomotor,
+//! * more answers than requests point to two servomotors sharing the same ID.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::diagnostics::{BusDiagnostics, Fault};
+//! use drs_0x01::reader::ACKReader;
+//!
+//! let mut reader = ACKReader::new();
+//! let mut diagnostics = BusDiagnostics::new();
+//! diagnostics.record_request(0xFD);
+//! // Send the request and read the answer ...
+//! reader.parse_with(&[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01], &mut diagnostics);
+//!
+//! let report = diagnostics.report();
+//! assert_eq!(report.fault, None);
+//! assert_eq!(report.servos[0].health, 100);
+//! ```
+
+use arrayvec::ArrayVec;
+
+use reader::{ACKHandler, ACKPacket, StatusDetail, StatusError};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The maximum number of servomotors a [`BusDiagnostics`](struct.BusDiagnostics.html) can follow.
+pub const MAX_DIAGNOSED_SERVOS: usize = 32;
+
+/// The minimum number of exchanges with a servomotor before a fault is reported.
+const MIN_EXCHANGES: u32 = 4;
+
+/// The likely cause of the problems of a servomotor or of the bus.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// The servomotor never answers : it is not powered, not connected, or has another ID or
+    /// baudrate.
+    Unreachable,
+    /// Frames are corrupted on the bus : check the wiring, the ground and the baudrate.
+    Wiring,
+    /// The servomotor rejects the commands it receives, or reports a corrupted EEP memory.
+    Firmware,
+    /// The servomotor answers more than it is asked : several servomotors share its ID.
+    IdConflict,
+}
+
+/// The counters of a servomotor.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServoCounters {
+    /// The number of requests expecting an answer sent to the servomotor
+    pub requests: u32,
+    /// The number of valid packets received from the servomotor
+    pub answers: u32,
+    /// The number of requests which were not answered
+    pub timeouts: u32,
+    /// The number of corrupted frames received from the servomotor
+    pub checksum_errors: u32,
+    /// The number of answers reporting that the servomotor received a corrupted frame
+    pub corrupted_requests: u32,
+    /// The number of answers reporting a command or a register rejected by the servomotor
+    pub rejected_requests: u32,
+}
+
+impl ServoCounters {
+    fn exchanges(&self) -> u32 {
+        self.answers + self.timeouts + self.checksum_errors
+    }
+
+    /// Return the ratio of exchanges which went well, between 0 and 100.
+    pub fn health(&self) -> u8 {
+        let exchanges = self.exchanges();
+        if exchanges == 0 {
+            return 100;
+        }
+        let failures =
+            self.timeouts + self.checksum_errors + self.corrupted_requests + self.rejected_requests;
+        let successes = exchanges.saturating_sub(failures);
+        (u64::from(successes) * 100 / u64::from(exchanges)) as u8
+    }
+
+    /// Return the likely cause of the problems of the servomotor, if any.
+    pub fn fault(&self) -> Option<Fault> {
+        if self.exchanges() < MIN_EXCHANGES {
+            None
+        } else if self.answers > self.requests {
+            Some(Fault::IdConflict)
+        } else if self.answers == 0 {
+            Some(Fault::Unreachable)
+        } else if (self.checksum_errors + self.corrupted_requests) * 10 > self.exchanges() {
+            Some(Fault::Wiring)
+        } else if self.rejected_requests * 10 > self.exchanges() {
+            Some(Fault::Firmware)
+        } else {
+            None
+        }
+    }
+}
+
+/// The diagnostic of a servomotor.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ServoReport {
+    /// The ID of the servomotor
+    pub id: u8,
+    /// The ratio of exchanges which went well, between 0 and 100
+    pub health: u8,
+    /// The likely cause of the problems of the servomotor
+    pub fault: Option<Fault>,
+    /// The counters the diagnostic is based on
+    pub counters: ServoCounters,
+}
+
+/// The diagnostic of the whole bus.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticsReport {
+    /// The likely cause of the problems of the bus. `Wiring` is reported when several
+    /// servomotors suffer from corrupted frames.
+    pub fault: Option<Fault>,
+    /// The lowest health of the servomotors, between 0 and 100
+    pub health: u8,
+    /// The diagnostic of every servomotor
+    pub servos: ArrayVec<[ServoReport; MAX_DIAGNOSED_SERVOS]>,
+}
+
+/// This struct collects the counters of up to `MAX_DIAGNOSED_SERVOS` servomotors.
+///
+/// Valid packets and corrupted frames are counted when it is used as an
+/// [`ACKHandler`](../reader/trait.ACKHandler.html), the requests and the timeouts must be
+/// recorded by the application.
+#[derive(Clone, Debug, Default)]
+pub struct BusDiagnostics {
+    servos: ArrayVec<[(u8, ServoCounters); MAX_DIAGNOSED_SERVOS]>,
+}
+
+impl BusDiagnostics {
+    /// Create a diagnostic without any counter.
+    pub fn new() -> BusDiagnostics {
+        BusDiagnostics::default()
+    }
+
+    fn entry(&mut self, id: u8) -> Option<&mut ServoCounters> {
+        if !self.servos.iter().any(|&(servo, _)| servo == id) {
+            self.servos.try_push((id, ServoCounters::default())).ok()?;
+        }
+        self.servos
+            .iter_mut()
+            .find(|&&mut (servo, _)| servo == id)
+            .map(|&mut (_, ref mut counters)| counters)
+    }
+
+    /// Count a request expecting an answer sent to the servomotor `id`.
+    pub fn record_request(&mut self, id: u8) {
+        if let Some(counters) = self.entry(id) {
+            counters.requests += 1;
+        }
+    }
+
+    /// Count a request to the servomotor `id` which was not answered.
+    pub fn record_timeout(&mut self, id: u8) {
+        log_debug!("no answer from 0x{:02X}", id);
+        if let Some(counters) = self.entry(id) {
+            counters.timeouts += 1;
+        }
+    }
+
+    /// Count a valid packet.
+    pub fn record_packet(&mut self, packet: &ACKPacket) {
+        if let Some(counters) = self.entry(packet.pid) {
+            counters.answers += 1;
+            match packet.detail {
+                StatusDetail::ChecksumError | StatusDetail::GarbageDetected => {
+                    counters.corrupted_requests += 1
+                }
+                StatusDetail::UnknownCommand | StatusDetail::ExceedREGRange => {
+                    counters.rejected_requests += 1
+                }
+                _ if packet.error == StatusError::EEPREGDistorded => {
+                    counters.rejected_requests += 1
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Count a corrupted frame apparently sent by the servomotor `id`.
+    pub fn record_checksum_error(&mut self, id: u8) {
+        if let Some(counters) = self.entry(id) {
+            counters.checksum_errors += 1;
+        }
+    }
+
+    /// Return the counters of the servomotor `id`.
+    pub fn counters(&self, id: u8) -> Option<ServoCounters> {
+        self.servos
+            .iter()
+            .find(|&&(servo, _)| servo == id)
+            .map(|&(_, counters)| counters)
+    }
+
+    /// Analyse the counters collected so far.
+    pub fn report(&self) -> DiagnosticsReport {
+        let servos: ArrayVec<[ServoReport; MAX_DIAGNOSED_SERVOS]> = self
+            .servos
+            .iter()
+            .map(|&(id, counters)| ServoReport {
+                id,
+                health: counters.health(),
+                fault: counters.fault(),
+                counters,
+            })
+            .collect();
+        let wiring = servos
+            .iter()
+            .filter(|servo| servo.fault == Some(Fault::Wiring))
+            .count();
+        let fault = if wiring > 1 {
+            Some(Fault::Wiring)
+        } else {
+            servos.iter().filter_map(|servo| servo.fault).next()
+        };
+        DiagnosticsReport {
+            fault,
+            health: servos.iter().map(|servo| servo.health).min().unwrap_or(100),
+            servos,
+        }
+    }
+
+    /// Forget every counter.
+    pub fn clear(&mut self) {
+        self.servos.clear();
+    }
+}
+
+impl ACKHandler for BusDiagnostics {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        self.record_packet(packet);
+    }
+
+    fn on_checksum_error(&mut self, pid: u8) {
+        self.record_checksum_error(pid);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use diagnostics::{BusDiagnostics, Fault};
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+
+    fn answer(diagnostics: &mut BusDiagnostics, id: u8, detail: StatusDetail) {
+        diagnostics.record_request(id);
+        diagnostics.record_packet(&ACKPacket {
+            pid: id,
+            cmd: Command::Stat,
+            error: StatusError::NoError,
+            detail,
+            error_raw: 0x00,
+            detail_raw: detail.bits(),
+        });
+    }
+
+    #[test]
+    fn servo_faults() {
+        let mut diagnostics = BusDiagnostics::new();
+        for _ in 0..10 {
+            answer(&mut diagnostics, 1, StatusDetail::NoDetail);
+            answer(&mut diagnostics, 2, StatusDetail::UnknownCommand);
+            answer(&mut diagnostics, 3, StatusDetail::NoDetail);
+            diagnostics.record_request(4);
+            diagnostics.record_timeout(4);
+        }
+        diagnostics.record_checksum_error(3);
+        answer(&mut diagnostics, 1, StatusDetail::NoDetail);
+        diagnostics.record_packet(&ACKPacket {
+            pid: 1,
+            cmd: Command::Stat,
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        });
+
+        let report = diagnostics.report();
+        let faults: std::vec::Vec<_> = report
+            .servos
+            .iter()
+            .map(|servo| (servo.id, servo.fault))
+            .collect();
+        assert_eq!(
+            faults,
+            vec![
+                (1, Some(Fault::IdConflict)),
+                (2, Some(Fault::Firmware)),
+                (3, None),
+                (4, Some(Fault::Unreachable)),
+            ]
+        );
+        assert_eq!(report.fault, Some(Fault::IdConflict));
+        assert_eq!(report.health, 0);
+        assert_eq!(report.servos[2].health, 90);
+    }
+
+    #[test]
+    fn wiring_fault() {
+        let mut diagnostics = BusDiagnostics::new();
+        for _ in 0..4 {
+            answer(&mut diagnostics, 1, StatusDetail::ChecksumError);
+            diagnostics.record_checksum_error(2);
+            answer(&mut diagnostics, 2, StatusDetail::NoDetail);
+        }
+        let report = diagnostics.report();
+        assert_eq!(report.servos[0].fault, Some(Fault::Wiring));
+        assert_eq!(report.servos[1].fault, Some(Fault::Wiring));
+        assert_eq!(report.fault, Some(Fault::Wiring));
+    }
+}
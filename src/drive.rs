@@ -0,0 +1,189 @@
+//! Differential drive for wheeled robots, with servomotors in continuous rotation.
+//!
+//! A [`DifferentialDrive`](struct.DifferentialDrive.html) turns a linear and an angular speed into
+//! the speeds of the left and the right wheels, sent in a single **S_JOG** request.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::drive::DifferentialDrive;
+//!
+//! // The right wheel is mounted facing the left one, it turns the other way to go forward.
+//! let drive = DifferentialDrive::new(0x40, 0x41).with_inversion(false, true);
+//! // Go forward at half speed while turning left.
+//! let message = drive.drive(0.5, 0.2);
+//! // Send the message ...
+//! ```
+
+use builder::{HerkulexMessage, MessageBuilder};
+use config::MAX_PWM;
+use message::{JogColor, JogMode, Rotation};
+use servo::BROADCAST_ID;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Wheel {
+    id: u8,
+    inverted: bool,
+    scale: f32,
+}
+
+impl Wheel {
+    /// Return the jog of this wheel at `speed`, between -1 and 1.
+    fn jog(&self, speed: f32) -> JogMode {
+        let speed = if self.inverted { -speed } else { speed } * self.scale;
+        let rotation = if speed < 0.0 {
+            Rotation::Clockwise
+        } else {
+            Rotation::CounterClockwise
+        };
+        JogMode::Continuous {
+            speed: (speed.abs().min(1.0) * f32::from(MAX_PWM) + 0.5) as u16,
+            rotation,
+        }
+    }
+}
+
+/// This struct computes the commands of the two wheels of a differential drive.
+///
+/// Speeds are fractions of the maximum speed of the wheels, between -1 and 1. Positive speeds
+/// rotate a wheel counterclockwise, unless it is inverted.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DifferentialDrive {
+    left: Wheel,
+    right: Wheel,
+    color: JogColor,
+}
+
+impl DifferentialDrive {
+    /// Create a drive with the servomotors `left_id` and `right_id`, none of them inverted.
+    pub fn new(left_id: u8, right_id: u8) -> DifferentialDrive {
+        let wheel = |id| Wheel {
+            id,
+            inverted: false,
+            scale: 1.0,
+        };
+        DifferentialDrive {
+            left: wheel(left_id),
+            right: wheel(right_id),
+            color: JogColor::Green,
+        }
+    }
+
+    /// Invert the rotation sense of the left and the right wheels.
+    pub fn with_inversion(mut self, left: bool, right: bool) -> DifferentialDrive {
+        self.left.inverted = left;
+        self.right.inverted = right;
+        self
+    }
+
+    /// Scale the speeds of the left and the right wheels, to compensate for different wheel
+    /// diameters or motors. Both are 1 by default.
+    pub fn with_scale(mut self, left: f32, right: f32) -> DifferentialDrive {
+        self.left.scale = left;
+        self.right.scale = right;
+        self
+    }
+
+    /// Set the color of the LED of the servomotors.
+    pub fn with_color(mut self, color: JogColor) -> DifferentialDrive {
+        self.color = color;
+        self
+    }
+
+    /// Return the speeds of the left and the right wheels going forward at `linear` and turning
+    /// left at `angular`. When a wheel would go faster than its maximum speed, both speeds are
+    /// reduced so that the radius of the turn is kept.
+    pub fn wheel_speeds(linear: f32, angular: f32) -> (f32, f32) {
+        let (left, right) = (linear - angular, linear + angular);
+        let max = left.abs().max(right.abs());
+        if max > 1.0 {
+            (left / max, right / max)
+        } else {
+            (left, right)
+        }
+    }
+
+    /// Return the request driving forward at `linear` and turning left at `angular`.
+    pub fn drive(&self, linear: f32, angular: f32) -> HerkulexMessage {
+        let (left, right) = DifferentialDrive::wheel_speeds(linear, angular);
+        let mut message = MessageBuilder::new_with_id(BROADCAST_ID).s_jog(
+            0,
+            self.left.jog(left),
+            self.color,
+            self.left.id,
+        );
+        // The request only holds two servomotors.
+        message
+            .s_jog(self.right.jog(right), self.color, self.right.id)
+            .ok();
+        message.build()
+    }
+
+    /// Return the request stopping both wheels.
+    pub fn stop(&self) -> HerkulexMessage {
+        self.drive(0.0, 0.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use builder::{HerkulexMessage, MessageBuilder};
+    use drive::DifferentialDrive;
+    use message::{JogColor, JogMode, Rotation};
+    use servo::BROADCAST_ID;
+
+    fn jog(left: (u16, Rotation), right: (u16, Rotation)) -> HerkulexMessage {
+        let mut message = MessageBuilder::new_with_id(BROADCAST_ID).s_jog(
+            0,
+            JogMode::Continuous {
+                speed: left.0,
+                rotation: left.1,
+            },
+            JogColor::Green,
+            0x40,
+        );
+        message
+            .s_jog(
+                JogMode::Continuous {
+                    speed: right.0,
+                    rotation: right.1,
+                },
+                JogColor::Green,
+                0x41,
+            )
+            .unwrap();
+        message.build()
+    }
+
+    #[test]
+    fn wheels() {
+        assert_eq!(DifferentialDrive::wheel_speeds(0.5, 0.25), (0.25, 0.75));
+        assert_eq!(DifferentialDrive::wheel_speeds(1.0, 1.0), (0.0, 1.0));
+        assert_eq!(DifferentialDrive::wheel_speeds(-0.5, 1.5), (-1.0, 0.5));
+
+        let drive = DifferentialDrive::new(0x40, 0x41).with_inversion(false, true);
+        assert_eq!(
+            drive.drive(1.0, 0.0),
+            jog(
+                (1023, Rotation::CounterClockwise),
+                (1023, Rotation::Clockwise)
+            )
+        );
+        assert_eq!(
+            drive.stop(),
+            jog(
+                (0, Rotation::CounterClockwise),
+                (0, Rotation::CounterClockwise)
+            )
+        );
+        let scaled = drive.with_scale(1.0, 0.5);
+        assert_eq!(
+            scaled.drive(-0.5, 0.0),
+            jog(
+                (512, Rotation::Clockwise),
+                (256, Rotation::CounterClockwise)
+            )
+        );
+    }
+}
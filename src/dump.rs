@@ -0,0 +1,305 @@
+//! Reads of whole memory regions, larger than the data an ACK can carry.
+//!
+//! [`read_region`](fn.read_region.html) splits a region of the RAM or of the EEP memory into
+//! reads of at most [`MAX_READ_LEN`](../frame/constant.MAX_READ_LEN.html) bytes, sends them one
+//! after the other and gathers the answers into a buffer, to dump the memory of a servomotor.
+//!
+//! Every read sent starts at the address of a register and never splits a register, and the
+//! addresses which are not covered by any register are not read : their bytes are left
+//! untouched in the buffer.
+
+#[cfg(feature = "eep")]
+use addr::{ReadableEEPAddr, EEP_MAP_SIZE};
+use addr::{ReadableRamAddr, RAM_MAP_SIZE};
+use builder::{HerkulexMessage, MessageBuilder};
+use clock::Clock;
+use events::Memory;
+use frame::MAX_READ_LEN;
+use reader::{ACKPacket, ACKReader, Command};
+use transaction::{AckKind, ExpectedAck};
+use transport::{bytes_read, Transport};
+use try_from::TryFrom;
+
+/// The error returned by [`read_region`](fn.read_region.html).
+#[derive(Debug, PartialEq, Eq)]
+pub enum DumpError<E> {
+    /// The transport failed.
+    Transport(E),
+    /// The servomotor did not answer in time to the read at `addr`.
+    Timeout {
+        /// The address of the read which was not answered
+        addr: u8,
+    },
+    /// The region goes past the end of the memory.
+    OutOfRange,
+}
+
+/// Return the size of the register at `addr`, if a register starts at `addr`.
+fn register_len(memory: Memory, addr: u8) -> Option<u8> {
+    match memory {
+        Memory::Ram => ReadableRamAddr::try_from(addr)
+            .ok()
+            .map(|addr| addr.bytes()),
+        #[cfg(feature = "eep")]
+        Memory::EEP => ReadableEEPAddr::try_from(addr)
+            .ok()
+            .map(|addr| addr.bytes()),
+        #[cfg(not(feature = "eep"))]
+        Memory::EEP => None,
+    }
+}
+
+/// Return the request reading `len` bytes at `addr`, with the ACK it expects.
+fn read_request(id: u8, memory: Memory, addr: u8, len: u8) -> Option<(HerkulexMessage, AckKind)> {
+    let builder = MessageBuilder::new_with_id(id);
+    match memory {
+        Memory::Ram => {
+            let addr = ReadableRamAddr::try_from(addr).ok()?;
+            Some((
+                builder.read_ram(addr, len).build(),
+                AckKind::RamRead { addr, len },
+            ))
+        }
+        #[cfg(feature = "eep")]
+        Memory::EEP => {
+            let addr = ReadableEEPAddr::try_from(addr).ok()?;
+            Some((
+                builder.read_eep(addr, len).build(),
+                AckKind::EEPRead { addr, len },
+            ))
+        }
+        #[cfg(not(feature = "eep"))]
+        Memory::EEP => None,
+    }
+}
+
+/// Read `buf.len()` bytes of `memory` from the address `start` of the servomotor `id` into
+/// `buf`, and call `progress` with the number of bytes done and the total after every read.
+///
+/// Every read busy-waits on `transport` until its answer is received or until `timeout` ticks
+/// have elapsed, like [`ping`](../latency/fn.ping.html). Every other packet received in the
+/// meantime is discarded.
+///
+/// # Errors
+///
+/// Return [DumpError::OutOfRange](enum.DumpError.html) if the region goes past the end of the
+/// memory, which is the case of the whole EEP memory without the `eep` feature, and
+/// [DumpError::Timeout](enum.DumpError.html) if a read was not answered. The bytes read before
+/// the error are in `buf`.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::addr::RAM_MAP_SIZE;
+/// use drs_0x01::dump::{read_region, DumpError};
+/// use drs_0x01::events::Memory;
+/// # use drs_0x01::transport::Transport;
+/// # struct Uart;
+/// # impl Transport for Uart {
+/// #     type Error = ();
+/// #     fn write(&mut self, _frame: &[u8]) -> Result<(), ()> { Ok(()) }
+/// #     fn read(&mut self, _buf: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+/// # }
+/// # let mut uart = Uart;
+/// # let tick = std::cell::Cell::new(0);
+/// # let clock = || { tick.set(tick.get() + 1); tick.get() };
+///
+/// let mut ram = [0u8; RAM_MAP_SIZE as usize];
+/// let result = read_region(&mut uart, &clock, 0x40, Memory::Ram, 0, &mut ram, 100, |done, total| {
+///     // Report the progress ...
+/// });
+/// assert_eq!(result, Err(DumpError::Timeout { addr: 0 }));
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn read_region<T, C, F>(
+    transport: &mut T,
+    clock: &C,
+    id: u8,
+    memory: Memory,
+    start: u8,
+    buf: &mut [u8],
+    timeout: u32,
+    mut progress: F,
+) -> Result<(), DumpError<T::Error>>
+where
+    T: Transport + ?Sized,
+    C: Clock + ?Sized,
+    F: FnMut(usize, usize),
+{
+    let size = match memory {
+        Memory::Ram => RAM_MAP_SIZE,
+        #[cfg(feature = "eep")]
+        Memory::EEP => EEP_MAP_SIZE,
+        // Without the `eep` feature, every region of the EEP memory is out of range
+        #[cfg(not(feature = "eep"))]
+        Memory::EEP => 0,
+    };
+    let end = usize::from(start) + buf.len();
+    if end > usize::from(size) {
+        return Err(DumpError::OutOfRange);
+    }
+    let _span = span!("read_region", id, start, len = buf.len());
+    let mut reader = ACKReader::new();
+    let mut bytes = [0; 32];
+    let mut addr = usize::from(start);
+    while addr < end {
+        let mut len = MAX_READ_LEN.min(end - addr);
+        // Do not read the first byte of a register without the rest of it.
+        if len > 1 && register_len(memory, (addr + len - 1) as u8).is_some_and(|len| len > 1) {
+            len -= 1;
+        }
+        let (request, kind) = match read_request(id, memory, addr as u8, len as u8) {
+            Some(request) => request,
+            None => {
+                addr += 1;
+                continue;
+            }
+        };
+        let expected = ExpectedAck { id, kind };
+        let _read = span!("read", id, cmd = request[4], addr, len);
+        transport.write(&request).map_err(DumpError::Transport)?;
+        let begin = clock.now();
+        let mut data = None;
+        while data.is_none() {
+            let read = transport.read(&mut bytes).map_err(DumpError::Transport)?;
+            reader.parse_with(bytes_read(&bytes, read), &mut |packet: &ACKPacket| {
+                if expected.matches(packet) {
+                    data = match packet.cmd {
+                        Command::RamRead { data } => Some(data.data),
+                        Command::EEPRead { data } => Some(data.data),
+                        _ => None,
+                    };
+                }
+            });
+            if data.is_none() && clock.now().wrapping_sub(begin) > timeout {
+                log_debug!("no answer from 0x{:02X} to the read at 0x{:02X}", id, addr);
+                return Err(DumpError::Timeout { addr: addr as u8 });
+            }
+        }
+        let offset = addr - usize::from(start);
+        if let Some(data) = data {
+            buf[offset..offset + len].copy_from_slice(&data[..len]);
+        }
+        addr += len;
+        progress(addr - usize::from(start), buf.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use addr::RAM_MAP_SIZE;
+    use dump::{read_region, DumpError};
+    use events::Memory;
+    use test_support::AckFrameBuilder;
+    use transport::Transport;
+
+    use core::cell::Cell;
+    use std::vec::Vec;
+
+    /// A servomotor 0x40 whose RAM holds its addresses, answering the RAM reads.
+    struct Memory40 {
+        pending: Vec<u8>,
+        reads: Vec<(u8, u8)>,
+    }
+
+    impl Transport for Memory40 {
+        type Error = ();
+
+        fn write(&mut self, frame: &[u8]) -> Result<(), ()> {
+            let (addr, len) = (frame[7], frame[8]);
+            self.reads.push((addr, len));
+            let data: Vec<u8> = (addr..addr + len).collect();
+            if frame[3] == 0x40 && frame[4] == 0x04 {
+                self.pending = AckFrameBuilder::ram_read(0x40, addr, &data)
+                    .build()
+                    .to_vec();
+            }
+            Ok(())
+        }
+
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+            let len = self.pending.len();
+            buf[..len].copy_from_slice(&self.pending);
+            self.pending.clear();
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn region() {
+        let tick = Cell::new(0u32);
+        let clock = || {
+            tick.set(tick.get() + 1);
+            tick.get()
+        };
+        let mut servo = Memory40 {
+            pending: Vec::new(),
+            reads: Vec::new(),
+        };
+        let mut buf = [0xAA; 8];
+        let mut steps = Vec::new();
+        read_region(
+            &mut servo,
+            &clock,
+            0x40,
+            Memory::Ram,
+            43,
+            &mut buf,
+            10,
+            |done, total| steps.push((done, total)),
+        )
+        .unwrap();
+        // 45 and 46 are not registers.
+        assert_eq!(servo.reads, [(43, 2), (47, 2), (49, 2)]);
+        assert_eq!(buf, [43, 44, 0xAA, 0xAA, 47, 48, 49, 50]);
+        assert_eq!(steps, [(2, 8), (6, 8), (8, 8)]);
+
+        // The read at 11 does not split SaturatorSlope, at 12.
+        servo.reads.clear();
+        let mut buf = [0; 5];
+        read_region(
+            &mut servo,
+            &clock,
+            0x40,
+            Memory::Ram,
+            11,
+            &mut buf,
+            10,
+            |_, _| (),
+        )
+        .unwrap();
+        assert_eq!(servo.reads, [(11, 1), (12, 2), (14, 2)]);
+        assert_eq!(buf, [11, 12, 13, 14, 15]);
+
+        let mut buf = [0; 2];
+        assert_eq!(
+            read_region(
+                &mut servo,
+                &clock,
+                0x41,
+                Memory::Ram,
+                0,
+                &mut buf,
+                10,
+                |_, _| ()
+            ),
+            Err(DumpError::Timeout { addr: 0 })
+        );
+        let mut buf = [0; 4];
+        assert_eq!(
+            read_region(
+                &mut servo,
+                &clock,
+                0x40,
+                Memory::Ram,
+                RAM_MAP_SIZE - 2,
+                &mut buf,
+                10,
+                |_, _| ()
+            ),
+            Err(DumpError::OutOfRange)
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use builder::{HerkulexMessage, MessageBuilder};
+use message::{JogColor, JogMode, Rotation};
+use servo::BROADCAST_ID;
+
+use addr::WritableRamAddr;
+
+/// The value of the `TorqueControl` register which brakes the motor.
+const TORQUE_BRAKE: u8 = 0x40;
+
+fn stop_all(torque: u8) -> [HerkulexMessage; 2] {
+    let builder = MessageBuilder::new_with_id(BROADCAST_ID);
+    let stop = JogMode::Continuous {
+        speed: 0,
+        rotation: Rotation::CounterClockwise,
+    };
+    [
+        builder.s_jog(0, stop, JogColor::Red, BROADCAST_ID).build(),
+        builder
+            .write_ram_unchecked(WritableRamAddr::TorqueControl(torque))
+            .build(),
+    ]
+}
+
+/// Create the messages stopping every servomotor on the bus and leaving their output shaft free.
+///
+/// The first message stops any continuous rotation and turns the LEDs red, the second one
+/// disables the torque. Both are broadcasted, so no servomotor answers to them.
+///
+/// This function does not allocate, does not panic and does not use any shared state : it can be
+/// called from any context, including interrupt handlers.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::emergency_stop_all;
+///
+/// for message in emergency_stop_all().iter() {
+///     // Send the message ...
+/// }
+/// ```
+pub fn emergency_stop_all() -> [HerkulexMessage; 2] {
+    stop_all(0)
+}
+
+/// Create the messages stopping every servomotor on the bus and braking their output shaft.
+///
+/// This is the same as [`emergency_stop_all`](fn.emergency_stop_all.html), but the motors are
+/// shorted instead of being left free, which stops loaded joints faster.
+pub fn emergency_brake_all() -> [HerkulexMessage; 2] {
+    stop_all(TORQUE_BRAKE)
+}
+
+#[cfg(test)]
+mod test {
+    use emergency::{emergency_brake_all, emergency_stop_all};
+
+    #[test]
+    fn broadcast_frames() {
+        let [jog, torque] = emergency_stop_all();
+        assert_eq!(
+            jog.as_slice(),
+            &[0xFF, 0xFF, 0x0C, 0xFE, 0x06, 0x18, 0xE6, 0x00, 0x00, 0x00, 0x12, 0xFE]
+        );
+        assert_eq!(
+            torque.as_slice(),
+            &[0xFF, 0xFF, 0x0A, 0xFE, 0x03, 0xC2, 0x3C, 0x34, 0x01, 0x00]
+        );
+        assert_eq!(emergency_brake_all()[1][9], 0x40);
+    }
+}
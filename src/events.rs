@@ -0,0 +1,161 @@
+use addr::EEPReadData;
+use addr::{RamReadData, ReadableRamAddr};
+use reader::{ACKPacket, Command, StatusDetail, StatusError};
+
+/// The memory targeted by a write.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Memory {
+    /// The volatile RAM memory
+    Ram,
+    /// The permanent EEP memory
+    EEP,
+}
+
+/// A semantic view of an [`ACKPacket`](../reader/struct.ACKPacket.html).
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::events::Event;
+/// use drs_0x01::reader::ACKReader;
+///
+/// let mut reader = ACKReader::new();
+/// reader.parse(&[0xFF, 0xFF, 0x09, 0xFD, 0x46, 0xB2, 0x4C, 0x08, 0x08]);
+/// while let Some(packet) = reader.pop_ack_packet() {
+///     match Event::from(packet) {
+///         Event::PositionReport { id, pos } => { /* ... */ }
+///         Event::Fault { id, error, .. } => { /* ... */ }
+///         _ => (),
+///     }
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// The servomotor reported an error. This takes precedence over the other events.
+    Fault {
+        /// The ID of the servomotor
+        id: u8,
+        /// The error reported
+        error: StatusError,
+        /// The detail of the error
+        detail: StatusDetail,
+    },
+    /// The servomotor sent its calibrated or absolute position.
+    PositionReport {
+        /// The ID of the servomotor
+        id: u8,
+        /// The position read
+        pos: u16,
+    },
+    /// The servomotor sent some data from its RAM memory.
+    RamRead {
+        /// The ID of the servomotor
+        id: u8,
+        /// The data read
+        data: RamReadData,
+    },
+    /// The servomotor sent some data from its EEP memory.
+    EEPRead {
+        /// The ID of the servomotor
+        id: u8,
+        /// The data read
+        data: EEPReadData,
+    },
+    /// The servomotor acknowledged a write. The ACK does not contain the address written.
+    WriteAcked {
+        /// The ID of the servomotor
+        id: u8,
+        /// The memory written
+        memory: Memory,
+    },
+    /// The servomotor answered to a STAT request.
+    Status {
+        /// The ID of the servomotor
+        id: u8,
+        /// The content of the status detail register
+        detail: StatusDetail,
+    },
+    /// The servomotor acknowledged another command (jog, reboot or rollback).
+    Acked {
+        /// The ID of the servomotor
+        id: u8,
+        /// The command acknowledged
+        cmd: Command,
+    },
+}
+
+impl From<ACKPacket> for Event {
+    fn from(packet: ACKPacket) -> Event {
+        let id = packet.pid;
+        if packet.error != StatusError::NoError {
+            return Event::Fault {
+                id,
+                error: packet.error,
+                detail: packet.detail,
+            };
+        }
+        match packet.cmd {
+            Command::RamRead { data } => match (data.addr, data.as_u16()) {
+                (ReadableRamAddr::CalibratedPosition, Some(pos))
+                | (ReadableRamAddr::AbsolutePosition, Some(pos)) => {
+                    Event::PositionReport { id, pos }
+                }
+                _ => Event::RamRead { id, data },
+            },
+            Command::EEPRead { data } => Event::EEPRead { id, data },
+            Command::RamWrite => Event::WriteAcked {
+                id,
+                memory: Memory::Ram,
+            },
+            Command::EEPWrite => Event::WriteAcked {
+                id,
+                memory: Memory::EEP,
+            },
+            Command::Stat => Event::Status {
+                id,
+                detail: packet.detail,
+            },
+            cmd => Event::Acked { id, cmd },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use events::{Event, Memory};
+    use reader::{decode_ack, Command, StatusDetail, StatusError};
+    use test_support::AckFrameBuilder;
+
+    #[test]
+    fn events_from_packets() {
+        let position = AckFrameBuilder::ram_read(0x40, 0x3A, &[0x00, 0x02]);
+        assert_eq!(
+            Event::from(decode_ack(&position.build())),
+            Event::PositionReport { id: 0x40, pos: 512 }
+        );
+        let overload = position.status(StatusError::OverloadDetected.bits(), 0x00);
+        assert_eq!(
+            Event::from(decode_ack(&overload.build())),
+            Event::Fault {
+                id: 0x40,
+                error: StatusError::OverloadDetected,
+                detail: StatusDetail::NoDetail,
+            }
+        );
+        assert_eq!(
+            Event::from(decode_ack(&AckFrameBuilder::new(0x40, 0x01).build())),
+            Event::WriteAcked {
+                id: 0x40,
+                memory: Memory::EEP,
+            }
+        );
+        assert_eq!(
+            Event::from(decode_ack(&AckFrameBuilder::new(0x40, 0x06).build())),
+            Event::Acked {
+                id: 0x40,
+                cmd: Command::SJog,
+            }
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! A physical indication of the faults : the LED of a servomotor turns red when it reports an
+//! error, and is restored when the error is gone.
+//!
+//! A [`FaultLed`](struct.FaultLed.html) is fed with every parsed packet, and yields the messages
+//! changing the color of the LED of the servomotors whose fault state changed. No application
+//! code is needed besides sending those messages.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::fault_led::FaultLed;
+//! use drs_0x01::reader::ACKReader;
+//! use drs_0x01::{Servo, WritableRamAddr};
+//!
+//! let mut reader = ACKReader::new();
+//! let mut leds = FaultLed::new();
+//! // The servomotor 0xFD reports an overload.
+//! reader.parse_with(&[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xBA, 0x44, 0x08, 0x00], &mut leds);
+//! assert_eq!(
+//!     leds.next_message(),
+//!     Servo::new(0xFD).ram_write(WritableRamAddr::LEDControl(0x04)).ok()
+//! );
+//! assert_eq!(leds.next_message(), None);
+//! ```
+
+use addr::WritableRamAddr;
+use builder::{HerkulexMessage, MessageBuilder};
+use message::{JogColor, JogMode};
+use reader::{ACKHandler, ACKPacket, StatusError};
+
+/// The value of the `LEDControl` register lighting the red LED.
+const LED_RED: u8 = 0x04;
+
+/// The number of words of a set of servomotor IDs, the broadcast ID excluded.
+const ID_WORDS: usize = 8;
+
+/// A set of servomotor IDs.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+struct IdSet([u32; ID_WORDS]);
+
+impl IdSet {
+    fn contains(&self, id: u8) -> bool {
+        self.0[usize::from(id >> 5)] & 1 << (id & 0x1F) != 0
+    }
+
+    fn set(&mut self, id: u8, value: bool) {
+        let word = &mut self.0[usize::from(id >> 5)];
+        if value {
+            *word |= 1 << (id & 0x1F);
+        } else {
+            *word &= !(1 << (id & 0x1F));
+        }
+    }
+
+    fn first(&self) -> Option<u8> {
+        self.0
+            .iter()
+            .enumerate()
+            .find(|&(_, word)| *word != 0)
+            .map(|(index, word)| (index as u32 * 32 + word.trailing_zeros()) as u8)
+    }
+}
+
+/// How the color of the LED is changed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LedSignal {
+    /// Write the `LEDControl` register : red on fault, off on recovery.
+    Register,
+    /// Send a **S_JOG** which does not move the servomotor : red on fault, the given color on
+    /// recovery.
+    Jog(JogColor),
+}
+
+/// This struct follows the fault state of every servomotor, and yields the messages showing it
+/// on their LED.
+#[derive(Clone, Debug)]
+pub struct FaultLed {
+    signal: LedSignal,
+    faulted: IdSet,
+    changed: IdSet,
+}
+
+impl Default for FaultLed {
+    fn default() -> FaultLed {
+        FaultLed {
+            signal: LedSignal::Register,
+            faulted: IdSet::default(),
+            changed: IdSet::default(),
+        }
+    }
+}
+
+impl FaultLed {
+    /// Create an indicator writing the `LEDControl` register.
+    pub fn new() -> FaultLed {
+        FaultLed::default()
+    }
+
+    /// Change how the color of the LED is changed.
+    pub fn with_signal(mut self, signal: LedSignal) -> FaultLed {
+        self.signal = signal;
+        self
+    }
+
+    /// Update the fault state of the servomotor which sent `packet`. The packets of the broadcast
+    /// ID are ignored.
+    pub fn update(&mut self, packet: &ACKPacket) {
+        if packet.pid >= 0xFE {
+            return;
+        }
+        let faulted = packet.error != StatusError::NoError;
+        if faulted != self.faulted.contains(packet.pid) {
+            self.faulted.set(packet.pid, faulted);
+            // A fault followed by a recovery before any message was sent cancels out.
+            let changed = !self.changed.contains(packet.pid);
+            self.changed.set(packet.pid, changed);
+        }
+    }
+
+    /// Return true if the last packet of the servomotor `id` reported an error.
+    pub fn is_faulted(&self, id: u8) -> bool {
+        id < 0xFE && self.faulted.contains(id)
+    }
+
+    /// Return the next message changing the color of a LED, if any.
+    pub fn next_message(&mut self) -> Option<HerkulexMessage> {
+        let id = self.changed.first()?;
+        self.changed.set(id, false);
+        let faulted = self.faulted.contains(id);
+        let builder = MessageBuilder::new_with_id(id);
+        let message = match self.signal {
+            LedSignal::Register => {
+                let led = if faulted { LED_RED } else { 0x00 };
+                builder
+                    .write_ram_unchecked(WritableRamAddr::LEDControl(led))
+                    .build()
+            }
+            LedSignal::Jog(color) => {
+                let color = if faulted { JogColor::Red } else { color };
+                builder.s_jog(0, JogMode::NoMove, color, id).build()
+            }
+        };
+        Some(message)
+    }
+
+    /// Forget the fault state of every servomotor, without sending any message.
+    pub fn clear(&mut self) {
+        self.faulted = IdSet::default();
+        self.changed = IdSet::default();
+    }
+}
+
+impl ACKHandler for FaultLed {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        self.update(packet);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::WritableRamAddr;
+    use builder::MessageBuilder;
+    use fault_led::{FaultLed, LedSignal};
+    use message::{JogColor, JogMode};
+    use reader::{stat_ack, StatusError};
+    use servo::Servo;
+
+    #[test]
+    fn register() {
+        let mut leds = FaultLed::new();
+        leds.update(&stat_ack(0x40, StatusError::NoError));
+        assert_eq!(leds.next_message(), None);
+
+        leds.update(&stat_ack(0x40, StatusError::OverloadDetected));
+        leds.update(&stat_ack(0x40, StatusError::OverloadDetected));
+        leds.update(&stat_ack(0x41, StatusError::ExceedTemperatureLimit));
+        assert!(leds.is_faulted(0x40));
+        let led = |id, value| {
+            Servo::new(id)
+                .ram_write(WritableRamAddr::LEDControl(value))
+                .ok()
+        };
+        assert_eq!(leds.next_message(), led(0x40, 0x04));
+        assert_eq!(leds.next_message(), led(0x41, 0x04));
+        assert_eq!(leds.next_message(), None);
+
+        leds.update(&stat_ack(0x40, StatusError::NoError));
+        assert!(!leds.is_faulted(0x40));
+        assert_eq!(leds.next_message(), led(0x40, 0x00));
+
+        // The recovery happened before the fault was shown.
+        leds.update(&stat_ack(0x41, StatusError::NoError));
+        leds.update(&stat_ack(0x41, StatusError::ExceedTemperatureLimit));
+        assert_eq!(leds.next_message(), None);
+    }
+
+    #[test]
+    fn jog() {
+        let mut leds = FaultLed::new().with_signal(LedSignal::Jog(JogColor::Blue));
+        leds.update(&stat_ack(0xFD, StatusError::InvalidPacket));
+        leds.update(&stat_ack(0xFE, StatusError::InvalidPacket));
+        let jog = |color| {
+            Some(
+                MessageBuilder::new_with_id(0xFD)
+                    .s_jog(0, JogMode::NoMove, color, 0xFD)
+                    .build(),
+            )
+        };
+        assert_eq!(leds.next_message(), jog(JogColor::Red));
+        assert_eq!(leds.next_message(), None);
+        leds.update(&stat_ack(0xFD, StatusError::NoError));
+        assert_eq!(leds.next_message(), jog(JogColor::Blue));
+    }
+}
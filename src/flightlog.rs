@@ -0,0 +1,355 @@
+//! Fixed-size records of the frames exchanged on the bus, for black-box recorders.
+//!
+//! A [`FlightRecorder`](struct.FlightRecorder.html) encodes every frame sent or received into a
+//! record of [`RECORD_LEN`](constant.RECORD_LEN.html) bytes, which the application writes to a
+//! circular log in flash. After a failure in the field, the log is read back and decoded on the
+//! host with [`Records`](struct.Records.html), or [`decode_log`](fn.decode_log.html) which puts
+//! the records of a circular log back in order.
+//!
+//! A record is laid out as `[magic][flags][len][seq_lo][seq_hi][timestamp...][data...][crc...]` :
+//!
+//! * `magic` is [`MAGIC`](constant.MAGIC.html), erased flash is never taken for a record,
+//! * `flags` holds the direction in its bit 0 and the status in its high nibble,
+//! * `len` is the length of the frame, of which only the first
+//!   [`RECORD_DATA_LEN`](constant.RECORD_DATA_LEN.html) bytes are kept,
+//! * `seq` is the sequence number of the record and `timestamp` the tick of the application
+//!   clock, both little endian,
+//! * the CRC is the CRC-16/CCITT-FALSE of every previous byte, so that a record torn by a power
+//!   loss is detected.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::flightlog::{Direction, FlightRecorder, Record, RecordStatus};
+//! use drs_0x01::Servo;
+//!
+//! let mut recorder = FlightRecorder::new();
+//! let record = recorder.record(1000, Direction::Sent, &Servo::new(0x40).stat(), RecordStatus::Ok);
+//! // Write the record to the flash ...
+//! let decoded = Record::decode(&record).unwrap();
+//! assert_eq!(decoded.timestamp, 1000);
+//! assert_eq!(decoded.bytes(), &Servo::new(0x40).stat()[..]);
+//! ```
+
+use bridge::crc16;
+
+/// The length of a record.
+pub const RECORD_LEN: usize = 32;
+
+/// The number of bytes of a frame kept in a record.
+pub const RECORD_DATA_LEN: usize = 21;
+
+/// The first byte of every record.
+pub const MAGIC: u8 = 0xA5;
+
+const DATA_OFFSET: usize = 9;
+
+/// The direction of a recorded frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// A request sent to the servomotors
+    Sent,
+    /// An answer received from a servomotor
+    Received,
+}
+
+/// What the application made of a recorded frame.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RecordStatus {
+    /// The frame was sent, or received and valid.
+    Ok,
+    /// The frame was received with invalid checksums.
+    ChecksumError,
+    /// The answer expected was not received in time, the frame is the request.
+    Timeout,
+    /// The bytes received could not be decoded as a frame.
+    Garbage,
+}
+
+impl RecordStatus {
+    /// Return the code of the status in a record.
+    pub fn code(self) -> u8 {
+        match self {
+            RecordStatus::Ok => 0,
+            RecordStatus::ChecksumError => 1,
+            RecordStatus::Timeout => 2,
+            RecordStatus::Garbage => 3,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<RecordStatus> {
+        match code {
+            0 => Some(RecordStatus::Ok),
+            1 => Some(RecordStatus::ChecksumError),
+            2 => Some(RecordStatus::Timeout),
+            3 => Some(RecordStatus::Garbage),
+            _ => None,
+        }
+    }
+}
+
+/// The error returned when decoding a record.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FlightLogError {
+    /// The bytes end before the record.
+    Incomplete,
+    /// The record was never written : every byte is 0xFF.
+    Erased,
+    /// The first byte is not `MAGIC`.
+    InvalidMagic(u8),
+    /// The CRC of the record is invalid.
+    InvalidCrc,
+    /// The status of the record is unknown.
+    InvalidStatus(u8),
+}
+
+/// A decoded record.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// The sequence number of the record
+    pub sequence: u16,
+    /// The tick of the application clock at which the frame was recorded
+    pub timestamp: u32,
+    /// The direction of the frame
+    pub direction: Direction,
+    /// What the application made of the frame
+    pub status: RecordStatus,
+    /// The length of the frame
+    pub len: u8,
+    /// The first bytes of the frame, padded with zeros
+    pub data: [u8; RECORD_DATA_LEN],
+}
+
+impl Record {
+    /// Return the bytes of the frame kept in the record.
+    pub fn bytes(&self) -> &[u8] {
+        &self.data[..usize::from(self.len).min(RECORD_DATA_LEN)]
+    }
+
+    /// Return true if the frame was too long to be kept whole.
+    pub fn is_truncated(&self) -> bool {
+        usize::from(self.len) > RECORD_DATA_LEN
+    }
+
+    /// Encode the record.
+    pub fn encode(&self) -> [u8; RECORD_LEN] {
+        let mut record = [0; RECORD_LEN];
+        let direction = match self.direction {
+            Direction::Sent => 0,
+            Direction::Received => 1,
+        };
+        record[0] = MAGIC;
+        record[1] = self.status.code() << 4 | direction;
+        record[2] = self.len;
+        record[3..5].copy_from_slice(&self.sequence.to_le_bytes());
+        record[5..DATA_OFFSET].copy_from_slice(&self.timestamp.to_le_bytes());
+        record[DATA_OFFSET..DATA_OFFSET + RECORD_DATA_LEN].copy_from_slice(&self.data);
+        let crc = crc16(&record[..RECORD_LEN - 2]);
+        record[RECORD_LEN - 2..].copy_from_slice(&crc.to_le_bytes());
+        record
+    }
+
+    /// Decode the record at the start of `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Return [`FlightLogError::Erased`](enum.FlightLogError.html) for a slot of the log which was
+    /// never written, and the other errors if the record is invalid.
+    pub fn decode(bytes: &[u8]) -> Result<Record, FlightLogError> {
+        let record = bytes.get(..RECORD_LEN).ok_or(FlightLogError::Incomplete)?;
+        if record.iter().all(|&byte| byte == 0xFF) {
+            return Err(FlightLogError::Erased);
+        }
+        if record[0] != MAGIC {
+            return Err(FlightLogError::InvalidMagic(record[0]));
+        }
+        let crc = u16::from_le_bytes([record[RECORD_LEN - 2], record[RECORD_LEN - 1]]);
+        if crc16(&record[..RECORD_LEN - 2]) != crc {
+            return Err(FlightLogError::InvalidCrc);
+        }
+        let status = RecordStatus::from_code(record[1] >> 4)
+            .ok_or(FlightLogError::InvalidStatus(record[1] >> 4))?;
+        let mut data = [0; RECORD_DATA_LEN];
+        data.copy_from_slice(&record[DATA_OFFSET..DATA_OFFSET + RECORD_DATA_LEN]);
+        Ok(Record {
+            sequence: u16::from_le_bytes([record[3], record[4]]),
+            timestamp: u32::from_le_bytes([record[5], record[6], record[7], record[8]]),
+            direction: if record[1] & 0x01 == 0 {
+                Direction::Sent
+            } else {
+                Direction::Received
+            },
+            status,
+            len: record[2],
+            data,
+        })
+    }
+}
+
+/// This struct numbers and encodes the records of a log.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FlightRecorder {
+    sequence: u16,
+}
+
+impl FlightRecorder {
+    /// Create a recorder numbering its records from 0.
+    pub fn new() -> FlightRecorder {
+        FlightRecorder::default()
+    }
+
+    /// Create a recorder numbering its records from `sequence`, to resume a log after a reboot.
+    pub fn starting_at(sequence: u16) -> FlightRecorder {
+        FlightRecorder { sequence }
+    }
+
+    /// Return the sequence number of the next record.
+    pub fn sequence(&self) -> u16 {
+        self.sequence
+    }
+
+    /// Return the record of `frame`, recorded at `timestamp`.
+    pub fn record(
+        &mut self,
+        timestamp: u32,
+        direction: Direction,
+        frame: &[u8],
+        status: RecordStatus,
+    ) -> [u8; RECORD_LEN] {
+        let mut data = [0; RECORD_DATA_LEN];
+        let kept = frame.len().min(RECORD_DATA_LEN);
+        data[..kept].copy_from_slice(&frame[..kept]);
+        let record = Record {
+            sequence: self.sequence,
+            timestamp,
+            direction,
+            status,
+            len: frame.len().min(usize::from(u8::MAX)) as u8,
+            data,
+        };
+        self.sequence = self.sequence.wrapping_add(1);
+        record.encode()
+    }
+}
+
+/// An iterator over the records of a log, skipping the slots which were never written.
+#[derive(Clone, Debug)]
+pub struct Records<'a> {
+    slots: core::slice::ChunksExact<'a, u8>,
+}
+
+impl<'a> Records<'a> {
+    /// Iterate over the records of `log`. The bytes after the last whole record are ignored.
+    pub fn new(log: &'a [u8]) -> Records<'a> {
+        Records {
+            slots: log.chunks_exact(RECORD_LEN),
+        }
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Result<Record, FlightLogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots
+            .by_ref()
+            .map(Record::decode)
+            .find(|record| *record != Err(FlightLogError::Erased))
+    }
+}
+
+/// Decode the valid records of the circular log `log`, from the oldest to the newest, and return
+/// them with the number of slots which held an invalid record.
+///
+/// The oldest record is the one following the largest gap between the sequence numbers, so the
+/// log must hold less than 65536 records.
+///
+/// This function is available with the `std` feature.
+#[cfg(feature = "std")]
+pub fn decode_log(log: &[u8]) -> (std::vec::Vec<Record>, usize) {
+    let mut invalid = 0;
+    let mut records: std::vec::Vec<Record> = Records::new(log)
+        .filter_map(|record| record.map_err(|_| invalid += 1).ok())
+        .collect();
+    records.sort_by_key(|record| record.sequence);
+    let oldest = (0..records.len())
+        .max_by_key(|&i| {
+            let previous = records[(i + records.len() - 1) % records.len()].sequence;
+            records[i].sequence.wrapping_sub(previous).wrapping_sub(1)
+        })
+        .unwrap_or(0);
+    records.rotate_left(oldest);
+    (records, invalid)
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "std")]
+    use flightlog::decode_log;
+    use flightlog::{
+        Direction, FlightLogError, FlightRecorder, Record, RecordStatus, Records, RECORD_LEN,
+    };
+    use servo::Servo;
+    use std::vec::Vec;
+
+    #[test]
+    fn records() {
+        let request = Servo::new(0x40).stat();
+        let ack = [0xFF, 0xFF, 0x09, 0x40, 0x47, 0xC0, 0x3E, 0x00, 0x00];
+        let jog: Vec<u8> = (0..30).collect();
+
+        // A circular log of 4 slots, wrapped around : the slot 1 is torn and the slot 2 erased.
+        let mut recorder = FlightRecorder::starting_at(u16::MAX - 2);
+        let mut log = [0xFF; 4 * RECORD_LEN];
+        let slot = |log: &mut [u8], index: usize, record: [u8; RECORD_LEN]| {
+            log[index * RECORD_LEN..][..RECORD_LEN].copy_from_slice(&record);
+        };
+        slot(
+            &mut log,
+            3,
+            recorder.record(10, Direction::Sent, &request, RecordStatus::Ok),
+        );
+        let received = recorder.record(12, Direction::Received, &ack, RecordStatus::ChecksumError);
+        slot(&mut log, 0, received);
+        slot(
+            &mut log,
+            1,
+            recorder.record(20, Direction::Sent, &jog, RecordStatus::Timeout),
+        );
+        log[RECORD_LEN + 12] ^= 0x01;
+        assert_eq!(recorder.sequence(), 0);
+
+        let decoded = Record::decode(&received).unwrap();
+        assert_eq!(decoded.sequence, u16::MAX - 1);
+        assert_eq!(decoded.direction, Direction::Received);
+        assert_eq!(decoded.status, RecordStatus::ChecksumError);
+        assert_eq!(decoded.bytes(), ack);
+        assert!(!decoded.is_truncated());
+
+        let records: Vec<_> = Records::new(&log).collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[1], Err(FlightLogError::InvalidCrc));
+        #[cfg(feature = "std")]
+        {
+            let (records, invalid) = decode_log(&log);
+            assert_eq!(invalid, 1);
+            assert_eq!(
+                records.iter().map(|r| r.timestamp).collect::<Vec<_>>(),
+                [10, 12]
+            );
+            assert_eq!(records[0].bytes(), &request[..]);
+        }
+
+        let mut recorder = FlightRecorder::new();
+        let truncated =
+            Record::decode(&recorder.record(0, Direction::Sent, &jog, RecordStatus::Ok));
+        assert!(truncated.unwrap().is_truncated());
+        assert_eq!(truncated.unwrap().bytes(), &jog[..21]);
+        assert_eq!(Record::decode(&log[..10]), Err(FlightLogError::Incomplete));
+        assert_eq!(
+            Record::decode(&[0; RECORD_LEN]),
+            Err(FlightLogError::InvalidMagic(0))
+        );
+    }
+}
@@ -0,0 +1,493 @@
+//! The sizes of the Herkulex frames, to size the buffers of a serial port.
+//!
+//! A frame is laid out as `[0xFF][0xFF][psize][pid][cmd][checksum1][checksum2][data...]`, the data
+//! of an ACK ending with the status error and status detail registers. The buffers of this crate
+//! are derived from those constants.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::frame::{MAX_ACK_LEN, MAX_REQUEST_LEN};
+//!
+//! // Enough room for the largest request and its answer.
+//! let mut uart_buffer = [0u8; MAX_REQUEST_LEN + MAX_ACK_LEN];
+//! ```
+
+use checksum::{checksum1, checksum2};
+use config::AckPolicy;
+use servo::BROADCAST_ID;
+
+use core::fmt;
+use try_from::TryFrom;
+
+/// The length of the header (`0xFF 0xFF`).
+pub const HEADER_LEN: usize = 2;
+
+/// The length of a frame without any data : header, size, ID, command and both checksums.
+pub const MIN_FRAME_LEN: usize = HEADER_LEN + 5;
+
+/// The length of the status registers ending every ACK.
+pub const ACK_STATUS_LEN: usize = 2;
+
+/// The length of the address and length bytes of the memory reads and writes.
+pub const MEM_OVERHEAD: usize = 2;
+
+/// The maximum number of servomotors addressed by a single **S_JOG** or **I_JOG** request.
+pub const MAX_JOG_ENTRIES: usize = 10;
+
+/// The length of the playtime shared by every servomotor of a **S_JOG** request.
+pub const SJOG_OVERHEAD: usize = 1;
+
+/// The length of the data of each servomotor of a **S_JOG** request.
+pub const SJOG_ENTRY_LEN: usize = 4;
+
+/// The length of the data of each servomotor of a **I_JOG** request.
+pub const IJOG_ENTRY_LEN: usize = 5;
+
+/// The maximum length of the data of a request built by this crate, which is a full **I_JOG**.
+pub const MAX_REQUEST_DATA_LEN: usize = MAX_JOG_ENTRIES * IJOG_ENTRY_LEN;
+
+/// The maximum length of a request built by this crate.
+pub const MAX_REQUEST_LEN: usize = MIN_FRAME_LEN + MAX_REQUEST_DATA_LEN;
+
+/// The maximum number of bytes read from a register by a single request decoded by an
+/// [`ACKReader`](../reader/struct.ACKReader.html).
+pub const MAX_READ_LEN: usize = 2;
+
+/// The length of the shortest ACK carrying the status registers, which answers a command without
+/// data. Some firmwares also send ACKs of `MIN_FRAME_LEN` bytes, without the status registers.
+pub const MIN_ACK_LEN: usize = MIN_FRAME_LEN + ACK_STATUS_LEN;
+
+/// The length of the longest ACK decoded by an [`ACKReader`](../reader/struct.ACKReader.html),
+/// which answers a read of `MAX_READ_LEN` bytes.
+pub const MAX_ACK_LEN: usize = MIN_ACK_LEN + MEM_OVERHEAD + MAX_READ_LEN;
+
+/// The command of a request, sent to the servomotors.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RequestCommand {
+    /// **EEP_WRITE**
+    EEPWrite = 0x01,
+    /// **EEP_READ**
+    EEPRead = 0x02,
+    /// **RAM_WRITE**
+    RamWrite = 0x03,
+    /// **RAM_READ**
+    RamRead = 0x04,
+    /// **I_JOG**
+    IJog = 0x05,
+    /// **S_JOG**
+    SJog = 0x06,
+    /// **STAT**
+    Stat = 0x07,
+    /// **ROLLBACK**
+    Rollback = 0x08,
+    /// **REBOOT**
+    Reboot = 0x09,
+}
+
+/// The command of an ACK, sent back by the servomotors : the code of the request answered plus
+/// `0x40`.
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::frame::{AckCommand, RequestCommand};
+///
+/// assert_eq!(RequestCommand::Stat.ack(), AckCommand::Stat);
+/// assert_eq!(u8::from(AckCommand::Stat), 0x47);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AckCommand {
+    /// The answer to a **EEP_WRITE**
+    EEPWrite = 0x41,
+    /// The answer to a **EEP_READ**
+    EEPRead = 0x42,
+    /// The answer to a **RAM_WRITE**
+    RamWrite = 0x43,
+    /// The answer to a **RAM_READ**
+    RamRead = 0x44,
+    /// The answer to a **I_JOG**
+    IJog = 0x45,
+    /// The answer to a **S_JOG**
+    SJog = 0x46,
+    /// The answer to a **STAT**
+    Stat = 0x47,
+    /// The answer to a **ROLLBACK**
+    Rollback = 0x48,
+    /// The answer to a **REBOOT**
+    Reboot = 0x49,
+}
+
+// The offset between the code of a request and the code of its ACK.
+const ACK_OFFSET: u8 = 0x40;
+
+impl RequestCommand {
+    const ALL: [RequestCommand; 9] = [
+        RequestCommand::EEPWrite,
+        RequestCommand::EEPRead,
+        RequestCommand::RamWrite,
+        RequestCommand::RamRead,
+        RequestCommand::IJog,
+        RequestCommand::SJog,
+        RequestCommand::Stat,
+        RequestCommand::Rollback,
+        RequestCommand::Reboot,
+    ];
+
+    /// Return the command of the ACK answering this request.
+    pub fn ack(self) -> AckCommand {
+        AckCommand::ALL[self as usize - 1]
+    }
+}
+
+impl AckCommand {
+    const ALL: [AckCommand; 9] = [
+        AckCommand::EEPWrite,
+        AckCommand::EEPRead,
+        AckCommand::RamWrite,
+        AckCommand::RamRead,
+        AckCommand::IJog,
+        AckCommand::SJog,
+        AckCommand::Stat,
+        AckCommand::Rollback,
+        AckCommand::Reboot,
+    ];
+
+    /// Return the command of the request answered by this ACK.
+    pub fn request(self) -> RequestCommand {
+        RequestCommand::ALL[(self as u8 - ACK_OFFSET) as usize - 1]
+    }
+}
+
+impl From<RequestCommand> for u8 {
+    fn from(cmd: RequestCommand) -> u8 {
+        cmd as u8
+    }
+}
+
+impl From<AckCommand> for u8 {
+    fn from(cmd: AckCommand) -> u8 {
+        cmd as u8
+    }
+}
+
+impl TryFrom<u8> for RequestCommand {
+    type Err = FrameError;
+
+    fn try_from(code: u8) -> Result<RequestCommand, FrameError> {
+        RequestCommand::ALL
+            .iter()
+            .cloned()
+            .find(|cmd| u8::from(*cmd) == code)
+            .ok_or(FrameError::UnknownCommand(code))
+    }
+}
+
+impl TryFrom<u8> for AckCommand {
+    type Err = FrameError;
+
+    fn try_from(code: u8) -> Result<AckCommand, FrameError> {
+        AckCommand::ALL
+            .iter()
+            .cloned()
+            .find(|cmd| u8::from(*cmd) == code)
+            .ok_or(FrameError::UnknownCommand(code))
+    }
+}
+
+/// The structural error found in a request by [`ValidateFrame`](trait.ValidateFrame.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FrameError {
+    /// The frame is shorter than `MIN_FRAME_LEN` or longer than `MAX_REQUEST_LEN`.
+    InvalidLength(usize),
+    /// The frame does not start with `0xFF 0xFF`.
+    InvalidHeader,
+    /// The packet size announced by the frame is not its length.
+    SizeMismatch {
+        /// The packet size announced by the frame
+        announced: u8,
+        /// The length of the frame
+        actual: usize,
+    },
+    /// The command is not one of the request commands.
+    UnknownCommand(u8),
+    /// The length of the data does not match the command.
+    InvalidData,
+    /// The first checksum does not match the content of the frame.
+    Checksum1,
+    /// The second checksum does not match the first one.
+    Checksum2,
+}
+
+/// A self-check of a request, to make sure it is valid before sending it.
+///
+/// This is implemented for byte slices, so it can be called on a
+/// [`HerkulexMessage`](../builder/type.HerkulexMessage.html) which was modified by hand, or on a
+/// buffer which may have been corrupted since the message was built.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::frame::{FrameError, ValidateFrame};
+/// use drs_0x01::Servo;
+///
+/// let mut message = Servo::new(0x40).stat();
+/// assert_eq!(message.validate(), Ok(()));
+/// message[3] = 0x42;
+/// assert_eq!(message.validate(), Err(FrameError::Checksum1));
+/// ```
+pub trait ValidateFrame {
+    /// Check the header, the size, the command, the length of the data and both checksums.
+    ///
+    /// # Errors
+    ///
+    /// Return the first error found, in the order of the frame.
+    fn validate(&self) -> Result<(), FrameError>;
+}
+
+impl ValidateFrame for [u8] {
+    fn validate(&self) -> Result<(), FrameError> {
+        if self.len() < MIN_FRAME_LEN || self.len() > MAX_REQUEST_LEN {
+            return Err(FrameError::InvalidLength(self.len()));
+        }
+        if self[..HEADER_LEN] != [0xFF, 0xFF] {
+            return Err(FrameError::InvalidHeader);
+        }
+        if usize::from(self[2]) != self.len() {
+            return Err(FrameError::SizeMismatch {
+                announced: self[2],
+                actual: self.len(),
+            });
+        }
+        let data = &self[MIN_FRAME_LEN..];
+        let valid_data = match RequestCommand::try_from(self[4])? {
+            // EEP_WRITE and RAM_WRITE : address, length and the bytes written
+            RequestCommand::EEPWrite | RequestCommand::RamWrite => {
+                data.len() > MEM_OVERHEAD && data[1] as usize == data.len() - 2
+            }
+            // EEP_READ and RAM_READ : address and length
+            RequestCommand::EEPRead | RequestCommand::RamRead => data.len() == MEM_OVERHEAD,
+            RequestCommand::IJog => !data.is_empty() && data.len() % IJOG_ENTRY_LEN == 0,
+            RequestCommand::SJog => {
+                data.len() > SJOG_OVERHEAD && (data.len() - SJOG_OVERHEAD) % SJOG_ENTRY_LEN == 0
+            }
+            RequestCommand::Stat | RequestCommand::Reboot => data.is_empty(),
+            // ROLLBACK : the ID and baudrate skip flags
+            RequestCommand::Rollback => data.len() == 2,
+        };
+        if !valid_data {
+            return Err(FrameError::InvalidData);
+        }
+        if self[5] != checksum1(&self[2..5]) ^ checksum1(data) {
+            return Err(FrameError::Checksum1);
+        }
+        if self[6] != checksum2(self[5]) {
+            return Err(FrameError::Checksum2);
+        }
+        Ok(())
+    }
+}
+
+/// The length of the answer to a request, to size the reception of a serial port (a DMA
+/// transfer or an idle line timeout) instead of reading the answer byte by byte.
+///
+/// This is implemented for byte slices, like [`ValidateFrame`](trait.ValidateFrame.html).
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::config::AckPolicy;
+/// use drs_0x01::frame::{ExpectedReply, MIN_ACK_LEN};
+/// use drs_0x01::{ReadableRamAddr, Servo};
+///
+/// let servo = Servo::new(0x40);
+/// let read = servo.ram_request(ReadableRamAddr::CalibratedPosition);
+/// assert_eq!(read.expected_reply_len(AckPolicy::ReplyToRead), Some(MIN_ACK_LEN + 4));
+/// let reboot = servo.reboot();
+/// assert_eq!(reboot.expected_reply_len(AckPolicy::ReplyToRead), None);
+/// assert_eq!(reboot.expected_reply_len(AckPolicy::ReplyToAll), Some(MIN_ACK_LEN));
+/// ```
+pub trait ExpectedReply {
+    /// Return the length of the answer to this request by a servomotor whose `AckPolicy`
+    /// register is `policy`, or `None` if it is not answered. Requests to the broadcast ID and
+    /// frames too short to hold a command are never answered.
+    fn expected_reply_len(&self, policy: AckPolicy) -> Option<usize>;
+}
+
+impl ExpectedReply for [u8] {
+    fn expected_reply_len(&self, policy: AckPolicy) -> Option<usize> {
+        if self.len() < MIN_FRAME_LEN || self[3] == BROADCAST_ID {
+            return None;
+        }
+        let read = match RequestCommand::try_from(self[4]).ok()? {
+            // EEP_READ and RAM_READ : the bytes requested follow the address and the length
+            RequestCommand::EEPRead | RequestCommand::RamRead => {
+                MEM_OVERHEAD + usize::from(*self.get(MIN_FRAME_LEN + 1)?)
+            }
+            RequestCommand::Stat => 0,
+            _ => {
+                return match policy {
+                    AckPolicy::ReplyToAll => Some(MIN_ACK_LEN),
+                    _ => None,
+                };
+            }
+        };
+        match policy {
+            AckPolicy::NoReply => None,
+            _ => Some(MIN_ACK_LEN + read),
+        }
+    }
+}
+
+/// Write `frame` to `out` as uppercase hexadecimal bytes separated by spaces, without any
+/// allocation, to print frames on a serial console.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::frame::fmt_hex;
+/// use drs_0x01::Servo;
+///
+/// let mut line = String::new();
+/// fmt_hex(&Servo::new(0xFD).stat(), &mut line).unwrap();
+/// assert_eq!(line, "FF FF 07 FD 07 FC 02");
+/// ```
+pub fn fmt_hex<W: fmt::Write + ?Sized>(frame: &[u8], out: &mut W) -> fmt::Result {
+    for (index, byte) in frame.iter().enumerate() {
+        if index > 0 {
+            out.write_char(' ')?;
+        }
+        write!(out, "{:02X}", byte)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{ReadableRamAddr, WritableRamAddr};
+    use builder::MessageBuilder;
+    use config::AckPolicy;
+    use frame::{
+        fmt_hex, AckCommand, ExpectedReply, FrameError, RequestCommand, ValidateFrame, MAX_ACK_LEN,
+        MAX_JOG_ENTRIES, MAX_REQUEST_LEN, MIN_ACK_LEN, MIN_FRAME_LEN,
+    };
+    use message::{JogColor, JogMode};
+    #[cfg(feature = "testvectors")]
+    use testvectors::REQUESTS;
+    use try_from::TryFrom;
+
+    #[test]
+    fn hex() {
+        let mut line = std::string::String::new();
+        fmt_hex(&[], &mut line).unwrap();
+        assert_eq!(line, "");
+        fmt_hex(&[0x0A, 0xB0, 0x00], &mut line).unwrap();
+        assert_eq!(line, "0A B0 00");
+    }
+
+    #[test]
+    fn commands() {
+        for cmd in &RequestCommand::ALL {
+            let ack = cmd.ack();
+            assert_eq!(ack.request(), *cmd);
+            assert_eq!(u8::from(ack), u8::from(*cmd) + 0x40);
+            assert_eq!(RequestCommand::try_from(u8::from(*cmd)), Ok(*cmd));
+            assert_eq!(AckCommand::try_from(u8::from(ack)), Ok(ack));
+        }
+        assert_eq!(
+            RequestCommand::try_from(0x47),
+            Err(FrameError::UnknownCommand(0x47))
+        );
+        assert_eq!(
+            AckCommand::try_from(0x07),
+            Err(FrameError::UnknownCommand(0x07))
+        );
+    }
+
+    #[test]
+    fn largest_request() {
+        assert_eq!(
+            MessageBuilder::new_with_id(0).stat().build().len(),
+            MIN_FRAME_LEN
+        );
+        let mut builder = MessageBuilder::new_with_id(0xFE).i_jog(
+            60,
+            JogMode::Normal { position: 512 },
+            JogColor::Green,
+            0,
+        );
+        for id in 1..MAX_JOG_ENTRIES as u8 {
+            builder
+                .s_jog(JogMode::Normal { position: 512 }, JogColor::Green, 60, id)
+                .unwrap();
+        }
+        assert_eq!(builder.build().len(), MAX_REQUEST_LEN);
+    }
+
+    #[cfg(feature = "testvectors")]
+    #[test]
+    fn validate_vectors() {
+        for vector in REQUESTS {
+            assert_eq!(vector.frame.validate(), Ok(()), "{}", vector.name);
+        }
+    }
+
+    #[test]
+    fn validate() {
+        let frame = MessageBuilder::new_with_id(0xFD).reboot().build();
+        let corrupt = |index: usize, byte: u8| {
+            let mut frame = frame.clone();
+            frame[index] = byte;
+            frame.validate()
+        };
+        assert_eq!(corrupt(0, 0xFE), Err(FrameError::InvalidHeader));
+        assert_eq!(
+            corrupt(2, 0x08),
+            Err(FrameError::SizeMismatch {
+                announced: 0x08,
+                actual: 7
+            })
+        );
+        assert_eq!(corrupt(4, 0x0A), Err(FrameError::UnknownCommand(0x0A)));
+        assert_eq!(corrupt(5, frame[5] ^ 0x02), Err(FrameError::Checksum1));
+        assert_eq!(corrupt(6, 0x00), Err(FrameError::Checksum2));
+        assert_eq!(frame[..6].validate(), Err(FrameError::InvalidLength(6)));
+
+        let mut stat = MessageBuilder::new_with_id(0xFD).stat().build();
+        stat.push(0x00);
+        stat[2] = 8;
+        assert_eq!(stat.validate(), Err(FrameError::InvalidData));
+    }
+
+    #[test]
+    fn expected_reply_len() {
+        let read = MessageBuilder::new_with_id(0x40)
+            .read_ram(ReadableRamAddr::MaxPWM, None)
+            .build();
+        let stat = MessageBuilder::new_with_id(0x40).stat().build();
+        let write = MessageBuilder::new_with_id(0x40)
+            .write_ram(WritableRamAddr::LEDControl(0x01))
+            .unwrap()
+            .build();
+        let broadcast = MessageBuilder::new_with_id(0xFE).stat().build();
+        assert_eq!(
+            read.expected_reply_len(AckPolicy::ReplyToRead),
+            Some(MAX_ACK_LEN)
+        );
+        assert_eq!(
+            stat.expected_reply_len(AckPolicy::ReplyToRead),
+            Some(MIN_ACK_LEN)
+        );
+        assert_eq!(write.expected_reply_len(AckPolicy::ReplyToRead), None);
+        assert_eq!(
+            write.expected_reply_len(AckPolicy::ReplyToAll),
+            Some(MIN_ACK_LEN)
+        );
+        assert_eq!(read.expected_reply_len(AckPolicy::NoReply), None);
+        assert_eq!(broadcast.expected_reply_len(AckPolicy::ReplyToAll), None);
+        assert_eq!(read[..4].expected_reply_len(AckPolicy::ReplyToAll), None);
+    }
+}
@@ -0,0 +1,246 @@
+//! Grippers closed by a servomotor, detecting the grasp of an object.
+//!
+//! A [`Gripper`](struct.Gripper.html) closes a servomotor with a limited PWM and follows its
+//! position and PWM, read by a [`PollPlan`](../poll/struct.PollPlan.html). When the servomotor
+//! stalls before reaching its closed position, an object is grasped and the gripper holds it with
+//! a reduced PWM, otherwise the gripper is empty.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::gripper::{GraspOutcome, Gripper};
+//! use drs_0x01::poll::PollPlan;
+//!
+//! let mut plan = PollPlan::new();
+//! let mut gripper = Gripper::new(0x40, 300, 700).with_pwm(400, 150);
+//! // With a 1 kHz tick, read the position and the PWM every 20 ms.
+//! gripper.poll(&mut plan, 20).unwrap();
+//! for message in gripper.close().iter() {
+//!     // Send the message ...
+//! }
+//! // Feed the answers to gripper.update(&packet) until it returns an outcome ...
+//! # let outcome = Some(GraspOutcome::Grasped { position: 650 });
+//! if let Some(GraspOutcome::Grasped { .. }) = outcome {
+//!     let hold = gripper.hold();
+//!     // Send the message ...
+//! }
+//! ```
+
+use addr::{ReadableRamAddr, WritableRamAddr};
+use builder::HerkulexMessage;
+use config::MAX_PWM;
+use motion::position_of;
+use poll::{PollPlan, PollPlanError};
+use reader::{ACKPacket, Command};
+use servo::Servo;
+
+/// The result of closing a [`Gripper`](struct.Gripper.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GraspOutcome {
+    /// The gripper stalled on an object at this position.
+    Grasped {
+        /// The position at which the gripper stalled
+        position: u16,
+    },
+    /// The gripper reached its closed position without meeting any object.
+    Empty,
+}
+
+/// This struct drives a gripper and detects whether it grasped an object.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Gripper {
+    servo: Servo,
+    open: u16,
+    closed: u16,
+    close_pwm: u16,
+    hold_pwm: u16,
+    margin: u16,
+    stall_samples: u8,
+    closing: bool,
+    stalled: u8,
+    position: Option<u16>,
+    pwm: Option<u16>,
+}
+
+impl Gripper {
+    /// Create a gripper driven by the servomotor `id`, open at the position `open` and closed at
+    /// the position `closed`. It closes with half of the maximum PWM and holds with a quarter of
+    /// it by default.
+    pub fn new(id: u8, open: u16, closed: u16) -> Gripper {
+        Gripper {
+            servo: Servo::new(id),
+            open,
+            closed,
+            close_pwm: MAX_PWM / 2,
+            hold_pwm: MAX_PWM / 4,
+            margin: 3,
+            stall_samples: 3,
+            closing: false,
+            stalled: 0,
+            position: None,
+            pwm: None,
+        }
+    }
+
+    /// Close with a PWM up to `close_pwm`, and hold a grasped object with a PWM up to `hold_pwm`.
+    pub fn with_pwm(mut self, close_pwm: u16, hold_pwm: u16) -> Gripper {
+        self.close_pwm = close_pwm.min(MAX_PWM);
+        self.hold_pwm = hold_pwm.min(MAX_PWM);
+        self
+    }
+
+    /// Consider the gripper stalled when its position changes by at most `margin` during
+    /// `samples` position reads in a row (at least 1), and closed when it is within `margin` of
+    /// its closed position. The defaults are 3 and 3.
+    pub fn with_stall_detection(mut self, margin: u16, samples: u8) -> Gripper {
+        self.margin = margin;
+        self.stall_samples = samples.max(1);
+        self
+    }
+
+    /// Read the position and the PWM of the servomotor every `period` ticks with `plan`.
+    ///
+    /// # Errors
+    ///
+    /// Return the errors of [`PollPlan::add`](../poll/struct.PollPlan.html#method.add).
+    pub fn poll(&self, plan: &mut PollPlan, period: u32) -> Result<(), PollPlanError> {
+        plan.add(self.servo.id(), ReadableRamAddr::CalibratedPosition, period)?;
+        plan.add(self.servo.id(), ReadableRamAddr::PWM, period)
+    }
+
+    /// Return the messages closing the gripper, which must be sent in order : the PWM is limited,
+    /// then the servomotor moves to the closed position.
+    pub fn close(&mut self) -> [HerkulexMessage; 2] {
+        self.closing = true;
+        self.stalled = 0;
+        [
+            self.max_pwm(self.close_pwm),
+            self.servo.set_position(self.closed),
+        ]
+    }
+
+    /// Return the message reducing the PWM to hold a grasped object.
+    pub fn hold(&self) -> HerkulexMessage {
+        self.max_pwm(self.hold_pwm)
+    }
+
+    /// Return the messages opening the gripper, which must be sent in order : the PWM limit is
+    /// lifted, then the servomotor moves to the open position.
+    pub fn open(&mut self) -> [HerkulexMessage; 2] {
+        self.closing = false;
+        [self.max_pwm(MAX_PWM), self.servo.set_position(self.open)]
+    }
+
+    /// Return true if the gripper is closing and no outcome was returned yet.
+    pub fn is_closing(&self) -> bool {
+        self.closing
+    }
+
+    /// Follow the position or the PWM carried by `packet`. While the gripper is closing, return
+    /// the outcome as soon as it is known.
+    pub fn update(&mut self, packet: &ACKPacket) -> Option<GraspOutcome> {
+        if packet.pid != self.servo.id() {
+            return None;
+        }
+        if let Command::RamRead { data } = packet.cmd {
+            if data.addr == ReadableRamAddr::PWM {
+                self.pwm = data.as_i16().map(i16::unsigned_abs);
+                return None;
+            }
+        }
+        let position = position_of(packet)?;
+        let last = self.position.replace(position);
+        if !self.closing {
+            return None;
+        }
+        if position.abs_diff(self.closed) <= self.margin {
+            self.closing = false;
+            return Some(GraspOutcome::Empty);
+        }
+        // The PWM of a stalled servomotor rises up to its limit.
+        let pushing = self.pwm.is_none_or(|pwm| pwm >= self.close_pwm / 2);
+        match last {
+            Some(last) if last.abs_diff(position) <= self.margin && pushing => self.stalled += 1,
+            _ => self.stalled = 0,
+        }
+        if self.stalled >= self.stall_samples {
+            self.closing = false;
+            Some(GraspOutcome::Grasped { position })
+        } else {
+            None
+        }
+    }
+
+    // The `MaxPWM` register is in RAM and never refused, so this write can not fail.
+    fn max_pwm(&self, pwm: u16) -> HerkulexMessage {
+        self.servo
+            .ram_write_unchecked(WritableRamAddr::MaxPWM(pwm as u8, (pwm >> 8) as u8))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{RamReadData, ReadableRamAddr};
+    use gripper::{GraspOutcome, Gripper};
+    use poll::PollPlan;
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+    use reg;
+    use servo::Servo;
+
+    fn read(addr: ReadableRamAddr, value: u16) -> ACKPacket {
+        ACKPacket {
+            pid: 0x40,
+            cmd: Command::RamRead {
+                data: RamReadData {
+                    addr,
+                    data_len: 2,
+                    data: [value as u8, (value >> 8) as u8],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        }
+    }
+
+    #[test]
+    fn grasp() {
+        let servo = Servo::new(0x40);
+        let mut plan = PollPlan::new();
+        let mut gripper = Gripper::new(0x40, 300, 700)
+            .with_pwm(400, 150)
+            .with_stall_detection(2, 2);
+        gripper.poll(&mut plan, 20).unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(
+            gripper.close(),
+            [
+                servo.write::<reg::MaxPWM>(400).unwrap(),
+                servo.set_position(700)
+            ]
+        );
+        let position = |value| read(ReadableRamAddr::CalibratedPosition, value);
+        assert_eq!(gripper.update(&position(400)), None);
+        assert_eq!(gripper.update(&position(550)), None);
+        // The servomotor stops, but its PWM is low : it is not pushing on anything.
+        assert_eq!(gripper.update(&read(ReadableRamAddr::PWM, 50)), None);
+        assert_eq!(gripper.update(&position(551)), None);
+        assert_eq!(gripper.update(&position(551)), None);
+        assert_eq!(gripper.update(&read(ReadableRamAddr::PWM, 400)), None);
+        assert_eq!(gripper.update(&position(552)), None);
+        assert_eq!(
+            gripper.update(&position(552)),
+            Some(GraspOutcome::Grasped { position: 552 })
+        );
+        assert!(!gripper.is_closing());
+        assert_eq!(gripper.hold(), servo.write::<reg::MaxPWM>(150).unwrap());
+
+        gripper.open();
+        assert_eq!(gripper.update(&position(300)), None);
+        gripper.close();
+        assert_eq!(gripper.update(&position(500)), None);
+        assert_eq!(gripper.update(&position(699)), Some(GraspOutcome::Empty));
+    }
+}
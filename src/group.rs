@@ -0,0 +1,331 @@
+//! Groups of servomotors driven together.
+//!
+//! A [`ServoGroup`](struct.ServoGroup.html) holds up to `N` [`Servo`](../struct.Servo.html)s.
+//! Its methods return [`GroupMessages`](enum.GroupMessages.html) : a single broadcast frame when
+//! one can address the whole group without reaching other servomotors, or one frame per
+//! servomotor otherwise. Both are iterated the same way.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::group::ServoGroup;
+//! use drs_0x01::Servo;
+//!
+//! let mut arm: ServoGroup<4> = ServoGroup::new();
+//! arm.add(Servo::new(0x40)).unwrap();
+//! arm.add(Servo::new(0x41)).unwrap();
+//! for message in arm.enable_torque() {
+//!     // Send the message ...
+//! }
+//! // A single S_JOG request moves both servomotors.
+//! assert_eq!(arm.goto(&[300, 700]).unwrap().count(), 1);
+//! ```
+
+use core::cmp::min;
+use core::slice;
+
+use builder::{HerkulexMessage, MessageBuilder};
+use message::JogMode;
+use servo::{Servo, BROADCAST_ID};
+
+/// The error returned by a [`ServoGroup`](struct.ServoGroup.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GroupError {
+    /// The group already holds its maximum number of servomotors.
+    GroupFull,
+    /// A servomotor with this ID is already in the group.
+    DuplicateId(u8),
+    /// The broadcast ID can not be a member of a group.
+    BroadcastId,
+    /// The pose does not have a position for every servomotor of the group.
+    InvalidPoseLength,
+}
+
+/// A group of up to `N` servomotors, with distinct IDs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ServoGroup<const N: usize> {
+    servos: [Servo; N],
+    len: usize,
+    whole_bus: bool,
+}
+
+impl<const N: usize> Default for ServoGroup<N> {
+    fn default() -> Self {
+        ServoGroup {
+            servos: [Servo::default(); N],
+            len: 0,
+            whole_bus: false,
+        }
+    }
+}
+
+impl<const N: usize> ServoGroup<N> {
+    /// Create an empty group.
+    pub fn new() -> ServoGroup<N> {
+        ServoGroup::default()
+    }
+
+    /// Declare that the group holds every servomotor of the bus, so that the writes addressed
+    /// to the whole group are broadcast instead of being sent to each servomotor.
+    pub fn with_whole_bus(mut self) -> ServoGroup<N> {
+        self.whole_bus = true;
+        self
+    }
+
+    /// Add `servo` to the group.
+    ///
+    /// # Errors
+    ///
+    /// Return [GroupError::GroupFull](enum.GroupError.html) if the group holds `N` servomotors,
+    /// [GroupError::DuplicateId](enum.GroupError.html) if a servomotor with the same ID is in the
+    /// group and [GroupError::BroadcastId](enum.GroupError.html) for the broadcast ID.
+    pub fn add(&mut self, servo: Servo) -> Result<(), GroupError> {
+        if servo.id() == BROADCAST_ID {
+            return Err(GroupError::BroadcastId);
+        }
+        if self.contains(servo.id()) {
+            return Err(GroupError::DuplicateId(servo.id()));
+        }
+        let slot = self.servos.get_mut(self.len).ok_or(GroupError::GroupFull)?;
+        *slot = servo;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Remove the servomotor with the ID `id` from the group, keeping the order of the others.
+    /// Return the servomotor removed, if any.
+    pub fn remove(&mut self, id: u8) -> Option<Servo> {
+        let index = self.servos().iter().position(|servo| servo.id() == id)?;
+        let servo = self.servos[index];
+        self.servos[index..self.len].rotate_left(1);
+        self.len -= 1;
+        Some(servo)
+    }
+
+    /// Return true if the servomotor with the ID `id` is in the group.
+    pub fn contains(&self, id: u8) -> bool {
+        self.servos().iter().any(|servo| servo.id() == id)
+    }
+
+    /// Return the servomotors of the group, in the order they were added.
+    pub fn servos(&self) -> &[Servo] {
+        &self.servos[..self.len]
+    }
+
+    /// Return the number of servomotors in the group.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return true if the group is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Request every servomotor of the group to enable torque. The write is broadcast if the
+    /// group was declared to hold the whole bus.
+    pub fn enable_torque(&self) -> GroupMessages<'_> {
+        if self.whole_bus && !self.is_empty() {
+            GroupMessages::Broadcast(Some(Servo::new(BROADCAST_ID).enable_torque()))
+        } else {
+            self.per_servo(GroupCommand::EnableTorque)
+        }
+    }
+
+    /// Request the servomotors of the group to go to the positions of `pose`, given in the order
+    /// of [`servos`](#method.servos). The positions are clamped to 1023, like
+    /// [`Servo::set_position`](../struct.Servo.html#method.set_position).
+    ///
+    /// A single **S_JOG** request moves the group if it has at most 10 servomotors sharing the
+    /// same playtime. The color of each servomotor comes from its options.
+    ///
+    /// # Errors
+    ///
+    /// Return [GroupError::InvalidPoseLength](enum.GroupError.html) if `pose` does not have one
+    /// position per servomotor.
+    pub fn goto<'a>(&'a self, pose: &'a [u16]) -> Result<GroupMessages<'a>, GroupError> {
+        if pose.len() != self.len {
+            return Err(GroupError::InvalidPoseLength);
+        }
+        let per_servo = self.per_servo(GroupCommand::Goto(pose.iter()));
+        let mode = |position| JogMode::Normal {
+            position: min(position, 1023),
+        };
+        let mut entries = self.servos().iter().zip(pose.iter());
+        let (first, &position) = match entries.next() {
+            Some(entry) => entry,
+            None => return Ok(per_servo),
+        };
+        let playtime = first.options().default_playtime;
+        let mut message = MessageBuilder::new_with_id(BROADCAST_ID).s_jog(
+            playtime,
+            mode(position),
+            first.options().default_color,
+            first.id(),
+        );
+        for (servo, &position) in entries {
+            let color = servo.options().default_color;
+            if servo.options().default_playtime != playtime
+                || message.s_jog(mode(position), color, servo.id()).is_err()
+            {
+                return Ok(per_servo);
+            }
+        }
+        Ok(GroupMessages::Broadcast(Some(message.build())))
+    }
+
+    /// Request every servomotor of the group to send its status. The servomotors do not answer
+    /// to the broadcast ID, so there is always one request per servomotor.
+    pub fn stat_all(&self) -> GroupMessages<'_> {
+        self.per_servo(GroupCommand::Stat)
+    }
+
+    fn per_servo<'a>(&'a self, command: GroupCommand<'a>) -> GroupMessages<'a> {
+        GroupMessages::PerServo(PerServo {
+            servos: self.servos().iter(),
+            command,
+        })
+    }
+}
+
+/// The messages addressed to a [`ServoGroup`](struct.ServoGroup.html), iterated in the order
+/// they must be sent.
+#[derive(Clone, Debug)]
+pub enum GroupMessages<'a> {
+    /// A single frame reaching the whole group, until it is iterated
+    Broadcast(Option<HerkulexMessage>),
+    /// One frame per servomotor of the group
+    PerServo(PerServo<'a>),
+}
+
+impl<'a> Iterator for GroupMessages<'a> {
+    type Item = HerkulexMessage;
+
+    fn next(&mut self) -> Option<HerkulexMessage> {
+        match *self {
+            GroupMessages::Broadcast(ref mut message) => message.take(),
+            GroupMessages::PerServo(ref mut messages) => messages.next(),
+        }
+    }
+}
+
+/// An iterator over the frames addressed to each servomotor of a
+/// [`ServoGroup`](struct.ServoGroup.html).
+#[derive(Clone, Debug)]
+pub struct PerServo<'a> {
+    servos: slice::Iter<'a, Servo>,
+    command: GroupCommand<'a>,
+}
+
+#[derive(Clone, Debug)]
+enum GroupCommand<'a> {
+    EnableTorque,
+    Goto(slice::Iter<'a, u16>),
+    Stat,
+}
+
+impl<'a> Iterator for PerServo<'a> {
+    type Item = HerkulexMessage;
+
+    fn next(&mut self) -> Option<HerkulexMessage> {
+        let servo = self.servos.next()?;
+        Some(match self.command {
+            GroupCommand::EnableTorque => servo.enable_torque(),
+            GroupCommand::Goto(ref mut pose) => servo.set_position(*pose.next()?),
+            GroupCommand::Stat => servo.stat(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use builder::MessageBuilder;
+    use group::{GroupError, GroupMessages, ServoGroup};
+    use message::{JogColor, JogMode};
+    use servo::{Servo, BROADCAST_ID};
+    use std::vec::Vec;
+
+    #[test]
+    fn membership() {
+        let mut group: ServoGroup<2> = ServoGroup::new();
+        assert!(group.is_empty());
+        group.add(Servo::new(0x40)).unwrap();
+        assert_eq!(
+            group.add(Servo::new(0x40)),
+            Err(GroupError::DuplicateId(0x40))
+        );
+        assert_eq!(
+            group.add(Servo::new(BROADCAST_ID)),
+            Err(GroupError::BroadcastId)
+        );
+        group.add(Servo::new(0x41)).unwrap();
+        assert_eq!(group.add(Servo::new(0x42)), Err(GroupError::GroupFull));
+        assert_eq!(group.remove(0x40), Some(Servo::new(0x40)));
+        assert_eq!(group.remove(0x40), None);
+        assert_eq!(group.servos(), &[Servo::new(0x41)]);
+        group.add(Servo::new(0x42)).unwrap();
+        assert_eq!(group.len(), 2);
+        assert!(group.contains(0x42));
+    }
+
+    #[test]
+    fn broadcast_or_per_servo() {
+        let mut group: ServoGroup<12> = ServoGroup::new();
+        group.add(Servo::new(0x40)).unwrap();
+        group
+            .add(Servo::new(0x41).with_color(JogColor::Red))
+            .unwrap();
+
+        let torque = group.enable_torque().collect::<Vec<_>>();
+        assert_eq!(
+            torque,
+            [
+                Servo::new(0x40).enable_torque(),
+                Servo::new(0x41).enable_torque()
+            ]
+        );
+        let torque = group.with_whole_bus().enable_torque().collect::<Vec<_>>();
+        assert_eq!(torque, [Servo::new(BROADCAST_ID).enable_torque()]);
+        let stat = group.with_whole_bus().stat_all().collect::<Vec<_>>();
+        assert_eq!(stat, [Servo::new(0x40).stat(), Servo::new(0x41).stat()]);
+
+        let mut expected = MessageBuilder::new_with_id(BROADCAST_ID).s_jog(
+            60,
+            JogMode::Normal { position: 300 },
+            JogColor::Blue,
+            0x40,
+        );
+        expected
+            .s_jog(JogMode::Normal { position: 1023 }, JogColor::Red, 0x41)
+            .unwrap();
+        assert_eq!(
+            group.goto(&[300, 2000]).unwrap().collect::<Vec<_>>(),
+            [expected.build()]
+        );
+        assert_eq!(
+            group.goto(&[300]).map(|_| ()),
+            Err(GroupError::InvalidPoseLength)
+        );
+
+        // Different playtimes, or too many servomotors for a S_JOG request.
+        group.remove(0x41);
+        group.add(Servo::new(0x41).with_playtime(30)).unwrap();
+        assert_eq!(
+            group.goto(&[300, 700]).unwrap().collect::<Vec<_>>(),
+            [
+                Servo::new(0x40).set_position(300),
+                Servo::new(0x41).with_playtime(30).set_position(700)
+            ]
+        );
+        group.remove(0x41);
+        for id in 1..11 {
+            group.add(Servo::new(id)).unwrap();
+        }
+        let pose = [512; 11];
+        match group.goto(&pose).unwrap() {
+            GroupMessages::PerServo(messages) => assert_eq!(messages.count(), 11),
+            GroupMessages::Broadcast(_) => panic!("11 servomotors can not share a S_JOG request"),
+        }
+    }
+}
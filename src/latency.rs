@@ -0,0 +1,282 @@
+//! Round-trip measurements, to help choosing a baudrate and a polling schedule.
+//!
+//! [`ping`](fn.ping.html) measures the time a servomotor takes to answer to a **STAT** request,
+//! and a [`LatencyTracker`](struct.LatencyTracker.html) keeps statistics of those measurements
+//! for every servomotor. Durations are expressed in ticks of the [`Clock`](../clock/trait.Clock.html)
+//! used.
+
+use arrayvec::ArrayVec;
+
+use clock::Clock;
+use reader::{ACKPacket, ACKReader, Command};
+use servo::Servo;
+use transport::{bytes_read, Transport};
+
+/// The maximum number of servomotors a [`LatencyTracker`](struct.LatencyTracker.html) can follow.
+pub const MAX_TRACKED_SERVOS: usize = 32;
+
+/// The error returned by [`ping`](fn.ping.html).
+#[derive(Debug, PartialEq, Eq)]
+pub enum PingError<E> {
+    /// The transport failed.
+    Transport(E),
+    /// The servomotor did not answer in time.
+    Timeout,
+}
+
+/// Send a **STAT** request to the servomotor `id` and return the number of ticks elapsed until its
+/// answer was parsed.
+///
+/// This function busy-waits on `transport` until the answer is received or until `timeout` ticks
+/// have elapsed. Every other packet received in the meantime is discarded. A timeout suited to
+/// the baudrate is given by
+/// [`Timings::reply_timeout_ticks`](../timings/struct.Timings.html#method.reply_timeout_ticks).
+///
+/// # Errors
+///
+/// Return [PingError::Timeout](enum.PingError.html) if the servomotor did not answer in time.
+pub fn ping<T: Transport + ?Sized, C: Clock + ?Sized>(
+    transport: &mut T,
+    clock: &C,
+    id: u8,
+    timeout: u32,
+) -> Result<u32, PingError<T::Error>> {
+    let _span = span!("ping", id, cmd = 0x07u8);
+    let mut reader = ACKReader::new();
+    let mut buf = [0; 32];
+    let start = clock.now();
+    transport
+        .write(&Servo::new(id).stat())
+        .map_err(PingError::Transport)?;
+    loop {
+        let read = transport.read(&mut buf).map_err(PingError::Transport)?;
+        let mut answered = false;
+        reader.parse_with(bytes_read(&buf, read), &mut |packet: &ACKPacket| {
+            answered |= packet.pid == id && packet.cmd == Command::Stat;
+        });
+        let elapsed = clock.now().wrapping_sub(start);
+        if answered {
+            return Ok(elapsed);
+        } else if elapsed > timeout {
+            log_debug!("no answer from 0x{:02X} to the ping", id);
+            return Err(PingError::Timeout);
+        }
+    }
+}
+
+/// Statistics of the round-trip times of a servomotor.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// The number of answers received
+    pub count: u32,
+    /// The number of requests which were not answered
+    pub timeouts: u32,
+    /// The shortest round-trip time
+    pub min: u32,
+    /// The longest round-trip time
+    pub max: u32,
+    /// The last round-trip time
+    pub last: u32,
+    total: u64,
+}
+
+impl LatencyStats {
+    /// Add a round-trip time to the statistics.
+    pub fn record(&mut self, ticks: u32) {
+        if self.count == 0 {
+            self.min = ticks;
+            self.max = ticks;
+        } else {
+            self.min = self.min.min(ticks);
+            self.max = self.max.max(ticks);
+        }
+        self.count += 1;
+        self.last = ticks;
+        self.total += u64::from(ticks);
+    }
+
+    /// Count a request which was not answered.
+    pub fn record_timeout(&mut self) {
+        self.timeouts += 1;
+    }
+
+    /// Return the mean round-trip time, if any answer was received.
+    pub fn mean(&self) -> Option<u32> {
+        if self.count == 0 {
+            None
+        } else {
+            Some((self.total / u64::from(self.count)) as u32)
+        }
+    }
+
+    /// Return the ratio of requests which were answered, between 0 and 1.
+    pub fn success_rate(&self) -> f32 {
+        let requests = self.count + self.timeouts;
+        if requests == 0 {
+            1.0
+        } else {
+            self.count as f32 / requests as f32
+        }
+    }
+}
+
+/// This struct keeps the round-trip statistics of up to `MAX_TRACKED_SERVOS` servomotors.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::latency::{ping, LatencyTracker, PingError};
+/// # use drs_0x01::transport::Transport;
+/// # struct Uart;
+/// # impl Transport for Uart {
+/// #     type Error = ();
+/// #     fn write(&mut self, _frame: &[u8]) -> Result<(), ()> { Ok(()) }
+/// #     fn read(&mut self, _buf: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+/// # }
+/// # let mut uart = Uart;
+/// # let tick = std::cell::Cell::new(0);
+/// # let clock = || { tick.set(tick.get() + 1); tick.get() };
+///
+/// let mut tracker = LatencyTracker::new();
+/// match ping(&mut uart, &clock, 0x40, 100) {
+///     Ok(ticks) => tracker.record(0x40, ticks),
+///     Err(PingError::Timeout) => tracker.record_timeout(0x40),
+///     Err(PingError::Transport(_)) => (),
+/// }
+/// assert_eq!(tracker.stats(0x40).unwrap().timeouts, 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LatencyTracker {
+    servos: ArrayVec<[(u8, LatencyStats); MAX_TRACKED_SERVOS]>,
+}
+
+impl LatencyTracker {
+    /// Create a tracker without any statistics.
+    pub fn new() -> LatencyTracker {
+        LatencyTracker::default()
+    }
+
+    fn entry(&mut self, id: u8) -> Option<&mut LatencyStats> {
+        if !self.servos.iter().any(|&(servo, _)| servo == id) {
+            self.servos.try_push((id, LatencyStats::default())).ok()?;
+        }
+        self.servos
+            .iter_mut()
+            .find(|&&mut (servo, _)| servo == id)
+            .map(|&mut (_, ref mut stats)| stats)
+    }
+
+    /// Add a round-trip time of the servomotor `id`. The measurement is ignored if
+    /// `MAX_TRACKED_SERVOS` other servomotors are already tracked.
+    pub fn record(&mut self, id: u8, ticks: u32) {
+        if let Some(stats) = self.entry(id) {
+            stats.record(ticks);
+        }
+    }
+
+    /// Count a request to the servomotor `id` which was not answered.
+    pub fn record_timeout(&mut self, id: u8) {
+        if let Some(stats) = self.entry(id) {
+            stats.record_timeout();
+        }
+    }
+
+    /// Return the statistics of the servomotor `id`.
+    pub fn stats(&self, id: u8) -> Option<LatencyStats> {
+        self.servos
+            .iter()
+            .find(|&&(servo, _)| servo == id)
+            .map(|&(_, stats)| stats)
+    }
+
+    /// Return the statistics of every tracked servomotor.
+    pub fn iter(&self) -> impl Iterator<Item = (u8, LatencyStats)> + '_ {
+        self.servos.iter().cloned()
+    }
+
+    /// Forget every statistic.
+    pub fn clear(&mut self) {
+        self.servos.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use latency::{ping, LatencyStats, LatencyTracker, PingError};
+    use transport::Transport;
+
+    use core::cell::Cell;
+    use std::vec::Vec;
+
+    /// A servomotor 0xFD answering to STAT requests after `delay` reads.
+    struct DelayedServo {
+        delay: usize,
+        pending: Option<usize>,
+    }
+
+    impl Transport for DelayedServo {
+        type Error = ();
+
+        fn write(&mut self, frame: &[u8]) -> Result<(), ()> {
+            if frame[3] == 0xFD {
+                self.pending = Some(self.delay);
+            }
+            Ok(())
+        }
+
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+            match self.pending {
+                Some(0) => {
+                    let ack = [0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01];
+                    buf[..ack.len()].copy_from_slice(&ack);
+                    self.pending = None;
+                    Ok(ack.len())
+                }
+                Some(delay) => {
+                    self.pending = Some(delay - 1);
+                    Ok(0)
+                }
+                None => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn ping_round_trip() {
+        let tick = Cell::new(0u32);
+        let clock = || {
+            tick.set(tick.get() + 1);
+            tick.get()
+        };
+        let mut servo = DelayedServo {
+            delay: 3,
+            pending: None,
+        };
+        assert_eq!(ping(&mut servo, &clock, 0xFD, 10), Ok(4));
+        assert_eq!(ping(&mut servo, &clock, 0xFD, 2), Err(PingError::Timeout));
+        assert_eq!(ping(&mut servo, &clock, 0x40, 10), Err(PingError::Timeout));
+    }
+
+    #[test]
+    fn statistics() {
+        let mut stats = LatencyStats::default();
+        assert_eq!(stats.mean(), None);
+        for ticks in [4, 2, 9].iter() {
+            stats.record(*ticks);
+        }
+        stats.record_timeout();
+        assert_eq!((stats.min, stats.max, stats.last), (2, 9, 9));
+        assert_eq!(stats.mean(), Some(5));
+        assert_eq!(stats.success_rate(), 0.75);
+
+        let mut tracker = LatencyTracker::new();
+        for id in 0..40 {
+            tracker.record(id, u32::from(id));
+        }
+        tracker.record(0, 2);
+        assert_eq!(tracker.stats(0).unwrap().mean(), Some(1));
+        assert_eq!(tracker.stats(39), None);
+        assert_eq!(tracker.iter().collect::<Vec<_>>().len(), 32);
+    }
+}
@@ -30,28 +30,150 @@
 //! # extern crate drs_0x01;
 //! use drs_0x01::builder::MessageBuilder;
 //! use drs_0x01::WritableRamAddr::TorqueControl;
-//! let message = MessageBuilder::new_with_id(35).write_ram(TorqueControl(1)).build();
+//! let message = MessageBuilder::new_with_id(35)
+//!     .write_ram(TorqueControl(1))
+//!     .unwrap()
+//!     .build();
 //! ```
 
 #![no_std]
 #![warn(missing_docs)]
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::unreachable,
+        clippy::panic
+    )
+)]
 
-#[cfg(test)]
+#[cfg(any(test, feature = "std"))]
 #[macro_use]
 extern crate std;
 
+#[cfg(test)]
+extern crate proptest;
+
 extern crate arrayvec;
+#[cfg(feature = "embedded-hal")]
+extern crate embedded_hal;
+#[cfg(feature = "log")]
+extern crate log;
+#[cfg(feature = "postcard")]
+extern crate postcard;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 extern crate try_from;
+#[cfg(feature = "uom")]
+extern crate uom;
+
+#[macro_use]
+mod logging;
 
 pub mod addr;
+#[cfg(feature = "runtime")]
+pub mod bridge;
 /// A module which implement the builder pattern to create advanced messages
 pub mod builder;
+#[cfg(feature = "runtime")]
+pub mod bus;
+#[cfg(feature = "runtime")]
+pub mod calibration;
+pub mod checksum;
+#[cfg(feature = "runtime")]
+pub mod clock;
+pub mod config;
+#[cfg(feature = "runtime")]
+pub mod conflict;
+#[cfg(feature = "control")]
+pub mod control;
+#[cfg(feature = "runtime")]
+pub mod deadband;
+#[cfg(feature = "runtime")]
+pub mod diagnostics;
+#[cfg(feature = "runtime")]
+pub mod drive;
+#[cfg(feature = "runtime")]
+pub mod dump;
+mod emergency;
+/// A module which translates `[ACKPacket]s` into meaningful events
+#[cfg(feature = "runtime")]
+pub mod events;
+#[cfg(feature = "runtime")]
+pub mod fault_led;
+#[cfg(feature = "runtime")]
+pub mod flightlog;
+pub mod frame;
+#[cfg(feature = "runtime")]
+pub mod gripper;
+#[cfg(feature = "runtime")]
+pub mod group;
+#[cfg(feature = "runtime")]
+pub mod latency;
 mod message;
+#[cfg(feature = "runtime")]
+pub mod mirror;
+mod model;
+/// A module which tracks the motion of the servomotors from their position feedback
+#[cfg(feature = "runtime")]
+pub mod motion;
+#[cfg(feature = "runtime")]
+pub mod outgoing;
+#[cfg(feature = "runtime")]
+pub mod playback;
+/// A module which schedules periodic reads to monitor the servomotors
+#[cfg(feature = "runtime")]
+pub mod poll;
+#[cfg(feature = "runtime")]
+pub mod pose;
+pub mod prelude;
+#[cfg(feature = "runtime")]
+pub mod presence;
+#[cfg(feature = "eep")]
+pub mod presets;
+pub mod protocol;
+#[cfg(feature = "runtime")]
+pub mod queue;
 /// A module which contains a Finite State Machine to transform bytes read form the servomotor
 /// into `[ACKPacket]s`
 pub mod reader;
+#[cfg(feature = "runtime")]
+pub mod reboot;
+pub mod reg;
+#[cfg(feature = "std")]
+pub mod ros;
+#[cfg(feature = "runtime")]
+pub mod script;
 mod servo;
+#[cfg(any(all(test, feature = "runtime", feature = "eep"), feature = "test_support"))]
+pub mod sim;
+#[cfg(feature = "runtime")]
+pub mod smooth;
+#[cfg(feature = "runtime")]
+pub mod status;
+#[cfg(any(test, feature = "test_support"))]
+pub mod test_support;
+#[cfg(feature = "testvectors")]
+pub mod testvectors;
+#[cfg(feature = "runtime")]
+pub mod timings;
+#[cfg(feature = "runtime")]
+pub mod trajectory;
+#[cfg(feature = "runtime")]
+pub mod transaction;
+#[cfg(feature = "runtime")]
+pub mod transport;
+#[cfg(feature = "uom")]
+pub mod units;
 
-pub use addr::{ReadableEEPAddr, ReadableRamAddr, WritableEEPAddr, WritableRamAddr};
-pub use message::{JogColor, JogMode, Rotation};
-pub use servo::Servo;
+pub use addr::ReadableEEPAddr;
+#[cfg(feature = "eep")]
+pub use addr::WritableEEPAddr;
+pub use addr::{ReadableRamAddr, WritableRamAddr};
+pub use emergency::{emergency_brake_all, emergency_stop_all};
+pub use message::{JogColor, JogMode, Rollback, Rotation};
+pub use model::{ContinuousControl, Model};
+pub use servo::{Servo, ServoOptions, TorqueRamp, BROADCAST_ID};
@@ -0,0 +1,107 @@
+//! The log records of the crate, emitted through the `log` facade with the `log` feature.
+//!
+//! Without the feature, the macros expand to nothing : the arguments are type-checked but never
+//! evaluated.
+//!
+//! The blocking exchanges with the servomotors are wrapped in spans with the `tracing` feature,
+//! entered until the value returned by `span!` is dropped. Without the feature, the fields are
+//! not evaluated.
+
+/// The guard returned by `span!` without the `tracing` feature.
+#[cfg(not(feature = "tracing"))]
+#[allow(dead_code)]
+pub(crate) struct NoSpan;
+
+#[cfg(feature = "log")]
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        ::log::trace!(target: "drs_0x01", $($arg)*)
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_trace {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(feature = "log")]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        ::log::debug!(target: "drs_0x01", $($arg)*)
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+#[cfg(feature = "tracing")]
+#[allow(unused_macros)]
+macro_rules! span {
+    ($name:expr $(, $key:ident $(= $value:expr)?)*) => {
+        ::tracing::debug_span!(target: "drs_0x01", $name $(, $key $(= $value)?)*).entered()
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+#[allow(unused_macros)]
+macro_rules! span {
+    ($name:expr $(, $key:ident $(= $value:expr)?)*) => {{
+        if false {
+            $(let _ = span!(@field $key $(, $value)?);)*
+        }
+        $crate::logging::NoSpan
+    }};
+    (@field $key:ident) => {
+        $key
+    };
+    (@field $key:ident, $value:expr) => {
+        $value
+    };
+}
+
+/// A logger keeping the records of each thread, so that the tests can check them.
+#[cfg(all(test, feature = "log", feature = "runtime"))]
+pub(crate) mod capture {
+    use std::cell::RefCell;
+    use std::string::String;
+    use std::vec::Vec;
+
+    struct Capture;
+
+    thread_local! {
+        static RECORDS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    impl ::log::Log for Capture {
+        fn enabled(&self, _metadata: &::log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &::log::Record) {
+            let line = format!("{} {}", record.level(), record.args());
+            RECORDS.with(|records| records.borrow_mut().push(line));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Capture = Capture;
+
+    /// Return the records logged by the current thread since the last call.
+    pub(crate) fn take() -> Vec<String> {
+        // The logger is installed by the first test calling this function.
+        let _ = ::log::set_logger(&LOGGER);
+        ::log::set_max_level(::log::LevelFilter::Trace);
+        RECORDS.with(|records| records.replace(Vec::new()))
+    }
+}
@@ -1,41 +1,56 @@
+#[cfg(feature = "eep")]
 use addr::ReadableEEPAddr;
 use addr::ReadableRamAddr;
+#[cfg(feature = "eep")]
 use addr::WritableEEPAddr;
 use addr::WritableRamAddr;
 
 use arrayvec::ArrayVec;
 
+use frame::MAX_JOG_ENTRIES;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum RegisterRequest {
+    #[cfg(feature = "eep")]
     EEPWrite(WritableEEPAddr),
+    #[cfg(feature = "eep")]
     EEPRead(ReadableEEPAddr),
     RamWrite(WritableRamAddr),
     RamRead(ReadableRamAddr),
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct SJogRequest {
-    pub(crate) data: ArrayVec<[SJogData; 10]>,
+    pub(crate) data: ArrayVec<[SJogData; MAX_JOG_ENTRIES]>,
     pub(crate) playtime: u8,
 }
 
-pub(crate) type IJogRequest = ArrayVec<[IJogData; 10]>;
+pub(crate) type IJogRequest = ArrayVec<[IJogData; MAX_JOG_ENTRIES]>;
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum SpecialRequest {
     Stat,
     Rollback { skip_id: u8, skip_baud: u8 },
     Reboot,
 }
 
-#[derive(Clone, Copy)]
+/// The registers preserved when the EEP memory is reset to its factory values with a
+/// **ROLLBACK** message.
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Rollback {
+    /// Keep the ID of the servomotor.
     SkipId,
+    /// Keep the baudrate of the servomotor.
     SkipBaud,
+    /// Keep both the ID and the baudrate of the servomotor.
     SkipBoth,
+    /// Reset every register.
     SkipNone,
 }
 
 /// This represent the rotation sense of the servomotor while controlled in `Speed`.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Rotation {
     /// CounterClockwise rotation, which is the default rotation sense.
     CounterClockwise,
@@ -45,7 +60,7 @@ pub enum Rotation {
 
 /// This represent the servomotor control mode.
 /// The servomotor is either controlled in `Position` or `Speed`.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum JogMode {
     /// Control the servomotor by position.
     /// Make sure that the position is in range for your servomotor.
@@ -64,6 +79,9 @@ pub enum JogMode {
         /// Inverts the rotation sense of the servo by modifying the 14th bit.
         rotation: Rotation,
     },
+    /// Do not move the servomotor ("JOG invalid" bit), only change the color of the LED. This can
+    /// also be used to reserve a slot in a message.
+    NoMove,
 }
 
 impl JogMode {
@@ -72,6 +90,7 @@ impl JogMode {
             JogMode::Normal { position } => position,
             JogMode::Continuous { speed, rotation: Rotation::Clockwise } => 0x4000 | speed,
             JogMode::Continuous { speed, rotation: Rotation::CounterClockwise } => speed,
+            JogMode::NoMove => 0,
         }
     }
 }
@@ -83,23 +102,18 @@ impl Default for JogMode {
 }
 
 /// The color of the LED of the servomotor.
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub enum JogColor {
     /// Red
     Red,
     /// Green
+    #[default]
     Green,
     /// Blue
     Blue,
 }
 
-impl Default for JogColor {
-    fn default() -> Self {
-        JogColor::Green
-    }
-}
-
-#[derive(Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct SJogData {
     pub mode: JogMode,
     pub color: JogColor,
@@ -112,7 +126,7 @@ impl SJogData {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
 pub(crate) struct IJogData {
     pub mode: JogMode,
     pub color: JogColor,
@@ -0,0 +1,239 @@
+//! Master-slave coupling of servomotors.
+//!
+//! A [`Mirror`](struct.Mirror.html) makes follower servomotors copy the position reported by a
+//! master servomotor, possibly inverted and shifted. This is useful for grippers with two fingers
+//! or for joints driven by two servomotors.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::mirror::Mirror;
+//! use drs_0x01::reader::ACKReader;
+//!
+//! let mut reader = ACKReader::new();
+//! // The servomotor 0x41 faces the master 0x40, and is mounted 10 units off.
+//! let mut mirror = Mirror::new(0x40);
+//! mirror.add_follower(0x41, true, 10).unwrap();
+//! // Poll the position of the master, then send the command of the followers for every answer.
+//! reader.parse(&[/* ... */]);
+//! while let Some(packet) = reader.pop_ack_packet() {
+//!     if let Some(message) = mirror.update(&packet) {
+//!         // Send the message ...
+//!     }
+//! }
+//! ```
+
+use arrayvec::ArrayVec;
+
+use builder::{HerkulexMessage, MessageBuilder, MessageBuilderPositionSJOG};
+use frame::MAX_JOG_ENTRIES;
+use message::{JogColor, JogMode};
+use motion::{position_of, POSITION_RANGE};
+use reader::ACKPacket;
+use servo::BROADCAST_ID;
+
+/// The error returned by [`Mirror::add_follower`](struct.Mirror.html#method.add_follower).
+#[derive(Debug, PartialEq, Eq)]
+pub enum MirrorError {
+    /// The mirror already has as many followers as a **S_JOG** request can move.
+    TooManyFollowers,
+    /// The master can not follow itself.
+    FollowerIsMaster,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Follower {
+    id: u8,
+    inverted: bool,
+    offset: i16,
+}
+
+impl Follower {
+    fn position(&self, master: u16) -> u16 {
+        let max = POSITION_RANGE as i32 - 1;
+        let position = if self.inverted {
+            max - i32::from(master)
+        } else {
+            i32::from(master)
+        };
+        (position + i32::from(self.offset)).clamp(0, max) as u16
+    }
+}
+
+/// This struct computes the commands of up to 10 followers from the position of a master.
+#[derive(Clone, Debug)]
+pub struct Mirror {
+    master: u8,
+    followers: ArrayVec<[Follower; MAX_JOG_ENTRIES]>,
+    playtime: u8,
+    color: JogColor,
+}
+
+impl Mirror {
+    /// Create a mirror of the servomotor `master`, without any follower.
+    pub fn new(master: u8) -> Mirror {
+        Mirror {
+            master,
+            followers: ArrayVec::new(),
+            playtime: 0,
+            color: JogColor::Green,
+        }
+    }
+
+    /// Set the playtime of the commands (in units of 11.2ms), which smooths the motion of the
+    /// followers between two updates. It is 0 by default.
+    pub fn with_playtime(mut self, playtime: u8) -> Mirror {
+        self.playtime = playtime;
+        self
+    }
+
+    /// Set the color of the LED of the followers.
+    pub fn with_color(mut self, color: JogColor) -> Mirror {
+        self.color = color;
+        self
+    }
+
+    /// Return the ID of the master.
+    pub fn master(&self) -> u8 {
+        self.master
+    }
+
+    /// Make the servomotor `id` follow the master. Its position is the position of the master,
+    /// mirrored around the middle of the range if `inverted` is true, plus `offset`. A follower
+    /// added again is updated.
+    ///
+    /// # Errors
+    ///
+    /// Return [MirrorError::FollowerIsMaster](enum.MirrorError.html) if `id` is the master, and
+    /// [MirrorError::TooManyFollowers](enum.MirrorError.html) if there are already 10 followers.
+    pub fn add_follower(&mut self, id: u8, inverted: bool, offset: i16) -> Result<(), MirrorError> {
+        if id == self.master {
+            return Err(MirrorError::FollowerIsMaster);
+        }
+        let follower = Follower {
+            id,
+            inverted,
+            offset,
+        };
+        match self.followers.iter_mut().find(|follower| follower.id == id) {
+            Some(existing) => *existing = follower,
+            None => self
+                .followers
+                .try_push(follower)
+                .map_err(|_| MirrorError::TooManyFollowers)?,
+        }
+        Ok(())
+    }
+
+    /// Stop the servomotor `id` from following the master.
+    pub fn remove_follower(&mut self, id: u8) {
+        self.followers.retain(|follower| follower.id != id);
+    }
+
+    /// Return the command of the followers when the master is at `position`, or `None` if there
+    /// is no follower.
+    pub fn follow(&self, position: u16) -> Option<HerkulexMessage> {
+        let mut message: Option<MessageBuilderPositionSJOG> = None;
+        for follower in &self.followers {
+            let mode = JogMode::Normal {
+                position: follower.position(position),
+            };
+            match message {
+                Some(ref mut message) => {
+                    // There are at most MAX_JOG_ENTRIES followers.
+                    message.s_jog(mode, self.color, follower.id).ok();
+                }
+                None => {
+                    message = Some(MessageBuilder::new_with_id(BROADCAST_ID).s_jog(
+                        self.playtime,
+                        mode,
+                        self.color,
+                        follower.id,
+                    ))
+                }
+            }
+        }
+        message.map(|message| message.build())
+    }
+
+    /// Return the command of the followers if `packet` carries the position of the master.
+    pub fn update(&self, packet: &ACKPacket) -> Option<HerkulexMessage> {
+        if packet.pid != self.master {
+            return None;
+        }
+        position_of(packet).and_then(|position| self.follow(position))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{RamReadData, ReadableRamAddr};
+    use builder::MessageBuilder;
+    use message::{JogColor, JogMode};
+    use mirror::{Mirror, MirrorError};
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+    use servo::BROADCAST_ID;
+
+    #[test]
+    fn followers() {
+        let mut mirror = Mirror::new(0x40).with_playtime(2);
+        assert_eq!(mirror.follow(512), None);
+        assert_eq!(
+            mirror.add_follower(0x40, false, 0),
+            Err(MirrorError::FollowerIsMaster)
+        );
+        mirror.add_follower(0x41, false, 0).unwrap();
+        mirror.add_follower(0x42, true, 0).unwrap();
+        mirror.add_follower(0x42, true, -30).unwrap();
+        for id in 0..8 {
+            mirror.add_follower(id, false, 0).unwrap();
+        }
+        assert_eq!(
+            mirror.add_follower(0x50, false, 0),
+            Err(MirrorError::TooManyFollowers)
+        );
+        for id in 0..8 {
+            mirror.remove_follower(id);
+        }
+
+        let mut expected = MessageBuilder::new_with_id(BROADCAST_ID).s_jog(
+            2,
+            JogMode::Normal { position: 20 },
+            JogColor::Green,
+            0x41,
+        );
+        expected
+            .s_jog(JogMode::Normal { position: 973 }, JogColor::Green, 0x42)
+            .unwrap();
+        let packet = |pid| ACKPacket {
+            pid,
+            cmd: Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::CalibratedPosition,
+                    data_len: 2,
+                    data: [20, 0],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        };
+        assert_eq!(mirror.update(&packet(0x40)), Some(expected.build()));
+        assert_eq!(mirror.update(&packet(0x41)), None);
+
+        // The positions are clamped to the range of the servomotor.
+        mirror.add_follower(0x42, false, -30).unwrap();
+        let mut clamped = MessageBuilder::new_with_id(BROADCAST_ID).s_jog(
+            2,
+            JogMode::Normal { position: 20 },
+            JogColor::Green,
+            0x41,
+        );
+        clamped
+            .s_jog(JogMode::Normal { position: 0 }, JogColor::Green, 0x42)
+            .unwrap();
+        assert_eq!(mirror.follow(20), Some(clamped.build()));
+    }
+}
@@ -0,0 +1,37 @@
+/// The number of raw position values in a turn of the DRS-0101 and DRS-0201 position sensor.
+pub const POSITION_RANGE: u16 = 1024;
+
+/// The angle between two raw position values of the DRS-0101 and DRS-0201, in degrees.
+pub const DEGREES_PER_POSITION: f32 = 0.325;
+
+/// The servomotor models supported by this crate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Model {
+    /// Herkulex DRS-0101
+    Drs0101,
+    /// Herkulex DRS-0201
+    Drs0201,
+}
+
+/// How the servomotor interprets the value of a continuous rotation jog.
+///
+/// The supported models have no velocity regulation : there is no register to set a goal
+/// velocity, and the value of a continuous jog is always a PWM duty.
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ContinuousControl {
+    /// The value is the PWM duty applied to the motor (open loop).
+    Pwm,
+}
+
+impl Model {
+    /// Return how this model interprets continuous rotation jogs.
+    /// Both the DRS-0101 and the DRS-0201 only support open loop PWM control, see
+    /// [`Servo::set_pwm`](struct.Servo.html#method.set_pwm).
+    pub fn continuous_control(self) -> ContinuousControl {
+        match self {
+            Model::Drs0101 => ContinuousControl::Pwm,
+            Model::Drs0201 => ContinuousControl::Pwm,
+        }
+    }
+}
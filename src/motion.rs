@@ -0,0 +1,323 @@
+use config::InpositionMargin;
+use reader::ACKPacket;
+
+pub use model::{DEGREES_PER_POSITION, POSITION_RANGE};
+pub(crate) use reader::position_of;
+
+/// This struct keeps track of the number of turns done by a servomotor in continuous rotation,
+/// where the raw position wraps around every turn.
+///
+/// The position must be sampled often enough for the servomotor to travel less than half a turn
+/// between two samples, otherwise the direction of the wrap around can not be told.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::motion::MultiTurnTracker;
+///
+/// // A wheel driven through a 2:1 reduction.
+/// let mut tracker = MultiTurnTracker::new(1024).with_gear_ratio(2.0);
+/// tracker.update(1000);
+/// tracker.update(40); // The position wrapped around.
+/// assert_eq!(tracker.counts(), 64);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct MultiTurnTracker {
+    range: u16,
+    gear_ratio: f32,
+    last: Option<u16>,
+    counts: i32,
+}
+
+impl Default for MultiTurnTracker {
+    fn default() -> Self {
+        MultiTurnTracker::new(POSITION_RANGE)
+    }
+}
+
+impl MultiTurnTracker {
+    /// Create a tracker for a position sensor reporting `range` values per turn. A `range` of 0
+    /// is taken as 1.
+    pub fn new(range: u16) -> MultiTurnTracker {
+        MultiTurnTracker {
+            range: range.max(1),
+            gear_ratio: 1.0,
+            last: None,
+            counts: 0,
+        }
+    }
+
+    /// Set the number of servomotor turns needed for one turn of the tracked axis.
+    pub fn with_gear_ratio(mut self, gear_ratio: f32) -> MultiTurnTracker {
+        self.gear_ratio = gear_ratio;
+        self
+    }
+
+    /// Feed a new raw position to the tracker and return the number of raw positions travelled
+    /// since the first sample.
+    pub fn update(&mut self, position: u16) -> i32 {
+        let position = position % self.range;
+        if let Some(last) = self.last {
+            let range = i32::from(self.range);
+            let mut delta = i32::from(position) - i32::from(last);
+            if delta > range / 2 {
+                delta -= range;
+            } else if delta < -range / 2 {
+                delta += range;
+            }
+            self.counts += delta;
+        }
+        self.last = Some(position);
+        self.counts
+    }
+
+    /// Feed the tracker with a packet. Only the answers to RAM reads of `CalibratedPosition` or
+    /// `AbsolutePosition` are used, return true if `packet` was one of them.
+    pub fn update_from_packet(&mut self, packet: &ACKPacket) -> bool {
+        match position_of(packet) {
+            Some(position) => {
+                self.update(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return the number of raw positions travelled since the first sample.
+    pub fn counts(&self) -> i32 {
+        self.counts
+    }
+
+    /// Return the number of turns of the tracked axis since the first sample.
+    pub fn turns(&self) -> f32 {
+        self.counts as f32 / f32::from(self.range) / self.gear_ratio
+    }
+
+    /// Return the angle travelled by the tracked axis since the first sample, in degrees.
+    pub fn angle_deg(&self) -> f32 {
+        self.turns() * 360.0
+    }
+
+    /// Forget every sample, the next one will be the new origin.
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.counts = 0;
+    }
+}
+
+/// This struct estimates the velocity of a servomotor from timestamped position samples, for
+/// example obtained by polling `CalibratedPosition`.
+///
+/// The raw estimation is smoothed by an exponential moving average : with a smoothing factor
+/// `alpha`, every new estimation is `alpha * raw + (1 - alpha) * previous`. A factor of 1 disables
+/// the filtering.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::motion::VelocityEstimator;
+///
+/// // Timestamps in milliseconds.
+/// let mut estimator = VelocityEstimator::new(1000).with_smoothing(0.5);
+/// estimator.update(512, 0);
+/// let velocity = estimator.update(522, 10);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct VelocityEstimator {
+    tick_hz: u32,
+    alpha: f32,
+    tracker: MultiTurnTracker,
+    last: Option<(i32, u32)>,
+    velocity: f32,
+}
+
+impl VelocityEstimator {
+    /// Create an estimator for timestamps counting `tick_hz` ticks per second.
+    pub fn new(tick_hz: u32) -> VelocityEstimator {
+        VelocityEstimator {
+            tick_hz,
+            alpha: 1.0,
+            tracker: MultiTurnTracker::default(),
+            last: None,
+            velocity: 0.0,
+        }
+    }
+
+    /// Set the smoothing factor, between 0 (excluded) and 1.
+    pub fn with_smoothing(mut self, alpha: f32) -> VelocityEstimator {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Feed a raw position read at `timestamp` and return the estimated velocity in degrees per
+    /// second. The position is allowed to wrap around, as well as the timestamp.
+    pub fn update(&mut self, position: u16, timestamp: u32) -> f32 {
+        let counts = self.tracker.update(position);
+        if let Some((last_counts, last_timestamp)) = self.last {
+            let ticks = timestamp.wrapping_sub(last_timestamp);
+            if ticks == 0 {
+                return self.velocity;
+            }
+            let seconds = ticks as f32 / self.tick_hz as f32;
+            let raw = (counts - last_counts) as f32 * DEGREES_PER_POSITION / seconds;
+            self.velocity = self.alpha * raw + (1.0 - self.alpha) * self.velocity;
+        }
+        self.last = Some((counts, timestamp));
+        self.velocity
+    }
+
+    /// Return the last estimated velocity in degrees per second.
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    /// Forget every sample.
+    pub fn reset(&mut self) {
+        self.tracker.reset();
+        self.last = None;
+        self.velocity = 0.0;
+    }
+}
+
+/// This struct tells whether a servomotor reached its goal position, from its position feedback
+/// and the same [`InpositionMargin`](../config/struct.InpositionMargin.html) as the servomotor.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::config::InpositionMargin;
+/// use drs_0x01::motion::MotionMonitor;
+///
+/// let mut monitor = MotionMonitor::new(InpositionMargin::from_degrees(1.0));
+/// monitor.set_goal(512);
+/// assert!(!monitor.update(400));
+/// assert!(monitor.update(510));
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MotionMonitor {
+    margin: InpositionMargin,
+    goal: Option<u16>,
+    position: Option<u16>,
+}
+
+impl MotionMonitor {
+    /// Create a monitor considering the servomotor in position within `margin` of its goal.
+    pub fn new(margin: InpositionMargin) -> MotionMonitor {
+        MotionMonitor {
+            margin,
+            goal: None,
+            position: None,
+        }
+    }
+
+    /// Change the margin, for example after reading the `InpositionMargin` register.
+    pub fn set_margin(&mut self, margin: InpositionMargin) {
+        self.margin = margin;
+    }
+
+    /// Return the margin.
+    pub fn margin(&self) -> InpositionMargin {
+        self.margin
+    }
+
+    /// Set the goal position sent to the servomotor.
+    pub fn set_goal(&mut self, goal: u16) {
+        self.goal = Some(goal);
+    }
+
+    /// Feed a new calibrated position and return true if the servomotor is in position.
+    pub fn update(&mut self, position: u16) -> bool {
+        self.position = Some(position);
+        self.in_position()
+    }
+
+    /// Feed the monitor with a packet. Only the answers to RAM reads of `CalibratedPosition` or
+    /// `AbsolutePosition` are used, return true if `packet` was one of them.
+    pub fn update_from_packet(&mut self, packet: &ACKPacket) -> bool {
+        match position_of(packet) {
+            Some(position) => {
+                self.update(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return the difference between the last position and the goal, if both are known.
+    pub fn error(&self) -> Option<i32> {
+        Some(i32::from(self.position?) - i32::from(self.goal?))
+    }
+
+    /// Return true if the last position is within the margin of the goal.
+    pub fn in_position(&self) -> bool {
+        match (self.goal, self.position) {
+            (Some(goal), Some(position)) => self.margin.contains(goal, position),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use config::InpositionMargin;
+    use motion::{MotionMonitor, MultiTurnTracker, VelocityEstimator};
+
+    #[test]
+    fn multi_turn_wrap_around() {
+        let mut tracker = MultiTurnTracker::new(1024);
+        assert_eq!(tracker.update(1000), 0);
+        assert_eq!(tracker.update(10), 34);
+        assert_eq!(tracker.update(1010), 10);
+        for position in [200, 400, 600, 800, 1000].iter() {
+            tracker.update(*position);
+        }
+        assert_eq!(tracker.update(200), 1248);
+        assert_eq!(tracker.turns(), 1248.0 / 1024.0);
+
+        // An empty range does not divide by zero.
+        let mut tracker = MultiTurnTracker::new(0);
+        assert_eq!(tracker.update(10), 0);
+        assert_eq!(tracker.update(20), 0);
+        assert_eq!(tracker.turns(), 0.0);
+    }
+
+    #[test]
+    fn multi_turn_gear_ratio() {
+        let mut tracker = MultiTurnTracker::new(1024).with_gear_ratio(4.0);
+        tracker.update(0);
+        tracker.update(256);
+        tracker.update(512);
+        assert_eq!(tracker.angle_deg(), 45.0);
+        tracker.reset();
+        assert_eq!(tracker.update(512), 0);
+    }
+
+    #[test]
+    fn velocity_estimation() {
+        let mut estimator = VelocityEstimator::new(1000);
+        assert_eq!(estimator.update(1000, 0), 0.0);
+        assert_eq!(estimator.update(1020, 100), 20.0 * 0.325 * 10.0);
+        assert_eq!(estimator.update(1020, 100), 20.0 * 0.325 * 10.0);
+
+        let mut estimator = VelocityEstimator::new(1000).with_smoothing(0.5);
+        estimator.update(0, u32::MAX - 49);
+        assert_eq!(estimator.update(1014, 50), -10.0 * 0.325 * 10.0 * 0.5);
+    }
+
+    #[test]
+    fn motion_monitor() {
+        let mut monitor = MotionMonitor::new(InpositionMargin::new(3));
+        assert!(!monitor.update(512));
+        monitor.set_goal(520);
+        assert!(!monitor.in_position());
+        assert_eq!(monitor.error(), Some(-8));
+        assert!(monitor.update(517));
+        assert!(monitor.update(523));
+        assert!(!monitor.update(524));
+        monitor.set_margin(InpositionMargin::from_degrees(1.3));
+        assert!(monitor.in_position());
+    }
+}
@@ -0,0 +1,248 @@
+//! A bounded queue of messages waiting to be sent, ordered by priority.
+//!
+//! Messages of higher [`Priority`](enum.Priority.html) are always sent first, so that a flood of
+//! telemetry requests can never delay a stop command. Messages sharing the same priority are sent
+//! in the order they were pushed. A message can be given a deadline, after which it is dropped
+//! instead of being sent.
+//!
+//! Timestamps are expressed in ticks of any clock, and are allowed to wrap around.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::outgoing::{OutgoingQueue, Priority};
+//! use drs_0x01::Servo;
+//!
+//! let servo = Servo::new(0x40);
+//! let mut queue = OutgoingQueue::new();
+//! // This status request is useless if it is not sent within 10 ticks.
+//! queue.push(Priority::Telemetry, servo.stat(), Some(10)).unwrap();
+//! queue.push(Priority::Safety, servo.disable_torque(), None).unwrap();
+//!
+//! assert_eq!(queue.pop(0), Some(servo.disable_torque()));
+//! assert_eq!(queue.pop(20), None);
+//! ```
+
+use arrayvec::ArrayVec;
+
+use builder::HerkulexMessage;
+use clock::expired;
+use emergency::emergency_stop_all;
+
+/// The maximum number of messages an `OutgoingQueue` can hold.
+pub const OUTGOING_QUEUE_SIZE: usize = 16;
+
+/// The priority of a message, from the least to the most urgent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Status and position polling
+    Telemetry,
+    /// Motion commands
+    Control,
+    /// Emergency stops and torque cuts
+    Safety,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    priority: Priority,
+    deadline: Option<u32>,
+    message: HerkulexMessage,
+}
+
+/// A bounded priority queue of messages.
+#[derive(Clone, Debug, Default)]
+pub struct OutgoingQueue {
+    entries: ArrayVec<[Entry; OUTGOING_QUEUE_SIZE]>,
+    dropped: u32,
+}
+
+impl OutgoingQueue {
+    /// Create an empty queue.
+    pub fn new() -> OutgoingQueue {
+        OutgoingQueue::default()
+    }
+
+    /// Queue `message`, which must be sent before `deadline` if there is one.
+    ///
+    /// If the queue is full, the oldest message with the lowest priority is dropped to make room,
+    /// provided its priority is lower than the one of `message`. Otherwise `message` is given
+    /// back.
+    #[allow(clippy::result_large_err)]
+    pub fn push(
+        &mut self,
+        priority: Priority,
+        message: HerkulexMessage,
+        deadline: Option<u32>,
+    ) -> Result<(), HerkulexMessage> {
+        if self.entries.is_full() {
+            let victim = self
+                .entries
+                .iter()
+                .enumerate()
+                .min_by_key(|&(index, entry)| (entry.priority, index))
+                .filter(|&(_, entry)| entry.priority < priority)
+                .map(|(index, _)| index);
+            match victim {
+                Some(index) => {
+                    self.entries.remove(index);
+                    self.dropped += 1;
+                }
+                None => return Err(message),
+            }
+        }
+        self.entries.push(Entry {
+            priority,
+            deadline,
+            message,
+        });
+        Ok(())
+    }
+
+    /// Drop every waiting message and queue the messages of
+    /// [`emergency_stop_all`](../fn.emergency_stop_all.html) with the `Safety` priority, so that
+    /// they are the next ones to be sent and that no motion command is sent after them.
+    pub fn emergency_stop(&mut self) {
+        self.dropped += self.entries.len() as u32;
+        self.entries.clear();
+        for message in emergency_stop_all().iter() {
+            self.entries.push(Entry {
+                priority: Priority::Safety,
+                deadline: None,
+                message: message.clone(),
+            });
+        }
+    }
+
+    /// Return the most urgent message which can still be sent at `now`, dropping the expired
+    /// ones.
+    pub fn pop(&mut self, now: u32) -> Option<HerkulexMessage> {
+        let before = self.entries.len();
+        self.entries.retain(|entry| match entry.deadline {
+            Some(deadline) => !expired(deadline, now),
+            None => true,
+        });
+        let expired = before - self.entries.len();
+        if expired > 0 {
+            log_debug!("dropped {} expired messages", expired);
+        }
+        self.dropped += expired as u32;
+
+        let index = self
+            .entries
+            .iter()
+            .enumerate()
+            .max_by_key(|&(index, entry)| (entry.priority, usize::MAX - index))
+            .map(|(index, _)| index)?;
+        Some(self.entries.remove(index).message)
+    }
+
+    /// Return the number of messages waiting.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return true if no message is waiting.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop every waiting message.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Return the number of messages dropped because they expired or because the queue was full.
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use emergency::emergency_stop_all;
+    use outgoing::{OutgoingQueue, Priority, OUTGOING_QUEUE_SIZE};
+    use servo::Servo;
+
+    #[test]
+    fn priority_order() {
+        let mut queue = OutgoingQueue::new();
+        let (a, b) = (Servo::new(1), Servo::new(2));
+        queue.push(Priority::Telemetry, a.stat(), None).unwrap();
+        queue
+            .push(Priority::Control, a.set_position(512), None)
+            .unwrap();
+        queue.push(Priority::Telemetry, b.stat(), None).unwrap();
+        queue
+            .push(Priority::Safety, a.disable_torque(), None)
+            .unwrap();
+        assert_eq!(queue.pop(0), Some(a.disable_torque()));
+        assert_eq!(queue.pop(0), Some(a.set_position(512)));
+        assert_eq!(queue.pop(0), Some(a.stat()));
+        assert_eq!(queue.pop(0), Some(b.stat()));
+        assert_eq!(queue.pop(0), None);
+    }
+
+    #[test]
+    fn deadlines() {
+        let mut queue = OutgoingQueue::new();
+        let servo = Servo::new(1);
+        queue
+            .push(Priority::Control, servo.stat(), Some(5))
+            .unwrap();
+        queue
+            .push(Priority::Telemetry, servo.reboot(), Some(100))
+            .unwrap();
+        assert_eq!(queue.pop(2), Some(servo.stat()));
+        // The deadline is reached but not passed.
+        assert_eq!(queue.pop(100), Some(servo.reboot()));
+
+        // The clock wraps around before the deadline.
+        queue
+            .push(Priority::Control, servo.stat(), Some(2))
+            .unwrap();
+        assert_eq!(queue.pop(u32::MAX), Some(servo.stat()));
+
+        // The clock wrapped around after the deadline.
+        queue
+            .push(Priority::Control, servo.stat(), Some(u32::MAX))
+            .unwrap();
+        assert_eq!(queue.pop(3), None);
+        assert_eq!(queue.dropped(), 1);
+    }
+
+    #[test]
+    fn full_queue_drops_lower_priorities() {
+        let mut queue = OutgoingQueue::new();
+        for id in 0..OUTGOING_QUEUE_SIZE as u8 {
+            queue
+                .push(Priority::Telemetry, Servo::new(id).stat(), None)
+                .unwrap();
+        }
+        let stop = Servo::new(0xFE).disable_torque();
+        assert!(queue.push(Priority::Telemetry, stop.clone(), None).is_err());
+        assert!(queue.push(Priority::Safety, stop.clone(), None).is_ok());
+        assert_eq!(queue.len(), OUTGOING_QUEUE_SIZE);
+        assert_eq!(queue.dropped(), 1);
+        assert_eq!(queue.pop(0), Some(stop));
+        // The oldest telemetry request was dropped.
+        assert_eq!(queue.pop(0), Some(Servo::new(1).stat()));
+    }
+
+    #[test]
+    fn emergency_stop() {
+        let mut queue = OutgoingQueue::new();
+        for id in 0..OUTGOING_QUEUE_SIZE as u8 {
+            queue
+                .push(Priority::Safety, Servo::new(id).set_position(0), None)
+                .unwrap();
+        }
+        queue.emergency_stop();
+        let [jog, torque] = emergency_stop_all();
+        assert_eq!(queue.pop(0), Some(jog));
+        assert_eq!(queue.pop(0), Some(torque));
+        assert_eq!(queue.pop(0), None);
+        assert_eq!(queue.dropped(), OUTGOING_QUEUE_SIZE as u32);
+    }
+}
@@ -0,0 +1,478 @@
+//! Teach and repeat : record the positions of servomotors moved by hand, then replay them.
+//!
+//! A [`MotionRecorder`](struct.MotionRecorder.html) stores the positions read by a
+//! [`PollPlan`](../poll/struct.PollPlan.html), with the time at which they were received. A
+//! [`MotionPlayer`](struct.MotionPlayer.html) then sends them back as **S_JOG** requests, with the
+//! original timing.
+//!
+//! A [`TeachMode`](struct.TeachMode.html) releases the torque of the servomotors to move by hand
+//! and polls their positions, which can be recorded as they come.
+//!
+//! Timestamps are expressed in ticks of any clock, and are allowed to wrap around.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::playback::{MotionPlayer, MotionRecorder};
+//! use drs_0x01::poll::PollPlan;
+//!
+//! let mut plan = PollPlan::new();
+//! let mut recorder = MotionRecorder::new();
+//! // With a 1 kHz tick, sample the position of the servomotor 0x40 at 50 Hz.
+//! recorder.poll(&mut plan, 0x40, 20).unwrap();
+//! // Send plan.next_message(now) and feed the answers to recorder.record_packet(&packet, now) ...
+//! # recorder.record(0x40, 512, 0).unwrap();
+//!
+//! let mut player = MotionPlayer::new(recorder.samples());
+//! while let Some(message) = player.next_message(0) {
+//!     // Send the message ...
+//! }
+//! ```
+
+use arrayvec::ArrayVec;
+
+use addr::ReadableRamAddr;
+use builder::{HerkulexMessage, MessageBuilder, MessageBuilderPositionSJOG};
+use message::{JogColor, JogMode};
+use motion::position_of;
+use poll::{PollPlan, PollPlanError};
+use reader::{ACKHandler, ACKPacket};
+use servo::{Servo, BROADCAST_ID};
+
+/// The maximum number of samples a [`MotionRecorder`](struct.MotionRecorder.html) can hold.
+pub const MOTION_RECORDER_CAPACITY: usize = 256;
+
+/// The maximum number of servomotors a [`TeachMode`](struct.TeachMode.html) can release at once.
+pub const MAX_TAUGHT_SERVOS: usize = 16;
+
+/// The maximum number of positions waiting to be popped from a
+/// [`TeachMode`](struct.TeachMode.html). When it is reached, the oldest position is dropped.
+pub const TEACH_POSITIONS_SIZE: usize = 32;
+
+/// A position of a servomotor, and the time at which it was reached.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MotionSample {
+    /// The ID of the servomotor
+    pub id: u8,
+    /// The number of ticks since the first sample of the recording
+    pub offset: u32,
+    /// The calibrated position
+    pub position: u16,
+}
+
+/// This struct records the positions of one or several servomotors.
+#[derive(Clone, Default)]
+pub struct MotionRecorder {
+    samples: ArrayVec<[MotionSample; MOTION_RECORDER_CAPACITY]>,
+    start: Option<u32>,
+}
+
+impl MotionRecorder {
+    /// Create an empty recording.
+    pub fn new() -> MotionRecorder {
+        MotionRecorder::default()
+    }
+
+    /// Read the position of the servomotor `id` every `period` ticks with `plan`.
+    ///
+    /// # Errors
+    ///
+    /// Return the errors of [`PollPlan::add`](../poll/struct.PollPlan.html#method.add).
+    pub fn poll(&self, plan: &mut PollPlan, id: u8, period: u32) -> Result<(), PollPlanError> {
+        plan.add(id, ReadableRamAddr::CalibratedPosition, period)
+    }
+
+    /// Record that the servomotor `id` was at `position` at `timestamp`.
+    ///
+    /// # Errors
+    ///
+    /// Return the sample if the recording is full.
+    pub fn record(&mut self, id: u8, position: u16, timestamp: u32) -> Result<(), MotionSample> {
+        let start = *self.start.get_or_insert(timestamp);
+        let sample = MotionSample {
+            id,
+            offset: timestamp.wrapping_sub(start),
+            position,
+        };
+        self.samples
+            .try_push(sample)
+            .map_err(|error| error.element())
+    }
+
+    /// Record the position carried by `packet`, received at `timestamp`. Only the answers to RAM
+    /// reads of `CalibratedPosition` or `AbsolutePosition` are recorded, return true if `packet`
+    /// was recorded.
+    pub fn record_packet(&mut self, packet: &ACKPacket, timestamp: u32) -> bool {
+        match position_of(packet) {
+            Some(position) => self.record(packet.pid, position, timestamp).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Return the samples recorded, oldest first.
+    pub fn samples(&self) -> &[MotionSample] {
+        &self.samples
+    }
+
+    /// Return true if no more sample can be recorded.
+    pub fn is_full(&self) -> bool {
+        self.samples.is_full()
+    }
+
+    /// Forget every sample, the next one will start a new recording.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.start = None;
+    }
+}
+
+/// This struct replays recorded samples as **S_JOG** requests.
+///
+/// Every sample is sent as soon as its offset is elapsed since the first call to
+/// [`next_message`](#method.next_message). The samples due at the same time are grouped in a
+/// single request sent to every servomotor.
+#[derive(Clone, Debug)]
+pub struct MotionPlayer<'a> {
+    samples: &'a [MotionSample],
+    playtime: u8,
+    color: JogColor,
+    start: Option<u32>,
+    cursor: usize,
+}
+
+impl<'a> MotionPlayer<'a> {
+    /// Create a player for `samples`, which must be sorted by offset.
+    pub fn new(samples: &'a [MotionSample]) -> MotionPlayer<'a> {
+        MotionPlayer {
+            samples,
+            playtime: 0,
+            color: JogColor::Green,
+            start: None,
+            cursor: 0,
+        }
+    }
+
+    /// Set the playtime of the requests (in units of 11.2ms), which smooths the motion between
+    /// two samples. It is 0 by default, the servomotors then move as fast as possible.
+    pub fn with_playtime(mut self, playtime: u8) -> MotionPlayer<'a> {
+        self.playtime = playtime;
+        self
+    }
+
+    /// Set the color of the LED of the servomotors during the playback.
+    pub fn with_color(mut self, color: JogColor) -> MotionPlayer<'a> {
+        self.color = color;
+        self
+    }
+
+    /// Return the next request due at `now`, if any.
+    pub fn next_message(&mut self, now: u32) -> Option<HerkulexMessage> {
+        let start = *self.start.get_or_insert(now);
+        let elapsed = now.wrapping_sub(start);
+        let mut message: Option<MessageBuilderPositionSJOG> = None;
+        while let Some(sample) = self.samples.get(self.cursor) {
+            if sample.offset > elapsed {
+                break;
+            }
+            let mode = JogMode::Normal {
+                position: sample.position,
+            };
+            match message {
+                // The request is full, the remaining samples go in the next one.
+                Some(ref mut message) => {
+                    if message.s_jog(mode, self.color, sample.id).is_err() {
+                        break;
+                    }
+                }
+                None => {
+                    message = Some(MessageBuilder::new_with_id(BROADCAST_ID).s_jog(
+                        self.playtime,
+                        mode,
+                        self.color,
+                        sample.id,
+                    ))
+                }
+            }
+            self.cursor += 1;
+        }
+        message.map(|message| message.build())
+    }
+
+    /// Return true if every sample was sent.
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.samples.len()
+    }
+
+    /// Start the playback again, the next call to `next_message` will be the new origin.
+    pub fn restart(&mut self) {
+        self.start = None;
+        self.cursor = 0;
+    }
+}
+
+/// This struct puts servomotors in teach mode : their torque is released so that they can be
+/// moved by hand, and their position is polled at a high rate.
+///
+/// The positions read are queued when it is used as an
+/// [`ACKHandler`](../reader/trait.ACKHandler.html), and can be popped one by one or with the
+/// [`positions`](#method.positions) iterator.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::playback::TeachMode;
+/// use drs_0x01::poll::PollPlan;
+/// use drs_0x01::Servo;
+///
+/// let mut plan = PollPlan::new();
+/// // With a 1 kHz tick, read the position at 100 Hz.
+/// let mut teach = TeachMode::new(10);
+/// let release = teach.enable(&mut plan, 0x40).unwrap();
+/// assert_eq!(release, Servo::new(0x40).disable_torque());
+/// // Send plan.next_message(now) and parse the answers with reader.parse_with(.., &mut teach) ...
+/// for (id, position) in teach.positions() {
+///     // Record the position ...
+/// }
+/// let hold = teach.disable(&mut plan, 0x40);
+/// assert_eq!(hold, Servo::new(0x40).enable_torque());
+/// assert!(plan.is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct TeachMode {
+    period: u32,
+    servos: ArrayVec<[u8; MAX_TAUGHT_SERVOS]>,
+    positions: ArrayVec<[(u8, u16); TEACH_POSITIONS_SIZE]>,
+}
+
+impl TeachMode {
+    /// Create a teach mode polling the positions every `period` ticks.
+    pub fn new(period: u32) -> TeachMode {
+        TeachMode {
+            period,
+            servos: ArrayVec::new(),
+            positions: ArrayVec::new(),
+        }
+    }
+
+    /// Put the servomotor `id` in teach mode : poll its position with `plan` and return the
+    /// message releasing its torque, which must be sent.
+    ///
+    /// # Errors
+    ///
+    /// Return the errors of [`PollPlan::add`](../poll/struct.PollPlan.html#method.add), and
+    /// `PlanFull` if `MAX_TAUGHT_SERVOS` servomotors are already in teach mode.
+    pub fn enable(
+        &mut self,
+        plan: &mut PollPlan,
+        id: u8,
+    ) -> Result<HerkulexMessage, PollPlanError> {
+        if !self.is_enabled(id) {
+            if self.servos.is_full() {
+                return Err(PollPlanError::PlanFull);
+            }
+            plan.add(id, ReadableRamAddr::CalibratedPosition, self.period)?;
+            self.servos.push(id);
+        }
+        Ok(Servo::new(id).disable_torque())
+    }
+
+    /// Take the servomotor `id` out of teach mode : stop polling its position and return the
+    /// message enabling its torque, which must be sent.
+    pub fn disable(&mut self, plan: &mut PollPlan, id: u8) -> HerkulexMessage {
+        if self.is_enabled(id) {
+            plan.remove_read(id, ReadableRamAddr::CalibratedPosition);
+            self.servos.retain(|&mut servo| servo != id);
+        }
+        Servo::new(id).enable_torque()
+    }
+
+    /// Return true if the servomotor `id` is in teach mode.
+    pub fn is_enabled(&self, id: u8) -> bool {
+        self.servos.contains(&id)
+    }
+
+    /// Queue the position carried by `packet` if it comes from a servomotor in teach mode.
+    /// Return true if `packet` was queued.
+    pub fn update(&mut self, packet: &ACKPacket) -> bool {
+        match position_of(packet) {
+            Some(position) if self.is_enabled(packet.pid) => {
+                if self.positions.is_full() {
+                    self.positions.remove(0);
+                }
+                self.positions.push((packet.pid, position));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Return the oldest position read and the ID of its servomotor, if any.
+    pub fn pop_position(&mut self) -> Option<(u8, u16)> {
+        if self.positions.is_empty() {
+            None
+        } else {
+            Some(self.positions.remove(0))
+        }
+    }
+
+    /// Return an iterator popping the positions read, oldest first.
+    pub fn positions(&mut self) -> impl Iterator<Item = (u8, u16)> + '_ {
+        core::iter::from_fn(move || self.pop_position())
+    }
+}
+
+impl ACKHandler for TeachMode {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        self.update(packet);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{RamReadData, ReadableRamAddr};
+    use builder::MessageBuilder;
+    use message::{JogColor, JogMode};
+    use playback::{MotionPlayer, MotionRecorder, MotionSample, TeachMode};
+    use poll::PollPlan;
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+    use servo::Servo;
+    use std::vec::Vec;
+
+    #[test]
+    fn record() {
+        let mut recorder = MotionRecorder::new();
+        let packet = ACKPacket {
+            pid: 0x40,
+            cmd: Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::CalibratedPosition,
+                    data_len: 2,
+                    data: [0x00, 0x02],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        };
+        assert!(recorder.record_packet(&packet, u32::MAX - 4));
+        assert!(recorder.record_packet(&packet, 5));
+        assert!(!recorder.record_packet(
+            &ACKPacket {
+                cmd: Command::Stat,
+                ..packet
+            },
+            10
+        ));
+        assert_eq!(
+            recorder.samples(),
+            &[
+                MotionSample {
+                    id: 0x40,
+                    offset: 0,
+                    position: 512,
+                },
+                MotionSample {
+                    id: 0x40,
+                    offset: 10,
+                    position: 512,
+                },
+            ]
+        );
+        recorder.clear();
+        assert!(recorder.samples().is_empty());
+    }
+
+    #[test]
+    fn replay() {
+        let mut recorder = MotionRecorder::new();
+        recorder.record(1, 100, 1000).unwrap();
+        recorder.record(2, 200, 1000).unwrap();
+        recorder.record(1, 150, 1020).unwrap();
+
+        let mut player = MotionPlayer::new(recorder.samples()).with_playtime(2);
+        let mut expected = MessageBuilder::new_with_id(0xFE).s_jog(
+            2,
+            JogMode::Normal { position: 100 },
+            JogColor::Green,
+            1,
+        );
+        expected
+            .s_jog(JogMode::Normal { position: 200 }, JogColor::Green, 2)
+            .unwrap();
+        assert_eq!(player.next_message(50), Some(expected.build()));
+        assert_eq!(player.next_message(69), None);
+        assert_eq!(
+            player.next_message(70),
+            Some(
+                MessageBuilder::new_with_id(0xFE)
+                    .s_jog(2, JogMode::Normal { position: 150 }, JogColor::Green, 1)
+                    .build()
+            )
+        );
+        assert!(player.is_finished());
+        assert_eq!(player.next_message(100), None);
+    }
+
+    #[test]
+    fn teach_mode() {
+        let mut plan = PollPlan::new();
+        plan.add(0x40, ReadableRamAddr::Temperature, 1000).unwrap();
+        let mut teach = TeachMode::new(10);
+        assert_eq!(
+            teach.enable(&mut plan, 0x40),
+            Ok(Servo::new(0x40).disable_torque())
+        );
+        teach.enable(&mut plan, 0x40).unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(
+            plan.next_message(0),
+            Some(
+                MessageBuilder::new_with_id(0x40)
+                    .read_ram(ReadableRamAddr::Temperature, None)
+                    .build()
+            )
+        );
+        assert_eq!(
+            plan.next_message(0),
+            Some(
+                MessageBuilder::new_with_id(0x40)
+                    .read_ram(ReadableRamAddr::CalibratedPosition, None)
+                    .build()
+            )
+        );
+
+        let position = |pid, position: u16| ACKPacket {
+            pid,
+            cmd: Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::CalibratedPosition,
+                    data_len: 2,
+                    data: [position as u8, (position >> 8) as u8],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        };
+        assert!(teach.update(&position(0x40, 512)));
+        assert!(!teach.update(&position(0x41, 300)));
+        assert!(teach.update(&position(0x40, 520)));
+        assert_eq!(
+            teach.positions().collect::<Vec<_>>(),
+            [(0x40, 512), (0x40, 520)]
+        );
+        assert_eq!(teach.pop_position(), None);
+
+        assert_eq!(
+            teach.disable(&mut plan, 0x40),
+            Servo::new(0x40).enable_torque()
+        );
+        assert!(!teach.is_enabled(0x40));
+        assert_eq!(plan.len(), 1);
+        assert!(!teach.update(&position(0x40, 512)));
+    }
+}
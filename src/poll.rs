@@ -0,0 +1,193 @@
+use addr::ReadableRamAddr;
+use builder::{HerkulexMessage, MessageBuilder};
+
+use arrayvec::ArrayVec;
+
+/// The maximum number of periodic reads a [`PollPlan`](struct.PollPlan.html) can hold.
+pub const POLL_PLAN_CAPACITY: usize = 32;
+
+/// The error returned by [`PollPlan`](struct.PollPlan.html).
+#[derive(Debug, PartialEq, Eq)]
+pub enum PollPlanError {
+    /// The plan already contains `POLL_PLAN_CAPACITY` reads.
+    PlanFull,
+    /// A read can not be scheduled every 0 tick.
+    NullPeriod,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PollEntry {
+    id: u8,
+    addr: ReadableRamAddr,
+    period: u32,
+    // `None` until the first read, which is due right away.
+    next: Option<u32>,
+}
+
+/// This struct describes a repeating set of RAM reads and tells which message should be sent
+/// next.
+///
+/// The plan does not know anything about time : it works with a tick provided by the user (for
+/// example a millisecond counter), and every period is expressed in those ticks.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::ReadableRamAddr;
+/// use drs_0x01::poll::PollPlan;
+///
+/// // With a 1 kHz tick : position and errors at 100 Hz, temperature at 1 Hz.
+/// let mut plan = PollPlan::new();
+/// plan.add(0x40, ReadableRamAddr::CalibratedPosition, 10).ok();
+/// plan.add(0x40, ReadableRamAddr::StatusError, 10).ok();
+/// plan.add(0x40, ReadableRamAddr::Temperature, 1000).ok();
+///
+/// let tick = 0;
+/// while let Some(message) = plan.next_message(tick) {
+///     // Send the message ...
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct PollPlan {
+    entries: ArrayVec<[PollEntry; POLL_PLAN_CAPACITY]>,
+    cursor: usize,
+}
+
+impl PollPlan {
+    /// Create an empty plan.
+    pub fn new() -> PollPlan {
+        PollPlan::default()
+    }
+
+    /// Read `addr` from the servo `id` every `period` ticks.
+    /// The first read is due as soon as the plan is polled.
+    ///
+    /// # Errors
+    ///
+    /// Return [PollPlanError::PlanFull](enum.PollPlanError.html) if the plan is full, and
+    /// [PollPlanError::NullPeriod](enum.PollPlanError.html) if `period` is 0.
+    pub fn add(&mut self, id: u8, addr: ReadableRamAddr, period: u32) -> Result<(), PollPlanError> {
+        if period == 0 {
+            return Err(PollPlanError::NullPeriod);
+        }
+        self.entries
+            .try_push(PollEntry {
+                id,
+                addr,
+                period,
+                next: None,
+            })
+            .map_err(|_| PollPlanError::PlanFull)
+    }
+
+    /// Remove every read of the servo `id` from the plan.
+    pub fn remove(&mut self, id: u8) {
+        self.entries.retain(|entry| entry.id != id);
+        self.cursor = 0;
+    }
+
+    /// Remove the reads of `addr` from the servo `id` from the plan.
+    pub fn remove_read(&mut self, id: u8, addr: ReadableRamAddr) {
+        self.entries
+            .retain(|entry| entry.id != id || entry.addr != addr);
+        self.cursor = 0;
+    }
+
+    /// Return the number of reads in the plan.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Return true if the plan does not contain any read.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return the next read which is due at `tick`, if any, and schedule it again `period`
+    /// ticks later.
+    /// Due reads are returned in a round robin fashion, so that a fast read can not starve the
+    /// others. The tick is allowed to wrap around.
+    pub fn next_message(&mut self, tick: u32) -> Option<HerkulexMessage> {
+        let len = self.entries.len();
+        for i in 0..len {
+            let index = (self.cursor + i) % len;
+            let entry = &mut self.entries[index];
+            let due = entry
+                .next
+                .is_none_or(|next| tick.wrapping_sub(next) as i32 >= 0);
+            if due {
+                entry.next = Some(tick.wrapping_add(entry.period));
+                self.cursor = (index + 1) % len;
+                return Some(
+                    MessageBuilder::new_with_id(entry.id)
+                        .read_ram(entry.addr, None)
+                        .build(),
+                );
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::ReadableRamAddr;
+    use builder::MessageBuilder;
+    use poll::{PollPlan, PollPlanError};
+
+    #[test]
+    fn poll_plan_schedule() {
+        let mut plan = PollPlan::new();
+        plan.add(0x40, ReadableRamAddr::CalibratedPosition, 10)
+            .unwrap();
+        plan.add(0x40, ReadableRamAddr::Temperature, 1000).unwrap();
+
+        let position = MessageBuilder::new_with_id(0x40)
+            .read_ram(ReadableRamAddr::CalibratedPosition, None)
+            .build();
+        let temperature = MessageBuilder::new_with_id(0x40)
+            .read_ram(ReadableRamAddr::Temperature, None)
+            .build();
+
+        assert_eq!(plan.next_message(0), Some(position.clone()));
+        assert_eq!(plan.next_message(0), Some(temperature.clone()));
+        assert_eq!(plan.next_message(0), None);
+        assert_eq!(plan.next_message(9), None);
+        assert_eq!(plan.next_message(10), Some(position));
+        assert_eq!(plan.next_message(10), None);
+        assert_eq!(plan.next_message(1000), Some(temperature));
+    }
+
+    #[test]
+    fn poll_plan_first_read_late_tick() {
+        let mut plan = PollPlan::new();
+        plan.add(0x40, ReadableRamAddr::CalibratedPosition, 10)
+            .unwrap();
+        let position = MessageBuilder::new_with_id(0x40)
+            .read_ram(ReadableRamAddr::CalibratedPosition, None)
+            .build();
+
+        assert_eq!(plan.next_message(0x8000_0000), Some(position.clone()));
+        assert_eq!(plan.next_message(0x8000_0009), None);
+        assert_eq!(plan.next_message(0x8000_000A), Some(position));
+    }
+
+    #[test]
+    fn poll_plan_errors() {
+        let mut plan = PollPlan::new();
+        assert_eq!(
+            plan.add(1, ReadableRamAddr::Tick, 0),
+            Err(PollPlanError::NullPeriod)
+        );
+        for _ in 0..super::POLL_PLAN_CAPACITY {
+            assert_eq!(plan.add(1, ReadableRamAddr::Tick, 1), Ok(()));
+        }
+        assert_eq!(
+            plan.add(1, ReadableRamAddr::Tick, 1),
+            Err(PollPlanError::PlanFull)
+        );
+        plan.remove(1);
+        assert!(plan.is_empty());
+    }
+}
@@ -0,0 +1,375 @@
+//! Named poses of a group of servomotors, captured and restored at once.
+//!
+//! A [`PoseLibrary`](struct.PoseLibrary.html) follows the positions reported by a fixed group of
+//! servomotors. The current positions can be captured under a name, and a pose is restored with
+//! a single **S_JOG** request moving the whole group, which makes it a building block for
+//! animations.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::pose::PoseLibrary;
+//!
+//! let mut library: PoseLibrary<2, 4> = PoseLibrary::new([0x40, 0x41]);
+//! library.store("rest", [512, 512]).unwrap();
+//! // Feed the answers to the position reads to library.update(&packet) ...
+//! let message = library.goto("rest", 60).unwrap();
+//! // Send the message ...
+//! ```
+
+#[cfg(feature = "postcard")]
+use postcard;
+#[cfg(feature = "postcard")]
+use serde::ser::{Serialize, SerializeSeq, SerializeTuple, Serializer};
+
+use builder::{HerkulexMessage, MessageBuilder};
+use frame::MAX_JOG_ENTRIES;
+use message::{JogColor, JogMode};
+use motion::position_of;
+use reader::{ACKHandler, ACKPacket};
+use servo::BROADCAST_ID;
+
+/// The error returned by a [`PoseLibrary`](struct.PoseLibrary.html).
+#[derive(Debug, PartialEq, Eq)]
+pub enum PoseError {
+    /// The position of the servomotor with this ID was never reported.
+    UnknownPosition(u8),
+    /// The library already holds its maximum number of poses.
+    LibraryFull,
+    /// There is no pose with this name.
+    UnknownPose,
+    /// The group is empty, or has more servomotors than a **S_JOG** request can move.
+    InvalidGroupSize,
+}
+
+/// This struct stores up to `POSES` poses of a group of `SERVOS` servomotors.
+///
+/// The positions of the servomotors are updated when it is used as an
+/// [`ACKHandler`](../reader/trait.ACKHandler.html), from the answers to RAM reads of
+/// `CalibratedPosition` or `AbsolutePosition`.
+#[derive(Clone, Debug)]
+pub struct PoseLibrary<'a, const SERVOS: usize, const POSES: usize> {
+    ids: [u8; SERVOS],
+    current: [Option<u16>; SERVOS],
+    poses: [Option<(&'a str, [u16; SERVOS])>; POSES],
+    color: JogColor,
+}
+
+impl<'a, const SERVOS: usize, const POSES: usize> PoseLibrary<'a, SERVOS, POSES> {
+    /// Create an empty library for the servomotors `ids`. A pose can only be restored if there
+    /// are between 1 and 10 servomotors.
+    pub fn new(ids: [u8; SERVOS]) -> PoseLibrary<'a, SERVOS, POSES> {
+        PoseLibrary {
+            ids,
+            current: [None; SERVOS],
+            poses: [None; POSES],
+            color: JogColor::Green,
+        }
+    }
+
+    /// Set the color of the LED of the servomotors while they move to a pose.
+    pub fn with_color(mut self, color: JogColor) -> PoseLibrary<'a, SERVOS, POSES> {
+        self.color = color;
+        self
+    }
+
+    /// Return the IDs of the servomotors of the group.
+    pub fn ids(&self) -> &[u8; SERVOS] {
+        &self.ids
+    }
+
+    /// Record the position carried by `packet` if it comes from a servomotor of the group.
+    /// Return true if `packet` was recorded.
+    pub fn update(&mut self, packet: &ACKPacket) -> bool {
+        match (
+            position_of(packet),
+            self.ids.iter().position(|&id| id == packet.pid),
+        ) {
+            (Some(position), Some(index)) => {
+                self.current[index] = Some(position);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Store `positions`, in the order of the IDs of the group, under `name`. A pose with the
+    /// same name is replaced.
+    ///
+    /// # Errors
+    ///
+    /// Return [PoseError::LibraryFull](enum.PoseError.html) if `POSES` other poses are stored.
+    pub fn store(&mut self, name: &'a str, positions: [u16; SERVOS]) -> Result<(), PoseError> {
+        let slot = match self.index(name) {
+            Some(index) => index,
+            None => self
+                .poses
+                .iter()
+                .position(Option::is_none)
+                .ok_or(PoseError::LibraryFull)?,
+        };
+        self.poses[slot] = Some((name, positions));
+        Ok(())
+    }
+
+    /// Store the last positions reported by the group under `name`.
+    ///
+    /// # Errors
+    ///
+    /// Return [PoseError::UnknownPosition](enum.PoseError.html) if a servomotor never reported
+    /// its position, and the errors of [`store`](#method.store).
+    pub fn capture(&mut self, name: &'a str) -> Result<(), PoseError> {
+        let mut positions = [0; SERVOS];
+        for (index, position) in positions.iter_mut().enumerate() {
+            *position = self.current[index].ok_or(PoseError::UnknownPosition(self.ids[index]))?;
+        }
+        self.store(name, positions)
+    }
+
+    /// Return the positions of the pose `name`, in the order of the IDs of the group.
+    pub fn pose(&self, name: &str) -> Option<[u16; SERVOS]> {
+        self.index(name)
+            .and_then(|index| self.poses[index])
+            .map(|(_, positions)| positions)
+    }
+
+    /// Forget the pose `name`, return true if it was stored.
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.index(name) {
+            Some(index) => {
+                self.poses[index] = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Return the names of the stored poses.
+    pub fn names(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.poses
+            .iter()
+            .filter_map(|pose| pose.map(|(name, _)| name))
+    }
+
+    /// Return the **S_JOG** request moving the group to the pose `name` in `playtime` (in units
+    /// of 11.2ms).
+    ///
+    /// # Errors
+    ///
+    /// Return [PoseError::UnknownPose](enum.PoseError.html) if there is no such pose, and
+    /// [PoseError::InvalidGroupSize](enum.PoseError.html) if the group can not be moved by a
+    /// single request.
+    pub fn goto(&self, name: &str, playtime: u8) -> Result<HerkulexMessage, PoseError> {
+        if SERVOS == 0 || SERVOS > MAX_JOG_ENTRIES {
+            return Err(PoseError::InvalidGroupSize);
+        }
+        let positions = self.pose(name).ok_or(PoseError::UnknownPose)?;
+        let mode = |position| JogMode::Normal { position };
+        let mut message = MessageBuilder::new_with_id(BROADCAST_ID).s_jog(
+            playtime,
+            mode(positions[0]),
+            self.color,
+            self.ids[0],
+        );
+        for (&id, &position) in self.ids.iter().zip(positions.iter()).skip(1) {
+            message
+                .s_jog(mode(position), self.color, id)
+                .map_err(|_| PoseError::InvalidGroupSize)?;
+        }
+        Ok(message.build())
+    }
+
+    /// Serialize the stored poses into `buf` with [`postcard`](https://docs.rs/postcard), to
+    /// persist them in the flash of the application. Return the used part of `buf`.
+    ///
+    /// This method is available with the `postcard` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate drs_0x01;
+    /// use drs_0x01::pose::PoseLibrary;
+    ///
+    /// let mut library: PoseLibrary<2, 4> = PoseLibrary::new([0x40, 0x41]);
+    /// library.store("rest", [512, 512]).unwrap();
+    /// let mut buf = [0u8; 64];
+    /// let saved = library.save(&mut buf).unwrap();
+    ///
+    /// let mut restored: PoseLibrary<2, 4> = PoseLibrary::new([0x40, 0x41]);
+    /// restored.load(saved).unwrap();
+    /// assert_eq!(restored.pose("rest"), Some([512, 512]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Return the errors of `postcard`, `SerializeBufferFull` if `buf` is too small.
+    #[cfg(feature = "postcard")]
+    pub fn save<'b>(&self, buf: &'b mut [u8]) -> Result<&'b mut [u8], postcard::Error> {
+        postcard::to_slice(&StoredPoses(&self.poses), buf)
+    }
+
+    /// Replace the stored poses by the poses serialized in `bytes` by [`save`](#method.save).
+    /// The names of the poses borrow `bytes`. The poses are kept if `bytes` is invalid.
+    ///
+    /// This method is available with the `postcard` feature.
+    ///
+    /// # Errors
+    ///
+    /// Return the errors of `postcard`, `DeserializeBadEncoding` if `bytes` holds more than
+    /// `POSES` poses.
+    #[cfg(feature = "postcard")]
+    pub fn load(&mut self, bytes: &'a [u8]) -> Result<(), postcard::Error> {
+        let (count, mut bytes): (usize, _) = postcard::take_from_bytes(bytes)?;
+        if count > POSES {
+            return Err(postcard::Error::DeserializeBadEncoding);
+        }
+        let mut poses = [None; POSES];
+        for pose in poses.iter_mut().take(count) {
+            let (name, rest) = postcard::take_from_bytes(bytes)?;
+            bytes = rest;
+            let mut positions = [0; SERVOS];
+            for position in positions.iter_mut() {
+                let (value, rest) = postcard::take_from_bytes(bytes)?;
+                *position = value;
+                bytes = rest;
+            }
+            *pose = Some((name, positions));
+        }
+        self.poses = poses;
+        Ok(())
+    }
+
+    fn index(&self, name: &str) -> Option<usize> {
+        self.poses
+            .iter()
+            .position(|pose| pose.is_some_and(|(pose, _)| pose == name))
+    }
+}
+
+// The stored poses, serialized as a sequence of names followed by their positions. serde only
+// implements `Serialize` for arrays up to 32 items, so the positions are serialized as a tuple.
+#[cfg(feature = "postcard")]
+struct StoredPoses<'p, 'a, const SERVOS: usize>(&'p [Option<(&'a str, [u16; SERVOS])>]);
+
+#[cfg(feature = "postcard")]
+struct Positions<'p, const SERVOS: usize>(&'p [u16; SERVOS]);
+
+#[cfg(feature = "postcard")]
+impl<'p, 'a, const SERVOS: usize> Serialize for StoredPoses<'p, 'a, SERVOS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let poses = self.0.iter().filter_map(Option::as_ref);
+        let mut seq = serializer.serialize_seq(Some(poses.clone().count()))?;
+        for (name, positions) in poses {
+            seq.serialize_element(&(name, Positions(positions)))?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(feature = "postcard")]
+impl<'p, const SERVOS: usize> Serialize for Positions<'p, SERVOS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(SERVOS)?;
+        for position in self.0.iter() {
+            tuple.serialize_element(position)?;
+        }
+        tuple.end()
+    }
+}
+
+impl<'a, const SERVOS: usize, const POSES: usize> ACKHandler for PoseLibrary<'a, SERVOS, POSES> {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        self.update(packet);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{RamReadData, ReadableRamAddr};
+    use builder::MessageBuilder;
+    use message::{JogColor, JogMode};
+    use pose::{PoseError, PoseLibrary};
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+    use servo::BROADCAST_ID;
+    use std::vec::Vec;
+
+    fn position(pid: u8, position: u16) -> ACKPacket {
+        ACKPacket {
+            pid,
+            cmd: Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::CalibratedPosition,
+                    data_len: 2,
+                    data: [position as u8, (position >> 8) as u8],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        }
+    }
+
+    #[test]
+    fn capture_and_goto() {
+        let mut library: PoseLibrary<2, 2> = PoseLibrary::new([0x40, 0x41]);
+        assert!(library.update(&position(0x40, 300)));
+        assert!(!library.update(&position(0x42, 100)));
+        assert_eq!(
+            library.capture("wave"),
+            Err(PoseError::UnknownPosition(0x41))
+        );
+        library.update(&position(0x41, 700));
+        library.capture("wave").unwrap();
+        assert_eq!(library.pose("wave"), Some([300, 700]));
+
+        library.store("rest", [512, 512]).unwrap();
+        library.store("rest", [500, 520]).unwrap();
+        assert_eq!(library.store("sit", [0, 0]), Err(PoseError::LibraryFull));
+        assert_eq!(library.names().collect::<Vec<_>>(), ["wave", "rest"]);
+
+        let mut expected = MessageBuilder::new_with_id(BROADCAST_ID).s_jog(
+            60,
+            JogMode::Normal { position: 500 },
+            JogColor::Green,
+            0x40,
+        );
+        expected
+            .s_jog(JogMode::Normal { position: 520 }, JogColor::Green, 0x41)
+            .unwrap();
+        assert_eq!(library.goto("rest", 60), Ok(expected.build()));
+        assert_eq!(library.goto("sit", 60), Err(PoseError::UnknownPose));
+
+        assert!(library.remove("wave"));
+        assert!(!library.remove("wave"));
+        library.store("sit", [0, 0]).unwrap();
+
+        let empty: PoseLibrary<0, 1> = PoseLibrary::new([]);
+        assert_eq!(empty.goto("rest", 60), Err(PoseError::InvalidGroupSize));
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn save_and_load() {
+        let mut library: PoseLibrary<3, 2> = PoseLibrary::new([0x40, 0x41, 0x42]);
+        library.store("rest", [512, 512, 512]).unwrap();
+        library.store("wave", [300, 700, 1023]).unwrap();
+        let mut buf = [0u8; 32];
+        let saved = library.save(&mut buf).unwrap();
+        assert_eq!(saved.len(), 1 + 5 + 6 + 5 + 6);
+
+        let mut restored: PoseLibrary<3, 2> = PoseLibrary::new([0x40, 0x41, 0x42]);
+        restored.load(saved).unwrap();
+        assert_eq!(restored.names().collect::<Vec<_>>(), ["rest", "wave"]);
+        assert_eq!(restored.pose("wave"), Some([300, 700, 1023]));
+
+        // Too many poses for the library, or truncated poses.
+        let mut small: PoseLibrary<3, 1> = PoseLibrary::new([0x40, 0x41, 0x42]);
+        small.store("sit", [0, 0, 0]).unwrap();
+        assert!(small.load(saved).is_err());
+        assert!(restored.load(&saved[..saved.len() - 1]).is_err());
+        assert_eq!(small.pose("sit"), Some([0, 0, 0]));
+        assert_eq!(restored.pose("wave"), Some([300, 700, 1023]));
+    }
+}
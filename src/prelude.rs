@@ -0,0 +1,24 @@
+//! The most commonly used types of this crate.
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::prelude::*;
+//!
+//! let message: HerkulexMessage = MessageBuilder::new_with_id(0x40)
+//!     .rollback(Rollback::SkipId)
+//!     .build();
+//! ```
+
+pub use addr::ReadableEEPAddr;
+#[cfg(feature = "eep")]
+pub use addr::WritableEEPAddr;
+pub use addr::{ReadableRamAddr, WritableRamAddr};
+pub use builder::{HerkulexMessage, MessageBuilder, MessageBuilderError};
+pub use emergency::emergency_stop_all;
+pub use frame::{ExpectedReply, ValidateFrame};
+pub use message::{JogColor, JogMode, Rollback, Rotation};
+pub use model::Model;
+#[cfg(feature = "runtime")]
+pub use reader::ACKReader;
+pub use reader::{ACKHandler, ACKPacket, Command, StatusDetail, StatusError, StatusPair};
+pub use servo::{Servo, ServoOptions};
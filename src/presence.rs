@@ -0,0 +1,217 @@
+//! Detection of the servomotors disappearing from the bus or coming back.
+//!
+//! A [`PresenceMonitor`](struct.PresenceMonitor.html) counts the consecutive timeouts and answers
+//! of every servomotor. A servomotor is lost after several timeouts in a row and found again after
+//! several answers in a row, so that a single corrupted frame does not toggle its state. The
+//! application can then degrade gracefully when a cable is bumped.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::presence::{PresenceEvent, PresenceMonitor};
+//! use drs_0x01::reader::ACKReader;
+//!
+//! let mut reader = ACKReader::new();
+//! let mut presence = PresenceMonitor::new().with_hysteresis(2, 1);
+//! reader.parse_with(&[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01], &mut presence);
+//! assert_eq!(presence.pop_event(), Some(PresenceEvent::ServoFound(0xFD)));
+//!
+//! // The next two requests are not answered.
+//! presence.record_timeout(0xFD);
+//! presence.record_timeout(0xFD);
+//! assert_eq!(presence.pop_event(), Some(PresenceEvent::ServoLost(0xFD)));
+//! ```
+
+use arrayvec::ArrayVec;
+
+use reader::{ACKHandler, ACKPacket};
+
+/// The maximum number of servomotors a [`PresenceMonitor`](struct.PresenceMonitor.html) can
+/// follow.
+pub const MAX_MONITORED_SERVOS: usize = 32;
+
+/// The maximum number of events waiting to be popped. When it is reached, the oldest event is
+/// dropped.
+pub const PRESENCE_EVENTS_SIZE: usize = 16;
+
+/// A change of the presence of a servomotor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PresenceEvent {
+    /// The servomotor with this ID stopped answering.
+    ServoLost(u8),
+    /// The servomotor with this ID answers, for the first time or after being lost.
+    ServoFound(u8),
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Presence {
+    id: u8,
+    present: Option<bool>,
+    timeouts: u8,
+    answers: u8,
+}
+
+/// This struct follows the presence of up to `MAX_MONITORED_SERVOS` servomotors.
+///
+/// Answers are counted when it is used as an
+/// [`ACKHandler`](../reader/trait.ACKHandler.html), the timeouts must be recorded by the
+/// application.
+#[derive(Clone, Debug)]
+pub struct PresenceMonitor {
+    lost_after: u8,
+    found_after: u8,
+    servos: ArrayVec<[Presence; MAX_MONITORED_SERVOS]>,
+    events: ArrayVec<[PresenceEvent; PRESENCE_EVENTS_SIZE]>,
+}
+
+impl Default for PresenceMonitor {
+    fn default() -> PresenceMonitor {
+        PresenceMonitor {
+            lost_after: 3,
+            found_after: 2,
+            servos: ArrayVec::new(),
+            events: ArrayVec::new(),
+        }
+    }
+}
+
+impl PresenceMonitor {
+    /// Create a monitor losing a servomotor after 3 timeouts in a row, and finding it after 2
+    /// answers in a row.
+    pub fn new() -> PresenceMonitor {
+        PresenceMonitor::default()
+    }
+
+    /// Lose a servomotor after `lost_after` timeouts in a row, and find it after `found_after`
+    /// answers in a row. Both are at least 1.
+    pub fn with_hysteresis(mut self, lost_after: u8, found_after: u8) -> PresenceMonitor {
+        self.lost_after = lost_after.max(1);
+        self.found_after = found_after.max(1);
+        self
+    }
+
+    fn entry(&mut self, id: u8) -> Option<&mut Presence> {
+        if !self.servos.iter().any(|servo| servo.id == id) {
+            self.servos
+                .try_push(Presence {
+                    id,
+                    present: None,
+                    timeouts: 0,
+                    answers: 0,
+                })
+                .ok()?;
+        }
+        self.servos.iter_mut().find(|servo| servo.id == id)
+    }
+
+    fn push_event(&mut self, event: PresenceEvent) {
+        if self.events.is_full() {
+            self.events.remove(0);
+        }
+        self.events.push(event);
+    }
+
+    /// Count a request to the servomotor `id` which was not answered.
+    pub fn record_timeout(&mut self, id: u8) {
+        let lost_after = self.lost_after;
+        let lost = match self.entry(id) {
+            Some(servo) => {
+                servo.answers = 0;
+                servo.timeouts = servo.timeouts.saturating_add(1);
+                if servo.timeouts >= lost_after && servo.present != Some(false) {
+                    let was_present = servo.present == Some(true);
+                    servo.present = Some(false);
+                    was_present
+                } else {
+                    false
+                }
+            }
+            None => false,
+        };
+        if lost {
+            self.push_event(PresenceEvent::ServoLost(id));
+        }
+    }
+
+    /// Count an answer of the servomotor `id`.
+    pub fn record_answer(&mut self, id: u8) {
+        let found_after = self.found_after;
+        let found = match self.entry(id) {
+            Some(servo) => {
+                servo.timeouts = 0;
+                servo.answers = servo.answers.saturating_add(1);
+                if servo.answers >= found_after && servo.present != Some(true) {
+                    servo.present = Some(true);
+                    true
+                } else {
+                    false
+                }
+            }
+            None => false,
+        };
+        if found {
+            self.push_event(PresenceEvent::ServoFound(id));
+        }
+    }
+
+    /// Return true if the servomotor `id` is present, false if it is lost, and `None` if it is
+    /// not known yet.
+    pub fn is_present(&self, id: u8) -> Option<bool> {
+        self.servos
+            .iter()
+            .find(|servo| servo.id == id)
+            .and_then(|servo| servo.present)
+    }
+
+    /// Return the oldest event, if any.
+    pub fn pop_event(&mut self) -> Option<PresenceEvent> {
+        if self.events.is_empty() {
+            None
+        } else {
+            Some(self.events.remove(0))
+        }
+    }
+}
+
+impl ACKHandler for PresenceMonitor {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        self.record_answer(packet.pid);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use presence::{PresenceEvent, PresenceMonitor};
+
+    #[test]
+    fn hysteresis() {
+        let mut presence = PresenceMonitor::new();
+        // A servomotor which never answered is not reported as lost.
+        for _ in 0..3 {
+            presence.record_timeout(0x40);
+        }
+        assert_eq!(presence.is_present(0x40), Some(false));
+        assert_eq!(presence.pop_event(), None);
+
+        presence.record_answer(0x40);
+        presence.record_timeout(0x40);
+        presence.record_answer(0x40);
+        assert_eq!(presence.pop_event(), None);
+        presence.record_answer(0x40);
+        assert_eq!(presence.pop_event(), Some(PresenceEvent::ServoFound(0x40)));
+        assert_eq!(presence.is_present(0x40), Some(true));
+
+        presence.record_timeout(0x40);
+        presence.record_timeout(0x40);
+        presence.record_answer(0x40);
+        presence.record_timeout(0x40);
+        presence.record_timeout(0x40);
+        assert_eq!(presence.pop_event(), None);
+        presence.record_timeout(0x40);
+        presence.record_timeout(0x40);
+        assert_eq!(presence.pop_event(), Some(PresenceEvent::ServoLost(0x40)));
+        assert_eq!(presence.pop_event(), None);
+        assert_eq!(presence.is_present(0x41), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! Tuning presets for common uses of the DRS-0101 and the DRS-0201.
+//!
+//! Every preset is a [`ServoConfig`](../config/struct.ServoConfig.html) starting from the factory
+//! defaults of its model, with the compliance, gains and limits adjusted for a use. They are
+//! baselines to start tuning from, not values fitted to a given mechanism.
+//!
+//! A preset is applied by writing the registers which differ from the current configuration of
+//! the servomotor, see
+//! [`ServoConfig::eep_writes`](../config/struct.ServoConfig.html#method.eep_writes). The ID and
+//! the baudrate of the servomotor are kept.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::config::ServoConfig;
+//! use drs_0x01::presets;
+//! use drs_0x01::{Model, Servo};
+//!
+//! let servo = Servo::new(0x40);
+//! let current = ServoConfig::factory_default(Model::Drs0101);
+//! for write in presets::DRS0101_STIFF_PAN_TILT.eep_writes(&current) {
+//!     let message = servo.eep_write(write).unwrap();
+//!     // Send the message ...
+//! }
+//! // Then reboot the servomotor for the preset to take effect.
+//! ```
+
+use config::{ComplianceSettings, InpositionMargin, OverloadThreshold, ServoConfig};
+use model::Model;
+
+/// A compliant joint of an arm : a dead zone and a soft slope absorb the shocks, and the PWM is
+/// limited.
+const fn compliant_arm_joint(model: Model) -> ServoConfig {
+    let (position_kp, max_pwm) = match model {
+        Model::Drs0101 => (0x0120, 0x0300),
+        Model::Drs0201 => (0x0150, 0x0340),
+    };
+    ServoConfig {
+        compliance: ComplianceSettings {
+            dead_zone: 0x02,
+            saturator_offset: 0x10,
+            saturator_slope: 0x0C00,
+        },
+        max_pwm,
+        overload_threshold: OverloadThreshold::Pwm(max_pwm),
+        position_kp,
+        position_kd: 0x1000,
+        acceleration_ratio: 0x20,
+        inposition_margin: InpositionMargin::new(0x05),
+        ..ServoConfig::factory_default(model)
+    }
+}
+
+/// A stiff pan-tilt head : no dead zone, higher gains and a small integral gain to hold the
+/// position precisely, and short accelerations.
+const fn stiff_pan_tilt(model: Model) -> ServoConfig {
+    let (position_kp, position_kd) = match model {
+        Model::Drs0101 => (0x0260, 0x2800),
+        Model::Drs0201 => (0x0290, 0x2C00),
+    };
+    ServoConfig {
+        compliance: ComplianceSettings {
+            dead_zone: 0x00,
+            saturator_offset: 0x00,
+            saturator_slope: 0x0000,
+        },
+        position_kp,
+        position_kd,
+        position_ki: 0x0004,
+        acceleration_ratio: 0x0A,
+        inposition_margin: InpositionMargin::new(0x01),
+        ..ServoConfig::factory_default(model)
+    }
+}
+
+/// A wheel in continuous rotation : the position limits span the whole sensor and the overload
+/// is detected later, since a wheel often pushes against the ground for a while.
+const fn continuous_wheel(model: Model) -> ServoConfig {
+    ServoConfig {
+        min_position: 0x0000,
+        max_position: 0x03FF,
+        acceleration_ratio: 0x00,
+        overload_detection_period: 0xFF,
+        ..ServoConfig::factory_default(model)
+    }
+}
+
+/// A compliant arm joint on a DRS-0101.
+pub const DRS0101_COMPLIANT_ARM_JOINT: ServoConfig = compliant_arm_joint(Model::Drs0101);
+/// A compliant arm joint on a DRS-0201.
+pub const DRS0201_COMPLIANT_ARM_JOINT: ServoConfig = compliant_arm_joint(Model::Drs0201);
+/// A stiff pan-tilt head on a DRS-0101.
+pub const DRS0101_STIFF_PAN_TILT: ServoConfig = stiff_pan_tilt(Model::Drs0101);
+/// A stiff pan-tilt head on a DRS-0201.
+pub const DRS0201_STIFF_PAN_TILT: ServoConfig = stiff_pan_tilt(Model::Drs0201);
+/// A wheel in continuous rotation on a DRS-0101.
+pub const DRS0101_CONTINUOUS_WHEEL: ServoConfig = continuous_wheel(Model::Drs0101);
+/// A wheel in continuous rotation on a DRS-0201.
+pub const DRS0201_CONTINUOUS_WHEEL: ServoConfig = continuous_wheel(Model::Drs0201);
+
+/// Every preset, with its name and its model.
+pub const ALL: [(&str, Model, ServoConfig); 6] = [
+    (
+        "compliant arm joint",
+        Model::Drs0101,
+        DRS0101_COMPLIANT_ARM_JOINT,
+    ),
+    (
+        "compliant arm joint",
+        Model::Drs0201,
+        DRS0201_COMPLIANT_ARM_JOINT,
+    ),
+    ("stiff pan-tilt", Model::Drs0101, DRS0101_STIFF_PAN_TILT),
+    ("stiff pan-tilt", Model::Drs0201, DRS0201_STIFF_PAN_TILT),
+    ("continuous wheel", Model::Drs0101, DRS0101_CONTINUOUS_WHEEL),
+    ("continuous wheel", Model::Drs0201, DRS0201_CONTINUOUS_WHEEL),
+];
+
+/// Return the preset `name` for `model`.
+pub fn find(name: &str, model: Model) -> Option<ServoConfig> {
+    ALL.iter()
+        .find(|&&(preset, preset_model, _)| preset == name && preset_model == model)
+        .map(|&(_, _, config)| config)
+}
+
+#[cfg(test)]
+mod test {
+    use addr::WritableEEPAddr;
+    use config::{ServoConfig, CONFIG_TABLE_LEN};
+    use model::Model;
+    use presets;
+    use std::vec::Vec;
+
+    #[test]
+    fn presets() {
+        for &(_, model, preset) in presets::ALL.iter() {
+            preset.compliance.validate().unwrap();
+            // The table round trip rejects out of range values.
+            assert_eq!(
+                ServoConfig::from_table(&preset.to_table())
+                    .unwrap()
+                    .to_table(),
+                preset.to_table()
+            );
+            assert_eq!(preset.to_table().len(), CONFIG_TABLE_LEN);
+            let factory = ServoConfig::factory_default(model);
+            assert_eq!(
+                (preset.id, preset.baud_rate),
+                (factory.id, factory.baud_rate)
+            );
+        }
+        assert_eq!(
+            presets::find("stiff pan-tilt", Model::Drs0201),
+            Some(presets::DRS0201_STIFF_PAN_TILT)
+        );
+        assert_eq!(presets::find("hexapod leg", Model::Drs0101), None);
+
+        let mut current = ServoConfig::factory_default(Model::Drs0101);
+        current.id = 0x40;
+        assert_eq!(
+            presets::DRS0101_CONTINUOUS_WHEEL
+                .eep_writes(&current)
+                .collect::<Vec<_>>(),
+            [
+                WritableEEPAddr::AccelerationRatio(0x00),
+                WritableEEPAddr::MinPosition(0x00, 0x00),
+                WritableEEPAddr::MaxPosition(0xFF, 0x03),
+                WritableEEPAddr::OverloadDetectionPeriod(0xFF),
+            ]
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! The codec of the Herkulex protocol, without any buffer larger than a frame.
+//!
+//! The crate is organised in two layers :
+//!
+//! * the protocol, gathered in this module : the register map, the checksums, the frame sizes,
+//!   the request builders and the [`FrameDecoder`](../reader/struct.FrameDecoder.html) decoding the
+//!   ACKs one frame at a time. It has no dependency on any feature, so that the smallest targets
+//!   (a bootloader flashing the servomotors for example) can depend on it alone with
+//!   `default-features = false`.
+//! * the runtime, enabled by the `runtime` feature (on by default) : the
+//!   [`ACKReader`](../reader/struct.ACKReader.html) and its packet buffer, the transactions, the
+//!   transports, the status cache, the bus sharing, the diagnostics and every other module keeping
+//!   track of several servomotors or several packets.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::protocol::{FrameDecoder, MessageBuilder, ACKPacket};
+//!
+//! let request = MessageBuilder::new_with_id(0xFD).stat().build();
+//! // Send the request ...
+//! let ack = [0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01];
+//! let mut decoder = FrameDecoder::new();
+//! decoder.parse_with(&ack, &mut |packet: &ACKPacket| assert_eq!(packet.pid, 0xFD));
+//! ```
+
+#[cfg(feature = "eep")]
+pub use addr::WritableEEPAddr;
+pub use addr::{EEPReadData, ReadableEEPAddr};
+pub use addr::{RamReadData, ReadableRamAddr, WritableRamAddr};
+pub use builder::{HerkulexMessage, MessageBuilder, MessageBuilderError};
+pub use checksum::{checksum1, checksum2};
+pub use frame::*;
+pub use message::{JogColor, JogMode, Rollback, Rotation};
+pub use model::{DEGREES_PER_POSITION, POSITION_RANGE};
+pub use reader::{
+    ACKHandler, ACKPacket, Command, FrameDecoder, ReaderStats, StatusDetail, StatusError,
+    StatusPair,
+};
+pub use reg::{Register, RegisterValue, Writable};
@@ -0,0 +1,251 @@
+//! A lock-free single-producer single-consumer queue of [`ACKPacket`](../reader/struct.ACKPacket.html).
+//!
+//! The queue is split into a [`Producer`](struct.Producer.html), fed from an interrupt handler
+//! with [`ACKReader::parse_with`](../reader/struct.ACKReader.html#method.parse_with), and a
+//! [`Consumer`](struct.Consumer.html) which pops the packets in thread context. Neither side needs
+//! a critical section.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::queue::PacketQueue;
+//! use drs_0x01::reader::ACKReader;
+//!
+//! let mut queue = PacketQueue::new();
+//! let (mut producer, mut consumer) = queue.split();
+//! let mut reader = ACKReader::new();
+//!
+//! // In the UART interrupt handler :
+//! reader.parse_with(&[0xFF, 0xFF, 0x09, 0xFD, 0x46, 0xB2, 0x4C, 0x08, 0x08], &mut producer);
+//!
+//! // In thread context :
+//! while let Some(packet) = consumer.dequeue() {
+//!     // ...
+//! }
+//! ```
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use reader::{ACKHandler, ACKPacket, TRAME_READER_INTERNAL_BUFFER_SIZE};
+
+/// The number of slots of a `PacketQueue`, one of them is always kept empty.
+const QUEUE_SLOTS: usize = TRAME_READER_INTERNAL_BUFFER_SIZE + 1;
+
+/// A bounded queue holding up to `TRAME_READER_INTERNAL_BUFFER_SIZE` packets.
+pub struct PacketQueue {
+    // The next slot to read, only written by the consumer.
+    head: AtomicUsize,
+    // The next slot to write, only written by the producer.
+    tail: AtomicUsize,
+    slots: [UnsafeCell<MaybeUninit<ACKPacket>>; QUEUE_SLOTS],
+}
+
+// The producer and the consumer never access the same slot at the same time.
+unsafe impl Sync for PacketQueue {}
+
+impl Default for PacketQueue {
+    fn default() -> PacketQueue {
+        PacketQueue::new()
+    }
+}
+
+impl PacketQueue {
+    /// Create an empty queue. This function can be used to initialize a `static`.
+    pub const fn new() -> PacketQueue {
+        PacketQueue {
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            slots: [const { UnsafeCell::new(MaybeUninit::uninit()) }; QUEUE_SLOTS],
+        }
+    }
+
+    /// Split the queue into its producer and consumer ends.
+    pub fn split<'a>(&'a mut self) -> (Producer<'a>, Consumer<'a>) {
+        (
+            Producer {
+                queue: self,
+                _marker: PhantomData,
+            },
+            Consumer {
+                queue: self,
+                _marker: PhantomData,
+            },
+        )
+    }
+
+    /// Return the maximum number of packets the queue can hold.
+    pub fn capacity(&self) -> usize {
+        QUEUE_SLOTS - 1
+    }
+
+    /// Return the number of packets in the queue.
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        (tail + QUEUE_SLOTS - head) % QUEUE_SLOTS
+    }
+
+    /// Return true if the queue holds no packet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The end of a [`PacketQueue`](struct.PacketQueue.html) which pushes packets.
+///
+/// Packets received while the queue is full are dropped when it is used as an
+/// [`ACKHandler`](../reader/trait.ACKHandler.html).
+pub struct Producer<'a> {
+    queue: *const PacketQueue,
+    // Not `Sync`, but can be sent to the interrupt handler.
+    _marker: PhantomData<&'a mut PacketQueue>,
+}
+
+unsafe impl<'a> Send for Producer<'a> {}
+
+impl<'a> Producer<'a> {
+    /// Push `packet` at the end of the queue, or give it back if the queue is full.
+    pub fn enqueue(&mut self, packet: ACKPacket) -> Result<(), ACKPacket> {
+        let queue = unsafe { &*self.queue };
+        let tail = queue.tail.load(Ordering::Relaxed);
+        let next = (tail + 1) % QUEUE_SLOTS;
+        if next == queue.head.load(Ordering::Acquire) {
+            return Err(packet);
+        }
+        unsafe { ptr::write((*queue.slots[tail].get()).as_mut_ptr(), packet) };
+        queue.tail.store(next, Ordering::Release);
+        Ok(())
+    }
+
+    /// Return true if no packet can be pushed.
+    pub fn is_full(&self) -> bool {
+        let queue = unsafe { &*self.queue };
+        queue.len() == queue.capacity()
+    }
+}
+
+impl<'a> ACKHandler for Producer<'a> {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        let _ = self.enqueue(ACKPacket {
+            pid: packet.pid,
+            cmd: packet.cmd,
+            error: packet.error,
+            detail: packet.detail,
+            error_raw: packet.error_raw,
+            detail_raw: packet.detail_raw,
+        });
+    }
+}
+
+/// The end of a [`PacketQueue`](struct.PacketQueue.html) which pops packets.
+pub struct Consumer<'a> {
+    queue: *const PacketQueue,
+    // Not `Sync`, but can be sent to another task.
+    _marker: PhantomData<&'a mut PacketQueue>,
+}
+
+unsafe impl<'a> Send for Consumer<'a> {}
+
+impl<'a> Consumer<'a> {
+    /// Pop the oldest packet of the queue.
+    pub fn dequeue(&mut self) -> Option<ACKPacket> {
+        let queue = unsafe { &*self.queue };
+        let head = queue.head.load(Ordering::Relaxed);
+        if head == queue.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        let packet = unsafe { ptr::read((*queue.slots[head].get()).as_ptr()) };
+        queue
+            .head
+            .store((head + 1) % QUEUE_SLOTS, Ordering::Release);
+        Some(packet)
+    }
+
+    /// Return the number of packets waiting in the queue.
+    pub fn len(&self) -> usize {
+        unsafe { &*self.queue }.len()
+    }
+
+    /// Return true if no packet is waiting in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use queue::PacketQueue;
+    use reader::{stat_ack, ACKPacket, ACKReader, Command, StatusDetail, StatusError};
+
+    use std::thread;
+
+    fn packet(pid: u8) -> ACKPacket {
+        stat_ack(pid, StatusError::NoError)
+    }
+
+    #[test]
+    fn fifo_order_and_capacity() {
+        let mut queue = PacketQueue::new();
+        let capacity = queue.capacity();
+        let (mut producer, mut consumer) = queue.split();
+        for pid in 0..capacity {
+            assert_eq!(producer.enqueue(packet(pid as u8)), Ok(()));
+        }
+        assert!(producer.is_full());
+        assert_eq!(producer.enqueue(packet(0xFF)), Err(packet(0xFF)));
+        assert_eq!(consumer.len(), capacity);
+        for pid in 0..capacity {
+            assert_eq!(consumer.dequeue(), Some(packet(pid as u8)));
+        }
+        assert_eq!(consumer.dequeue(), None);
+        assert!(producer.enqueue(packet(0)).is_ok());
+    }
+
+    #[test]
+    fn parse_into_queue() {
+        let mut queue = PacketQueue::new();
+        let (mut producer, mut consumer) = queue.split();
+        let mut reader = ACKReader::new();
+        reader.parse_with(
+            &[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01],
+            &mut producer,
+        );
+        assert_eq!(
+            consumer.dequeue(),
+            Some(ACKPacket {
+                pid: 0xFD,
+                cmd: Command::Stat,
+                error: StatusError::NoError,
+                detail: StatusDetail::MovingFlag,
+                error_raw: 0x00,
+                detail_raw: 0x01,
+            })
+        );
+    }
+
+    #[test]
+    fn concurrent_producer_and_consumer() {
+        static mut QUEUE: PacketQueue = PacketQueue::new();
+        #[allow(static_mut_refs)]
+        let (mut producer, mut consumer) = unsafe { QUEUE.split() };
+        let handle = thread::spawn(move || {
+            for pid in 0..=255u8 {
+                while producer.enqueue(packet(pid)).is_err() {}
+            }
+        });
+        for pid in 0..=255u8 {
+            loop {
+                if let Some(received) = consumer.dequeue() {
+                    assert_eq!(received, packet(pid));
+                    break;
+                }
+            }
+        }
+        handle.join().unwrap();
+    }
+}
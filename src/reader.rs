@@ -5,14 +5,27 @@ use arrayvec::ArrayVec;
 
 use addr::EEPReadData;
 use addr::RamReadData;
+use addr::ReadableRamAddr;
+#[cfg(feature = "eep")]
 use addr::WritableEEPAddr;
 use addr::WritableRamAddr;
+use checksum::{checksum1, checksum2};
+use config::MAX_PERIOD_TICKS;
+use frame::{AckCommand, HEADER_LEN, MEM_OVERHEAD, MIN_ACK_LEN, MIN_FRAME_LEN};
+#[cfg(test)]
+use test_support::AckFrameBuilder;
+
+use core::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// The size of the internal buffer of `ACKReader` where `ACKPacket` are stored when parsing data.
+#[cfg(feature = "runtime")]
 pub const TRAME_READER_INTERNAL_BUFFER_SIZE: usize = 64;
 
 /// An `ACKPacket` is a message sent by the servomotor and received by an `AckReader`.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub struct ACKPacket {
     /// The ID of the servomotor who sent this packet
     pub pid: u8,
@@ -22,6 +35,47 @@ pub struct ACKPacket {
     pub error: StatusError,
     /// Status Error register detail
     pub detail: StatusDetail,
+    /// Status Error register content, with every bit as received
+    pub error_raw: u8,
+    /// Status Detail register content, with every bit as received
+    pub detail_raw: u8,
+}
+
+impl ACKPacket {
+    /// Create a packet whose raw status registers are the bits of `error` and `detail`.
+    pub const fn new(pid: u8, cmd: Command, error: StatusError, detail: StatusDetail) -> ACKPacket {
+        ACKPacket {
+            pid,
+            cmd,
+            error,
+            detail,
+            error_raw: error.bits(),
+            detail_raw: detail.bits(),
+        }
+    }
+
+    /// Return true if any bit of the status error register is set.
+    pub fn is_fault(&self) -> bool {
+        self.error_raw != 0
+    }
+
+    /// Return true if the servomotor reported to be moving.
+    pub fn is_moving(&self) -> bool {
+        self.detail_raw & StatusDetail::MovingFlag.bits() != 0
+    }
+}
+
+/// Extract the position from an ACK to a RAM read of `CalibratedPosition` or `AbsolutePosition`.
+pub(crate) fn position_of(packet: &ACKPacket) -> Option<u16> {
+    match packet.cmd {
+        Command::RamRead { data } => match data.addr {
+            ReadableRamAddr::CalibratedPosition | ReadableRamAddr::AbsolutePosition => {
+                data.as_u16()
+            }
+            _ => None,
+        },
+        _ => None,
+    }
 }
 
 impl From<RawACKPacket> for ACKPacket {
@@ -29,8 +83,10 @@ impl From<RawACKPacket> for ACKPacket {
         ACKPacket {
             pid: packet.pid,
             cmd: packet.cmd,
-            error: packet.error,
-            detail: packet.detail,
+            error: StatusError::from_bits(packet.error),
+            detail: StatusDetail::from_bits(packet.detail),
+            error_raw: packet.error,
+            detail_raw: packet.detail,
         }
     }
 }
@@ -48,58 +104,63 @@ struct RawACKPacket {
     /// The checksum2 of the packet
     pub chk2: u8,
     /// Status Error register content
-    pub error: StatusError,
+    pub error: u8,
     /// Status Error register detail
-    pub detail: StatusDetail,
+    pub detail: u8,
 }
 
 impl RawACKPacket {
+    // The checksums cover every byte of the frame but the header and the checksums themselves :
+    // the size, the ID, the command, the data and the status registers, which are only missing
+    // from the minimal ACK. The size must also be the length of the frame.
     pub fn is_valid(&self) -> bool {
-        use addr::ReadableEEPAddr;
-        use addr::ReadableRamAddr;
-        use reader::Command::*;
-
-        // Construction de chk1
-        let mut chk1 = self.psize;
-        chk1 ^= self.pid;
-        chk1 ^= u8::from(self.cmd);
+        let mut covered: ArrayVec<[u8; 16]> = ArrayVec::new();
+        covered.push(self.psize);
+        covered.push(self.pid);
+        covered.push(u8::from(self.cmd));
 
         match self.cmd {
             Command::EEPRead { data } => {
-                let a: u8 = data.addr.into();
-                chk1 ^= a;
-                chk1 ^= data.data_len;
-                for i in &data.data[0..data.data_len as usize] {
-                    chk1 ^= i;
-                }
+                covered.push(data.addr.into());
+                covered.push(data.data_len);
+                covered.extend(data.data.iter().take(data.data_len as usize).cloned());
             }
             Command::RamRead { data } => {
-                let a: u8 = data.addr.into();
-                chk1 ^= a;
-                chk1 ^= data.data_len;
-                for i in &data.data[0..data.data_len as usize] {
-                    chk1 ^= i;
-                }
+                covered.push(data.addr.into());
+                covered.push(data.data_len);
+                covered.extend(data.data.iter().take(data.data_len as usize).cloned());
+            }
+            Command::ReadFailed {
+                addr: Some(addr), ..
+            } => {
+                covered.push(addr);
+                covered.push(0);
             }
             _ => (),
         };
-        chk1 &= 0xFE;
-        self.chk1 == chk1 && self.chk2 == !chk1 & 0xFE
+        if usize::from(self.psize) > MIN_FRAME_LEN {
+            covered.push(self.error);
+            covered.push(self.detail);
+        }
+        let chk1 = checksum1(&covered);
+        usize::from(self.psize) == covered.len() + HEADER_LEN + 2
+            && self.chk1 == chk1
+            && self.chk2 == checksum2(chk1)
     }
 }
 
-impl Into<Command> for RawACKPacket {
-    fn into(self) -> Command {
-        self.cmd
+impl From<RawACKPacket> for Command {
+    fn from(packet: RawACKPacket) -> Command {
+        packet.cmd
     }
 }
 
 /// The kind of command the servomotor is answering to.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Command {
     /// EEPWrite command
     EEPWrite,
-    /// EEPRead command
+    /// EEPRead command, only decoded with the `eep` feature
     EEPRead {
         /// The data read
         data: EEPReadData,
@@ -121,59 +182,65 @@ pub enum Command {
     Rollback,
     /// Reboot command
     Reboot,
+    /// A read answered without any data, the address read being invalid for example
+    ReadFailed {
+        /// The command of the ACK, `AckCommand::EEPRead` or `AckCommand::RamRead`
+        cmd: AckCommand,
+        /// The address read, if the servomotor sent it back
+        addr: Option<u8>,
+    },
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
-enum InternalCommand {
-    EEPWrite,
-    EEPRead,
-    RamWrite,
-    RamRead,
-    IJog,
-    SJog,
-    Stat,
-    Rollback,
-    Reboot,
+impl AckCommand {
+    // Return `None` if the payload does not belong to this command.
+    fn inject_payload(self, payload: AssociatedData) -> Option<Command> {
+        use self::Command::*;
+        let cmd = match (self, payload) {
+            (AckCommand::EEPWrite, AssociatedData::Nothing) => EEPWrite,
+            (AckCommand::RamWrite, AssociatedData::Nothing) => RamWrite,
+            (AckCommand::IJog, AssociatedData::Nothing) => IJog,
+            (AckCommand::SJog, AssociatedData::Nothing) => SJog,
+            (AckCommand::Stat, AssociatedData::Nothing) => Stat,
+            (AckCommand::Rollback, AssociatedData::Nothing) => Rollback,
+            (AckCommand::Reboot, AssociatedData::Nothing) => Reboot,
+            #[cfg(feature = "eep")]
+            (AckCommand::EEPRead, AssociatedData::EEP(data)) => EEPRead { data },
+            (AckCommand::RamRead, AssociatedData::Ram(data)) => RamRead { data },
+            (cmd, AssociatedData::Failed(addr)) => ReadFailed { cmd, addr },
+            _ => return None,
+        };
+        Some(cmd)
+    }
 }
 
-impl InternalCommand {
-    fn inject_payload(self, payload: AssociatedData) -> Command {
-        use self::Command::*;
-        match (self, payload) {
-            (InternalCommand::EEPWrite, AssociatedData::Nothing) => EEPWrite,
-            (InternalCommand::RamWrite, AssociatedData::Nothing) => RamWrite,
-            (InternalCommand::IJog, AssociatedData::Nothing) => IJog,
-            (InternalCommand::SJog, AssociatedData::Nothing) => SJog,
-            (InternalCommand::Stat, AssociatedData::Nothing) => Stat,
-            (InternalCommand::Rollback, AssociatedData::Nothing) => Rollback,
-            (InternalCommand::Reboot, AssociatedData::Nothing) => Reboot,
-            (InternalCommand::EEPRead, AssociatedData::EEP(data)) => EEPRead { data },
-            (InternalCommand::RamRead, AssociatedData::Ram(data)) => RamRead { data },
-            _ => unreachable!(),
+impl From<Command> for AckCommand {
+    fn from(cmd: Command) -> AckCommand {
+        use reader::Command::*;
+        match cmd {
+            EEPWrite => AckCommand::EEPWrite,
+            EEPRead { .. } => AckCommand::EEPRead,
+            RamWrite => AckCommand::RamWrite,
+            RamRead { .. } => AckCommand::RamRead,
+            IJog => AckCommand::IJog,
+            SJog => AckCommand::SJog,
+            Stat => AckCommand::Stat,
+            Rollback => AckCommand::Rollback,
+            Reboot => AckCommand::Reboot,
+            ReadFailed { cmd, .. } => cmd,
         }
     }
 }
 
 impl From<Command> for u8 {
     fn from(cmd: Command) -> Self {
-        use reader::Command::*;
-        match cmd {
-            EEPWrite => 0x41,
-            EEPRead { .. } => 0x42,
-            RamWrite => 0x43,
-            RamRead { .. } => 0x44,
-            IJog => 0x45,
-            SJog => 0x46,
-            Stat => 0x47,
-            Rollback => 0x48,
-            Reboot => 0x49,
-        }
+        AckCommand::from(cmd).into()
     }
 }
 
 /// The values of the status error register
 #[allow(missing_docs)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum StatusError {
     ExceedInputVoltageLimit,
     ExceedAllowedPOTLimit,
@@ -185,9 +252,46 @@ pub enum StatusError {
     NoError,
 }
 
+impl StatusError {
+    const FLAGS: [StatusError; 7] = [
+        StatusError::ExceedInputVoltageLimit,
+        StatusError::ExceedAllowedPOTLimit,
+        StatusError::ExceedTemperatureLimit,
+        StatusError::InvalidPacket,
+        StatusError::OverloadDetected,
+        StatusError::DriverFaultDetected,
+        StatusError::EEPREGDistorded,
+    ];
+
+    /// Return the bit of the register set by this error, or 0 for `NoError`.
+    pub const fn bits(self) -> u8 {
+        match self {
+            StatusError::ExceedInputVoltageLimit => 0x01,
+            StatusError::ExceedAllowedPOTLimit => 0x02,
+            StatusError::ExceedTemperatureLimit => 0x04,
+            StatusError::InvalidPacket => 0x08,
+            StatusError::OverloadDetected => 0x10,
+            StatusError::DriverFaultDetected => 0x20,
+            StatusError::EEPREGDistorded => 0x40,
+            StatusError::NoError => 0x00,
+        }
+    }
+
+    /// Decode the register : return the error of the lowest bit set, or `NoError` if no known
+    /// bit is set.
+    pub fn from_bits(bits: u8) -> StatusError {
+        StatusError::FLAGS
+            .iter()
+            .cloned()
+            .find(|error| bits & error.bits() != 0)
+            .unwrap_or(StatusError::NoError)
+    }
+}
+
 /// The values of the status detail error register
 #[allow(missing_docs)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum StatusDetail {
     MovingFlag,
     ImpositionFlag,
@@ -199,31 +303,262 @@ pub enum StatusDetail {
     NoDetail,
 }
 
+impl StatusDetail {
+    const FLAGS: [StatusDetail; 7] = [
+        StatusDetail::MovingFlag,
+        StatusDetail::ImpositionFlag,
+        StatusDetail::ChecksumError,
+        StatusDetail::UnknownCommand,
+        StatusDetail::ExceedREGRange,
+        StatusDetail::GarbageDetected,
+        StatusDetail::MotorOnFlag,
+    ];
+
+    /// Return the bit of the register set by this detail, or 0 for `NoDetail`.
+    pub const fn bits(self) -> u8 {
+        match self {
+            StatusDetail::MovingFlag => 0x01,
+            StatusDetail::ImpositionFlag => 0x02,
+            StatusDetail::ChecksumError => 0x04,
+            StatusDetail::UnknownCommand => 0x08,
+            StatusDetail::ExceedREGRange => 0x10,
+            StatusDetail::GarbageDetected => 0x20,
+            StatusDetail::MotorOnFlag => 0x40,
+            StatusDetail::NoDetail => 0x00,
+        }
+    }
+
+    /// Decode the register : return the detail of the lowest bit set, or `NoDetail` if no known
+    /// bit is set.
+    pub fn from_bits(bits: u8) -> StatusDetail {
+        StatusDetail::FLAGS
+            .iter()
+            .cloned()
+            .find(|detail| bits & detail.bits() != 0)
+            .unwrap_or(StatusDetail::NoDetail)
+    }
+}
+
+/// Both status registers, read at once by
+/// [`Servo::request_status`](../struct.Servo.html#method.request_status).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StatusPair {
+    /// Status Error register content
+    pub error: StatusError,
+    /// Status Detail register content
+    pub detail: StatusDetail,
+    /// Status Error register content, with every bit as read
+    pub error_raw: u8,
+    /// Status Detail register content, with every bit as read
+    pub detail_raw: u8,
+}
+
+impl StatusPair {
+    /// Decode the content of both registers.
+    pub fn from_bits(error: u8, detail: u8) -> StatusPair {
+        StatusPair {
+            error: StatusError::from_bits(error),
+            detail: StatusDetail::from_bits(detail),
+            error_raw: error,
+            detail_raw: detail,
+        }
+    }
+
+    /// Return true if any bit of the status error register is set.
+    pub fn is_fault(&self) -> bool {
+        self.error_raw != 0
+    }
+
+    /// Return true if the servomotor reported to be moving.
+    pub fn is_moving(&self) -> bool {
+        self.detail_raw & StatusDetail::MovingFlag.bits() != 0
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Copy, Clone, Debug)]
 enum AssociatedData {
+    #[cfg(feature = "eep")]
     EEP(EEPReadData),
     Ram(RamReadData),
+    // The address of a read answered without data
+    Failed(Option<u8>),
     Nothing,
 }
 
+/// Counters of the frames seen by an [`ACKReader`](struct.ACKReader.html).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReaderStats {
+    /// The number of packets sucessfully decoded
+    pub packets: u32,
+    /// The number of complete frames dropped because of invalid checksums
+    pub checksum_errors: u32,
+    /// The number of valid packets dropped because their ID is not accepted by the filter
+    pub filtered: u32,
+    /// The number of frames abandoned after their header, because of an invalid byte
+    pub abandoned: u32,
+    /// The number of valid packets dropped because the buffer of the
+    /// [`ACKReader`](struct.ACKReader.html) was full
+    pub overflowed: u32,
+}
+
+/// The gaps measured in the middle of the frames by
+/// [`FrameDecoder::parse_at_with`](struct.FrameDecoder.html#method.parse_at_with), in ticks of
+/// the clock of the application.
+///
+/// A gap is measured when a frame is split between two buffers, from the time the first buffer
+/// was parsed to the time the second one was : it includes the latency of the application
+/// reading the bytes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GapStats {
+    /// The number of gaps measured
+    pub count: u32,
+    /// The longest gap
+    pub max: u32,
+    /// The sum of the gaps
+    pub total: u64,
+}
+
+impl GapStats {
+    /// Return the mean gap, if any gap was measured.
+    pub fn mean(&self) -> Option<u32> {
+        if self.count == 0 {
+            None
+        } else {
+            Some((self.total / u64::from(self.count)) as u32)
+        }
+    }
+
+    /// Return the value of the `PacketGarbageDetectionPeriod` register, in periods of 11.2 ms,
+    /// leaving twice the longest gap before a partial frame is dropped, for a clock counting
+    /// `tick_hz` ticks per second. The value is at most
+    /// [`MAX_PERIOD_TICKS`](../config/constant.MAX_PERIOD_TICKS.html). Return `None` if no gap was
+    /// measured.
+    pub fn recommended_garbage_period(&self, tick_hz: u32) -> Option<u8> {
+        if self.count == 0 {
+            return None;
+        }
+        let us = 2 * u64::from(self.max) * 1_000_000 / u64::from(tick_hz.max(1));
+        Some(us.div_ceil(11_200).clamp(1, u64::from(MAX_PERIOD_TICKS)) as u8)
+    }
+}
+
+/// A set of servomotor IDs, used by a reader to ignore the packets of the servomotors it does
+/// not own on a shared bus.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::reader::{ACKPacket, FrameDecoder, IdFilter};
+///
+/// let mut decoder = FrameDecoder::new();
+/// decoder.set_filter(Some(IdFilter::from_ids(&[0x40, 0x41])));
+/// let ack = [0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01];
+/// decoder.parse_with(&ack, &mut |_: &ACKPacket| panic!("filtered"));
+/// assert_eq!(decoder.stats().filtered, 1);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct IdFilter {
+    bits: [u32; 8],
+}
+
+impl IdFilter {
+    /// Create a filter accepting no ID.
+    pub const fn new() -> IdFilter {
+        IdFilter { bits: [0; 8] }
+    }
+
+    /// Create a filter accepting the IDs `ids`.
+    pub fn from_ids(ids: &[u8]) -> IdFilter {
+        let mut filter = IdFilter::new();
+        for &id in ids {
+            filter.allow(id);
+        }
+        filter
+    }
+
+    /// Accept the ID `id`.
+    pub fn allow(&mut self, id: u8) {
+        self.bits[usize::from(id / 32)] |= 1 << (id % 32);
+    }
+
+    /// Stop accepting the ID `id`.
+    pub fn deny(&mut self, id: u8) {
+        self.bits[usize::from(id / 32)] &= !(1 << (id % 32));
+    }
+
+    /// Return true if the ID `id` is accepted.
+    pub fn contains(&self, id: u8) -> bool {
+        self.bits[usize::from(id / 32)] & 1 << (id % 32) != 0
+    }
+}
+
+/// This is the state machine of an [`ACKReader`](struct.ACKReader.html) without its buffer : every
+/// packet is handed to an [`ACKHandler`](trait.ACKHandler.html) as soon as it is decoded. It only
+/// holds the frame being decoded, which suits the smallest targets (bootloaders ...).
+pub struct FrameDecoder {
+    state: ReaderState,
+    stats: ReaderStats,
+    filter: Option<IdFilter>,
+    gaps: GapStats,
+    last_parse: Option<u32>,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> FrameDecoder {
+        FrameDecoder {
+            state: ReaderState::H1,
+            stats: ReaderStats::default(),
+            filter: None,
+            gaps: GapStats::default(),
+            last_parse: None,
+        }
+    }
+}
+
+impl fmt::Debug for FrameDecoder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FrameDecoder")
+            .field("state", &self.state)
+            .field("stats", &self.stats)
+            .field("filter", &self.filter.is_some())
+            .field("gaps", &self.gaps)
+            .finish()
+    }
+}
+
 /// This is a state machine that take in some bytes and outputs `[AckPacket]`.
 /// Please note that this structure will allocate roughly 1 kiB of stack.
+///
+/// This struct is available with the `runtime` feature, the
+/// [`FrameDecoder`](struct.FrameDecoder.html) decodes the same frames without it.
+#[cfg(feature = "runtime")]
+#[derive(Default)]
 pub struct ACKReader {
-    state: ReaderState,
+    decoder: FrameDecoder,
     buffer: ArrayVec<[ACKPacket; TRAME_READER_INTERNAL_BUFFER_SIZE]>,
 }
 
-impl Default for ACKReader {
-    fn default() -> ACKReader {
-        ACKReader {
-            state: ReaderState::H1,
-            buffer: ArrayVec::new(),
-        }
+#[cfg(feature = "runtime")]
+impl fmt::Debug for ACKReader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ACKReader")
+            .field("state", &self.decoder.state)
+            .field("buffered", &self.buffer.len())
+            .field("stats", &self.decoder.stats)
+            .finish()
     }
 }
 
+// A complete frame, and whether its checksums were valid
+enum Frame {
+    Valid(RawACKPacket),
+    Corrupted { pid: u8 },
+}
+
 // Structure permettant de gérer la machine à états
-#[derive(Debug, Clone, Copy)]
+#[derive(Clone, Copy)]
 enum ReaderState {
     H1,
     H2,
@@ -238,41 +573,52 @@ enum ReaderState {
     Checksum1 {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
     },
     Checksum2 {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
         chk1: u8,
     },
     DataAddr {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
+        chk1: u8,
+        chk2: u8,
+    },
+    DataLenFailed {
+        size: u8,
+        pid: u8,
+        cmd: AckCommand,
         chk1: u8,
         chk2: u8,
+        addr: u8,
     },
+    #[cfg(feature = "eep")]
     DataLenEEP {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
         chk1: u8,
         chk2: u8,
         data: EEPReadData,
     },
+    #[cfg(feature = "eep")]
     Data1EEP {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
         chk1: u8,
         chk2: u8,
         data: EEPReadData,
     },
+    #[cfg(feature = "eep")]
     Data2EEP {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
         chk1: u8,
         chk2: u8,
         data: EEPReadData,
@@ -280,7 +626,7 @@ enum ReaderState {
     DataLenRAM {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
         chk1: u8,
         chk2: u8,
         data: RamReadData,
@@ -288,7 +634,7 @@ enum ReaderState {
     Data1RAM {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
         chk1: u8,
         chk2: u8,
         data: RamReadData,
@@ -296,7 +642,7 @@ enum ReaderState {
     Data2RAM {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
         chk1: u8,
         chk2: u8,
         data: RamReadData,
@@ -304,7 +650,7 @@ enum ReaderState {
     Error {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
         chk1: u8,
         chk2: u8,
         payload: AssociatedData,
@@ -312,55 +658,106 @@ enum ReaderState {
     Detail {
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
         chk1: u8,
         chk2: u8,
         payload: AssociatedData,
-        status_error: StatusError,
+        status_error: u8,
     },
 }
 
 impl ReaderState {
-    fn step(&mut self, byte: u8) -> Option<RawACKPacket> {
+    // Return true if a header was read and the rest of the frame is expected
+    fn in_frame(&self) -> bool {
+        !matches!(*self, ReaderState::H1 | ReaderState::H2)
+    }
+
+    // The name of the state, which is the next byte expected
+    fn name(&self) -> &'static str {
+        use reader::ReaderState::*;
+        match *self {
+            H1 => "H1",
+            H2 => "H2",
+            Psize => "Psize",
+            Pid { .. } => "Pid",
+            Cmd { .. } => "Cmd",
+            Checksum1 { .. } => "Checksum1",
+            Checksum2 { .. } => "Checksum2",
+            DataAddr { .. } => "DataAddr",
+            DataLenFailed { .. } => "DataLenFailed",
+            #[cfg(feature = "eep")]
+            DataLenEEP { .. } => "DataLenEEP",
+            #[cfg(feature = "eep")]
+            Data1EEP { .. } => "Data1EEP",
+            #[cfg(feature = "eep")]
+            Data2EEP { .. } => "Data2EEP",
+            DataLenRAM { .. } => "DataLenRAM",
+            Data1RAM { .. } => "Data1RAM",
+            Data2RAM { .. } => "Data2RAM",
+            Error { .. } => "Error",
+            Detail { .. } => "Detail",
+        }
+    }
+
+    // The ID of the servomotor sending the frame, once it has been read
+    fn pid(&self) -> Option<u8> {
+        use reader::ReaderState::*;
+        match *self {
+            H1 | H2 | Psize | Pid { .. } => None,
+            Cmd { pid, .. }
+            | Checksum1 { pid, .. }
+            | Checksum2 { pid, .. }
+            | DataAddr { pid, .. }
+            | DataLenFailed { pid, .. }
+            | DataLenRAM { pid, .. }
+            | Data1RAM { pid, .. }
+            | Data2RAM { pid, .. }
+            | Error { pid, .. }
+            | Detail { pid, .. } => Some(pid),
+            #[cfg(feature = "eep")]
+            DataLenEEP { pid, .. } | Data1EEP { pid, .. } | Data2EEP { pid, .. } => Some(pid),
+        }
+    }
+}
+
+/// Only the name of the state and the ID of the servomotor are shown, to keep logs short.
+impl fmt::Debug for ReaderState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.pid() {
+            Some(pid) => write!(f, "{}(pid: {:#04X})", self.name(), pid),
+            None => f.write_str(self.name()),
+        }
+    }
+}
+
+impl ReaderState {
+    fn step(&mut self, byte: u8) -> Option<Frame> {
+        #[cfg(feature = "eep")]
         use addr::EEPReadData;
         use addr::RamReadData;
+        #[cfg(feature = "eep")]
         use addr::ReadableEEPAddr;
         use addr::ReadableRamAddr;
+        #[cfg(feature = "eep")]
         use addr::WritableEEPAddr::*;
         use addr::WritableRamAddr::*;
+        use frame::AckCommand::*;
         use reader::AssociatedData::*;
-        use reader::InternalCommand::*;
         use reader::ReaderState::*;
         use reader::StatusDetail::*;
         use reader::StatusError::*;
         use try_from::TryFrom;
 
-        let mut result: Option<RawACKPacket> = None;
+        let mut result: Option<Frame> = None;
         match *self {
             H1 => *self = H2,
             H2 => *self = Psize,
             Psize => *self = Pid { size: byte },
             Pid { size } => *self = Cmd { size, pid: byte },
             Cmd { size, pid } => {
-                let mut command: Option<InternalCommand> = None;
-                match byte {
-                    0x41 => command = Some(EEPWrite),
-                    0x42 => command = Some(EEPRead),
-                    0x43 => command = Some(RamWrite),
-                    0x44 => command = Some(RamRead),
-                    0x45 => command = Some(IJog),
-                    0x46 => command = Some(SJog),
-                    0x47 => command = Some(Stat),
-                    0x48 => command = Some(Rollback),
-                    0x49 => command = Some(Reboot),
-                    _ => *self = H1,
-                }
-                if let Some(command) = command {
-                    *self = Checksum1 {
-                        size,
-                        pid,
-                        cmd: command,
-                    }
+                *self = match AckCommand::try_from(byte) {
+                    Ok(cmd) => Checksum1 { size, pid, cmd },
+                    Err(_) => H1,
                 }
             }
             Checksum1 { size, pid, cmd } => {
@@ -376,30 +773,82 @@ impl ReaderState {
                 pid,
                 cmd,
                 chk1,
+            } => {
+                let read = cmd == EEPRead || cmd == RamRead;
+                let payload = if read { Failed(None) } else { Nothing };
+                match usize::from(size) {
+                    // The minimal ACK, without the status registers
+                    MIN_FRAME_LEN => {
+                        result = Some(self.make_packet(size, pid, cmd, chk1, byte, payload, 0, 0));
+                        *self = H1;
+                    }
+                    // Only the status registers, even for a read
+                    MIN_ACK_LEN => {
+                        *self = Error {
+                            size,
+                            pid,
+                            cmd,
+                            chk1,
+                            chk2: byte,
+                            payload,
+                        }
+                    }
+                    _ if read => {
+                        *self = DataAddr {
+                            size,
+                            pid,
+                            cmd,
+                            chk1,
+                            chk2: byte,
+                        }
+                    }
+                    _ => {
+                        *self = Error {
+                            size,
+                            pid,
+                            cmd,
+                            chk1,
+                            chk2: byte,
+                            payload: Nothing,
+                        }
+                    }
+                }
             }
-                if (cmd == EEPRead || cmd == RamRead) =>
-            {
-                *self = DataAddr {
+            // A read of no byte : the address is sent back, but not necessarily a register
+            DataAddr {
+                size,
+                pid,
+                cmd,
+                chk1,
+                chk2,
+            } if usize::from(size) == MIN_ACK_LEN + MEM_OVERHEAD => {
+                *self = DataLenFailed {
                     size,
                     pid,
                     cmd,
                     chk1,
-                    chk2: byte,
+                    chk2,
+                    addr: byte,
                 }
             }
-            Checksum2 {
+            DataLenFailed {
                 size,
                 pid,
                 cmd,
                 chk1,
+                chk2,
+                addr,
             } => {
-                *self = Error {
-                    size,
-                    pid,
-                    cmd,
-                    chk1,
-                    chk2: byte,
-                    payload: Nothing,
+                *self = match byte {
+                    0 => Error {
+                        size,
+                        pid,
+                        cmd,
+                        chk1,
+                        chk2,
+                        payload: Failed(Some(addr)),
+                    },
+                    _ => H1,
                 }
             }
             DataAddr {
@@ -409,6 +858,7 @@ impl ReaderState {
                 chk1,
                 chk2,
             } => match cmd {
+                #[cfg(feature = "eep")]
                 EEPRead => {
                     *self = match TryFrom::try_from(byte) {
                         Ok(data_addr) => DataLenEEP {
@@ -443,8 +893,11 @@ impl ReaderState {
                         Err(_) => H1,
                     }
                 }
-                _ => unreachable!(),
+                // Only the reads have data, and those of the EEP memory are dropped without the
+                // `eep` feature
+                _ => *self = H1,
             },
+            #[cfg(feature = "eep")]
             DataLenEEP {
                 size,
                 pid,
@@ -489,6 +942,7 @@ impl ReaderState {
                     data: new_data,
                 }
             }
+            #[cfg(feature = "eep")]
             Data1EEP {
                 size,
                 pid,
@@ -506,7 +960,7 @@ impl ReaderState {
                     *self = Data2EEP {
                         size,
                         pid,
-                        cmd: InternalCommand::EEPRead,
+                        cmd: AckCommand::EEPRead,
                         chk1,
                         chk2,
                         data: new_data,
@@ -515,13 +969,14 @@ impl ReaderState {
                     *self = Error {
                         size,
                         pid,
-                        cmd: InternalCommand::EEPRead,
+                        cmd: AckCommand::EEPRead,
                         chk1,
                         chk2,
                         payload: AssociatedData::EEP(new_data),
                     }
                 }
             }
+            #[cfg(feature = "eep")]
             Data2EEP {
                 size,
                 pid,
@@ -561,7 +1016,7 @@ impl ReaderState {
                     *self = Data2RAM {
                         size,
                         pid,
-                        cmd: InternalCommand::RamRead,
+                        cmd: AckCommand::RamRead,
                         chk1,
                         chk2,
                         data: new_data,
@@ -570,7 +1025,7 @@ impl ReaderState {
                     *self = Error {
                         size,
                         pid,
-                        cmd: InternalCommand::RamRead,
+                        cmd: AckCommand::RamRead,
                         chk1,
                         chk2,
                         payload: AssociatedData::Ram(new_data),
@@ -607,30 +1062,15 @@ impl ReaderState {
                 chk2,
                 payload,
             } => {
-                let status_error = match byte {
-                    0x00 => Some(NoError),
-                    0x01 => Some(ExceedInputVoltageLimit),
-                    0x02 => Some(ExceedAllowedPOTLimit),
-                    0x04 => Some(ExceedTemperatureLimit),
-                    0x08 => Some(InvalidPacket),
-                    0x10 => Some(OverloadDetected),
-                    0x20 => Some(DriverFaultDetected),
-                    0x40 => Some(EEPREGDistorded),
-                    _ => None,
+                *self = Detail {
+                    size,
+                    pid,
+                    cmd,
+                    chk1,
+                    chk2,
+                    payload,
+                    status_error: byte,
                 };
-                if let Some(valid_error) = status_error {
-                    *self = Detail {
-                        size,
-                        pid,
-                        cmd,
-                        chk1,
-                        chk2,
-                        payload,
-                        status_error: valid_error,
-                    };
-                } else {
-                    *self = H1;
-                }
             }
             Detail {
                 size,
@@ -641,48 +1081,30 @@ impl ReaderState {
                 payload,
                 status_error,
             } => {
-                let mut status_detail = None;
-                match byte {
-                    0x00 => status_detail = Some(NoDetail),
-                    0x01 => status_detail = Some(MovingFlag),
-                    0x02 => status_detail = Some(ImpositionFlag),
-                    0x04 => status_detail = Some(ChecksumError),
-                    0x08 => status_detail = Some(UnknownCommand),
-                    0x10 => status_detail = Some(ExceedREGRange),
-                    0x20 => status_detail = Some(GarbageDetected),
-                    0x40 => status_detail = Some(MotorOnFlag),
-                    _ => (),
-                };
-                if let Some(status_detail) = status_detail {
-                    result = self.make_packet(
-                        size,
-                        pid,
-                        cmd,
-                        chk1,
-                        chk2,
-                        payload,
-                        status_error,
-                        status_detail,
-                    );
-                }
+                result =
+                    Some(self.make_packet(size, pid, cmd, chk1, chk2, payload, status_error, byte));
                 *self = H1;
             }
         };
         result
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn make_packet(
         &mut self,
         size: u8,
         pid: u8,
-        cmd: InternalCommand,
+        cmd: AckCommand,
         chk1: u8,
         chk2: u8,
         payload: AssociatedData,
-        status_error: StatusError,
-        status_detail: StatusDetail,
-    ) -> Option<RawACKPacket> {
-        let cmd = cmd.inject_payload(payload);
+        status_error: u8,
+        status_detail: u8,
+    ) -> Frame {
+        let cmd = match cmd.inject_payload(payload) {
+            Some(cmd) => cmd,
+            None => return Frame::Corrupted { pid },
+        };
         let packet = RawACKPacket {
             psize: size,
             pid,
@@ -693,20 +1115,194 @@ impl ReaderState {
             detail: status_detail,
         };
         if packet.is_valid() {
-            Some(packet)
+            Frame::Valid(packet)
         } else {
-            None
+            Frame::Corrupted { pid }
         }
     }
 }
 
+/// A handler called by [`ACKReader::parse_with`](struct.ACKReader.html#method.parse_with) for
+/// every packet decoded, so that packets can be processed as soon as they are received (in an
+/// interrupt handler for example) instead of being buffered.
+///
+/// Every method does nothing by default, so that only the relevant ones need to be implemented.
+/// `on_packet` dispatches the packet to the other methods according to its command.
+pub trait ACKHandler {
+    /// Called for every packet decoded.
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        match packet.cmd {
+            Command::RamRead { data } => self.on_ram_read(packet.pid, data),
+            Command::EEPRead { data } => self.on_eep_read(packet.pid, data),
+            Command::Stat => self.on_stat(packet.pid, packet.error, packet.detail),
+            _ => self.on_other(packet),
+        }
+    }
+
+    /// Called when a servomotor sends some data read from its RAM.
+    fn on_ram_read(&mut self, _pid: u8, _data: RamReadData) {}
+
+    /// Called when a servomotor sends some data read from its EEP, with the `eep` feature.
+    fn on_eep_read(&mut self, _pid: u8, _data: EEPReadData) {}
+
+    /// Called when a servomotor answers to a **STAT** request.
+    fn on_stat(&mut self, _pid: u8, _error: StatusError, _detail: StatusDetail) {}
+
+    /// Called for every other packet (writes, jogs, reboot, rollback and failed reads).
+    fn on_other(&mut self, _packet: &ACKPacket) {}
+
+    /// Called when a frame apparently sent by the servomotor `pid` is dropped because its
+    /// checksums are invalid. As the frame is corrupted, `pid` itself may be wrong.
+    fn on_checksum_error(&mut self, _pid: u8) {}
+}
+
+/// A closure can be used as a handler, it is then called for every packet.
+impl<F: FnMut(&ACKPacket)> ACKHandler for F {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        self(packet)
+    }
+}
+
+impl FrameDecoder {
+    /// Creates a new state machine to decode incoming Herkulex messages
+    pub fn new() -> FrameDecoder {
+        FrameDecoder::default()
+    }
+
+    /// Return the name of the state of the decoder, which is the next byte of the frame it
+    /// expects ("H1" for the first header byte, "Checksum1", "Detail" for the last byte ...).
+    /// A decoder stuck in the same state shows where the frames stop.
+    pub fn state_name(&self) -> &'static str {
+        self.state.name()
+    }
+
+    /// Only hand the packets of the IDs accepted by `filter` to the handlers, or every packet if
+    /// it is `None`. The packets of the other IDs are dropped and counted in the stats.
+    pub fn set_filter(&mut self, filter: Option<IdFilter>) {
+        self.filter = filter;
+    }
+
+    /// Return the filter of the IDs accepted, if any.
+    pub fn filter(&self) -> Option<IdFilter> {
+        self.filter
+    }
+
+    // Feed a byte to the state machine, counting the complete and abandoned frames and dropping
+    // the packets rejected by the filter. A frame with invalid checksums yields the ID it was
+    // sent from as an error.
+    fn step(&mut self, byte: u8) -> Option<Result<ACKPacket, u8>> {
+        let in_frame = self.state.in_frame();
+        match self.state.step(byte) {
+            Some(Frame::Valid(trame)) => {
+                if self
+                    .filter
+                    .is_some_and(|filter| !filter.contains(trame.pid))
+                {
+                    self.stats.filtered += 1;
+                    return None;
+                }
+                self.stats.packets += 1;
+                let packet = ACKPacket::from(trame);
+                log_trace!("decoded packet {:?}", packet);
+                Some(Ok(packet))
+            }
+            Some(Frame::Corrupted { pid }) => {
+                self.stats.checksum_errors += 1;
+                log_debug!("dropped a frame of 0x{:02X} with invalid checksums", pid);
+                Some(Err(pid))
+            }
+            None => {
+                if in_frame && !self.state.in_frame() {
+                    self.stats.abandoned += 1;
+                }
+                None
+            }
+        }
+    }
+
+    /// Parse a buffer of bytes read at the tick `now`, like
+    /// [`parse_with`](#method.parse_with), and measure the gap since the previous buffer if a
+    /// frame was left unfinished.
+    pub fn parse_at_with<H: ACKHandler + ?Sized>(&mut self, buf: &[u8], now: u32, handler: &mut H) {
+        if !buf.is_empty() {
+            self.measure_gap(now);
+            self.parse_with(buf, handler)
+        }
+    }
+
+    // Record the gap since the previous buffer if it ended in the middle of a frame
+    fn measure_gap(&mut self, now: u32) {
+        if let (Some(last), true) = (self.last_parse, self.state.in_frame()) {
+            let gap = now.wrapping_sub(last);
+            self.gaps.count += 1;
+            self.gaps.max = self.gaps.max.max(gap);
+            self.gaps.total += u64::from(gap);
+        }
+        self.last_parse = Some(now);
+    }
+
+    /// Return the gaps measured in the middle of the frames since the creation of the decoder or
+    /// the last call to [`reset_stats`](#method.reset_stats).
+    pub fn gap_stats(&self) -> GapStats {
+        self.gaps
+    }
+
+    /// Parse a buffer of bytes, handing sucessfully decoded messages to `handler`.
+    pub fn parse_with<H: ACKHandler + ?Sized>(&mut self, buf: &[u8], handler: &mut H) {
+        self.parse_iter_with(buf.iter().cloned(), handler)
+    }
+
+    /// Parse bytes coming from any iterator, handing sucessfully decoded messages to `handler`.
+    pub fn parse_iter_with<I, H>(&mut self, bytes: I, handler: &mut H)
+    where
+        I: IntoIterator<Item = u8>,
+        H: ACKHandler + ?Sized,
+    {
+        for byte in bytes {
+            match self.step(byte) {
+                Some(Ok(packet)) => handler.on_packet(&packet),
+                Some(Err(pid)) => handler.on_checksum_error(pid),
+                None => (),
+            }
+        }
+    }
+
+    /// Return the counters of the frames parsed since the creation of the decoder or the last
+    /// call to [`reset_stats`](#method.reset_stats).
+    pub fn stats(&self) -> ReaderStats {
+        self.stats
+    }
+
+    /// Reset the counters of the frames parsed and the gaps measured.
+    pub fn reset_stats(&mut self) {
+        self.stats = ReaderStats::default();
+        self.gaps = GapStats::default();
+    }
+}
+
+#[cfg(feature = "runtime")]
 impl ACKReader {
     /// Creates a new state machine to read incoming Herkulex messages
     pub fn new() -> ACKReader {
-        ACKReader {
-            state: ReaderState::H1,
-            buffer: ArrayVec::new(),
-        }
+        ACKReader::default()
+    }
+
+    /// Return the name of the state of the reader, which is the next byte of the frame it
+    /// expects ("H1" for the first header byte, "Checksum1", "Detail" for the last byte ...).
+    /// A reader stuck in the same state shows where the frames stop.
+    pub fn state_name(&self) -> &'static str {
+        self.decoder.state_name()
+    }
+
+    /// Only keep the packets of the IDs accepted by `filter`, or every packet if it is `None`.
+    /// See [`FrameDecoder::set_filter`](struct.FrameDecoder.html#method.set_filter).
+    pub fn set_filter(&mut self, filter: Option<IdFilter>) {
+        self.decoder.set_filter(filter)
+    }
+
+    /// Return the filter of the IDs accepted, if any.
+    pub fn filter(&self) -> Option<IdFilter> {
+        self.decoder.filter()
     }
 
     /// Return the oldest [ACKPacket] read
@@ -715,35 +1311,146 @@ impl ACKReader {
     }
 
     /// Get the number of available messages in the internal buffer
-    pub fn available_messages(&mut self) -> usize {
+    pub fn available_messages(&self) -> usize {
         self.buffer.len()
     }
 
+    /// Return the number of messages the internal buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Return true if the internal buffer is full : some messages must be popped before more
+    /// bytes are parsed, the packets decoded in the meantime are dropped and counted as
+    /// `overflowed` in the [`stats`](#method.stats).
+    pub fn is_full(&self) -> bool {
+        self.buffer.is_full()
+    }
+
+    /// Drop every message of the internal buffer, keeping the frame being decoded and the
+    /// counters.
+    pub fn clear(&mut self) {
+        self.buffer.clear()
+    }
+
     /// Parse a buffer of bytes, adding sucessfully decoded  messages to the internal buffer
     pub fn parse(&mut self, buf: &[u8]) {
-        for byte in buf {
-            if let Some(trame) = self.state.step(*byte) {
-                self.buffer.push(ACKPacket::from(trame));
+        self.parse_iter(buf.iter().cloned())
+    }
+
+    /// Parse the content of a circular buffer, split in two slices (`b` follows `a`), adding
+    /// sucessfully decoded messages to the internal buffer.
+    pub fn parse_split(&mut self, a: &[u8], b: &[u8]) {
+        self.parse_iter(a.iter().chain(b).cloned())
+    }
+
+    /// Parse bytes coming from any iterator, adding sucessfully decoded messages to the internal
+    /// buffer.
+    pub fn parse_iter<I: IntoIterator<Item = u8>>(&mut self, bytes: I) {
+        for byte in bytes {
+            if let Some(Ok(packet)) = self.decoder.step(byte) {
+                if self.buffer.try_push(packet).is_err() {
+                    self.decoder.stats.overflowed += 1;
+                }
             }
         }
     }
+
+    /// Parse a buffer of bytes like [`parse`](#method.parse), but stop right after the
+    /// `max_packets`-th decoded packet, and return the number of bytes consumed. The remaining
+    /// bytes, `&buf[consumed..]`, must be parsed in a later call, which bounds the time spent in
+    /// each call of a real-time loop.
+    ///
+    /// The packets dropped because of the filter do not count, the packets dropped because the
+    /// internal buffer is full do.
+    pub fn parse_bounded(&mut self, buf: &[u8], max_packets: usize) -> usize {
+        let mut decoded = 0;
+        for (i, &byte) in buf.iter().enumerate() {
+            if decoded == max_packets {
+                return i;
+            }
+            if let Some(Ok(packet)) = self.decoder.step(byte) {
+                decoded += 1;
+                if self.buffer.try_push(packet).is_err() {
+                    self.decoder.stats.overflowed += 1;
+                }
+            }
+        }
+        buf.len()
+    }
+
+    /// Parse a buffer of bytes, handing sucessfully decoded messages to `handler` instead of
+    /// adding them to the internal buffer.
+    pub fn parse_with<H: ACKHandler + ?Sized>(&mut self, buf: &[u8], handler: &mut H) {
+        self.decoder.parse_with(buf, handler)
+    }
+
+    /// Parse a buffer of bytes read at the tick `now`, adding sucessfully decoded messages to
+    /// the internal buffer. See
+    /// [`FrameDecoder::parse_at_with`](struct.FrameDecoder.html#method.parse_at_with).
+    pub fn parse_at(&mut self, buf: &[u8], now: u32) {
+        if !buf.is_empty() {
+            self.decoder.measure_gap(now);
+            self.parse(buf)
+        }
+    }
+
+    /// Return the counters of the frames parsed since the creation of the reader or the last
+    /// call to [`reset_stats`](#method.reset_stats).
+    pub fn stats(&self) -> ReaderStats {
+        self.decoder.stats()
+    }
+
+    /// Return the gaps measured in the middle of the frames.
+    pub fn gap_stats(&self) -> GapStats {
+        self.decoder.gap_stats()
+    }
+
+    /// Reset the counters of the frames parsed and the gaps measured.
+    pub fn reset_stats(&mut self) {
+        self.decoder.reset_stats()
+    }
+}
+
+/// Decode the single ACK of `frame`, so that the tests build their packets from real frames.
+#[cfg(test)]
+pub(crate) fn decode_ack(frame: &[u8]) -> ACKPacket {
+    let mut packet = None;
+    FrameDecoder::new().parse_with(frame, &mut |ack: &ACKPacket| packet = Some(*ack));
+    packet.expect("an invalid ACK frame")
 }
 
+/// Decode the answer of the servomotor `pid` to a **STAT**, reporting `error`.
 #[cfg(test)]
+pub(crate) fn stat_ack(pid: u8, error: StatusError) -> ACKPacket {
+    decode_ack(
+        &AckFrameBuilder::stat(pid)
+            .status(error.bits(), 0x00)
+            .build(),
+    )
+}
+
+#[cfg(all(test, feature = "runtime"))]
 mod test {
     use addr::*;
+    use config::MAX_PERIOD_TICKS;
+    #[cfg(feature = "eep")]
+    use frame::AckCommand;
     use reader::{
-        ACKPacket, ACKReader, AssociatedData, Command, RawACKPacket, StatusDetail, StatusError,
+        ACKHandler, ACKPacket, ACKReader, AssociatedData, Command, GapStats, RawACKPacket,
+        ReaderStats, StatusDetail, StatusError,
     };
+    use test_support::AckFrameBuilder;
 
-    //#[test]
+    #[cfg(feature = "eep")]
+    #[test]
     fn test_eepread() {
         let mut reader = ACKReader::new();
 
         // Test de EEPRead
         // [H1][H2][psize][pid][cmd][chk1][chk2][data_addr][data_len][data][data][status_error][status_detail]
         let packet_eepread = [
-            0xFF, 0xFF, 0x0F, 0xFD, 0x42, 0x14, 0xEA, 0x1E, 0x02, 0xB8, 0x01, 0x08, 0x20,
+            0xFF, 0xFF, 0x0D, 0xFD, 0x42, 0x3E, 0xC0, 0x1E, 0x02, 0xB8, 0x01, 0x08, 0x20,
         ];
 
         reader.parse(&packet_eepread);
@@ -761,18 +1468,20 @@ mod test {
                 cmd: Command::EEPRead { data: data_eepread },
                 error: StatusError::InvalidPacket,
                 detail: StatusDetail::GarbageDetected,
+                error_raw: 0x08,
+                detail_raw: 0x20,
             }
         );
     }
 
-    //#[test]
+    #[test]
     fn test_ramread() {
         let mut reader = ACKReader::new();
 
         // Test de RamRead
         // [H1][H2][psize][pid][cmd][chk1][chk2][data_addr][data_len][data][status_error][status_detail]
         let packet_ramread = [
-            0xFF, 0xFF, 0x0C, 0xFD, 0x44, 0xA0, 0x5E, 0x14, 0x01, 0x01, 0x10, 0x40,
+            0xFF, 0xFF, 0x0C, 0xFD, 0x44, 0xF0, 0x0E, 0x14, 0x01, 0x01, 0x10, 0x40,
         ];
 
         reader.parse(&packet_ramread);
@@ -790,19 +1499,37 @@ mod test {
                 cmd: Command::RamRead { data: data_ramread },
                 error: StatusError::OverloadDetected,
                 detail: StatusDetail::MotorOnFlag,
+                error_raw: 0x10,
+                detail_raw: 0x40,
             }
         );
     }
 
+    #[test]
+    fn test_checksum_covers_status() {
+        let mut reader = ACKReader::new();
+        // The checksums of the RAM read above, computed without the status registers
+        reader.parse(&[
+            0xFF, 0xFF, 0x0C, 0xFD, 0x44, 0xA0, 0x5E, 0x14, 0x01, 0x01, 0x10, 0x40,
+        ]);
+        assert_eq!(reader.stats().checksum_errors, 1);
+        // A size which is not the length of the frame
+        reader.parse(
+            &AckFrameBuilder::ram_read(0xFD, 0x14, &[0x01])
+                .psize(0x0D)
+                .build(),
+        );
+        assert_eq!(reader.stats().checksum_errors, 2);
+        assert_eq!(reader.available_messages(), 0);
+    }
+
     #[test]
     fn test_sjog() {
         let mut reader = ACKReader::new();
 
         // Test de SJOG
         // [H1][H2][psize][pid][cmd][chk1][chk2][status_error][status_detail]
-        let packet_sjog = [
-            0xFF, 0xFF, 0x09, 0xFD, 0x46, /*0xF2*/ 0xB2, /*0x0C*/ 0x4C, 0x08, 0x08,
-        ];
+        let packet_sjog = [0xFF, 0xFF, 0x09, 0xFD, 0x46, 0xB2, 0x4C, 0x08, 0x08];
 
         reader.parse(&packet_sjog);
 
@@ -813,7 +1540,329 @@ mod test {
                 cmd: Command::SJog,
                 error: StatusError::InvalidPacket,
                 detail: StatusDetail::UnknownCommand,
+                error_raw: 0x08,
+                detail_raw: 0x08,
+            }
+        );
+    }
+
+    #[test]
+    fn test_handler() {
+        #[derive(Default)]
+        struct Handler {
+            stats: u8,
+            reads: u8,
+            others: u8,
+        }
+
+        impl ACKHandler for Handler {
+            fn on_ram_read(&mut self, pid: u8, data: RamReadData) {
+                assert_eq!(pid, 0xFD);
+                assert_eq!(data.addr, ReadableRamAddr::LEDControl);
+                self.reads += 1;
+            }
+
+            fn on_stat(&mut self, _pid: u8, _error: StatusError, detail: StatusDetail) {
+                assert_eq!(detail, StatusDetail::MovingFlag);
+                self.stats += 1;
+            }
+
+            fn on_other(&mut self, packet: &ACKPacket) {
+                assert_eq!(packet.cmd, Command::SJog);
+                self.others += 1;
+            }
+        }
+
+        let mut reader = ACKReader::new();
+        let mut handler = Handler::default();
+        reader.parse_with(
+            &[
+                0xFF, 0xFF, 0x09, 0xFD, 0x46, 0xB2, 0x4C, 0x08, 0x08, 0xFF, 0xFF, 0x09, 0xFD, 0x47,
+                0xB2, 0x4C, 0x00, 0x01, 0xFF, 0xFF, 0x0C, 0xFD, 0x44, 0x80, 0x7E, 0x35, 0x01, 0x01,
+                0x00, 0x00,
+            ],
+            &mut handler,
+        );
+        assert_eq!((handler.stats, handler.reads, handler.others), (1, 1, 1));
+        assert_eq!(reader.available_messages(), 0);
+    }
+
+    #[test]
+    fn test_stats() {
+        struct Corrupted(Option<u8>);
+
+        impl ACKHandler for Corrupted {
+            fn on_checksum_error(&mut self, pid: u8) {
+                self.0 = Some(pid);
+            }
+        }
+
+        let mut reader = ACKReader::new();
+        let mut handler = Corrupted(None);
+        reader.parse(&[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01]);
+        reader.parse_with(
+            &[0xFF, 0xFF, 0x09, 0x40, 0x47, 0xB2, 0x4C, 0x00, 0x01],
+            &mut handler,
+        );
+        assert_eq!(handler.0, Some(0x40));
+        assert_eq!(
+            reader.stats(),
+            ReaderStats {
+                packets: 1,
+                checksum_errors: 1,
+                filtered: 0,
+                abandoned: 0,
+                overflowed: 0,
+            }
+        );
+        reader.reset_stats();
+        assert_eq!(reader.stats(), ReaderStats::default());
+    }
+
+    #[test]
+    fn test_gap_stats() {
+        let mut reader = ACKReader::new();
+        assert_eq!(reader.gap_stats().recommended_garbage_period(1_000), None);
+        reader.parse_at(&[0xFF, 0xFF, 0x09, 0xFD], 100);
+        reader.parse_at(&[0x47, 0xB2, 0x4C], 130);
+        reader.parse_at(&[0x00, 0x01], 140);
+        reader.parse_at(&[0xFF, 0xFF, 0x09, 0xFD], 1000);
+        reader.parse_at(&[0x47, 0xB2, 0x4C, 0x00, 0x01], 1300);
+        assert_eq!(reader.available_messages(), 2);
+        let gaps = reader.gap_stats();
+        assert_eq!(
+            gaps,
+            GapStats {
+                count: 3,
+                max: 300,
+                total: 340,
+            }
+        );
+        assert_eq!(gaps.mean(), Some(113));
+        // 600 ms with a millisecond clock
+        assert_eq!(gaps.recommended_garbage_period(1_000), Some(54));
+        assert_eq!(gaps.recommended_garbage_period(1_000_000), Some(1));
+        assert_eq!(gaps.recommended_garbage_period(10), Some(MAX_PERIOD_TICKS));
+
+        reader.parse(&[0xFF, 0xFF, 0x09, 0xFD, 0xFF, 0xFF]);
+        assert_eq!(reader.stats().abandoned, 1);
+        reader.reset_stats();
+        assert_eq!(reader.gap_stats(), GapStats::default());
+    }
+
+    #[cfg(feature = "eep")]
+    #[test]
+    fn test_short_acks() {
+        let mut reader = ACKReader::new();
+        // No status registers at all
+        let mut minimal = AckFrameBuilder::stat(0x40).psize(7).build();
+        minimal.truncate(7);
+        reader.parse(&minimal);
+        // A read answered with the status registers only
+        reader.parse(&AckFrameBuilder::new(0x40, 0x04).status(0x02, 0x00).build());
+        // A read of no byte at an address which is not a register
+        reader.parse(&AckFrameBuilder::eep_read(0x40, 0x99, &[]).build());
+        reader.parse(
+            &AckFrameBuilder::ram_read(0x40, 0x99, &[0])
+                .data_len(0)
+                .build(),
+        );
+
+        let mut packets = std::vec::Vec::new();
+        while let Some(packet) = reader.pop_ack_packet() {
+            packets.push((packet.cmd, packet.error));
+        }
+        assert_eq!(
+            packets,
+            [
+                (
+                    Command::ReadFailed {
+                        cmd: AckCommand::EEPRead,
+                        addr: Some(0x99),
+                    },
+                    StatusError::NoError
+                ),
+                (
+                    Command::ReadFailed {
+                        cmd: AckCommand::RamRead,
+                        addr: None,
+                    },
+                    StatusError::ExceedAllowedPOTLimit
+                ),
+                (Command::Stat, StatusError::NoError),
+            ]
+        );
+        assert_eq!(reader.stats().abandoned, 1);
+        assert_eq!(u8::from(packets[0].0), 0x42);
+    }
+
+    #[test]
+    fn test_buffer() {
+        let mut reader = ACKReader::new();
+        let stat = AckFrameBuilder::stat(0xFD).build();
+        for _ in 0..reader.capacity() {
+            assert!(!reader.is_full());
+            reader.parse(&stat);
+        }
+        assert!(reader.is_full());
+        let reader_ref = &reader;
+        assert_eq!(reader_ref.available_messages(), reader_ref.capacity());
+        reader.parse(&stat);
+        assert_eq!(reader.available_messages(), reader.capacity());
+        assert_eq!(reader.stats().overflowed, 1);
+        reader.parse(&stat[..4]);
+        reader.clear();
+        assert_eq!(reader.available_messages(), 0);
+        reader.parse(&stat[4..]);
+        assert_eq!(reader.available_messages(), 1);
+    }
+
+    #[test]
+    fn test_state_name() {
+        let mut reader = ACKReader::new();
+        assert_eq!(reader.state_name(), "H1");
+        reader.parse(&[0xFF, 0xFF, 0x0D, 0xFD, 0x44, 0x8E, 0x70]);
+        assert_eq!(reader.state_name(), "DataAddr");
+        assert_eq!(
+            format!("{:?}", reader),
+            "ACKReader { state: DataAddr(pid: 0xFD), buffered: 0, \
+             stats: ReaderStats { packets: 0, checksum_errors: 0, filtered: 0, abandoned: 0, \
+             overflowed: 0 } }"
+        );
+    }
+
+    #[test]
+    fn test_parse_split() {
+        let frame = [
+            0xFF, 0xFF, 0x0D, 0xFD, 0x44, 0x8E, 0x70, 0x3A, 0x02, 0x00, 0x02, 0x00, 0x01,
+        ];
+        for split in 0..frame.len() {
+            let mut reader = ACKReader::new();
+            let (a, b) = frame.split_at(split);
+            reader.parse_split(a, b);
+            assert_eq!(reader.available_messages(), 1);
+        }
+        let mut reader = ACKReader::new();
+        reader.parse_iter(frame.iter().cloned().chain(frame.iter().cloned()));
+        assert_eq!(reader.available_messages(), 2);
+    }
+
+    #[test]
+    fn test_parse_bounded() {
+        let stat = AckFrameBuilder::stat(0xFD).build();
+        let mut buf = [0u8; 30];
+        buf[..9].copy_from_slice(&stat);
+        buf[9..18].copy_from_slice(&stat);
+        buf[18..27].copy_from_slice(&stat);
+        buf[27..].copy_from_slice(&stat[..3]);
+
+        let mut reader = ACKReader::new();
+        assert_eq!(reader.parse_bounded(&buf, 0), 0);
+        assert_eq!(reader.parse_bounded(&buf, 2), 18);
+        assert_eq!(reader.available_messages(), 2);
+        assert_eq!(reader.parse_bounded(&buf[18..], 2), 12);
+        assert_eq!(reader.available_messages(), 3);
+        assert_eq!(reader.state_name(), "Pid");
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_logs() {
+        use logging::capture;
+
+        let stat = AckFrameBuilder::stat(0xFD).build();
+        let corrupted = AckFrameBuilder::stat(0x40).corrupt_checksum().build();
+        capture::take();
+        let mut reader = ACKReader::new();
+        reader.parse(&stat);
+        reader.parse_split(&corrupted[..4], &corrupted[4..]);
+        reader.parse_bounded(&stat, 1);
+        let records = capture::take();
+        assert_eq!(records.len(), 3);
+        assert!(records[0].starts_with("TRACE decoded packet ACKPacket { pid: 253,"));
+        assert_eq!(
+            records[1],
+            "DEBUG dropped a frame of 0x40 with invalid checksums"
+        );
+        assert_eq!(records[2], records[0]);
+    }
+
+    #[test]
+    fn test_frame_decoder() {
+        use reader::FrameDecoder;
+
+        let mut decoder = FrameDecoder::new();
+        let mut pids = [0u8; 2];
+        let mut count = 0;
+        let frames = [
+            0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01, 0xFF, 0xFF, 0x09, 0x01, 0x47,
+            0x4E, 0xB0, 0x00, 0x01,
+        ];
+        decoder.parse_with(&frames, &mut |packet: &ACKPacket| {
+            pids[count] = packet.pid;
+            count += 1;
+        });
+        assert_eq!(pids, [0xFD, 0x01]);
+        assert_eq!(decoder.stats().packets, 2);
+        assert_eq!(decoder.state_name(), "H1");
+    }
+
+    #[test]
+    fn test_filter() {
+        use reader::IdFilter;
+
+        let mut filter = IdFilter::from_ids(&[0x01, 0xFD]);
+        assert!(filter.contains(0x01) && filter.contains(0xFD));
+        assert!(!filter.contains(0x40));
+        filter.deny(0xFD);
+        assert!(!filter.contains(0xFD));
+
+        let frames = [
+            0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01, 0xFF, 0xFF, 0x09, 0x01, 0x47,
+            0x4E, 0xB0, 0x00, 0x01,
+        ];
+        let mut reader = ACKReader::new();
+        reader.set_filter(Some(filter));
+        assert_eq!(reader.filter(), Some(filter));
+        reader.parse(&frames);
+        assert_eq!(reader.available_messages(), 1);
+        assert_eq!(reader.pop_ack_packet().unwrap().pid, 0x01);
+        assert_eq!(
+            reader.stats(),
+            ReaderStats {
+                packets: 1,
+                checksum_errors: 0,
+                filtered: 1,
+                abandoned: 0,
+                overflowed: 0,
             }
         );
+
+        reader.set_filter(None);
+        reader.parse(&frames);
+        assert_eq!(reader.available_messages(), 2);
+    }
+
+    #[test]
+    fn test_raw_status() {
+        let mut reader = ACKReader::new();
+        reader.parse(&[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0x6A, 0x94, 0x18, 0xC1]);
+        let packet = reader.pop_ack_packet().unwrap();
+        assert_eq!(packet.error, StatusError::InvalidPacket);
+        assert_eq!(packet.detail, StatusDetail::MovingFlag);
+        assert_eq!((packet.error_raw, packet.detail_raw), (0x18, 0xC1));
+        assert!(packet.is_fault());
+        assert!(packet.is_moving());
+
+        let packet = ACKPacket::new(
+            0xFD,
+            Command::Stat,
+            StatusError::NoError,
+            StatusDetail::MotorOnFlag,
+        );
+        assert_eq!((packet.error_raw, packet.detail_raw), (0x00, 0x40));
+        assert!(!packet.is_fault());
+        assert!(!packet.is_moving());
+        assert_eq!(StatusError::from_bits(0x80), StatusError::NoError);
     }
 }
@@ -0,0 +1,427 @@
+//! Tracking of the servomotors rebooting, whose RAM is reloaded from their EEP memory.
+//!
+//! After a **REBOOT**, every setting written to the RAM of the servomotor is lost and it does not
+//! answer until it has booted. A [`RebootGuard`](struct.RebootGuard.html) watches the messages
+//! sent : it reports the rebooted servomotors so that their cached state can be dropped, tells
+//! when they can be addressed again, and can replay a startup [script](../script/index.html)
+//! once they are.
+//!
+//! A servomotor is considered booted once `boot_time` ticks are elapsed since the reboot was sent,
+//! or as soon as it answers a **STAT**. Alternatively, [`wait_until_ready`](fn.wait_until_ready.html)
+//! busy-waits until a servomotor answers after a power-up or a reboot.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::reboot::RebootGuard;
+//! use drs_0x01::script::Op;
+//! use drs_0x01::status::StatusCache;
+//! use drs_0x01::{Servo, WritableRamAddr, BROADCAST_ID};
+//!
+//! const STARTUP: &[Op] = &[Op::Write(WritableRamAddr::TorqueControl(0x60))];
+//!
+//! let mut cache = StatusCache::new();
+//! // With a 1 kHz tick, the servomotors boot in 300 ms.
+//! let mut guard = RebootGuard::new(300).with_startup(STARTUP);
+//!
+//! let message = Servo::new(0x40).reboot();
+//! match guard.record_sent(&message, 0) {
+//!     Some(BROADCAST_ID) => cache.clear(),
+//!     Some(id) => cache.forget(id),
+//!     None => (),
+//! }
+//! assert!(!guard.is_ready(0x40, 100));
+//! assert_eq!(guard.next_message(300), Some(Servo::new(0x40).enable_torque()));
+//! assert!(guard.is_ready(0x40, 300));
+//! ```
+
+use arrayvec::ArrayVec;
+
+use builder::HerkulexMessage;
+use clock::{expired, Clock};
+use frame::RequestCommand;
+use reader::{ACKHandler, ACKPacket, ACKReader, Command, StatusError};
+use script::{Op, ScriptPlayer};
+use servo::{Servo, BROADCAST_ID};
+use transport::{bytes_read, Transport};
+
+/// The maximum number of servomotors a [`RebootGuard`](struct.RebootGuard.html) can follow at
+/// once. When it is reached, the oldest reboot is forgotten.
+pub const MAX_REBOOTING: usize = 8;
+
+/// The error returned by [`wait_until_ready`](fn.wait_until_ready.html).
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReadyError<E> {
+    /// The transport failed.
+    Transport(E),
+    /// The servomotor did not answer before the deadline.
+    Timeout,
+    /// The servomotor answered, but kept reporting a distorted EEP memory until the deadline.
+    EEPDistorted,
+}
+
+/// Poll the servomotor `id` with **STAT** requests until it answers without the
+/// `EEPREGDistorded` error, and return its answer.
+///
+/// A request is sent every `poll_interval` ticks, and the function busy-waits on `transport`
+/// until the tick `deadline`. If `clear_errors` is true, the alarms reported by the servomotor at
+/// startup are cleared once it is ready (see
+/// [`Servo::clear_alarms`](../struct.Servo.html#method.clear_alarms)), the returned answer still
+/// holds them.
+///
+/// # Errors
+///
+/// * `Transport` if the transport failed,
+/// * `Timeout` if the servomotor did not answer before `deadline`,
+/// * `EEPDistorted` if its EEP memory is still reported distorted at `deadline`. The EEP memory
+///   must then be rewritten, see [`ServoConfig`](../config/struct.ServoConfig.html).
+pub fn wait_until_ready<T, C>(
+    transport: &mut T,
+    clock: &C,
+    id: u8,
+    deadline: u32,
+    poll_interval: u32,
+    clear_errors: bool,
+) -> Result<ACKPacket, ReadyError<T::Error>>
+where
+    T: Transport + ?Sized,
+    C: Clock + ?Sized,
+{
+    let servo = Servo::new(id);
+    let mut reader = ACKReader::new();
+    let mut buf = [0; 32];
+    let mut distorted = false;
+    let _span = span!("wait_until_ready", id);
+    let mut attempt = 0u32;
+    loop {
+        attempt = attempt.wrapping_add(1);
+        let _poll = span!("poll", id, cmd = 0x07u8, attempt);
+        let sent = clock.now();
+        if expired(deadline, sent) {
+            return Err(if distorted {
+                ReadyError::EEPDistorted
+            } else {
+                ReadyError::Timeout
+            });
+        }
+        if attempt > 1 {
+            log_debug!(
+                "0x{:02X} is not ready, polling again (attempt {})",
+                id,
+                attempt
+            );
+        }
+        transport
+            .write(&servo.stat())
+            .map_err(ReadyError::Transport)?;
+        loop {
+            let read = transport.read(&mut buf).map_err(ReadyError::Transport)?;
+            let mut answer = None;
+            reader.parse_with(bytes_read(&buf, read), &mut |packet: &ACKPacket| {
+                if packet.pid == id && packet.cmd == Command::Stat {
+                    answer = Some(*packet);
+                }
+            });
+            if let Some(packet) = answer {
+                if packet.error_raw & StatusError::EEPREGDistorded.bits() == 0 {
+                    if clear_errors && packet.error_raw != 0 {
+                        for message in &servo.clear_alarms(false) {
+                            transport.write(message).map_err(ReadyError::Transport)?;
+                        }
+                    }
+                    return Ok(packet);
+                }
+                distorted = true;
+            }
+            let now = clock.now();
+            if now.wrapping_sub(sent) >= poll_interval || expired(deadline, now) {
+                break;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Rebooting<'a> {
+    id: u8,
+    sent: u32,
+    booted: bool,
+    startup: Option<ScriptPlayer<'a>>,
+}
+
+/// This struct follows the servomotors rebooting.
+#[derive(Clone, Debug)]
+pub struct RebootGuard<'a> {
+    boot_time: u32,
+    startup: &'a [Op],
+    rebooting: ArrayVec<[Rebooting<'a>; MAX_REBOOTING]>,
+}
+
+impl<'a> RebootGuard<'a> {
+    /// Create a guard considering that the servomotors boot in `boot_time` ticks.
+    pub fn new(boot_time: u32) -> RebootGuard<'a> {
+        RebootGuard {
+            boot_time,
+            startup: &[],
+            rebooting: ArrayVec::new(),
+        }
+    }
+
+    /// Replay `ops` to every servomotor once it has booted.
+    pub fn with_startup(mut self, ops: &'a [Op]) -> RebootGuard<'a> {
+        self.startup = ops;
+        self
+    }
+
+    /// Record that `message` was sent at `now`. If it is a **REBOOT**, return the ID of the
+    /// servomotor rebooted (`BROADCAST_ID` if every servomotor reboots), whose cached state must
+    /// be dropped.
+    pub fn record_sent(&mut self, message: &[u8], now: u32) -> Option<u8> {
+        if message.len() < 5 || message[4] != u8::from(RequestCommand::Reboot) {
+            return None;
+        }
+        let id = message[3];
+        self.rebooting.retain(|entry| entry.id != id);
+        if self.rebooting.is_full() {
+            self.rebooting.remove(0);
+        }
+        let startup = if self.startup.is_empty() {
+            None
+        } else {
+            Some(ScriptPlayer::new(Servo::new(id), self.startup))
+        };
+        self.rebooting.push(Rebooting {
+            id,
+            sent: now,
+            booted: false,
+            startup,
+        });
+        Some(id)
+    }
+
+    /// Return true if the servomotor `id` can be addressed at `now` : it is not rebooting, or it
+    /// has booted and its startup script was sent.
+    pub fn is_ready(&self, id: u8, now: u32) -> bool {
+        self.rebooting
+            .iter()
+            .filter(|entry| entry.id == id || entry.id == BROADCAST_ID)
+            .all(|entry| {
+                entry.startup.is_none()
+                    && (entry.booted || now.wrapping_sub(entry.sent) >= self.boot_time)
+            })
+    }
+
+    /// Return the next message of the startup scripts due at `now`, if any. This must be called
+    /// regularly, even without startup script, to forget the servomotors which have booted.
+    pub fn next_message(&mut self, now: u32) -> Option<HerkulexMessage> {
+        let boot_time = self.boot_time;
+        let mut index = 0;
+        while index < self.rebooting.len() {
+            let entry = &mut self.rebooting[index];
+            if !entry.booted && now.wrapping_sub(entry.sent) < boot_time {
+                index += 1;
+                continue;
+            }
+            entry.booted = true;
+            let (message, finished) = match entry.startup {
+                Some(ref mut startup) => (startup.next_message(now), startup.is_finished()),
+                None => (None, true),
+            };
+            if finished {
+                self.rebooting.remove(index);
+            } else {
+                index += 1;
+            }
+            if message.is_some() {
+                return message;
+            }
+        }
+        None
+    }
+
+    /// Record that the servomotor which sent `packet` has booted if it answers a **STAT**.
+    pub fn update(&mut self, packet: &ACKPacket) {
+        if packet.cmd == Command::Stat {
+            for entry in self.rebooting.iter_mut() {
+                if entry.id == packet.pid {
+                    entry.booted = true;
+                }
+            }
+        }
+    }
+}
+
+impl<'a> ACKHandler for RebootGuard<'a> {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        self.update(packet);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::WritableRamAddr;
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+    use reboot::{wait_until_ready, ReadyError, RebootGuard, MAX_REBOOTING};
+    use script::Op;
+    use servo::Servo;
+    use test_support::AckFrameBuilder;
+    use transport::Transport;
+
+    use core::cell::Cell;
+    use std::vec::Vec;
+
+    /// A servomotor 0x40 ignoring the first `silent` STAT requests, then reporting a distorted
+    /// EEP memory `distorted` times.
+    struct Booting {
+        silent: usize,
+        distorted: usize,
+        pending: Vec<u8>,
+        cleared: bool,
+    }
+
+    impl Transport for Booting {
+        type Error = ();
+
+        fn write(&mut self, frame: &[u8]) -> Result<(), ()> {
+            if frame[3] != 0x40 {
+                return Ok(());
+            }
+            if frame[4] == 0x03 {
+                self.cleared = true;
+            } else if self.silent > 0 {
+                self.silent -= 1;
+            } else if self.distorted > 0 {
+                self.distorted -= 1;
+                self.pending = AckFrameBuilder::stat(0x40).status(0x40, 0).build().to_vec();
+            } else {
+                self.pending = AckFrameBuilder::stat(0x40).status(0x02, 0).build().to_vec();
+            }
+            Ok(())
+        }
+
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+            let len = self.pending.len();
+            buf[..len].copy_from_slice(&self.pending);
+            self.pending.clear();
+            Ok(len)
+        }
+    }
+
+    fn booting(silent: usize, distorted: usize) -> Booting {
+        Booting {
+            silent,
+            distorted,
+            pending: Vec::new(),
+            cleared: false,
+        }
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn logs_retries() {
+        use logging::capture;
+
+        let tick = Cell::new(0u32);
+        let clock = || {
+            tick.set(tick.get() + 1);
+            tick.get()
+        };
+        capture::take();
+        assert!(wait_until_ready(&mut booting(2, 0), &clock, 0x40, 100, 5, false).is_ok());
+        let retries: Vec<_> = capture::take()
+            .into_iter()
+            .filter(|record| record.starts_with("DEBUG"))
+            .collect();
+        assert_eq!(
+            retries,
+            [
+                "DEBUG 0x40 is not ready, polling again (attempt 2)",
+                "DEBUG 0x40 is not ready, polling again (attempt 3)",
+            ]
+        );
+    }
+
+    #[test]
+    fn ready() {
+        let tick = Cell::new(0u32);
+        let clock = || {
+            tick.set(tick.get() + 1);
+            tick.get()
+        };
+        let mut servo = booting(2, 1);
+        let packet = wait_until_ready(&mut servo, &clock, 0x40, 100, 5, true).unwrap();
+        assert_eq!(packet.error, StatusError::ExceedAllowedPOTLimit);
+        assert!(servo.cleared);
+
+        let mut quiet = booting(2, 0);
+        tick.set(0);
+        assert!(wait_until_ready(&mut quiet, &clock, 0x40, 100, 5, false).is_ok());
+        assert!(!quiet.cleared);
+
+        tick.set(0);
+        assert_eq!(
+            wait_until_ready(&mut booting(0, 100), &clock, 0x40, 100, 5, false),
+            Err(ReadyError::EEPDistorted)
+        );
+        tick.set(0);
+        assert_eq!(
+            wait_until_ready(&mut booting(100, 0), &clock, 0x40, 100, 5, false),
+            Err(ReadyError::Timeout)
+        );
+    }
+
+    #[test]
+    fn boot_time() {
+        let mut guard = RebootGuard::new(100);
+        assert_eq!(guard.record_sent(&Servo::new(0x40).stat(), 0), None);
+        assert_eq!(guard.record_sent(&Servo::new(0x40).reboot(), 0), Some(0x40));
+        assert_eq!(
+            guard.record_sent(&Servo::new(0xFE).reboot(), 50),
+            Some(0xFE)
+        );
+        assert!(!guard.is_ready(0x40, 99));
+        assert!(!guard.is_ready(0x41, 100));
+        assert!(guard.is_ready(0x40, 150));
+        assert_eq!(guard.next_message(150), None);
+        assert!(guard.is_ready(0x41, 0));
+
+        // A STAT answer means that the servomotor has booted.
+        guard.record_sent(&Servo::new(0x40).reboot(), 200);
+        assert!(!guard.is_ready(0x40, 201));
+        guard.update(&ACKPacket::new(
+            0x40,
+            Command::Stat,
+            StatusError::NoError,
+            StatusDetail::NoDetail,
+        ));
+        assert!(guard.is_ready(0x40, 201));
+
+        for id in 0..MAX_REBOOTING as u8 + 1 {
+            guard.record_sent(&Servo::new(id).reboot(), 300);
+        }
+        assert!(guard.is_ready(0, 301));
+        assert!(!guard.is_ready(1, 301));
+    }
+
+    #[test]
+    fn startup() {
+        let ops = [
+            Op::Write(WritableRamAddr::TorqueControl(0x60)),
+            Op::Wait(10),
+            Op::Write(WritableRamAddr::LEDControl(0x01)),
+        ];
+        let servo = Servo::new(0x40);
+        let mut guard = RebootGuard::new(100).with_startup(&ops);
+        guard.record_sent(&servo.reboot(), 0);
+        assert_eq!(guard.next_message(50), None);
+        assert_eq!(guard.next_message(100), Some(servo.enable_torque()));
+        assert!(!guard.is_ready(0x40, 100));
+        assert_eq!(guard.next_message(105), None);
+        assert_eq!(
+            guard.next_message(110),
+            servo.ram_write(WritableRamAddr::LEDControl(0x01)).ok()
+        );
+        assert!(guard.is_ready(0x40, 110));
+    }
+}
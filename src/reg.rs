@@ -0,0 +1,330 @@
+//! Registers described once for both memories.
+//!
+//! Most registers of the servomotor exist twice : in the EEP memory, which is kept across reboots,
+//! and in the RAM, which is loaded from the EEP at startup and used by the servomotor. Each
+//! register is described here by a marker type implementing [`Register`](trait.Register.html),
+//! which gives its addresses in both memories and the type of its value. This allows to write a
+//! register without caring about the memory it lives in :
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::reg;
+//! use drs_0x01::{Servo, WritableRamAddr};
+//!
+//! let servo = Servo::new(0x40);
+//! assert_eq!(
+//!     servo.write::<reg::PositionKp>(420),
+//!     servo.ram_write(WritableRamAddr::PositionKp(0xA4, 0x01))
+//! );
+//! // The baudrate only exists in the EEP memory.
+//! #[cfg(feature = "eep")]
+//! let message = servo.write::<reg::BaudRate>(0x10).unwrap();
+//! // A value which is not a baudrate code is refused.
+//! #[cfg(feature = "eep")]
+//! assert!(servo.write::<reg::BaudRate>(0x11).is_err());
+//! ```
+
+/// The value of a register, encoded in little endian on one or two bytes.
+pub trait RegisterValue: Copy {
+    /// The size of the value in bytes
+    const BYTES: u8;
+
+    /// Encode the value, the unused bytes are zero.
+    fn to_bytes(self) -> [u8; 2];
+
+    /// Decode the value from its bytes.
+    fn from_bytes(bytes: [u8; 2]) -> Self;
+}
+
+impl RegisterValue for u8 {
+    const BYTES: u8 = 1;
+
+    fn to_bytes(self) -> [u8; 2] {
+        [self, 0]
+    }
+
+    fn from_bytes(bytes: [u8; 2]) -> u8 {
+        bytes[0]
+    }
+}
+
+impl RegisterValue for i8 {
+    const BYTES: u8 = 1;
+
+    fn to_bytes(self) -> [u8; 2] {
+        [self as u8, 0]
+    }
+
+    fn from_bytes(bytes: [u8; 2]) -> i8 {
+        bytes[0] as i8
+    }
+}
+
+impl RegisterValue for u16 {
+    const BYTES: u8 = 2;
+
+    fn to_bytes(self) -> [u8; 2] {
+        [self as u8, (self >> 8) as u8]
+    }
+
+    fn from_bytes(bytes: [u8; 2]) -> u16 {
+        u16::from(bytes[0]) | u16::from(bytes[1]) << 8
+    }
+}
+
+/// A register of the servomotor, at a given address in the RAM, in the EEP memory, or in both.
+pub trait Register {
+    /// The name of the register
+    const NAME: &'static str;
+    /// The address of the register in the RAM
+    const RAM: Option<u8>;
+    /// The address of the register in the EEP memory
+    const EEP: Option<u8>;
+
+    /// The type of the value of the register
+    type Value: RegisterValue;
+
+    /// Encode `value` as the data of a write request.
+    fn encode(value: Self::Value) -> [u8; 2] {
+        value.to_bytes()
+    }
+
+    /// Decode the data read from the register, or return `None` if its size is not the size of the
+    /// register.
+    fn decode(data: &[u8]) -> Option<Self::Value> {
+        match *data {
+            [d] if Self::Value::BYTES == 1 => Some(Self::Value::from_bytes([d, 0])),
+            [d, d2] if Self::Value::BYTES == 2 => Some(Self::Value::from_bytes([d, d2])),
+            _ => None,
+        }
+    }
+}
+
+/// A register which can be written, in at least one memory.
+pub trait Writable: Register {}
+
+// Generate the marker types from a table of `access Name: Value = ram, eep;` rows. The rows of
+// the `eep` block are the registers which only exist in the EEP memory, left out without the `eep`
+// feature.
+macro_rules! registers {
+    (
+        eep {
+            $(
+                $(#[$eep_meta:meta])*
+                $eep_access:ident $eep_name:ident: $eep_value:ty = $eep_ram:expr, $eep_eep:expr;
+            )*
+        }
+        $($(#[$meta:meta])* $access:ident $name:ident: $value:ty = $ram:expr, $eep:expr;)*
+    ) => {
+        $(
+            #[cfg(feature = "eep")]
+            register!($(#[$eep_meta])* $eep_access $eep_name: $eep_value = $eep_ram, $eep_eep);
+        )*
+        $(register!($(#[$meta])* $access $name: $value = $ram, $eep);)*
+
+        #[cfg(test)]
+        fn check_all(check: &mut dyn FnMut(&'static str, Option<u8>, Option<u8>, u8, bool)) {
+            $(#[cfg(feature = "eep")]
+            check(
+                $eep_name::NAME,
+                $eep_name::RAM,
+                $eep_name::EEP,
+                <$eep_value as RegisterValue>::BYTES,
+                register_access!(@writable $eep_access),
+            );)*
+            $(check(
+                $name::NAME,
+                $name::RAM,
+                $name::EEP,
+                <$value as RegisterValue>::BYTES,
+                register_access!(@writable $access),
+            );)*
+        }
+    };
+}
+
+macro_rules! register {
+    ($(#[$meta:meta])* $access:ident $name:ident: $value:ty = $ram:expr, $eep:expr) => {
+        $(#[$meta])*
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        pub struct $name;
+
+        impl Register for $name {
+            const NAME: &'static str = stringify!($name);
+            const RAM: Option<u8> = $ram;
+            const EEP: Option<u8> = $eep;
+            type Value = $value;
+        }
+
+        register_access!($access $name);
+    };
+}
+
+macro_rules! register_access {
+    (rw $name:ident) => {
+        impl Writable for $name {}
+    };
+    (ro $name:ident) => {};
+    (@writable rw) => {
+        true
+    };
+    (@writable ro) => {
+        false
+    };
+}
+
+registers! {
+    eep {
+        /// The model of the servomotor, first byte
+        ro ModelNo1: u8 = None, Some(0);
+        /// The model of the servomotor, second byte
+        ro ModelNo2: u8 = None, Some(1);
+        /// The version of the firmware, first byte
+        ro Version1: u8 = None, Some(2);
+        /// The version of the firmware, second byte
+        ro Version2: u8 = None, Some(3);
+        /// The baudrate of the servomotor, see [`BaudRate`](../config/enum.BaudRate.html)
+        rw BaudRate: u8 = None, Some(4);
+    }
+    /// The ID of the servomotor
+    rw ID: u8 = Some(0), Some(6);
+    /// The policy of the servomotor regarding the ACK
+    rw AckPolicy: u8 = Some(1), Some(7);
+    /// The errors turning the LED on
+    rw AlarmLEDPolicy: u8 = Some(2), Some(8);
+    /// The errors releasing the torque
+    rw TorquePolicy: u8 = Some(3), Some(9);
+    /// The maximum operational temperature
+    rw MaxTemperature: u8 = Some(5), Some(11);
+    /// The minimum operational voltage
+    rw MinVoltage: u8 = Some(6), Some(12);
+    /// The maximum operational voltage
+    rw MaxVoltage: u8 = Some(7), Some(13);
+    /// The ratio of time reaching the goal position to the acceleration or deceleration
+    rw AccelerationRatio: u8 = Some(8), Some(14);
+    /// The maximum acceleration time
+    rw MaxAccelerationTime: u8 = Some(9), Some(15);
+    /// The outer error margin where the servomotor stops
+    rw DeadZone: u8 = Some(10), Some(16);
+    /// The PWM offset value at the limit of the dead zone
+    rw SaturatorOffset: u8 = Some(11), Some(17);
+    /// The PWM slope beyond the dead zone
+    rw SaturatorSlope: u16 = Some(12), Some(18);
+    /// The PWM offset, to compensate the load of the servomotor
+    rw PWMOffset: i8 = Some(14), Some(20);
+    /// The minimum PWM
+    rw MinPWM: u8 = Some(15), Some(21);
+    /// The maximum PWM
+    rw MaxPWM: u16 = Some(16), Some(22);
+    /// The PWM above which the overload is detected
+    rw OverloadPWMThreshold: u16 = Some(18), Some(24);
+    /// The minimum position
+    rw MinPosition: u16 = Some(20), Some(26);
+    /// The maximum position
+    rw MaxPosition: u16 = Some(22), Some(28);
+    /// The proportional gain
+    rw PositionKp: u16 = Some(24), Some(30);
+    /// The derivative gain
+    rw PositionKd: u16 = Some(26), Some(32);
+    /// The integral gain
+    rw PositionKi: u16 = Some(28), Some(34);
+    /// The position feed forward first gain
+    rw PositionFFFirstGain: u16 = Some(30), Some(36);
+    /// The position feed forward second gain
+    rw PositionFFSecondGain: u16 = Some(32), Some(38);
+    /// The blinking period of the LED when an alarm is raised
+    rw LedBlinkPeriod: u8 = Some(38), Some(44);
+    /// The period of the temperature and voltage checks
+    rw ADCFaultCheckPeriod: u8 = Some(39), Some(45);
+    /// The period of the garbage packet checks
+    rw PacketGarbageDetectionPeriod: u8 = Some(40), Some(46);
+    /// The period of the stop checks
+    rw StopDetectionPeriod: u8 = Some(41), Some(47);
+    /// The period of the overload checks
+    rw OverloadDetectionPeriod: u8 = Some(42), Some(48);
+    /// The position change under which the servomotor is considered stopped
+    rw StopThreshold: u8 = Some(43), Some(49);
+    /// The position error under which the servomotor is considered in position
+    rw InpositionMargin: u8 = Some(44), Some(50);
+    /// The calibration offset of the position
+    rw CalibrationDifference: u8 = Some(47), Some(53);
+    /// The status error register
+    rw StatusError: u8 = Some(48), None;
+    /// The status detail register
+    rw StatusDetail: u8 = Some(49), None;
+    /// The torque control register, see [`TorqueState`](../config/enum.TorqueState.html)
+    rw TorqueControl: u8 = Some(52), None;
+    /// The LED control register
+    rw LEDControl: u8 = Some(53), None;
+    /// The input voltage
+    ro Voltage: u8 = Some(54), None;
+    /// The temperature
+    ro Temperature: u8 = Some(55), None;
+    /// The current control mode
+    ro CurrentControlMode: u8 = Some(56), None;
+    /// The internal clock of the servomotor, in ticks of 11.2ms
+    ro Tick: u8 = Some(57), None;
+    /// The calibrated position
+    ro CalibratedPosition: u16 = Some(58), None;
+    /// The raw position
+    ro AbsolutePosition: u16 = Some(60), None;
+    /// The position change during the last tick
+    ro DifferentialPosition: u16 = Some(62), None;
+    /// The current PWM
+    ro PWM: u16 = Some(64), None;
+    /// The goal position
+    ro AbsoluteGoalPosition: u16 = Some(68), None;
+    /// The position of the trajectory at the current tick
+    ro AbsoluteDesiredTrajectoryPosition: u16 = Some(70), None;
+    /// The velocity of the trajectory at the current tick
+    ro DesiredVelocity: u8 = Some(72), None;
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "eep")]
+    use addr::WritableEEPAddr;
+    use addr::{ReadableEEPAddr, ReadableRamAddr, WritableRamAddr};
+    use reg::{self, Register};
+    use try_from::TryFrom;
+
+    #[test]
+    fn table_matches_addresses() {
+        reg::check_all(&mut |name, ram, eep, bytes, writable| {
+            if let Some(addr) = ram {
+                let read = ReadableRamAddr::try_from(addr).expect(name);
+                assert_eq!(read.bytes(), bytes, "{}", name);
+                assert_eq!(
+                    WritableRamAddr::try_from(addr).is_ok(),
+                    writable,
+                    "{}",
+                    name
+                );
+            }
+            if let Some(addr) = eep {
+                let read = ReadableEEPAddr::try_from(addr).expect(name);
+                assert_eq!(read.bytes(), bytes, "{}", name);
+                #[cfg(feature = "eep")]
+                if ram.is_none() {
+                    assert_eq!(
+                        WritableEEPAddr::try_from(addr).is_ok(),
+                        writable,
+                        "{}",
+                        name
+                    );
+                }
+            }
+            assert!(ram.is_some() || eep.is_some(), "{}", name);
+        });
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(reg::PositionKp::encode(420), [0xA4, 0x01]);
+        assert_eq!(reg::PWMOffset::encode(-2), [0xFE, 0x00]);
+        assert_eq!(reg::PositionKp::decode(&[0xA4, 0x01]), Some(420));
+        assert_eq!(reg::PWMOffset::decode(&[0xFE]), Some(-2));
+        assert_eq!(reg::PositionKp::decode(&[0xA4]), None);
+        assert_eq!(reg::Temperature::decode(&[0xA4, 0x01]), None);
+    }
+}
@@ -0,0 +1,207 @@
+//! Conversion of the state of the servomotors into messages shaped like the ROS
+//! [`sensor_msgs/JointState`](https://docs.ros2.org/latest/api/sensor_msgs/msg/JointState.html).
+//!
+//! A [`JointStateCollector`](struct.JointStateCollector.html) follows the position, velocity and
+//! PWM reads of named servomotors, and produces a [`JointState`](struct.JointState.html) whose
+//! arrays are indexed like its names. With the `serde` feature, `JointState` is serializable, so
+//! that a ROS node only has to forward it.
+//!
+//! The positions are in radians from the middle of the range of the servomotor, the velocities in
+//! radians per second, derived from the `DifferentialPosition` register, and the efforts are the
+//! PWM as a fraction of the maximum PWM, between -1 and 1. The values which were never read are
+//! NaN.
+//!
+//! This module is available with the `std` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::reader::FrameDecoder;
+//! use drs_0x01::ros::JointStateCollector;
+//!
+//! let mut decoder = FrameDecoder::new();
+//! let mut joints = JointStateCollector::new();
+//! joints.add_joint(0x40, "shoulder");
+//! joints.add_joint(0x41, "elbow");
+//! decoder.parse_with(&[/* ... */], &mut joints);
+//! let state = joints.joint_state();
+//! assert_eq!(state.name, ["shoulder", "elbow"]);
+//! ```
+
+use std::string::String;
+use std::vec::Vec;
+
+#[cfg(all(feature = "serde", feature = "std"))]
+use serde::{Deserialize, Serialize};
+
+use addr::ReadableRamAddr;
+use config::MAX_PWM;
+use model::{DEGREES_PER_POSITION, POSITION_RANGE};
+use reader::{position_of, ACKHandler, ACKPacket, Command};
+
+/// The duration counted by the `DifferentialPosition` register, in seconds.
+const DIFFERENTIAL_PERIOD: f64 = 0.0112;
+
+/// The state of a set of joints, with the fields of a ROS `JointState` message except its header.
+#[cfg_attr(
+    all(feature = "serde", feature = "std"),
+    derive(Serialize, Deserialize)
+)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct JointState {
+    /// The names of the joints
+    pub name: Vec<String>,
+    /// The positions of the joints, in radians
+    pub position: Vec<f64>,
+    /// The velocities of the joints, in radians per second
+    pub velocity: Vec<f64>,
+    /// The efforts of the joints, as fractions of the maximum PWM
+    pub effort: Vec<f64>,
+}
+
+#[derive(Clone, Debug)]
+struct Joint {
+    id: u8,
+    name: String,
+    position: Option<u16>,
+    velocity: Option<i16>,
+    pwm: Option<i16>,
+}
+
+fn radians(positions: f64) -> f64 {
+    (positions * f64::from(DEGREES_PER_POSITION)).to_radians()
+}
+
+/// This struct follows the state of named servomotors to build `JointState` messages.
+#[derive(Clone, Debug, Default)]
+pub struct JointStateCollector {
+    joints: Vec<Joint>,
+}
+
+impl JointStateCollector {
+    /// Create a collector without any joint.
+    pub fn new() -> JointStateCollector {
+        JointStateCollector::default()
+    }
+
+    /// Follow the servomotor `id` as the joint `name`. A servomotor added again is renamed.
+    pub fn add_joint<S: Into<String>>(&mut self, id: u8, name: S) {
+        let name = name.into();
+        match self.joints.iter_mut().find(|joint| joint.id == id) {
+            Some(joint) => joint.name = name,
+            None => self.joints.push(Joint {
+                id,
+                name,
+                position: None,
+                velocity: None,
+                pwm: None,
+            }),
+        }
+    }
+
+    /// Stop following the servomotor `id`.
+    pub fn remove_joint(&mut self, id: u8) {
+        self.joints.retain(|joint| joint.id != id);
+    }
+
+    /// Follow the position, the velocity or the PWM carried by `packet`.
+    pub fn update(&mut self, packet: &ACKPacket) {
+        let joint = match self.joints.iter_mut().find(|joint| joint.id == packet.pid) {
+            Some(joint) => joint,
+            None => return,
+        };
+        if let Some(position) = position_of(packet) {
+            joint.position = Some(position);
+        } else if let Command::RamRead { data } = packet.cmd {
+            match data.addr {
+                ReadableRamAddr::DifferentialPosition => joint.velocity = data.as_i16(),
+                ReadableRamAddr::PWM => joint.pwm = data.as_i16(),
+                _ => (),
+            }
+        }
+    }
+
+    /// Return the state of the joints, in the order in which they were added.
+    pub fn joint_state(&self) -> JointState {
+        let center = f64::from(POSITION_RANGE / 2);
+        let value = |value: Option<f64>| value.unwrap_or(f64::NAN);
+        JointState {
+            name: self.joints.iter().map(|joint| joint.name.clone()).collect(),
+            position: self
+                .joints
+                .iter()
+                .map(|joint| value(joint.position.map(|p| radians(f64::from(p) - center))))
+                .collect(),
+            velocity: self
+                .joints
+                .iter()
+                .map(|joint| {
+                    value(
+                        joint
+                            .velocity
+                            .map(|v| radians(f64::from(v)) / DIFFERENTIAL_PERIOD),
+                    )
+                })
+                .collect(),
+            effort: self
+                .joints
+                .iter()
+                .map(|joint| value(joint.pwm.map(|pwm| f64::from(pwm) / f64::from(MAX_PWM))))
+                .collect(),
+        }
+    }
+}
+
+impl ACKHandler for JointStateCollector {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        self.update(packet);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{RamReadData, ReadableRamAddr};
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+    use ros::JointStateCollector;
+
+    fn read(pid: u8, addr: ReadableRamAddr, value: i16) -> ACKPacket {
+        ACKPacket::new(
+            pid,
+            Command::RamRead {
+                data: RamReadData {
+                    addr,
+                    data_len: 2,
+                    data: [value as u8, (value >> 8) as u8],
+                },
+            },
+            StatusError::NoError,
+            StatusDetail::NoDetail,
+        )
+    }
+
+    #[test]
+    fn joint_state() {
+        let mut joints = JointStateCollector::new();
+        joints.add_joint(0x40, "shoulder");
+        joints.add_joint(0x41, "wrist");
+        joints.add_joint(0x41, "elbow");
+        joints.update(&read(0x40, ReadableRamAddr::CalibratedPosition, 512));
+        joints.update(&read(0x41, ReadableRamAddr::CalibratedPosition, 0));
+        joints.update(&read(0x41, ReadableRamAddr::DifferentialPosition, -2));
+        joints.update(&read(0x41, ReadableRamAddr::PWM, 1023));
+        joints.update(&read(0x42, ReadableRamAddr::PWM, 1023));
+
+        let state = joints.joint_state();
+        assert_eq!(state.name, ["shoulder", "elbow"]);
+        assert_eq!(state.position[0], 0.0);
+        assert!((state.position[1] + 166.4f64.to_radians()).abs() < 1e-6);
+        assert!(state.velocity[0].is_nan());
+        assert!((state.velocity[1] + 0.65f64.to_radians() / 0.0112).abs() < 1e-6);
+        assert!(state.effort[0].is_nan());
+        assert_eq!(state.effort[1], 1.0);
+
+        joints.remove_joint(0x40);
+        assert_eq!(joints.joint_state().name, ["elbow"]);
+    }
+}
@@ -0,0 +1,224 @@
+//! Sequences of messages described as data, and replayed with their delays.
+//!
+//! A script is a slice of [`Op`](enum.Op.html), which can be declared as a constant and shared
+//! by the test fixtures and the initialisation sequences of several targets. A
+//! [`ScriptPlayer`](struct.ScriptPlayer.html) turns it into the messages for one servomotor.
+//!
+//! Timestamps and delays are expressed in ticks of any clock, and are allowed to wrap around.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::script::{Op, ScriptPlayer};
+//! use drs_0x01::{JogColor, JogMode, ReadableRamAddr, Servo, WritableRamAddr};
+//!
+//! const INIT: &[Op] = &[
+//!     Op::Write(WritableRamAddr::StatusError(0)),
+//!     Op::Write(WritableRamAddr::TorqueControl(0x60)),
+//!     // With a 1 kHz tick, wait 100 ms for the torque to settle.
+//!     Op::Wait(100),
+//!     Op::Jog {
+//!         mode: JogMode::Normal { position: 512 },
+//!         color: JogColor::Blue,
+//!         playtime: 60,
+//!     },
+//!     Op::Read(ReadableRamAddr::CalibratedPosition),
+//! ];
+//!
+//! let mut player = ScriptPlayer::new(Servo::new(0x40), INIT);
+//! assert!(player.next_message(0).is_some());
+//! assert!(player.next_message(0).is_some());
+//! assert!(player.next_message(50).is_none());
+//! assert!(player.next_message(100).is_some());
+//! ```
+
+#[cfg(feature = "eep")]
+use addr::{ReadableEEPAddr, WritableEEPAddr};
+use addr::{ReadableRamAddr, WritableRamAddr};
+use builder::{HerkulexMessage, MessageBuilder};
+use message::{JogColor, JogMode};
+use servo::Servo;
+
+/// A step of a script.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+    /// Write a register of the RAM.
+    Write(WritableRamAddr),
+    /// Write a register of the EEP memory.
+    #[cfg(feature = "eep")]
+    WriteEEP(WritableEEPAddr),
+    /// Read a register of the RAM.
+    Read(ReadableRamAddr),
+    /// Read a register of the EEP memory.
+    #[cfg(feature = "eep")]
+    ReadEEP(ReadableEEPAddr),
+    /// Wait for this number of ticks before the next step.
+    Wait(u32),
+    /// Move the servomotor with a **S_JOG**.
+    Jog {
+        /// The position or speed to reach
+        mode: JogMode,
+        /// The color of the LED
+        color: JogColor,
+        /// The duration of the motion, in units of 11.2ms
+        playtime: u8,
+    },
+}
+
+impl Op {
+    /// Return the message of this step sent to `servo`, or `None` for a `Wait` and for a write
+    /// refused by [`Servo::ram_write`](../struct.Servo.html#method.ram_write) or
+    /// [`Servo::eep_write`](../struct.Servo.html#method.eep_write).
+    pub fn message(&self, servo: Servo) -> Option<HerkulexMessage> {
+        let message = match *self {
+            Op::Write(addr) => servo.ram_write(addr).ok()?,
+            #[cfg(feature = "eep")]
+            Op::WriteEEP(addr) => servo.eep_write(addr).ok()?,
+            Op::Read(addr) => servo.ram_request(addr),
+            #[cfg(feature = "eep")]
+            Op::ReadEEP(addr) => servo.eep_request(addr),
+            Op::Wait(_) => return None,
+            Op::Jog {
+                mode,
+                color,
+                playtime,
+            } => MessageBuilder::new_with_id(servo.id())
+                .s_jog(playtime, mode, color, servo.id())
+                .build(),
+        };
+        Some(message)
+    }
+}
+
+/// This struct plays a script for a servomotor, honoring its `Wait` steps.
+#[derive(Clone, Debug)]
+pub struct ScriptPlayer<'a> {
+    servo: Servo,
+    ops: &'a [Op],
+    cursor: usize,
+    resume: Option<u32>,
+}
+
+impl<'a> ScriptPlayer<'a> {
+    /// Create a player sending the messages of `ops` to `servo`.
+    pub fn new(servo: Servo, ops: &'a [Op]) -> ScriptPlayer<'a> {
+        ScriptPlayer {
+            servo,
+            ops,
+            cursor: 0,
+            resume: None,
+        }
+    }
+
+    /// Return the next message if it is due at `now`. The steps following a `Wait` are due once
+    /// its ticks are elapsed since the previous message was sent (or since the first call for a
+    /// script starting with a `Wait`).
+    pub fn next_message(&mut self, now: u32) -> Option<HerkulexMessage> {
+        if self.resume.is_none() {
+            self.skip_waits(now);
+        }
+        if let Some(resume) = self.resume {
+            if (now.wrapping_sub(resume) as i32) < 0 {
+                return None;
+            }
+            self.resume = None;
+        }
+        let op = self.ops.get(self.cursor)?;
+        self.cursor += 1;
+        let message = op.message(self.servo);
+        self.skip_waits(now);
+        message
+    }
+
+    // Consume the `Wait` steps at the cursor, delaying the next step from `now`.
+    fn skip_waits(&mut self, now: u32) {
+        while let Some(&Op::Wait(ticks)) = self.ops.get(self.cursor) {
+            self.resume = Some(self.resume.unwrap_or(now).wrapping_add(ticks));
+            self.cursor += 1;
+        }
+    }
+
+    /// Return true if every step was played.
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.ops.len() && self.resume.is_none()
+    }
+
+    /// Play the script again from its first step.
+    pub fn restart(&mut self) {
+        self.cursor = 0;
+        self.resume = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "eep")]
+    use addr::{ReadableEEPAddr, WritableEEPAddr};
+    use addr::{ReadableRamAddr, WritableRamAddr};
+    use builder::MessageBuilder;
+    use message::{JogColor, JogMode};
+    use script::{Op, ScriptPlayer};
+    use servo::Servo;
+
+    #[test]
+    fn play() {
+        let servo = Servo::new(0x40);
+        let ops = [
+            Op::Wait(10),
+            Op::Write(WritableRamAddr::TorqueControl(0x60)),
+            Op::Wait(0),
+            Op::Read(ReadableRamAddr::Temperature),
+            Op::Wait(20),
+            Op::Jog {
+                mode: JogMode::NoMove,
+                color: JogColor::Red,
+                playtime: 0,
+            },
+            Op::Wait(5),
+        ];
+        let mut player = ScriptPlayer::new(servo, &ops);
+        assert_eq!(player.next_message(u32::MAX - 4), None);
+        assert_eq!(player.next_message(4), None);
+        assert_eq!(player.next_message(5), Some(servo.enable_torque()));
+        assert_eq!(
+            player.next_message(5),
+            Some(servo.ram_request(ReadableRamAddr::Temperature))
+        );
+        assert_eq!(player.next_message(24), None);
+        assert_eq!(
+            player.next_message(25),
+            Some(
+                MessageBuilder::new_with_id(0x40)
+                    .s_jog(0, JogMode::NoMove, JogColor::Red, 0x40)
+                    .build()
+            )
+        );
+        assert_eq!(player.next_message(25), None);
+        assert!(!player.is_finished());
+        assert_eq!(player.next_message(30), None);
+        assert!(player.is_finished());
+
+        player.restart();
+        assert_eq!(player.next_message(100), None);
+        assert_eq!(player.next_message(110), Some(servo.enable_torque()));
+    }
+
+    #[cfg(feature = "eep")]
+    #[test]
+    fn eep_ops() {
+        let servo = Servo::new(0x40);
+        assert_eq!(
+            Op::ReadEEP(ReadableEEPAddr::BaudRate).message(servo),
+            Some(servo.eep_request(ReadableEEPAddr::BaudRate))
+        );
+        assert_eq!(
+            Op::WriteEEP(WritableEEPAddr::AckPolicy(0x01)).message(servo),
+            servo.eep_write(WritableEEPAddr::AckPolicy(0x01)).ok()
+        );
+        assert_eq!(
+            Op::WriteEEP(WritableEEPAddr::BaudRate(0x11)).message(servo),
+            None
+        );
+    }
+}
@@ -1,20 +1,65 @@
-use builder::{HerkulexMessage, MessageBuilder};
+use arrayvec::ArrayVec;
+use builder::{HerkulexMessage, MessageBuilder, MessageBuilderError};
 
-use message::{JogColor, JogMode, Rotation};
+use message::{JogColor, JogMode, Rollback, Rotation};
 
 use addr::*;
+#[cfg(feature = "eep")]
+use config::BaudRate;
+#[cfg(feature = "runtime")]
+use config::TorqueState;
+use config::{
+    BlinkPattern, ComplianceSettings, ConfigError, InpositionMargin, OverloadThreshold, MAX_PWM,
+};
+use reader::StatusError;
+#[cfg(feature = "runtime")]
+use reader::{ACKPacket, Command, StatusDetail, StatusPair};
+#[cfg(feature = "runtime")]
+use reg::Register;
+use reg::Writable;
+#[cfg(feature = "runtime")]
+use transaction::{
+    decode_ram_u16, decode_ram_u8, decode_register, AckKind, ExpectedAck, Transaction,
+    VerifiedWrite,
+};
+
+use try_from::TryFrom;
 
 use core::cmp::min;
 
+/// The ID addressing every servomotor on the bus at once. The servomotors do not answer to the
+/// messages sent to this ID.
+pub const BROADCAST_ID: u8 = 0xFE;
+
+/// The values used by a [`Servo`](struct.Servo.html) for the jog parameters which are not given
+/// to its methods.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ServoOptions {
+    /// The time given to reach a position, in units of 11.2 ms
+    pub default_playtime: u8,
+    /// The color of the LED while jogging
+    pub default_color: JogColor,
+}
+
+impl Default for ServoOptions {
+    fn default() -> Self {
+        ServoOptions {
+            default_playtime: 60,
+            default_color: JogColor::Blue,
+        }
+    }
+}
+
 /// This struct allows you to build messages for a servomotor.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Servo {
     id: u8,
+    options: ServoOptions,
 }
 
 impl Default for Servo {
     fn default() -> Self {
-        Servo { id: 0xFD }
+        Servo::new(0xFD)
     }
 }
 
@@ -26,7 +71,33 @@ impl Servo {
     /// * Valid ID are in the range 0..253.
     /// * 254 is the broadcast ID.
     pub fn new(id: u8) -> Servo {
-        Servo { id }
+        Servo {
+            id,
+            options: ServoOptions::default(),
+        }
+    }
+
+    /// Use `options` for the jog parameters which are not given to the methods.
+    pub fn with_options(mut self, options: ServoOptions) -> Servo {
+        self.options = options;
+        self
+    }
+
+    /// Use `playtime` for the position jogs, in units of 11.2 ms.
+    pub fn with_playtime(mut self, playtime: u8) -> Servo {
+        self.options.default_playtime = playtime;
+        self
+    }
+
+    /// Use `color` for the LED while jogging.
+    pub fn with_color(mut self, color: JogColor) -> Servo {
+        self.options.default_color = color;
+        self
+    }
+
+    /// Return the options used for the jog parameters which are not given to the methods.
+    pub fn options(self) -> ServoOptions {
+        self.options
     }
 
     /// Change the servo ID.
@@ -45,38 +116,163 @@ impl Servo {
         MessageBuilder::new_with_id(self.id).reboot().build()
     }
 
-    /// Request the servo to go to a position.
+    /// Create a rollback message requesting the servo to reset its EEP memory to the factory
+    /// values, except for the registers preserved by `flags`.
+    pub fn rollback(self, flags: Rollback) -> HerkulexMessage {
+        MessageBuilder::new_with_id(self.id).rollback(flags).build()
+    }
+
+    /// Request the servo to go to a position, within the playtime of its options.
     /// The value can be between 0 and 1023 but should be between 21 and 1002 if you don't want
     /// the servomotor to go in error mode.
     pub fn set_position(self, position: u16) -> HerkulexMessage {
         MessageBuilder::new_with_id(self.id)
             .s_jog(
-                60,
+                self.options.default_playtime,
                 JogMode::Normal {
                     position: min(position, 1023),
                 },
-                JogColor::Blue,
+                self.options.default_color,
                 self.id,
             )
             .build()
     }
 
-    /// Request the servo to have a certain speed.
+    /// Request the servo to rotate continuously.
     /// The value should be between 0 and 1023.
+    ///
+    /// # Notes
+    ///
+    /// The value is the PWM duty applied to the motor, not a regulated velocity, see
+    /// [`set_pwm`](#method.set_pwm).
     pub fn set_speed(self, speed: u16, rotation: Rotation) -> HerkulexMessage {
         MessageBuilder::new_with_id(self.id)
             .s_jog(
-                60,
+                self.options.default_playtime,
                 JogMode::Continuous {
                     speed: min(speed, 1023),
                     rotation,
                 },
-                JogColor::Blue,
+                self.options.default_color,
                 self.id,
             )
             .build()
     }
 
+    /// Request the servo to rotate continuously by applying a PWM duty to its motor (open loop).
+    /// The value should be between -1023 and 1023, the sign giving the rotation sense :
+    /// positive values rotate counterclockwise.
+    pub fn set_pwm(self, pwm: i16) -> HerkulexMessage {
+        let rotation = if pwm < 0 {
+            Rotation::Clockwise
+        } else {
+            Rotation::CounterClockwise
+        };
+        self.set_speed(pwm.unsigned_abs(), rotation)
+    }
+
+    /// Add a constant offset to the PWM applied by the servo while it is holding a position,
+    /// which can be used to compensate a static load such as gravity on an arm joint.
+    /// The offset is a signed value, between -128 and 127, stored in the `PWMOffset` register.
+    pub fn set_pwm_offset(self, offset: i8) -> HerkulexMessage {
+        self.ram_write_unchecked(WritableRamAddr::PWMOffset(offset as u8))
+    }
+
+    /// Set the margin around the goal position, in degrees, within which the servo reports to be
+    /// in position. See [`InpositionMargin`](config/struct.InpositionMargin.html).
+    pub fn set_inposition_margin(self, degrees: f32) -> HerkulexMessage {
+        self.ram_write_unchecked(InpositionMargin::from_degrees(degrees).into())
+    }
+
+    /// Add a constant torque bias to the servo, as a fraction of the largest possible offset.
+    /// The bias is clamped between -1.0 and 1.0, the sign giving the direction of the torque.
+    /// See [`set_pwm_offset`](#method.set_pwm_offset).
+    pub fn set_holding_bias(self, bias: f32) -> HerkulexMessage {
+        let bias = bias.clamp(-1.0, 1.0);
+        self.set_pwm_offset((bias * 127.0) as i8)
+    }
+
+    /// Set the PWM above which the servo detects an overload.
+    /// An overload is reported when the PWM stays above the threshold for longer than the
+    /// `OverloadDetectionPeriod` register.
+    pub fn set_overload_threshold(self, threshold: OverloadThreshold) -> HerkulexMessage {
+        let (lsb, msb) = threshold.to_bytes();
+        self.ram_write_unchecked(WritableRamAddr::OverloadPWMThreshold(lsb, msb))
+    }
+
+    /// Request the servo to send back its overload threshold, which can be decoded with
+    /// [`OverloadThreshold::from_read`](config/enum.OverloadThreshold.html#method.from_read).
+    pub fn request_overload_threshold(self) -> HerkulexMessage {
+        self.ram_request(ReadableRamAddr::OverloadPWMThreshold)
+    }
+
+    /// Set the baudrate of the servo, which takes effect after the next reboot.
+    ///
+    /// # Errors
+    ///
+    /// Return [MessageBuilderError::BroadcastWrite](builder/enum.MessageBuilderError.html) for
+    /// the broadcast ID. [`eep_write_unchecked`](#method.eep_write_unchecked) must be used to
+    /// change the baudrate of every servo at once.
+    #[cfg(feature = "eep")]
+    pub fn set_baud_rate(self, baud: BaudRate) -> Result<HerkulexMessage, MessageBuilderError> {
+        self.eep_write(WritableEEPAddr::from(baud))
+    }
+
+    /// Request the servo to send back its baudrate, which can be decoded with
+    /// [`BaudRate::from_read`](config/enum.BaudRate.html#method.from_read).
+    #[cfg(feature = "eep")]
+    pub fn request_baud_rate(self) -> HerkulexMessage {
+        self.eep_request(ReadableEEPAddr::BaudRate)
+    }
+
+    /// Set the dead zone and saturator registers of the servo.
+    ///
+    /// # Errors
+    ///
+    /// Return [ConfigError::OutOfRange](config/enum.ConfigError.html) if a value is out of the
+    /// range given by the datasheet.
+    pub fn set_compliance(
+        self,
+        settings: &ComplianceSettings,
+    ) -> Result<[HerkulexMessage; 3], ConfigError> {
+        let [dead_zone, offset, slope] = settings.ram_writes()?;
+        Ok([
+            self.ram_write_unchecked(dead_zone),
+            self.ram_write_unchecked(offset),
+            self.ram_write_unchecked(slope),
+        ])
+    }
+
+    /// Request the servo to send back its dead zone and saturator registers, which can be
+    /// decoded with
+    /// [`ComplianceSettings::update_from_read`](config/struct.ComplianceSettings.html#method.update_from_read).
+    pub fn request_compliance(self) -> [HerkulexMessage; 3] {
+        let [dead_zone, offset, slope] = ComplianceSettings::ADDRESSES;
+        [
+            self.ram_request(dead_zone),
+            self.ram_request(offset),
+            self.ram_request(slope),
+        ]
+    }
+
+    /// Blink the LED every `period_ms` milliseconds when the servo reports one of `errors`, see
+    /// [`BlinkPattern::new`](config/struct.BlinkPattern.html#method.new).
+    pub fn set_alarm_blink(self, period_ms: u16, errors: &[StatusError]) -> [HerkulexMessage; 2] {
+        let [policy, period] = BlinkPattern::new(period_ms, errors).ram_writes();
+        [
+            self.ram_write_unchecked(policy),
+            self.ram_write_unchecked(period),
+        ]
+    }
+
+    /// Request the servo to send back its alarm LED policy and blink period, which can be decoded
+    /// with
+    /// [`BlinkPattern::update_from_read`](config/struct.BlinkPattern.html#method.update_from_read).
+    pub fn request_alarm_blink(self) -> [HerkulexMessage; 2] {
+        let [policy, period] = BlinkPattern::ADDRESSES;
+        [self.ram_request(policy), self.ram_request(period)]
+    }
+
     /// Request the servo to send it's status.
     pub fn stat(self) -> HerkulexMessage {
         MessageBuilder::new_with_id(self.id).stat().build()
@@ -84,15 +280,80 @@ impl Servo {
 
     /// Write to the volatile RAM of the servo.
     /// Ram is cleared on every reboot, and populated with data from the EEP memory.
-    pub fn ram_write(self, addr: WritableRamAddr) -> HerkulexMessage {
-        MessageBuilder::new_with_id(self.id).write_ram(addr).build()
+    ///
+    /// # Errors
+    ///
+    /// Return the errors of
+    /// [`MessageBuilderCmd::write_ram`](builder/struct.MessageBuilderCmd.html#method.write_ram),
+    /// if the `ID` register is written through the broadcast ID.
+    pub fn ram_write(self, addr: WritableRamAddr) -> Result<HerkulexMessage, MessageBuilderError> {
+        MessageBuilder::new_with_id(self.id)
+            .write_ram(addr)
+            .map(|message| message.build())
+    }
+
+    /// Write to the volatile RAM like [`ram_write`](#method.ram_write), without refusing the `ID`
+    /// register through the broadcast ID.
+    pub fn ram_write_unchecked(self, addr: WritableRamAddr) -> HerkulexMessage {
+        MessageBuilder::new_with_id(self.id)
+            .write_ram_unchecked(addr)
+            .build()
     }
 
     /// Write to the permanent EEP memory.
     /// For the change to take effect you need to reboot the servo so that the values are loaded
     /// in RAM.
-    pub fn eep_write(self, addr: WritableEEPAddr) -> HerkulexMessage {
-        MessageBuilder::new_with_id(self.id).write_eep(addr).build()
+    ///
+    /// # Errors
+    ///
+    /// Return the errors of
+    /// [`MessageBuilderCmd::write_eep`](builder/struct.MessageBuilderCmd.html#method.write_eep),
+    /// for an invalid `BaudRate` code, or the `ID` or `BaudRate` register written through the
+    /// broadcast ID.
+    #[cfg(feature = "eep")]
+    pub fn eep_write(self, addr: WritableEEPAddr) -> Result<HerkulexMessage, MessageBuilderError> {
+        MessageBuilder::new_with_id(self.id)
+            .write_eep(addr)
+            .map(|message| message.build())
+    }
+
+    /// Write to the permanent EEP memory like [`eep_write`](#method.eep_write), without checking
+    /// the value written nor refusing the broadcast ID.
+    #[cfg(feature = "eep")]
+    pub fn eep_write_unchecked(self, addr: WritableEEPAddr) -> HerkulexMessage {
+        MessageBuilder::new_with_id(self.id)
+            .write_eep_unchecked(addr)
+            .build()
+    }
+
+    /// Write to a register of the RAM and read it back, or return `None` for the broadcast ID,
+    /// which is never answered. The transaction is completed by the comparison of the value read
+    /// with the value written, see [`write_verified`](transaction/fn.write_verified.html) to run
+    /// both.
+    #[cfg(feature = "runtime")]
+    pub fn ram_write_verified(self, addr: WritableRamAddr) -> Option<VerifiedWrite> {
+        if self.id == BROADCAST_ID {
+            return None;
+        }
+        let readable = ReadableRamAddr::try_from(u8::from(addr)).ok()?;
+        let (d1, d2) = addr.associated_data();
+        let kind = AckKind::RamRead {
+            addr: readable,
+            len: addr.bytes(),
+        };
+        let request = MessageBuilder::new_with_id(self.id)
+            .read_ram(readable, addr.bytes())
+            .build();
+        let read = self.transaction(request, kind, |packet| match packet.cmd {
+            Command::RamRead { data } => Some(data),
+            _ => None,
+        });
+        let written = u16::from(d1) | u16::from(d2.unwrap_or(0)) << 8;
+        Some(VerifiedWrite::new(
+            self.ram_write_unchecked(addr),
+            read,
+            written,
+        ))
     }
 
     /// Request the servo to send back some data from RAM.
@@ -103,30 +364,684 @@ impl Servo {
     }
 
     /// Request the servo to send back some data from EEP.
+    #[cfg(feature = "eep")]
     pub fn eep_request(self, addr: ReadableEEPAddr) -> HerkulexMessage {
         MessageBuilder::new_with_id(self.id)
             .read_eep(addr, addr.bytes())
             .build()
     }
 
+    #[cfg(feature = "runtime")]
+    fn transaction<T>(
+        self,
+        message: HerkulexMessage,
+        kind: AckKind,
+        decode: fn(&ACKPacket) -> Option<T>,
+    ) -> Transaction<T> {
+        let expected = if self.id == BROADCAST_ID {
+            None
+        } else {
+            Some(ExpectedAck { id: self.id, kind })
+        };
+        Transaction::new(message, expected, decode)
+    }
+
+    /// Read some data from RAM, the transaction is completed by the data read.
+    #[cfg(feature = "runtime")]
+    pub fn ram_read(self, addr: ReadableRamAddr) -> Transaction<RamReadData> {
+        self.ram_transaction(addr, |packet| match packet.cmd {
+            Command::RamRead { data } => Some(data),
+            _ => None,
+        })
+    }
+
+    /// Read some data from EEP, the transaction is completed by the data read.
+    #[cfg(feature = "eep")]
+    #[cfg(feature = "runtime")]
+    pub fn eep_read(self, addr: ReadableEEPAddr) -> Transaction<EEPReadData> {
+        let kind = AckKind::EEPRead {
+            addr,
+            len: addr.bytes(),
+        };
+        self.transaction(self.eep_request(addr), kind, |packet| match packet.cmd {
+            Command::EEPRead { data } => Some(data),
+            _ => None,
+        })
+    }
+
+    /// Write `value` to the register `R`, in RAM if the register exists there, in the EEP memory
+    /// otherwise.
+    ///
+    /// ```
+    /// # extern crate drs_0x01;
+    /// use drs_0x01::{reg, Servo};
+    ///
+    /// let message = Servo::new(0x40).write::<reg::PositionKp>(420).unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Return [MessageBuilderError::UnmappedRegister](builder/enum.MessageBuilderError.html) if
+    /// `R` has no writable address, which is never the case of the registers of
+    /// [`reg`](reg/index.html), and the errors of [`ram_write`](#method.ram_write) or
+    /// [`eep_write`](#method.eep_write) if the value is refused, like an invalid `BaudRate` code.
+    pub fn write<R: Writable>(
+        self,
+        value: R::Value,
+    ) -> Result<HerkulexMessage, MessageBuilderError> {
+        match R::RAM.and_then(|addr| WritableRamAddr::try_from(addr).ok()) {
+            Some(addr) => self.ram_write(addr.with_data(R::encode(value))),
+            #[cfg(feature = "eep")]
+            None => self.eep_register_write::<R>(value),
+            #[cfg(not(feature = "eep"))]
+            None => Err(MessageBuilderError::UnmappedRegister(R::NAME)),
+        }
+    }
+
+    /// Write `value` to the register `R` like [`write`](#method.write), or return `None` if the
+    /// register has no writable address or refuses the value.
+    pub fn try_write<R: Writable>(self, value: R::Value) -> Option<HerkulexMessage> {
+        self.write::<R>(value).ok()
+    }
+
+    /// Write `value` to the register `R` in the EEP memory, or return `None` if the register only
+    /// exists in RAM or if the value is refused by [`eep_write`](#method.eep_write).
+    #[cfg(feature = "eep")]
+    pub fn write_eep<R: Writable>(self, value: R::Value) -> Option<HerkulexMessage> {
+        self.eep_register_write::<R>(value).ok()
+    }
+
+    #[cfg(feature = "eep")]
+    fn eep_register_write<R: Writable>(
+        self,
+        value: R::Value,
+    ) -> Result<HerkulexMessage, MessageBuilderError> {
+        match R::EEP.and_then(|addr| WritableEEPAddr::try_from(addr).ok()) {
+            Some(addr) => self.eep_write(addr.with_data(R::encode(value))),
+            None => Err(MessageBuilderError::UnmappedRegister(R::NAME)),
+        }
+    }
+
+    /// Read the register `R`, in RAM if the register exists there, in the EEP memory otherwise.
+    /// The transaction is completed by the value of the register.
+    ///
+    /// # Errors
+    ///
+    /// Return [MessageBuilderError::UnmappedRegister](builder/enum.MessageBuilderError.html) if
+    /// `R` has no readable address, which is never the case of the registers of
+    /// [`reg`](reg/index.html).
+    #[cfg(feature = "runtime")]
+    pub fn read<R: Register>(self) -> Result<Transaction<R::Value>, MessageBuilderError> {
+        match R::RAM.and_then(|addr| ReadableRamAddr::try_from(addr).ok()) {
+            Some(addr) => Ok(self.ram_transaction(addr, decode_register::<R>)),
+            #[cfg(feature = "eep")]
+            None => self
+                .read_eep::<R>()
+                .ok_or(MessageBuilderError::UnmappedRegister(R::NAME)),
+            #[cfg(not(feature = "eep"))]
+            None => Err(MessageBuilderError::UnmappedRegister(R::NAME)),
+        }
+    }
+
+    /// Read the register `R` like [`read`](#method.read), or return `None` if the register has no
+    /// readable address.
+    #[cfg(feature = "runtime")]
+    pub fn try_read<R: Register>(self) -> Option<Transaction<R::Value>> {
+        self.read::<R>().ok()
+    }
+
+    /// Read the register `R` in the EEP memory, or return `None` if the register only exists in
+    /// RAM. The transaction is completed by the value of the register.
+    #[cfg(feature = "eep")]
+    #[cfg(feature = "runtime")]
+    pub fn read_eep<R: Register>(self) -> Option<Transaction<R::Value>> {
+        let addr = ReadableEEPAddr::try_from(R::EEP?).ok()?;
+        let kind = AckKind::EEPRead {
+            addr,
+            len: addr.bytes(),
+        };
+        Some(self.transaction(self.eep_request(addr), kind, decode_register::<R>))
+    }
+
+    /// Read the status registers, the transaction is completed by their content.
+    #[cfg(feature = "runtime")]
+    pub fn status(self) -> Transaction<(StatusError, StatusDetail)> {
+        self.transaction(self.stat(), AckKind::Stat, |packet| {
+            Some((packet.error, packet.detail))
+        })
+    }
+
+    #[cfg(feature = "runtime")]
+    pub(crate) fn ram_transaction<T>(
+        self,
+        addr: ReadableRamAddr,
+        decode: fn(&ACKPacket) -> Option<T>,
+    ) -> Transaction<T> {
+        let kind = AckKind::RamRead {
+            addr,
+            len: addr.bytes(),
+        };
+        self.transaction(self.ram_request(addr), kind, decode)
+    }
+
+    /// Read the calibrated position, the transaction is completed by the raw position.
+    #[cfg(feature = "runtime")]
+    pub fn request_position(self) -> Transaction<u16> {
+        self.ram_transaction(ReadableRamAddr::CalibratedPosition, decode_ram_u16)
+    }
+
+    /// Read the temperature, the transaction is completed by the raw 8 bit value (refer to the
+    /// datasheet page 31 for the conversion).
+    #[cfg(feature = "runtime")]
+    pub fn request_temperature(self) -> Transaction<u8> {
+        self.ram_transaction(ReadableRamAddr::Temperature, decode_ram_u8)
+    }
+
+    /// Read the input voltage, the transaction is completed by the raw 8 bit value (refer to the
+    /// datasheet page 31 for the conversion).
+    #[cfg(feature = "runtime")]
+    pub fn request_voltage(self) -> Transaction<u8> {
+        self.ram_transaction(ReadableRamAddr::Voltage, decode_ram_u8)
+    }
+
+    /// Read the `Tick` register, the internal clock of the servomotor counting periods of 11.2 ms.
+    /// The transaction is completed by the raw 8 bit value, which wraps around.
+    #[cfg(feature = "runtime")]
+    pub fn request_tick(self) -> Transaction<u8> {
+        self.ram_transaction(ReadableRamAddr::Tick, decode_ram_u8)
+    }
+
+    /// Read the `TorqueControl` register, the transaction is completed by the torque state.
+    #[cfg(feature = "runtime")]
+    pub fn request_torque_state(self) -> Transaction<TorqueState> {
+        self.ram_transaction(ReadableRamAddr::TorqueControl, |packet| {
+            decode_ram_u8(packet).and_then(|code| TorqueState::try_from(code).ok())
+        })
+    }
+
+    /// Read the `InpositionMargin` register, the transaction is completed by the margin.
+    #[cfg(feature = "runtime")]
+    pub fn request_inposition_margin(self) -> Transaction<InpositionMargin> {
+        self.ram_transaction(ReadableRamAddr::InpositionMargin, |packet| {
+            decode_ram_u8(packet).map(InpositionMargin::new)
+        })
+    }
+
+    /// Read the `StatusDetail` register, the transaction is completed by every bit of the
+    /// register, where several flags can be set at once.
+    #[cfg(feature = "runtime")]
+    pub fn request_status_detail(self) -> Transaction<u8> {
+        self.ram_transaction(ReadableRamAddr::StatusDetail, decode_ram_u8)
+    }
+
+    /// Read both status registers with a single read of two bytes at `StatusError`, which is
+    /// followed by `StatusDetail`. The transaction is completed by the content of both registers.
+    #[cfg(feature = "runtime")]
+    pub fn request_status(self) -> Transaction<StatusPair> {
+        let addr = ReadableRamAddr::StatusError;
+        let request = MessageBuilder::new_with_id(self.id)
+            .read_ram(addr, 2)
+            .build();
+        let kind = AckKind::RamRead { addr, len: 2 };
+        self.transaction(request, kind, |packet| match packet.cmd {
+            Command::RamRead { data } if data.data_len == 2 => {
+                Some(StatusPair::from_bits(data.data[0], data.data[1]))
+            }
+            _ => None,
+        })
+    }
+
     /// Request the servo to enable torque.
     pub fn enable_torque(self) -> HerkulexMessage {
         MessageBuilder::new_with_id(self.id)
-            .write_ram(WritableRamAddr::TorqueControl(0x60))
+            .write_ram_unchecked(WritableRamAddr::TorqueControl(0x60))
             .build()
     }
 
+    /// Enable the torque gradually, to avoid the jolt of a servo enabled far from its goal
+    /// position : the `MaxPWM` register is lowered, the torque is enabled, then `MaxPWM` is raised
+    /// back to `MAX_PWM` in `steps` steps. See [`TorqueRamp`](struct.TorqueRamp.html).
+    pub fn soft_enable_torque(self, steps: u8) -> TorqueRamp {
+        TorqueRamp {
+            servo: self,
+            steps: steps.max(1),
+            target: MAX_PWM,
+            interval: 0,
+            index: 0,
+            next: None,
+        }
+    }
+
     /// Request the servo to disable torque.
     pub fn disable_torque(self) -> HerkulexMessage {
         MessageBuilder::new_with_id(self.id)
-            .write_ram(WritableRamAddr::TorqueControl(0))
+            .write_ram_unchecked(WritableRamAddr::TorqueControl(0))
             .build()
     }
 
     /// Clear the error register of the servo.
     pub fn clear_errors(self) -> HerkulexMessage {
         MessageBuilder::new_with_id(self.id)
-            .write_ram(WritableRamAddr::StatusError(0))
+            .write_ram_unchecked(WritableRamAddr::StatusError(0))
             .build()
     }
+
+    /// Create the messages clearing the alarms latched by the servo : both status registers are
+    /// zeroed by a single write. If `cycle_torque` is true, the torque is disabled before and
+    /// enabled after, for the alarms which released it through the `TorquePolicy` register.
+    pub fn clear_alarms(self, cycle_torque: bool) -> ArrayVec<[HerkulexMessage; 3]> {
+        let status = u8::from(WritableRamAddr::StatusError(0));
+        let mut messages = ArrayVec::new();
+        // The two status registers are in the RAM map : this write is never refused
+        if let Ok(clear) =
+            MessageBuilder::new_with_id(self.id).write_ram_bytes(status, [0u8, 0].iter().cloned())
+        {
+            if cycle_torque {
+                messages.push(self.disable_torque());
+                messages.push(clear.build());
+                messages.push(self.enable_torque());
+            } else {
+                messages.push(clear.build());
+            }
+        }
+        messages
+    }
+}
+
+/// The messages enabling the torque of a servo gradually, created by
+/// [`Servo::soft_enable_torque`](struct.Servo.html#method.soft_enable_torque).
+///
+/// The messages can be sent one after the other with the `Iterator` implementation, or spaced
+/// in time with [`next_message`](#method.next_message).
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::Servo;
+///
+/// // With a 1 kHz tick, raise the PWM limit every 50 ms.
+/// let mut ramp = Servo::new(0x40).soft_enable_torque(10).with_interval(50);
+/// let now = 0;
+/// while let Some(message) = ramp.next_message(now) {
+///     // Send the message ...
+/// }
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct TorqueRamp {
+    servo: Servo,
+    steps: u8,
+    target: u16,
+    interval: u32,
+    index: u8,
+    next: Option<u32>,
+}
+
+impl TorqueRamp {
+    /// Raise `MaxPWM` up to `pwm` instead of `MAX_PWM`.
+    pub fn up_to(mut self, pwm: u16) -> TorqueRamp {
+        self.target = pwm.min(MAX_PWM);
+        self
+    }
+
+    /// Set the number of ticks between two messages sent by `next_message`.
+    pub fn with_interval(mut self, interval: u32) -> TorqueRamp {
+        self.interval = interval;
+        self
+    }
+
+    /// Return the next message if it is due at `now`. The first message is due immediately, the
+    /// next ones every `interval` ticks. The tick is allowed to wrap around.
+    pub fn next_message(&mut self, now: u32) -> Option<HerkulexMessage> {
+        if let Some(next) = self.next {
+            if (now.wrapping_sub(next) as i32) < 0 {
+                return None;
+            }
+        }
+        let message = self.next()?;
+        self.next = Some(now.wrapping_add(self.interval));
+        Some(message)
+    }
+
+    /// Return true if every message was sent.
+    pub fn is_finished(&self) -> bool {
+        self.index > self.steps
+    }
+
+    fn max_pwm(&self, step: u8) -> HerkulexMessage {
+        let pwm = (u32::from(self.target) * u32::from(step) / u32::from(self.steps)) as u16;
+        self.servo
+            .ram_write_unchecked(WritableRamAddr::MaxPWM(pwm as u8, (pwm >> 8) as u8))
+    }
+}
+
+impl Iterator for TorqueRamp {
+    type Item = HerkulexMessage;
+
+    fn next(&mut self) -> Option<HerkulexMessage> {
+        let message = match self.index {
+            0 => self.max_pwm(1),
+            1 => self.servo.enable_torque(),
+            index if index <= self.steps => self.max_pwm(index),
+            _ => return None,
+        };
+        self.index += 1;
+        Some(message)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "eep")]
+    use addr::WritableEEPAddr;
+    use addr::WritableRamAddr;
+    #[cfg(all(feature = "eep", feature = "runtime"))]
+    use addr::{EEPReadData, ReadableEEPAddr};
+    #[cfg(feature = "runtime")]
+    use addr::{RamReadData, ReadableRamAddr};
+    use builder::{MessageBuilder, MessageBuilderError};
+    #[cfg(feature = "eep")]
+    use config::BaudRate;
+    #[cfg(feature = "runtime")]
+    use config::TorqueState;
+    use message::{JogColor, JogMode, Rotation};
+    use model::{ContinuousControl, Model};
+    #[cfg(feature = "runtime")]
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+    use reg::{self, Register, Writable};
+    use servo::{Servo, ServoOptions, BROADCAST_ID};
+
+    #[test]
+    fn pwm() {
+        let servo = Servo::new(0x40);
+        assert_eq!(
+            servo.set_pwm(-300),
+            servo.set_speed(300, Rotation::Clockwise)
+        );
+        assert_eq!(
+            servo.set_pwm(i16::MIN),
+            servo.set_speed(1023, Rotation::Clockwise)
+        );
+        assert_eq!(servo.set_pwm(512)[8..10], [0x00, 0x02]);
+        assert_eq!(Model::Drs0201.continuous_control(), ContinuousControl::Pwm);
+    }
+
+    #[test]
+    fn pwm_offset() {
+        let servo = Servo::new(0x40);
+        assert_eq!(
+            servo.set_pwm_offset(-2),
+            servo.ram_write(WritableRamAddr::PWMOffset(0xFE)).unwrap()
+        );
+        assert_eq!(servo.set_holding_bias(0.5), servo.set_pwm_offset(63));
+        assert_eq!(servo.set_holding_bias(-3.0), servo.set_pwm_offset(-127));
+    }
+
+    #[test]
+    fn options() {
+        let servo = Servo::new(0x40)
+            .with_playtime(30)
+            .with_color(JogColor::Green);
+        assert_eq!(
+            servo.options(),
+            ServoOptions {
+                default_playtime: 30,
+                default_color: JogColor::Green,
+            }
+        );
+        assert_eq!(
+            servo.set_position(512),
+            MessageBuilder::new_with_id(0x40)
+                .s_jog(30, JogMode::Normal { position: 512 }, JogColor::Green, 0x40)
+                .build()
+        );
+        let servo = servo.with_options(ServoOptions::default());
+        assert_eq!(servo.options().default_playtime, 60);
+    }
+
+    #[cfg(feature = "runtime")]
+    #[test]
+    fn telemetry_requests() {
+        let servo = Servo::new(0xFD);
+        let answer = |addr, value| ACKPacket {
+            pid: 0xFD,
+            cmd: Command::RamRead {
+                data: RamReadData {
+                    addr,
+                    data_len: 1,
+                    data: [value, 0],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        };
+
+        let temperature = servo.request_temperature();
+        assert_eq!(
+            temperature.message().as_slice(),
+            &[0xFF, 0xFF, 0x09, 0xFD, 0x04, 0xC6, 0x38, 0x37, 0x01]
+        );
+        let packet = answer(ReadableRamAddr::Temperature, 0x50);
+        assert_eq!(temperature.complete(&packet), Some(0x50));
+        let packet = answer(ReadableRamAddr::Voltage, 0x70);
+        assert_eq!(servo.request_voltage().complete(&packet), Some(0x70));
+        assert_eq!(temperature.complete(&packet), None);
+        let packet = answer(ReadableRamAddr::TorqueControl, 0x40);
+        assert_eq!(
+            servo.request_torque_state().complete(&packet),
+            Some(TorqueState::Brake)
+        );
+        let packet = answer(ReadableRamAddr::StatusDetail, 0x03);
+        assert_eq!(servo.request_status_detail().complete(&packet), Some(0x03));
+
+        let status = servo.request_status();
+        assert_eq!(
+            status.message().as_slice(),
+            &[0xFF, 0xFF, 0x09, 0xFD, 0x04, 0xC2, 0x3C, 0x30, 0x02]
+        );
+        let mut packet = answer(ReadableRamAddr::StatusError, 0x10);
+        assert_eq!(status.complete(&packet), None);
+        if let Command::RamRead { ref mut data } = packet.cmd {
+            data.data_len = 2;
+            data.data[1] = 0x41;
+        }
+        let pair = status.complete(&packet).unwrap();
+        assert_eq!(
+            (pair.error, pair.detail),
+            (StatusError::OverloadDetected, StatusDetail::MovingFlag)
+        );
+        assert!(pair.is_fault() && pair.is_moving());
+    }
+
+    #[test]
+    fn generic_registers() {
+        let servo = Servo::new(0xFD);
+        assert_eq!(
+            servo.write::<reg::PositionKp>(420),
+            servo.ram_write(WritableRamAddr::PositionKp(0xA4, 0x01))
+        );
+        assert_eq!(
+            servo.write::<reg::PWMOffset>(-2),
+            servo.ram_write(WritableRamAddr::PWMOffset(0xFE))
+        );
+        assert_eq!(
+            Servo::new(BROADCAST_ID).write::<reg::ID>(3),
+            Err(MessageBuilderError::BroadcastWrite(0))
+        );
+    }
+
+    #[cfg(feature = "eep")]
+    #[test]
+    fn generic_eep_registers() {
+        let servo = Servo::new(0xFD);
+        assert_eq!(
+            servo.write::<reg::BaudRate>(0x10),
+            servo.eep_write(WritableEEPAddr::BaudRate(0x10))
+        );
+        assert_eq!(
+            servo.write::<reg::BaudRate>(0x11),
+            Err(MessageBuilderError::InvalidBaudRate(0x11))
+        );
+        assert_eq!(servo.try_write::<reg::BaudRate>(0x11), None);
+        assert_eq!(
+            servo.eep_write(WritableEEPAddr::BaudRate(0x11)),
+            Err(MessageBuilderError::InvalidBaudRate(0x11))
+        );
+        assert_eq!(
+            servo.write_eep::<reg::PositionKp>(420),
+            servo
+                .eep_write(WritableEEPAddr::PositionKp(0xA4, 0x01))
+                .ok()
+        );
+        assert_eq!(servo.write_eep::<reg::TorqueControl>(0x60), None);
+    }
+
+    #[cfg(feature = "runtime")]
+    #[test]
+    fn generic_register_reads() {
+        let servo = Servo::new(0xFD);
+        let position = servo.read::<reg::CalibratedPosition>().unwrap();
+        assert_eq!(
+            position.message(),
+            &servo.ram_request(ReadableRamAddr::CalibratedPosition)
+        );
+        let packet = ACKPacket {
+            pid: 0xFD,
+            cmd: Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::CalibratedPosition,
+                    data_len: 2,
+                    data: [0x00, 0x02],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        };
+        assert_eq!(position.complete(&packet), Some(512));
+
+        #[cfg(feature = "eep")]
+        {
+            let kp = servo.read_eep::<reg::PositionKp>().unwrap();
+            let packet = ACKPacket {
+                cmd: Command::EEPRead {
+                    data: EEPReadData {
+                        addr: ReadableEEPAddr::PositionKp,
+                        data_len: 2,
+                        data: [0xA4, 0x01],
+                    },
+                },
+                ..packet
+            };
+            assert_eq!(kp.complete(&packet), Some(420));
+            assert!(servo.read_eep::<reg::Temperature>().is_none());
+        }
+    }
+
+    #[test]
+    fn broadcast_writes() {
+        let all = Servo::new(BROADCAST_ID);
+        #[cfg(feature = "eep")]
+        assert_eq!(
+            all.set_baud_rate(BaudRate::Baud115200),
+            Err(MessageBuilderError::BroadcastWrite(4))
+        );
+        assert_eq!(
+            all.ram_write(WritableRamAddr::ID(0x11)),
+            Err(MessageBuilderError::BroadcastWrite(0))
+        );
+        #[cfg(feature = "eep")]
+        assert_eq!(
+            all.eep_write(WritableEEPAddr::ID(0x11)),
+            Err(MessageBuilderError::BroadcastWrite(6))
+        );
+        assert_eq!(all.try_write::<reg::ID>(0x11), None);
+        #[cfg(feature = "eep")]
+        assert_eq!(all.write_eep::<reg::BaudRate>(0x10), None);
+        #[cfg(feature = "eep")]
+        assert_eq!(
+            all.eep_write_unchecked(WritableEEPAddr::from(BaudRate::Baud115200)),
+            MessageBuilder::new_with_id(BROADCAST_ID)
+                .write_eep_unchecked(WritableEEPAddr::BaudRate(0x10))
+                .build()
+        );
+        #[cfg(feature = "eep")]
+        assert!(Servo::new(0x40).set_baud_rate(BaudRate::Baud115200).is_ok());
+        assert!(all.ram_write(WritableRamAddr::LEDControl(0x01)).is_ok());
+    }
+
+    #[test]
+    fn registers_without_address() {
+        struct Unmapped;
+
+        impl Register for Unmapped {
+            const NAME: &'static str = "Unmapped";
+            const RAM: Option<u8> = Some(0xF0);
+            const EEP: Option<u8> = None;
+            type Value = u8;
+        }
+
+        impl Writable for Unmapped {}
+
+        let servo = Servo::new(0xFD);
+        assert_eq!(
+            servo.write::<Unmapped>(1),
+            Err(MessageBuilderError::UnmappedRegister("Unmapped"))
+        );
+        assert_eq!(servo.try_write::<Unmapped>(1), None);
+        assert_eq!(
+            servo.try_write::<reg::PositionKp>(420),
+            servo.write::<reg::PositionKp>(420).ok()
+        );
+        #[cfg(feature = "runtime")]
+        {
+            assert!(servo.read::<Unmapped>().is_err());
+            assert!(servo.try_read::<Unmapped>().is_none());
+            assert!(servo.try_read::<reg::Temperature>().is_some());
+        }
+    }
+
+    #[test]
+    fn soft_enable_torque() {
+        let servo = Servo::new(0x40);
+        let max_pwm = |pwm: u16| {
+            servo.ram_write_unchecked(WritableRamAddr::MaxPWM(pwm as u8, (pwm >> 8) as u8))
+        };
+        let messages: std::vec::Vec<_> = servo.soft_enable_torque(4).up_to(1000).collect();
+        assert_eq!(
+            messages,
+            vec![
+                max_pwm(250),
+                servo.enable_torque(),
+                max_pwm(500),
+                max_pwm(750),
+                max_pwm(1000),
+            ]
+        );
+
+        let mut ramp = servo.soft_enable_torque(1).with_interval(10);
+        assert_eq!(ramp.next_message(u32::MAX - 4), Some(max_pwm(1023)));
+        assert_eq!(ramp.next_message(4), None);
+        assert_eq!(ramp.next_message(5), Some(servo.enable_torque()));
+        assert!(ramp.is_finished());
+        assert_eq!(ramp.next_message(100), None);
+    }
+
+    #[test]
+    fn clear_alarms() {
+        let servo = Servo::new(0xFD);
+        let clear = [
+            0xFF, 0xFF, 0x0B, 0xFD, 0x03, 0xC6, 0x38, 0x30, 0x02, 0x00, 0x00,
+        ];
+        let messages = servo.clear_alarms(false);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].as_slice(), &clear);
+        let messages = servo.clear_alarms(true);
+        assert_eq!(messages[0], servo.disable_torque());
+        assert_eq!(messages[1].as_slice(), &clear);
+        assert_eq!(messages[2], servo.enable_torque());
+    }
 }
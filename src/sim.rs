@@ -0,0 +1,961 @@
+//! A simulated servomotor, to test an application without any hardware.
+//!
+//! A [`SimServo`](struct.SimServo.html) holds the RAM and the EEP memory of a servomotor and
+//! answers the requests like the servomotor would, following its `AckPolicy`. It implements
+//! [`Transport`](../transport/trait.Transport.html), so the helpers of this crate can talk to it
+//! directly. Time only advances when [`tick`](struct.SimServo.html#method.tick) is called, once
+//! every 11.2 ms of simulated time.
+//!
+//! Faults can be scheduled with a [`FaultScenario`](struct.FaultScenario.html) to exercise the
+//! health monitoring and recovery paths of an application. The errors they raise are latched in
+//! the `StatusError` register until the application clears it, like on the servomotor.
+//!
+//! By default the servomotor follows its trajectory exactly. A [`LoadModel`](struct.LoadModel.html)
+//! makes it drive an inertia against friction and a load torque instead, with the PWM limited by
+//! the `MaxPWM` register : it then overshoots, converges slowly or stalls, and it stops against
+//! an obstacle, like a gripper closing on an object.
+//!
+//! Several servomotors share a [`VirtualBus`](struct.VirtualBus.html), which forwards every
+//! request to all of them and merges their answers. Servomotors sharing an ID answer at the same
+//! time, and their answers collide like on a real bus.
+//!
+//! This module is available with the `test_support` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::latency::ping;
+//! use drs_0x01::sim::{FaultScenario, SimFault, SimServo};
+//! use drs_0x01::Model;
+//! # let tick = std::cell::Cell::new(0);
+//! # let clock = || { tick.set(tick.get() + 1); tick.get() };
+//!
+//! let mut servo = SimServo::new(0x40, Model::Drs0101);
+//! servo
+//!     .schedule(FaultScenario::new(SimFault::Overload, 10, 5))
+//!     .unwrap();
+//! assert!(ping(&mut servo, &clock, 0x40, 10).is_ok());
+//! ```
+
+use arrayvec::ArrayVec;
+
+use addr::{ReadableRamAddr, EEP_MAP_SIZE, RAM_MAP_SIZE};
+use builder::HerkulexMessage;
+use config::{ServoConfig, TorqueState, MAX_PWM};
+use frame::{
+    RequestCommand, ValidateFrame, IJOG_ENTRY_LEN, MIN_FRAME_LEN, SJOG_ENTRY_LEN, SJOG_OVERHEAD,
+};
+use model::Model;
+use reader::{StatusDetail, StatusError};
+use servo::BROADCAST_ID;
+use test_support::AckFrameBuilder;
+use transport::Transport;
+
+use try_from::TryFrom;
+
+/// The maximum number of fault scenarios a [`SimServo`](struct.SimServo.html) can hold.
+pub const MAX_SCENARIOS: usize = 8;
+
+/// The maximum number of servomotors on a [`VirtualBus`](struct.VirtualBus.html).
+pub const MAX_BUS_SERVOS: usize = 16;
+
+/// The first EEP address copied into the RAM when the servomotor boots.
+const EEP_MIRROR_START: usize = 6;
+
+/// The PWM applied by the position loop of a loaded servomotor, per position away from its
+/// trajectory.
+const STIFFNESS: f32 = 8.0;
+
+// Return the index of the register `addr` in the RAM.
+fn reg(addr: ReadableRamAddr) -> usize {
+    usize::from(u8::from(addr))
+}
+
+/// A fault raised by a [`SimServo`](struct.SimServo.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SimFault {
+    /// The motor is overloaded : the servomotor reports it and releases its torque.
+    Overload,
+    /// The temperature rises above `MaxTemperature`.
+    OverTemperature,
+    /// The input voltage falls below `MinVoltage`.
+    LowVoltage,
+    /// The answers of the servomotor are sent with invalid checksums.
+    ChecksumError,
+}
+
+/// A fault raised by a [`SimServo`](struct.SimServo.html) during a range of ticks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FaultScenario {
+    /// The fault raised
+    pub fault: SimFault,
+    /// The tick from which the fault is raised
+    pub start: u32,
+    /// The number of ticks during which the fault is raised
+    pub duration: u32,
+}
+
+impl FaultScenario {
+    /// Create a scenario raising `fault` from the tick `start` during `duration` ticks.
+    pub fn new(fault: SimFault, start: u32, duration: u32) -> FaultScenario {
+        FaultScenario {
+            fault,
+            start,
+            duration,
+        }
+    }
+
+    /// Return true if the fault is raised at the tick `tick`.
+    pub fn is_active(&self, tick: u32) -> bool {
+        tick >= self.start && tick - self.start < self.duration
+    }
+}
+
+/// The mechanical load driven by a [`SimServo`](struct.SimServo.html).
+///
+/// The torques are expressed in PWM, the velocities in positions per tick : every tick, the PWM
+/// left once the friction and the load torque are overcome changes the velocity by
+/// `PWM / inertia`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LoadModel {
+    /// The PWM needed to change the velocity by one position per tick in one tick
+    pub inertia: f32,
+    /// The PWM opposed to the motion per position per tick of velocity
+    pub friction: f32,
+    /// The PWM opposed to the motion by the load, the servomotor stalls below it
+    pub load_torque: f32,
+    /// The position of an object which the output can not go past
+    pub obstacle: Option<u16>,
+}
+
+impl LoadModel {
+    /// Create a load without obstacle. The inertia is at least 1.
+    pub fn new(inertia: f32, friction: f32, load_torque: f32) -> LoadModel {
+        LoadModel {
+            inertia: inertia.max(1.0),
+            friction,
+            load_torque,
+            obstacle: None,
+        }
+    }
+
+    /// Block the output at `position`, from the side where it is when the obstacle is met.
+    pub fn with_obstacle(mut self, position: u16) -> LoadModel {
+        self.obstacle = Some(position);
+        self
+    }
+}
+
+/// The error returned by a [`SimServo`](struct.SimServo.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SimError {
+    /// The servomotor already holds `MAX_SCENARIOS` scenarios.
+    TooManyScenarios,
+    /// The bus already holds `MAX_BUS_SERVOS` servomotors.
+    BusFull,
+}
+
+/// A servomotor simulated in memory.
+#[derive(Clone, Debug)]
+pub struct SimServo {
+    model: Model,
+    eep: [u8; EEP_MAP_SIZE as usize],
+    ram: [u8; RAM_MAP_SIZE as usize],
+    ticks: u32,
+    position: u16,
+    from: u16,
+    goal: u16,
+    playtime: u8,
+    elapsed: u8,
+    speed: i16,
+    load: Option<LoadModel>,
+    exact: f32,
+    velocity: f32,
+    pwm: i16,
+    side: f32,
+    scenarios: ArrayVec<[FaultScenario; MAX_SCENARIOS]>,
+    pending: HerkulexMessage,
+}
+
+impl SimServo {
+    /// Create a servomotor `id` with the factory configuration of `model`, at the middle of its
+    /// range and with its torque released.
+    pub fn new(id: u8, model: Model) -> SimServo {
+        let config = ServoConfig {
+            id,
+            ..ServoConfig::factory_default(model)
+        };
+        let mut servo = SimServo {
+            model,
+            eep: config.memory(),
+            ram: [0; RAM_MAP_SIZE as usize],
+            ticks: 0,
+            position: 512,
+            from: 512,
+            goal: 512,
+            playtime: 0,
+            elapsed: 0,
+            speed: 0,
+            load: None,
+            exact: 512.0,
+            velocity: 0.0,
+            pwm: 0,
+            side: 0.0,
+            scenarios: ArrayVec::new(),
+            pending: HerkulexMessage::new(),
+        };
+        servo.boot();
+        servo
+    }
+
+    /// Drive `load` instead of following the trajectory exactly.
+    pub fn with_load(mut self, load: LoadModel) -> SimServo {
+        self.load = Some(load);
+        self
+    }
+
+    /// Change the load driven by the servomotor, `None` to follow the trajectory exactly.
+    pub fn set_load(&mut self, load: Option<LoadModel>) {
+        self.load = load;
+        self.velocity = 0.0;
+    }
+
+    /// Return the ID of the servomotor, as stored in its RAM.
+    pub fn id(&self) -> u8 {
+        self.ram[reg(ReadableRamAddr::ID)]
+    }
+
+    /// Return the current position of the servomotor.
+    pub fn position(&self) -> u16 {
+        self.position
+    }
+
+    /// Move the servomotor to `position` at once, as if it was moved by hand.
+    pub fn set_position(&mut self, position: u16) {
+        self.position = position;
+        self.from = position;
+        self.goal = position;
+        self.playtime = 0;
+        self.exact = f32::from(position);
+        self.velocity = 0.0;
+        self.update_registers(0);
+    }
+
+    /// Return the number of ticks simulated.
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// Return the RAM of the servomotor.
+    pub fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    /// Return the EEP memory of the servomotor.
+    pub fn eep(&self) -> &[u8] {
+        &self.eep
+    }
+
+    /// Raise the fault of `scenario` during its range of ticks.
+    ///
+    /// # Errors
+    ///
+    /// Return [SimError::TooManyScenarios](enum.SimError.html) if `MAX_SCENARIOS` scenarios are
+    /// already scheduled.
+    pub fn schedule(&mut self, scenario: FaultScenario) -> Result<(), SimError> {
+        self.scenarios
+            .try_push(scenario)
+            .map_err(|_| SimError::TooManyScenarios)
+    }
+
+    /// Return true if `fault` is raised at the current tick.
+    pub fn is_faulty(&self, fault: SimFault) -> bool {
+        self.scenarios
+            .iter()
+            .any(|scenario| scenario.fault == fault && scenario.is_active(self.ticks))
+    }
+
+    /// Simulate 11.2 ms : move the servomotor, raise the faults and update its registers.
+    pub fn tick(&mut self) {
+        self.ticks = self.ticks.wrapping_add(1);
+        let last = self.position;
+        if self.speed != 0 {
+            let position = i32::from(self.position) + i32::from(self.speed) / 32;
+            self.position = position.rem_euclid(1024) as u16;
+        } else {
+            if self.elapsed < self.playtime {
+                self.elapsed += 1;
+            }
+            match self.load {
+                Some(load) => self.drive(load),
+                None => self.position = self.setpoint(),
+            }
+        }
+        self.raise_faults();
+        self.update_registers(i32::from(self.position) - i32::from(last));
+    }
+
+    /// Process the request `frame`, and return the answer of the servomotor, if any.
+    pub fn handle(&mut self, frame: &[u8]) -> Option<HerkulexMessage> {
+        if frame.validate().is_err() {
+            if frame.len() >= MIN_FRAME_LEN && frame[3] == self.id() {
+                self.latch(
+                    StatusError::InvalidPacket.bits(),
+                    StatusDetail::ChecksumError.bits(),
+                );
+            }
+            return None;
+        }
+        let (pid, data) = (frame[3], &frame[MIN_FRAME_LEN..]);
+        if pid != self.id() && pid != BROADCAST_ID {
+            return None;
+        }
+        let cmd = RequestCommand::try_from(frame[4]).ok()?;
+        let mut ack = AckFrameBuilder::new(self.id(), cmd.into());
+        let is_read = match cmd {
+            RequestCommand::EEPWrite => self.write_memory(false, data),
+            RequestCommand::EEPRead => {
+                ack = AckFrameBuilder::eep_read(self.id(), data[0], self.read_memory(false, data)?);
+                true
+            }
+            RequestCommand::RamWrite => self.write_memory(true, data),
+            RequestCommand::RamRead => {
+                ack = AckFrameBuilder::ram_read(self.id(), data[0], self.read_memory(true, data)?);
+                true
+            }
+            RequestCommand::IJog => {
+                for entry in data.chunks(IJOG_ENTRY_LEN) {
+                    self.jog(entry[0], entry[1], entry[2], entry[3], entry[4]);
+                }
+                false
+            }
+            RequestCommand::SJog => {
+                for entry in data[SJOG_OVERHEAD..].chunks(SJOG_ENTRY_LEN) {
+                    self.jog(entry[0], entry[1], entry[2], entry[3], data[0]);
+                }
+                false
+            }
+            RequestCommand::Stat => true,
+            RequestCommand::Rollback => {
+                self.rollback(data[0] != 0, data[1] != 0);
+                false
+            }
+            RequestCommand::Reboot => {
+                self.boot();
+                false
+            }
+        };
+        self.update_registers(0);
+        let answered = match self.ram[reg(ReadableRamAddr::AckPolicy)] {
+            0 => false,
+            1 => is_read,
+            _ => true,
+        };
+        if pid == BROADCAST_ID || !answered {
+            return None;
+        }
+        let ack = ack.status(
+            self.ram[reg(ReadableRamAddr::StatusError)],
+            self.ram[reg(ReadableRamAddr::StatusDetail)],
+        );
+        if self.is_faulty(SimFault::ChecksumError) {
+            Some(ack.corrupt_checksum().build())
+        } else {
+            Some(ack.build())
+        }
+    }
+
+    // Return the position of the trajectory at the current tick.
+    fn setpoint(&self) -> u16 {
+        if self.elapsed >= self.playtime {
+            return self.goal;
+        }
+        let (from, goal) = (i32::from(self.from), i32::from(self.goal));
+        let travelled = (goal - from) * i32::from(self.elapsed) / i32::from(self.playtime);
+        (from + travelled) as u16
+    }
+
+    // Move the servomotor through `load` toward the trajectory, with the PWM limited by MaxPWM.
+    fn drive(&mut self, load: LoadModel) {
+        let max_pwm = u16::from(self.ram[reg(ReadableRamAddr::MaxPWM)])
+            | u16::from(self.ram[reg(ReadableRamAddr::MaxPWM) + 1]) << 8;
+        let max_pwm = f32::from(max_pwm.min(MAX_PWM));
+        let pwm = if self.ram[reg(ReadableRamAddr::TorqueControl)] == TorqueState::On.code() {
+            (STIFFNESS * (f32::from(self.setpoint()) - self.exact)).clamp(-max_pwm, max_pwm)
+        } else {
+            0.0
+        };
+        // The load torque opposes the motion, and holds the output while the PWM is too low.
+        let direction = if self.velocity != 0.0 {
+            self.velocity
+        } else {
+            pwm
+        };
+        let resistance = load.load_torque.copysign(direction);
+        let velocity =
+            self.velocity + (pwm - load.friction * self.velocity - resistance) / load.inertia;
+        let stopped = self.velocity == 0.0 || velocity * self.velocity <= 0.0;
+        self.velocity = if stopped && (self.velocity != 0.0 || pwm.abs() <= load.load_torque) {
+            0.0
+        } else {
+            velocity
+        };
+        let last = self.exact;
+        self.exact = (self.exact + self.velocity).clamp(0.0, 1023.0);
+        if let Some(obstacle) = load.obstacle.map(f32::from) {
+            if last != obstacle {
+                self.side = last - obstacle;
+            }
+            if (self.exact - obstacle) * self.side < 0.0 {
+                self.exact = obstacle;
+                self.velocity = 0.0;
+            }
+        }
+        self.pwm = pwm as i16;
+        self.position = (self.exact + 0.5) as u16;
+    }
+
+    // Copy the configuration from the EEP memory to the RAM and clear the errors.
+    fn boot(&mut self) {
+        let mirrored = usize::from(EEP_MAP_SIZE) - EEP_MIRROR_START;
+        self.ram = [0; RAM_MAP_SIZE as usize];
+        self.ram[..mirrored].copy_from_slice(&self.eep[EEP_MIRROR_START..]);
+        self.goal = self.position;
+        self.playtime = 0;
+        self.speed = 0;
+        self.update_registers(0);
+    }
+
+    // Reset the EEP memory to the factory configuration.
+    fn rollback(&mut self, skip_id: bool, skip_baud: bool) {
+        let memory = ServoConfig::factory_default(self.model).memory();
+        for (addr, byte) in memory.iter().enumerate() {
+            let kept = (skip_id && addr == 6) || (skip_baud && addr == 4);
+            if !kept {
+                self.eep[addr] = *byte;
+            }
+        }
+    }
+
+    fn read_memory(&self, ram: bool, data: &[u8]) -> Option<&[u8]> {
+        let (start, len) = (usize::from(data[0]), usize::from(data[1]));
+        let memory: &[u8] = if ram { &self.ram } else { &self.eep };
+        memory.get(start..start + len)
+    }
+
+    fn write_memory(&mut self, ram: bool, data: &[u8]) -> bool {
+        let start = usize::from(data[0]);
+        let bytes = &data[MIN_FRAME_LEN - 5..][..usize::from(data[1])];
+        let memory: &mut [u8] = if ram { &mut self.ram } else { &mut self.eep };
+        if let Some(target) = memory.get_mut(start..start + bytes.len()) {
+            target.copy_from_slice(bytes);
+        }
+        false
+    }
+
+    fn jog(&mut self, lsb: u8, msb: u8, set: u8, id: u8, playtime: u8) {
+        if id != self.id() {
+            return;
+        }
+        // The LED bits of the jog are the bits of the LEDControl register, shifted.
+        self.ram[reg(ReadableRamAddr::LEDControl)] = (set >> 2) & 0x07;
+        let torque_on = self.ram[reg(ReadableRamAddr::TorqueControl)] == TorqueState::On.code();
+        if set & 0b0010_0000 != 0 || !torque_on {
+            return;
+        }
+        let value = u16::from(lsb) | u16::from(msb) << 8;
+        if set & 0b0000_0010 != 0 {
+            let speed = (value & 0x03FF) as i16;
+            self.speed = if value & 0x4000 != 0 { -speed } else { speed };
+        } else {
+            self.speed = 0;
+            self.from = self.position;
+            self.goal = value.min(1023);
+            self.playtime = playtime.max(1);
+            self.elapsed = 0;
+        }
+    }
+
+    fn latch(&mut self, error: u8, detail: u8) {
+        self.ram[reg(ReadableRamAddr::StatusError)] |= error;
+        self.ram[reg(ReadableRamAddr::StatusDetail)] |= detail;
+    }
+
+    fn raise_faults(&mut self) {
+        let min_voltage = self.ram[reg(ReadableRamAddr::MinVoltage)];
+        let max_voltage = self.ram[reg(ReadableRamAddr::MaxVoltage)];
+        let max_temperature = self.ram[reg(ReadableRamAddr::MaxTemperature)];
+        let mut voltage = min_voltage / 2 + max_voltage / 2;
+        let mut temperature = max_temperature.saturating_sub(0x40);
+        if self.is_faulty(SimFault::Overload) {
+            self.latch(StatusError::OverloadDetected.bits(), 0);
+            self.ram[reg(ReadableRamAddr::TorqueControl)] = TorqueState::Free.code();
+            self.goal = self.position;
+            self.speed = 0;
+        }
+        if self.is_faulty(SimFault::OverTemperature) {
+            temperature = max_temperature.saturating_add(1);
+            self.latch(StatusError::ExceedTemperatureLimit.bits(), 0);
+        }
+        if self.is_faulty(SimFault::LowVoltage) {
+            voltage = min_voltage.saturating_sub(1);
+            self.latch(StatusError::ExceedInputVoltageLimit.bits(), 0);
+        }
+        self.ram[reg(ReadableRamAddr::Voltage)] = voltage;
+        self.ram[reg(ReadableRamAddr::Temperature)] = temperature;
+    }
+
+    // Refresh the registers describing the motion, `travelled` being the positions travelled
+    // during the last tick.
+    fn update_registers(&mut self, travelled: i32) {
+        if self.ram[reg(ReadableRamAddr::Voltage)] == 0 {
+            self.raise_faults();
+        }
+        let words = [
+            (ReadableRamAddr::CalibratedPosition, self.position),
+            (ReadableRamAddr::AbsolutePosition, self.position),
+            (
+                ReadableRamAddr::DifferentialPosition,
+                travelled as i16 as u16,
+            ),
+            (ReadableRamAddr::PWM, self.pwm as u16),
+            (ReadableRamAddr::AbsoluteGoalPosition, self.goal),
+        ];
+        for &(addr, value) in &words {
+            self.ram[reg(addr)] = value as u8;
+            self.ram[reg(addr) + 1] = (value >> 8) as u8;
+        }
+        self.ram[reg(ReadableRamAddr::Tick)] = self.ticks as u8;
+        let margin = u16::from(self.ram[reg(ReadableRamAddr::InpositionMargin)]);
+        let torque_on = self.ram[reg(ReadableRamAddr::TorqueControl)] == TorqueState::On.code();
+        let mut detail = self.ram[reg(ReadableRamAddr::StatusDetail)]
+            & !(StatusDetail::MovingFlag.bits()
+                | StatusDetail::ImpositionFlag.bits()
+                | StatusDetail::MotorOnFlag.bits());
+        if travelled != 0 || self.speed != 0 || self.velocity != 0.0 || self.elapsed < self.playtime
+        {
+            detail |= StatusDetail::MovingFlag.bits();
+        }
+        if self.position.abs_diff(self.goal) <= margin {
+            detail |= StatusDetail::ImpositionFlag.bits();
+        }
+        if torque_on {
+            detail |= StatusDetail::MotorOnFlag.bits();
+        }
+        self.ram[reg(ReadableRamAddr::StatusDetail)] = detail;
+    }
+}
+
+impl Transport for SimServo {
+    type Error = ();
+
+    fn write(&mut self, frame: &[u8]) -> Result<(), ()> {
+        if let Some(answer) = self.handle(frame) {
+            self.pending.extend(
+                answer
+                    .iter()
+                    .cloned()
+                    .take(self.pending.capacity() - self.pending.len()),
+            );
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+        let len = buf.len().min(self.pending.len());
+        for (dst, src) in buf.iter_mut().zip(self.pending.drain(..len)) {
+            *dst = src;
+        }
+        Ok(len)
+    }
+}
+
+/// The way a [`VirtualBus`](struct.VirtualBus.html) merges the answers sent at the same time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Arbitration {
+    /// The answers are sent one after the other, in the order of the servomotors.
+    Sequential,
+    /// The answers collide : every bit is low when it is low in any answer, as on an idle-high
+    /// UART line.
+    Collide,
+}
+
+/// A bus shared by several simulated servomotors.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::latency::ping;
+/// use drs_0x01::sim::{SimServo, VirtualBus};
+/// use drs_0x01::Model;
+/// # let tick = std::cell::Cell::new(0);
+/// # let clock = || { tick.set(tick.get() + 1); tick.get() };
+///
+/// let mut bus = VirtualBus::new();
+/// bus.add(SimServo::new(0x40, Model::Drs0101)).unwrap();
+/// bus.add(SimServo::new(0x41, Model::Drs0201)).unwrap();
+/// assert!(ping(&mut bus, &clock, 0x41, 10).is_ok());
+/// assert!(ping(&mut bus, &clock, 0x42, 10).is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct VirtualBus {
+    servos: ArrayVec<[SimServo; MAX_BUS_SERVOS]>,
+    arbitration: Arbitration,
+    pending: ArrayVec<[u8; 256]>,
+}
+
+impl Default for VirtualBus {
+    fn default() -> VirtualBus {
+        VirtualBus::new()
+    }
+}
+
+impl VirtualBus {
+    /// Create a bus without any servomotor, on which the answers sent together collide.
+    pub fn new() -> VirtualBus {
+        VirtualBus {
+            servos: ArrayVec::new(),
+            arbitration: Arbitration::Collide,
+            pending: ArrayVec::new(),
+        }
+    }
+
+    /// Merge the answers sent together with `arbitration`.
+    pub fn with_arbitration(mut self, arbitration: Arbitration) -> VirtualBus {
+        self.arbitration = arbitration;
+        self
+    }
+
+    /// Connect `servo` to the bus. Several servomotors can share an ID.
+    ///
+    /// # Errors
+    ///
+    /// Return [SimError::BusFull](enum.SimError.html) if `MAX_BUS_SERVOS` servomotors are already
+    /// connected.
+    pub fn add(&mut self, servo: SimServo) -> Result<(), SimError> {
+        self.servos.try_push(servo).map_err(|_| SimError::BusFull)
+    }
+
+    /// Disconnect the servomotors `id`, and return how many were disconnected.
+    pub fn remove(&mut self, id: u8) -> usize {
+        let len = self.servos.len();
+        self.servos.retain(|servo| servo.id() != id);
+        len - self.servos.len()
+    }
+
+    /// Return the first servomotor `id` connected to the bus.
+    pub fn servo(&mut self, id: u8) -> Option<&mut SimServo> {
+        self.servos.iter_mut().find(|servo| servo.id() == id)
+    }
+
+    /// Return the servomotors connected to the bus, in the order in which they were connected.
+    pub fn servos(&mut self) -> &mut [SimServo] {
+        &mut self.servos
+    }
+
+    /// Simulate 11.2 ms on every servomotor.
+    pub fn tick(&mut self) {
+        for servo in self.servos.iter_mut() {
+            servo.tick();
+        }
+    }
+}
+
+impl Transport for VirtualBus {
+    type Error = ();
+
+    fn write(&mut self, frame: &[u8]) -> Result<(), ()> {
+        let start = self.pending.len();
+        for servo in self.servos.iter_mut() {
+            let answer = match servo.handle(frame) {
+                Some(answer) => answer,
+                None => continue,
+            };
+            let sent = self.pending.len() - start;
+            if self.arbitration == Arbitration::Collide && sent > 0 {
+                for (byte, &bit) in self.pending[start..].iter_mut().zip(answer.iter()) {
+                    *byte &= bit;
+                }
+                let tail = answer.iter().skip(sent).cloned();
+                self.pending
+                    .extend(tail.take(self.pending.capacity() - self.pending.len()));
+            } else {
+                let free = self.pending.capacity() - self.pending.len();
+                self.pending.extend(answer.iter().cloned().take(free));
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+        let len = buf.len().min(self.pending.len());
+        for (dst, src) in buf.iter_mut().zip(self.pending.drain(..len)) {
+            *dst = src;
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{ReadableRamAddr, WritableRamAddr};
+    use conflict::{Error, IdConflictDetector};
+    use gripper::{GraspOutcome, Gripper};
+    use model::Model;
+    use reader::{ACKReader, StatusError};
+    use reg as register;
+    use servo::Servo;
+    use sim::{
+        reg, Arbitration, FaultScenario, LoadModel, SimError, SimFault, SimServo, VirtualBus,
+        MAX_BUS_SERVOS, MAX_SCENARIOS,
+    };
+    use transport::Transport;
+
+    fn exchange(servo: &mut SimServo, frame: &[u8], reader: &mut ACKReader) {
+        servo.write(frame).unwrap();
+        let mut buf = [0; 32];
+        let read = servo.read(&mut buf).unwrap();
+        reader.parse(&buf[..read]);
+    }
+
+    #[test]
+    fn requests() {
+        let mut servo = SimServo::new(0x40, Model::Drs0101);
+        let mut reader = ACKReader::new();
+        let control = Servo::new(0x40);
+
+        // The torque is released : the servomotor does not move.
+        exchange(&mut servo, &control.set_position(600), &mut reader);
+        assert_eq!(reader.available_messages(), 0);
+        servo.tick();
+        assert_eq!(servo.position(), 512);
+
+        exchange(&mut servo, &control.enable_torque(), &mut reader);
+        exchange(
+            &mut servo,
+            &control.with_playtime(4).set_position(600),
+            &mut reader,
+        );
+        for _ in 0..2 {
+            servo.tick();
+        }
+        assert_eq!(servo.position(), 556);
+        exchange(&mut servo, &control.stat(), &mut reader);
+        assert!(reader.pop_ack_packet().unwrap().is_moving());
+        for _ in 0..2 {
+            servo.tick();
+        }
+        let transaction = control.request_position();
+        exchange(&mut servo, transaction.message(), &mut reader);
+        assert_eq!(
+            transaction.complete(&reader.pop_ack_packet().unwrap()),
+            Some(600)
+        );
+
+        // Writes are only answered once the AckPolicy asks for it.
+        exchange(
+            &mut servo,
+            &control.ram_write(WritableRamAddr::LEDControl(1)).unwrap(),
+            &mut reader,
+        );
+        assert_eq!(reader.available_messages(), 0);
+        exchange(
+            &mut servo,
+            &control.ram_write(WritableRamAddr::AckPolicy(2)).unwrap(),
+            &mut reader,
+        );
+        assert_eq!(reader.available_messages(), 1);
+        exchange(&mut servo, &Servo::new(0x41).stat(), &mut reader);
+        assert_eq!(reader.available_messages(), 1);
+        exchange(&mut servo, &control.reboot(), &mut reader);
+        assert_eq!(servo.ram()[reg(ReadableRamAddr::AckPolicy)], 1);
+    }
+
+    #[test]
+    fn faults() {
+        let mut servo = SimServo::new(0x40, Model::Drs0201);
+        let mut reader = ACKReader::new();
+        let control = Servo::new(0x40);
+        servo
+            .schedule(FaultScenario::new(SimFault::Overload, 2, 1))
+            .unwrap();
+        servo
+            .schedule(FaultScenario::new(SimFault::LowVoltage, 2, 2))
+            .unwrap();
+        servo
+            .schedule(FaultScenario::new(SimFault::ChecksumError, 5, 1))
+            .unwrap();
+        for _ in 3..MAX_SCENARIOS {
+            servo
+                .schedule(FaultScenario::new(SimFault::OverTemperature, 100, 1))
+                .unwrap();
+        }
+        assert_eq!(
+            servo.schedule(FaultScenario::new(SimFault::Overload, 0, 1)),
+            Err(SimError::TooManyScenarios)
+        );
+
+        exchange(&mut servo, &control.enable_torque(), &mut reader);
+        servo.tick();
+        exchange(&mut servo, &control.stat(), &mut reader);
+        assert!(!reader.pop_ack_packet().unwrap().is_fault());
+        servo.tick();
+        assert!(servo.is_faulty(SimFault::Overload));
+        assert_eq!(servo.ram()[reg(ReadableRamAddr::TorqueControl)], 0x00);
+
+        // The errors stay latched until they are cleared.
+        for _ in 0..2 {
+            servo.tick();
+        }
+        exchange(&mut servo, &control.stat(), &mut reader);
+        let packet = reader.pop_ack_packet().unwrap();
+        assert_eq!(packet.error_raw, 0x11);
+        assert_eq!(packet.error, StatusError::ExceedInputVoltageLimit);
+        exchange(
+            &mut servo,
+            &control.ram_write(WritableRamAddr::StatusError(0)).unwrap(),
+            &mut reader,
+        );
+        servo.tick();
+        exchange(&mut servo, &control.stat(), &mut reader);
+        assert_eq!(reader.available_messages(), 0);
+        assert_eq!(reader.stats().checksum_errors, 1);
+        servo.tick();
+        exchange(&mut servo, &control.stat(), &mut reader);
+        assert!(!reader.pop_ack_packet().unwrap().is_fault());
+    }
+
+    #[test]
+    fn load() {
+        let control = Servo::new(0x40).with_playtime(10);
+        let mut reader = ACKReader::new();
+
+        // A light load overshoots its goal, then settles on it.
+        let mut servo =
+            SimServo::new(0x40, Model::Drs0101).with_load(LoadModel::new(20.0, 1.0, 0.0));
+        exchange(&mut servo, &control.enable_torque(), &mut reader);
+        exchange(&mut servo, &control.set_position(600), &mut reader);
+        let mut highest = 0;
+        for _ in 0..20 {
+            servo.tick();
+            highest = highest.max(servo.position());
+        }
+        assert!(highest > 600);
+        for _ in 0..100 {
+            servo.tick();
+        }
+        assert!(servo.position().abs_diff(600) <= 2);
+
+        // A heavy load stalls the servomotor until its PWM is high enough.
+        servo.set_load(Some(LoadModel::new(20.0, 1.0, 300.0)));
+        exchange(
+            &mut servo,
+            &control.write::<register::MaxPWM>(250).unwrap(),
+            &mut reader,
+        );
+        exchange(&mut servo, &control.set_position(400), &mut reader);
+        for _ in 0..20 {
+            servo.tick();
+        }
+        assert!(servo.position().abs_diff(600) <= 2);
+        assert_eq!(servo.ram()[reg(ReadableRamAddr::PWM)..][..2], [0x06, 0xFF]);
+        exchange(
+            &mut servo,
+            &control.write::<register::MaxPWM>(1023).unwrap(),
+            &mut reader,
+        );
+        for _ in 0..20 {
+            servo.tick();
+        }
+        assert!(servo.position() < 500);
+
+        // A gripper closing on an object detects the grasp.
+        let mut servo = SimServo::new(0x40, Model::Drs0101)
+            .with_load(LoadModel::new(20.0, 1.0, 50.0).with_obstacle(650));
+        let mut gripper = Gripper::new(0x40, 500, 700).with_pwm(400, 150);
+        exchange(&mut servo, &control.enable_torque(), &mut reader);
+        for message in gripper.close().iter() {
+            exchange(&mut servo, message, &mut reader);
+        }
+        let mut outcome = None;
+        while outcome.is_none() && servo.ticks() < 200 {
+            servo.tick();
+            exchange(
+                &mut servo,
+                control.read::<register::PWM>().unwrap().message(),
+                &mut reader,
+            );
+            exchange(
+                &mut servo,
+                control.request_position().message(),
+                &mut reader,
+            );
+            while let Some(packet) = reader.pop_ack_packet() {
+                outcome = outcome.or(gripper.update(&packet));
+            }
+        }
+        assert_eq!(outcome, Some(GraspOutcome::Grasped { position: 650 }));
+    }
+
+    #[test]
+    fn bus() {
+        let read_position = |bus: &mut VirtualBus, id: u8, detector: &mut IdConflictDetector| {
+            let mut reader = ACKReader::new();
+            detector.request(id);
+            bus.write(Servo::new(id).request_position().message())
+                .unwrap();
+            let mut buf = [0; 64];
+            let read = bus.read(&mut buf).unwrap();
+            reader.parse_with(&buf[..read], detector);
+            if reader.stats().checksum_errors > 0 {
+                detector.check_checksum_error().err()
+            } else {
+                None
+            }
+        };
+        for &arbitration in &[Arbitration::Sequential, Arbitration::Collide] {
+            let mut bus = VirtualBus::new().with_arbitration(arbitration);
+            for &id in &[0x40, 0x41, 0x42, 0x42] {
+                bus.add(SimServo::new(id, Model::Drs0101)).unwrap();
+            }
+            // Identical answers collide into a valid frame : make them differ.
+            bus.servos()[3].set_position(300);
+            let mut detector = IdConflictDetector::new();
+            for &id in &[0x40, 0x41, 0x43] {
+                assert_eq!(read_position(&mut bus, id, &mut detector), None);
+            }
+            assert_eq!(detector.take_error(), Ok(()));
+            let mut conflict = None;
+            for _ in 0..3 {
+                conflict = conflict.or(read_position(&mut bus, 0x42, &mut detector));
+            }
+            assert_eq!(
+                conflict.map(Err).unwrap_or_else(|| detector.take_error()),
+                Err(Error::IdConflict(0x42))
+            );
+        }
+
+        // Broadcast requests are not answered.
+        let mut bus = VirtualBus::new();
+        for id in 0..MAX_BUS_SERVOS as u8 {
+            bus.add(SimServo::new(id, Model::Drs0101)).unwrap();
+        }
+        assert_eq!(
+            bus.add(SimServo::new(0x40, Model::Drs0101)),
+            Err(SimError::BusFull)
+        );
+        bus.write(&Servo::new(0xFE).enable_torque()).unwrap();
+        bus.write(&Servo::new(0xFE).stat()).unwrap();
+        assert_eq!(bus.read(&mut [0; 16]), Ok(0));
+        bus.tick();
+        assert!(bus
+            .servos()
+            .iter()
+            .all(|servo| servo.ticks() == 1
+                && servo.ram()[reg(ReadableRamAddr::TorqueControl)] == 0x60));
+        assert_eq!(bus.remove(3), 1);
+        assert!(bus.servo(3).is_none());
+    }
+}
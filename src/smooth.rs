@@ -0,0 +1,234 @@
+//! Long moves split in small interpolated steps.
+//!
+//! A single **S_JOG** request with a long playtime makes the servomotor accelerate as hard as it
+//! can, which can be jerky for a long move. A [`SmoothMover`](struct.SmoothMover.html) splits
+//! the move into segments sent at a fixed rate, whose targets follow an
+//! [`Easing`](enum.Easing.html) curve, or a velocity [`Profile`](../trajectory/struct.Profile.html).
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::smooth::{Easing, SmoothMover};
+//!
+//! // With a 1 kHz tick, move from 200 to 800 in 20 segments sent every 50 ms.
+//! let mut mover = SmoothMover::new(0x40, 200, 800, 20, 50)
+//!     .with_easing(Easing::SCurve)
+//!     .with_playtime(5);
+//! let mut now = 0;
+//! while !mover.is_finished() {
+//!     if let Some(message) = mover.tick(now) {
+//!         // Send the message ...
+//!     }
+//!     now += 1;
+//! }
+//! ```
+
+use builder::{HerkulexMessage, MessageBuilder};
+use message::{JogColor, JogMode};
+use trajectory::Profile;
+
+/// How the targets of the segments are spread between the start and the end of a move.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Easing {
+    /// The targets are evenly spaced, the speed is constant.
+    Linear,
+    /// The targets are closer at both ends, the speed ramps up and down smoothly.
+    SCurve,
+}
+
+impl Easing {
+    /// Return the fraction of the move done at the fraction `t` of its duration, both between 0
+    /// and 1.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::SCurve => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// The curve followed by the targets of a [`SmoothMover`](struct.SmoothMover.html).
+#[derive(Copy, Clone, Debug)]
+enum Curve {
+    Eased { from: u16, to: u16, easing: Easing },
+    Profile { profile: Profile, step: f32 },
+}
+
+/// This struct sends a move of a servomotor as a series of **S_JOG** requests.
+#[derive(Clone, Debug)]
+pub struct SmoothMover {
+    id: u8,
+    curve: Curve,
+    segments: u16,
+    period: u32,
+    playtime: u8,
+    color: JogColor,
+    start: Option<u32>,
+    sent: u16,
+}
+
+impl SmoothMover {
+    /// Create a move of the servomotor `id` from the position `from` to the position `to`, split
+    /// in `segments` segments (at least 1) sent every `period` ticks.
+    pub fn new(id: u8, from: u16, to: u16, segments: u16, period: u32) -> SmoothMover {
+        SmoothMover {
+            id,
+            curve: Curve::Eased {
+                from,
+                to,
+                easing: Easing::Linear,
+            },
+            segments: segments.max(1),
+            period,
+            playtime: 0,
+            color: JogColor::Green,
+            start: None,
+            sent: 0,
+        }
+    }
+
+    /// Create a move of the servomotor `id` following `profile`, with segments sent every
+    /// `period` ticks of a clock counting `tick_hz` ticks per second.
+    pub fn from_profile(id: u8, profile: &Profile, period: u32, tick_hz: u32) -> SmoothMover {
+        let step = period as f32 / tick_hz as f32;
+        let segments = (profile.duration() / step) as u16;
+        let segments = if step * f32::from(segments) < profile.duration() {
+            segments + 1
+        } else {
+            segments
+        };
+        SmoothMover {
+            curve: Curve::Profile {
+                profile: *profile,
+                step,
+            },
+            ..SmoothMover::new(id, 0, 0, segments, period)
+        }
+    }
+
+    /// Set the easing of the move, which is linear by default. It has no effect on a move
+    /// following a profile.
+    pub fn with_easing(mut self, easing: Easing) -> SmoothMover {
+        if let Curve::Eased {
+            easing: ref mut current,
+            ..
+        } = self.curve
+        {
+            *current = easing;
+        }
+        self
+    }
+
+    /// Set the playtime of every segment (in units of 11.2ms). It is 0 by default, it should be
+    /// close to the period of the segments for the servomotor to move continuously.
+    pub fn with_playtime(mut self, playtime: u8) -> SmoothMover {
+        self.playtime = playtime;
+        self
+    }
+
+    /// Set the color of the LED of the servomotor during the move.
+    pub fn with_color(mut self, color: JogColor) -> SmoothMover {
+        self.color = color;
+        self
+    }
+
+    /// Return the target of the segment `segment`, between 1 and the number of segments.
+    pub fn target(&self, segment: u16) -> u16 {
+        let position = match self.curve {
+            Curve::Eased { from, to, easing } => {
+                let t = easing.apply(f32::from(segment) / f32::from(self.segments));
+                let from = f32::from(from);
+                from + (f32::from(to) - from) * t
+            }
+            Curve::Profile { profile, step } => profile.position(f32::from(segment) * step),
+        };
+        (position + 0.5) as u16
+    }
+
+    /// Return the request of the next segment if it is due at `now`. The first segment is due at
+    /// the first call.
+    pub fn tick(&mut self, now: u32) -> Option<HerkulexMessage> {
+        if self.is_finished() {
+            return None;
+        }
+        let start = *self.start.get_or_insert(now);
+        if now.wrapping_sub(start) < u32::from(self.sent) * self.period {
+            return None;
+        }
+        self.sent += 1;
+        let position = self.target(self.sent);
+        Some(
+            MessageBuilder::new_with_id(self.id)
+                .s_jog(
+                    self.playtime,
+                    JogMode::Normal { position },
+                    self.color,
+                    self.id,
+                )
+                .build(),
+        )
+    }
+
+    /// Return true if every segment was sent.
+    pub fn is_finished(&self) -> bool {
+        self.sent >= self.segments
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use builder::MessageBuilder;
+    use message::{JogColor, JogMode};
+    use smooth::{Easing, SmoothMover};
+    use std::vec::Vec;
+    use trajectory::{Profile, Shape};
+
+    #[test]
+    fn segments() {
+        assert_eq!(Easing::SCurve.apply(0.5), 0.5);
+        assert!(Easing::SCurve.apply(0.1) < Easing::Linear.apply(0.1));
+        assert_eq!(Easing::SCurve.apply(2.0), 1.0);
+
+        let mut mover = SmoothMover::new(0x40, 800, 200, 4, 10).with_playtime(1);
+        let segment = |position| {
+            MessageBuilder::new_with_id(0x40)
+                .s_jog(1, JogMode::Normal { position }, JogColor::Green, 0x40)
+                .build()
+        };
+        assert_eq!(mover.tick(100), Some(segment(650)));
+        assert_eq!(mover.tick(105), None);
+        assert_eq!(mover.tick(110), Some(segment(500)));
+        assert_eq!(mover.tick(120), Some(segment(350)));
+        assert!(!mover.is_finished());
+        assert_eq!(mover.tick(130), Some(segment(200)));
+        assert!(mover.is_finished());
+        assert_eq!(mover.tick(140), None);
+
+        let curve = SmoothMover::new(0x40, 0, 1000, 4, 10).with_easing(Easing::SCurve);
+        assert_eq!(
+            [
+                curve.target(1),
+                curve.target(2),
+                curve.target(3),
+                curve.target(4)
+            ],
+            [156, 500, 844, 1000]
+        );
+
+        // With a 1 kHz tick, a segment every 250 ms along a move lasting 2 s.
+        let profile = Profile::new(Shape::Trapezoid, 200.0, 800.0, 400.0, 800.0);
+        let along = SmoothMover::from_profile(0x40, &profile, 250, 1000);
+        assert_eq!(
+            (1..9)
+                .map(|segment| along.target(segment))
+                .collect::<Vec<_>>(),
+            [225, 300, 400, 500, 600, 700, 775, 800]
+        );
+        assert_eq!(
+            SmoothMover::from_profile(0x40, &profile, 300, 1000).segments,
+            7
+        );
+    }
+}
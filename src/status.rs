@@ -0,0 +1,224 @@
+//! The latest status reported by every servomotor.
+//!
+//! Every ACK carries the status registers of the servomotor which sent it, so a
+//! [`StatusCache`](struct.StatusCache.html) fed with every parsed packet always knows the latest
+//! state of each servomotor, without keeping the packets around. The predicates of
+//! [`ServoStatus`](struct.ServoStatus.html) answer the usual questions without decoding the
+//! status registers.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::reader::{ACKReader, StatusDetail};
+//! use drs_0x01::status::StatusCache;
+//!
+//! let mut reader = ACKReader::new();
+//! let mut cache = StatusCache::new();
+//! reader.parse_with(&[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01], &mut cache);
+//! assert_eq!(cache.status(0xFD).unwrap().detail, StatusDetail::MovingFlag);
+//! assert!(cache.status(0xFD).unwrap().is_moving());
+//! ```
+
+use addr::ReadableRamAddr;
+use reader::{ACKHandler, ACKPacket, Command, StatusDetail, StatusError};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The number of servomotor IDs, the broadcast ID excluded.
+const SERVO_IDS: usize = 0xFE;
+
+/// The status reported by a servomotor in its last packet.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ServoStatus {
+    /// The content of the status error register
+    pub error: StatusError,
+    /// The content of the status detail register
+    pub detail: StatusDetail,
+    /// The raw content of the status error register, with every error set
+    pub error_raw: u8,
+    /// The raw content of the status detail register, with every flag set
+    pub detail_raw: u8,
+    /// The raw value of the last read of the `Temperature` register, if any
+    pub temperature: Option<u8>,
+    /// The sequence number of the packet, which grows with every packet fed to the cache
+    pub sequence: u32,
+}
+
+impl ServoStatus {
+    /// Return true if the servomotor was moving.
+    pub fn is_moving(&self) -> bool {
+        self.detail_raw & StatusDetail::MovingFlag.bits() != 0
+    }
+
+    /// Return true if the servomotor was within the inposition margin of its goal.
+    pub fn in_position(&self) -> bool {
+        self.detail_raw & StatusDetail::ImpositionFlag.bits() != 0
+    }
+
+    /// Return true if the motor was powered.
+    pub fn is_motor_on(&self) -> bool {
+        self.detail_raw & StatusDetail::MotorOnFlag.bits() != 0
+    }
+
+    /// Return the error reported by the servomotor, if any. When several errors are set, the
+    /// error of the lowest bit is returned, the others are in `error_raw`.
+    pub fn fault(&self) -> Option<StatusError> {
+        match self.error {
+            StatusError::NoError => None,
+            error => Some(error),
+        }
+    }
+
+    /// Return the raw temperature last read, if any. The conversion to degrees is the non linear
+    /// table of the datasheet, the `MaxTemperature` register uses the same encoding.
+    pub fn temperature(&self) -> Option<u8> {
+        self.temperature
+    }
+}
+
+/// This struct stores the latest status of every servomotor.
+/// Please note that this structure will allocate roughly 2 kiB of stack.
+#[derive(Clone)]
+pub struct StatusCache {
+    statuses: [Option<ServoStatus>; SERVO_IDS],
+    sequence: u32,
+}
+
+impl Default for StatusCache {
+    fn default() -> StatusCache {
+        StatusCache {
+            statuses: [None; SERVO_IDS],
+            sequence: 0,
+        }
+    }
+}
+
+impl StatusCache {
+    /// Create an empty cache.
+    pub fn new() -> StatusCache {
+        StatusCache::default()
+    }
+
+    /// Store the status carried by `packet`, and return its sequence number.
+    pub fn update(&mut self, packet: &ACKPacket) -> u32 {
+        self.sequence = self.sequence.wrapping_add(1);
+        if let Some(status) = self.statuses.get_mut(usize::from(packet.pid)) {
+            let temperature = match packet.cmd {
+                Command::RamRead { data } if data.addr == ReadableRamAddr::Temperature => {
+                    Some(data.data[0])
+                }
+                _ => status.and_then(|status| status.temperature),
+            };
+            *status = Some(ServoStatus {
+                error: packet.error,
+                detail: packet.detail,
+                error_raw: packet.error_raw,
+                detail_raw: packet.detail_raw,
+                temperature,
+                sequence: self.sequence,
+            });
+        }
+        self.sequence
+    }
+
+    /// Return the latest status of the servomotor `id`, if it sent any packet.
+    pub fn status(&self, id: u8) -> Option<ServoStatus> {
+        self.statuses
+            .get(usize::from(id))
+            .and_then(|status| *status)
+    }
+
+    /// Return the sequence number of the last packet fed to the cache.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// Forget the status of the servomotor `id`.
+    pub fn forget(&mut self, id: u8) {
+        if let Some(status) = self.statuses.get_mut(usize::from(id)) {
+            *status = None;
+        }
+    }
+
+    /// Forget every status. The sequence number keeps growing.
+    pub fn clear(&mut self) {
+        self.statuses = [None; SERVO_IDS];
+    }
+}
+
+impl ACKHandler for StatusCache {
+    fn on_packet(&mut self, packet: &ACKPacket) {
+        self.update(packet);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{RamReadData, ReadableRamAddr};
+    use reader::{decode_ack, stat_ack, ACKPacket, Command, StatusDetail, StatusError};
+    use status::{ServoStatus, StatusCache};
+    use test_support::AckFrameBuilder;
+
+    #[test]
+    fn latest_status() {
+        let mut cache = StatusCache::new();
+        assert_eq!(cache.status(0x40), None);
+        cache.update(&stat_ack(0x40, StatusError::OverloadDetected));
+        cache.update(&stat_ack(0x41, StatusError::NoError));
+        assert_eq!(cache.update(&stat_ack(0x40, StatusError::NoError)), 3);
+        assert_eq!(
+            cache.status(0x40),
+            Some(ServoStatus {
+                error: StatusError::NoError,
+                detail: StatusDetail::NoDetail,
+                error_raw: 0x00,
+                detail_raw: 0x00,
+                temperature: None,
+                sequence: 3,
+            })
+        );
+        assert_eq!(cache.status(0x41).unwrap().sequence, 2);
+
+        cache.forget(0x41);
+        assert_eq!(cache.status(0x41), None);
+        cache.update(&stat_ack(0xFE, StatusError::NoError));
+        assert_eq!(cache.status(0xFE), None);
+        cache.clear();
+        assert_eq!(cache.status(0x40), None);
+        assert_eq!(cache.sequence(), 4);
+    }
+
+    #[test]
+    fn predicates() {
+        let mut cache = StatusCache::new();
+        cache.update(&ACKPacket::new(
+            0x40,
+            Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::Temperature,
+                    data_len: 1,
+                    data: [0x9F, 0x00],
+                },
+            },
+            StatusError::NoError,
+            StatusDetail::MotorOnFlag,
+        ));
+        let status = cache.status(0x40).unwrap();
+        assert!(status.is_motor_on() && !status.is_moving() && !status.in_position());
+        assert_eq!(status.fault(), None);
+        assert_eq!(status.temperature(), Some(0x9F));
+
+        // An overtemperature reported along with an overload.
+        cache.update(&decode_ack(
+            &AckFrameBuilder::stat(0x40).status(0x14, 0x03).build(),
+        ));
+        let status = cache.status(0x40).unwrap();
+        assert!(status.is_moving() && status.in_position() && !status.is_motor_on());
+        assert_eq!(status.fault(), Some(StatusError::ExceedTemperatureLimit));
+        assert_eq!(status.error_raw, 0x14);
+        assert_eq!(status.temperature(), Some(0x9F));
+    }
+}
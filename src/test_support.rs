@@ -0,0 +1,239 @@
+//! Construction of arbitrary ACK frames, valid or not, to test the integration of a reader.
+//!
+//! An [`AckFrameBuilder`](struct.AckFrameBuilder.html) produces a valid frame by default, every
+//! field of which can then be overridden : checksums, size, header, command, address or status.
+//! This allows to check how an application behaves when the bus delivers corrupted frames.
+//!
+//! This module is available with the `test-support` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::reader::ACKReader;
+//! use drs_0x01::test_support::AckFrameBuilder;
+//!
+//! let mut reader = ACKReader::new();
+//! reader.parse(&AckFrameBuilder::stat(0xFD).corrupt_checksum().build());
+//! assert_eq!(reader.stats().checksum_errors, 1);
+//! reader.parse(&AckFrameBuilder::ram_read(0xFD, 0x3A, &[0x00, 0x02]).build());
+//! assert_eq!(reader.stats().packets, 1);
+//! ```
+
+use arrayvec::ArrayVec;
+
+use builder::HerkulexMessage;
+use checksum::{checksum1, checksum2};
+
+/// The maximum number of data bytes (address and length included) of a frame.
+pub const MAX_ACK_DATA: usize = 32;
+
+/// A builder of ACK frames, whose fields default to the values of a valid frame.
+#[derive(Clone, Debug)]
+pub struct AckFrameBuilder {
+    header: [u8; 2],
+    psize: Option<u8>,
+    pid: u8,
+    cmd: u8,
+    chk1: Option<u8>,
+    chk2: Option<u8>,
+    data: ArrayVec<[u8; MAX_ACK_DATA]>,
+    status: [u8; 2],
+}
+
+impl AckFrameBuilder {
+    /// Create the answer of the servomotor `pid` to the request command `request` (0x01 for
+    /// **EEP_WRITE**, 0x07 for **STAT** ...), without any data and without error.
+    pub fn new(pid: u8, request: u8) -> AckFrameBuilder {
+        AckFrameBuilder {
+            header: [0xFF, 0xFF],
+            psize: None,
+            pid,
+            cmd: request.wrapping_add(0x40),
+            chk1: None,
+            chk2: None,
+            data: ArrayVec::new(),
+            status: [0x00, 0x00],
+        }
+    }
+
+    /// Create the answer of the servomotor `pid` to a **STAT** request.
+    pub fn stat(pid: u8) -> AckFrameBuilder {
+        AckFrameBuilder::new(pid, 0x07)
+    }
+
+    /// Create the answer of the servomotor `pid` to a **RAM_READ** of `data.len()` bytes at
+    /// `addr`. Only the first `MAX_ACK_DATA - 2` bytes of `data` are kept.
+    pub fn ram_read(pid: u8, addr: u8, data: &[u8]) -> AckFrameBuilder {
+        AckFrameBuilder::new(pid, 0x04).read_data(addr, data)
+    }
+
+    /// Create the answer of the servomotor `pid` to a **EEP_READ** of `data.len()` bytes at
+    /// `addr`. Only the first `MAX_ACK_DATA - 2` bytes of `data` are kept.
+    pub fn eep_read(pid: u8, addr: u8, data: &[u8]) -> AckFrameBuilder {
+        AckFrameBuilder::new(pid, 0x02).read_data(addr, data)
+    }
+
+    fn read_data(mut self, addr: u8, data: &[u8]) -> AckFrameBuilder {
+        let data = &data[..data.len().min(MAX_ACK_DATA - 2)];
+        self.data.clear();
+        self.data.push(addr);
+        self.data.push(data.len() as u8);
+        self.data.extend(data.iter().cloned());
+        self
+    }
+
+    /// Replace the two header bytes.
+    pub fn header(mut self, header: [u8; 2]) -> AckFrameBuilder {
+        self.header = header;
+        self
+    }
+
+    /// Replace the packet size, which is computed from the content of the frame by default.
+    pub fn psize(mut self, psize: u8) -> AckFrameBuilder {
+        self.psize = Some(psize);
+        self
+    }
+
+    /// Replace the command byte of the ACK.
+    pub fn cmd(mut self, cmd: u8) -> AckFrameBuilder {
+        self.cmd = cmd;
+        self
+    }
+
+    /// Replace the data length announced by a read ACK, without changing the data.
+    pub fn data_len(mut self, len: u8) -> AckFrameBuilder {
+        if self.data.len() > 1 {
+            self.data[1] = len;
+        }
+        self
+    }
+
+    /// Set the raw status error and status detail registers.
+    pub fn status(mut self, error: u8, detail: u8) -> AckFrameBuilder {
+        self.status = [error, detail];
+        self
+    }
+
+    /// Replace the first checksum, which is computed from the content of the frame by default.
+    pub fn checksum1(mut self, chk1: u8) -> AckFrameBuilder {
+        self.chk1 = Some(chk1);
+        self
+    }
+
+    /// Replace the second checksum, which is computed from the first one by default.
+    pub fn checksum2(mut self, chk2: u8) -> AckFrameBuilder {
+        self.chk2 = Some(chk2);
+        self
+    }
+
+    /// Give the frame a wrong first checksum, keeping the second checksum consistent with it so
+    /// that only the comparison with the content of the frame fails.
+    pub fn corrupt_checksum(self) -> AckFrameBuilder {
+        let chk1 = self.valid_checksum1() ^ 0x02;
+        self.checksum1(chk1).checksum2(checksum2(chk1))
+    }
+
+    fn size(&self) -> u8 {
+        self.psize
+            .unwrap_or((self.data.len() + self.status.len() + 7) as u8)
+    }
+
+    fn valid_checksum1(&self) -> u8 {
+        checksum1(&[self.size(), self.pid, self.cmd])
+            ^ checksum1(&self.data)
+            ^ checksum1(&self.status)
+    }
+
+    /// Build the frame.
+    pub fn build(&self) -> HerkulexMessage {
+        let chk1 = self.chk1.unwrap_or_else(|| self.valid_checksum1());
+        let chk2 = self.chk2.unwrap_or_else(|| checksum2(chk1));
+        let mut frame = HerkulexMessage::new();
+        frame.extend(self.header.iter().cloned());
+        frame.extend(
+            [self.size(), self.pid, self.cmd, chk1, chk2]
+                .iter()
+                .cloned(),
+        );
+        frame.extend(self.data.iter().cloned());
+        frame.extend(self.status.iter().cloned());
+        frame
+    }
+}
+
+#[cfg(all(test, feature = "runtime"))]
+mod test {
+    use addr::{RamReadData, ReadableRamAddr};
+    #[cfg(feature = "testvectors")]
+    use frame::AckCommand;
+    use reader::{ACKReader, Command};
+    use test_support::AckFrameBuilder;
+    #[cfg(feature = "testvectors")]
+    use testvectors::ACKS;
+
+    #[test]
+    fn valid_frames() {
+        assert_eq!(
+            AckFrameBuilder::stat(0xFD)
+                .status(0x00, 0x01)
+                .build()
+                .as_slice(),
+            &[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01]
+        );
+    }
+
+    #[cfg(feature = "testvectors")]
+    #[test]
+    fn vector_frames() {
+        for vector in ACKS {
+            let mut reader = ACKReader::new();
+            reader.parse(vector.frame);
+            let packet = reader.pop_ack_packet().unwrap();
+            let frame = match packet.cmd {
+                Command::RamRead { data } => AckFrameBuilder::ram_read(
+                    packet.pid,
+                    data.addr.into(),
+                    &data.data[..data.data_len as usize],
+                ),
+                Command::EEPRead { data } => AckFrameBuilder::eep_read(
+                    packet.pid,
+                    data.addr.into(),
+                    &data.data[..data.data_len as usize],
+                ),
+                cmd => AckFrameBuilder::new(packet.pid, AckCommand::from(cmd).request().into()),
+            };
+            let frame = frame.status(
+                vector.frame[vector.frame.len() - 2],
+                vector.frame[vector.frame.len() - 1],
+            );
+            assert_eq!(frame.build().as_slice(), vector.frame, "{}", vector.name);
+        }
+    }
+
+    #[test]
+    fn invalid_frames() {
+        let mut reader = ACKReader::new();
+        reader.parse(&AckFrameBuilder::stat(0xFD).corrupt_checksum().build());
+        reader.parse(&AckFrameBuilder::stat(0xFD).checksum2(0x00).build());
+        assert_eq!(reader.stats().checksum_errors, 2);
+
+        reader.parse(&AckFrameBuilder::ram_read(0xFD, 0x3A, &[0x00, 0x02]).build());
+        assert_eq!(
+            reader.pop_ack_packet().unwrap().cmd,
+            Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::CalibratedPosition,
+                    data_len: 2,
+                    data: [0x00, 0x02],
+                },
+            }
+        );
+        let frame = AckFrameBuilder::ram_read(0xFD, 0x3A, &[0x00, 0x02])
+            .psize(0x20)
+            .build();
+        assert_eq!(frame[2], 0x20);
+        let frame = AckFrameBuilder::stat(0xFD).header([0xFF, 0x00]).build();
+        assert_eq!(&frame[..2], &[0xFF, 0x00]);
+    }
+}
@@ -0,0 +1,215 @@
+//! Known-good frames which can be used to validate a transport implementation.
+//!
+//! The request frames are the ones produced by this crate for the datasheet examples, and the ACK
+//! frames are decoded by an [`ACKReader`](../reader/struct.ACKReader.html) into the associated
+//! [`ACKPacket`](../reader/struct.ACKPacket.html).
+//!
+//! This module is available with the `testvectors` feature.
+
+use addr::{EEPReadData, RamReadData, ReadableEEPAddr, ReadableRamAddr};
+use reader::{ACKPacket, Command, StatusDetail, StatusError};
+
+/// A request frame sent to a servomotor.
+#[derive(Debug)]
+pub struct RequestVector {
+    /// A short description of the frame
+    pub name: &'static str,
+    /// The raw frame, header included
+    pub frame: &'static [u8],
+}
+
+/// An ACK frame sent by a servomotor, and the packet it must be decoded into.
+#[derive(Debug)]
+pub struct AckVector {
+    /// A short description of the frame
+    pub name: &'static str,
+    /// The raw frame, header included
+    pub frame: &'static [u8],
+    /// The packet decoded from `frame`
+    pub packet: ACKPacket,
+}
+
+/// Request frames, all addressed to the servomotor 0xFD.
+pub const REQUESTS: &[RequestVector] = &[
+    RequestVector {
+        name: "REBOOT",
+        frame: &[0xFF, 0xFF, 0x07, 0xFD, 0x09, 0xF2, 0x0C],
+    },
+    RequestVector {
+        name: "STAT",
+        frame: &[0xFF, 0xFF, 0x07, 0xFD, 0x07, 0xFC, 0x02],
+    },
+    RequestVector {
+        name: "ROLLBACK skipping ID and baudrate",
+        frame: &[0xFF, 0xFF, 0x09, 0xFD, 0x08, 0xFC, 0x02, 0x01, 0x01],
+    },
+    RequestVector {
+        name: "RAM_READ LEDControl",
+        frame: &[0xFF, 0xFF, 0x09, 0xFD, 0x04, 0xC4, 0x3A, 0x35, 0x01],
+    },
+    RequestVector {
+        name: "RAM_WRITE LEDControl green",
+        frame: &[0xFF, 0xFF, 0x0A, 0xFD, 0x03, 0xC0, 0x3E, 0x35, 0x01, 0x01],
+    },
+    RequestVector {
+        name: "RAM_WRITE TorqueControl on",
+        frame: &[0xFF, 0xFF, 0x0A, 0xFD, 0x03, 0xA0, 0x5E, 0x34, 0x01, 0x60],
+    },
+    RequestVector {
+        name: "EEP_READ PositionKp, 4 bytes",
+        frame: &[0xFF, 0xFF, 0x09, 0xFD, 0x02, 0xEC, 0x12, 0x1E, 0x04],
+    },
+    RequestVector {
+        name: "SJOG position 512, green, playtime 60",
+        frame: &[
+            0xFF, 0xFF, 0x0C, 0xFD, 0x06, 0x30, 0xCE, 0x3C, 0x00, 0x02, 0x04, 0xFD,
+        ],
+    },
+    RequestVector {
+        name: "IJOG position 512, green, playtime 60",
+        frame: &[
+            0xFF, 0xFF, 0x0C, 0xFD, 0x05, 0x32, 0xCC, 0x00, 0x02, 0x04, 0xFD, 0x3C,
+        ],
+    },
+];
+
+/// ACK frames, all sent by the servomotor 0xFD.
+pub const ACKS: &[AckVector] = &[
+    AckVector {
+        name: "SJOG ACK",
+        frame: &[0xFF, 0xFF, 0x09, 0xFD, 0x46, 0xB2, 0x4C, 0x08, 0x08],
+        packet: ACKPacket {
+            pid: 0xFD,
+            cmd: Command::SJog,
+            error: StatusError::InvalidPacket,
+            detail: StatusDetail::UnknownCommand,
+            error_raw: 0x08,
+            detail_raw: 0x08,
+        },
+    },
+    AckVector {
+        name: "STAT ACK",
+        frame: &[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01],
+        packet: ACKPacket {
+            pid: 0xFD,
+            cmd: Command::Stat,
+            error: StatusError::NoError,
+            detail: StatusDetail::MovingFlag,
+            error_raw: 0x00,
+            detail_raw: 0x01,
+        },
+    },
+    AckVector {
+        name: "RAM_READ LEDControl ACK",
+        frame: &[
+            0xFF, 0xFF, 0x0C, 0xFD, 0x44, 0x80, 0x7E, 0x35, 0x01, 0x01, 0x00, 0x00,
+        ],
+        packet: ACKPacket {
+            pid: 0xFD,
+            cmd: Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::LEDControl,
+                    data_len: 1,
+                    data: [0x01, 0x00],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        },
+    },
+    AckVector {
+        name: "RAM_READ CalibratedPosition ACK",
+        frame: &[
+            0xFF, 0xFF, 0x0D, 0xFD, 0x44, 0x8E, 0x70, 0x3A, 0x02, 0x00, 0x02, 0x00, 0x01,
+        ],
+        packet: ACKPacket {
+            pid: 0xFD,
+            cmd: Command::RamRead {
+                data: RamReadData {
+                    addr: ReadableRamAddr::CalibratedPosition,
+                    data_len: 2,
+                    data: [0x00, 0x02],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::MovingFlag,
+            error_raw: 0x00,
+            detail_raw: 0x01,
+        },
+    },
+    AckVector {
+        name: "EEP_READ PositionKp ACK",
+        frame: &[
+            0xFF, 0xFF, 0x0D, 0xFD, 0x42, 0x16, 0xE8, 0x1E, 0x02, 0xB8, 0x01, 0x00, 0x00,
+        ],
+        packet: ACKPacket {
+            pid: 0xFD,
+            cmd: Command::EEPRead {
+                data: EEPReadData {
+                    addr: ReadableEEPAddr::PositionKp,
+                    data_len: 2,
+                    data: [0xB8, 0x01],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        },
+    },
+];
+
+#[cfg(test)]
+mod test {
+    use addr::{ReadableEEPAddr, ReadableRamAddr, WritableRamAddr};
+    use builder::MessageBuilder;
+    use message::{JogColor, JogMode, Rollback};
+    use reader::ACKReader;
+    use testvectors::{ACKS, REQUESTS};
+
+    #[test]
+    fn requests_vectors() {
+        let builder = || MessageBuilder::new_with_id(0xFD);
+        let messages = [
+            builder().reboot().build(),
+            builder().stat().build(),
+            builder().rollback(Rollback::SkipBoth).build(),
+            builder()
+                .read_ram(ReadableRamAddr::LEDControl, None)
+                .build(),
+            builder()
+                .write_ram_unchecked(WritableRamAddr::LEDControl(1))
+                .build(),
+            builder()
+                .write_ram_unchecked(WritableRamAddr::TorqueControl(0x60))
+                .build(),
+            builder().read_eep(ReadableEEPAddr::PositionKp, 4).build(),
+            builder()
+                .s_jog(60, JogMode::Normal { position: 512 }, JogColor::Green, 0xFD)
+                .build(),
+            builder()
+                .i_jog(60, JogMode::Normal { position: 512 }, JogColor::Green, 0xFD)
+                .build(),
+        ];
+        assert_eq!(messages.len(), REQUESTS.len());
+        for (message, vector) in messages.iter().zip(REQUESTS) {
+            assert_eq!(message.as_slice(), vector.frame, "{}", vector.name);
+        }
+    }
+
+    #[test]
+    fn acks_vectors() {
+        for vector in ACKS {
+            let mut reader = ACKReader::new();
+            reader.parse(vector.frame);
+            assert_eq!(
+                reader.pop_ack_packet().as_ref(),
+                Some(&vector.packet),
+                "{}",
+                vector.name
+            );
+        }
+    }
+}
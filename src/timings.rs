@@ -0,0 +1,107 @@
+//! Transmission times of the frames, derived from the baudrate.
+//!
+//! The time a request takes to be answered depends on the baudrate : the request and its ACK are
+//! both sent byte after byte, 10 bits each (a start bit, 8 data bits and a stop bit). Rather than
+//! guessing a timeout, [`for_baud`](fn.for_baud.html) computes the transmission times and a
+//! recommended minimum reply timeout for a baudrate.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::timings;
+//!
+//! let timings = timings::for_baud(115_200);
+//! assert_eq!(timings.byte_us, 87);
+//! // With a 1 kHz clock, the timeout given to `latency::ping`.
+//! assert_eq!(timings.reply_timeout_ticks(1_000), 5);
+//! ```
+
+use config::BaudRate;
+use frame::{MAX_ACK_LEN, MEM_OVERHEAD, MIN_FRAME_LEN};
+
+/// The number of bits sent on the wire for every byte of a frame.
+pub const BITS_PER_BYTE: u32 = 10;
+
+/// The time allowed to a servomotor between the end of a request and the start of its ACK, in
+/// microseconds.
+pub const PROCESSING_TIME_US: u32 = 1_000;
+
+/// The length of the longest read request.
+const READ_REQUEST_LEN: usize = MIN_FRAME_LEN + MEM_OVERHEAD;
+
+/// The transmission times of a baudrate. Every duration is in microseconds.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Timings {
+    /// The baudrate, in bits per second
+    pub bps: u32,
+    /// The time to send a byte, rounded up
+    pub byte_us: u32,
+    /// The time to send the longest ACK
+    pub max_ack_us: u32,
+    /// The recommended minimum time to wait for the ACK of a read request, from the start of the
+    /// request
+    pub reply_timeout_us: u32,
+}
+
+/// Return the transmission times at `bps` bits per second, which must not be 0.
+pub fn for_baud(bps: u32) -> Timings {
+    let byte_us = (BITS_PER_BYTE * 1_000_000).div_ceil(bps);
+    let mut timings = Timings {
+        bps,
+        byte_us,
+        max_ack_us: 0,
+        reply_timeout_us: 0,
+    };
+    timings.max_ack_us = timings.frame_us(MAX_ACK_LEN);
+    timings.reply_timeout_us = timings.reply_timeout_us(READ_REQUEST_LEN);
+    timings
+}
+
+impl Timings {
+    /// Return the time to send a frame of `len` bytes.
+    pub fn frame_us(&self, len: usize) -> u32 {
+        self.byte_us * len as u32
+    }
+
+    /// Return the recommended minimum time to wait for the ACK of a request of `request_len`
+    /// bytes, from the start of the request. It leaves room for an ACK twice as long as the
+    /// longest one.
+    pub fn reply_timeout_us(&self, request_len: usize) -> u32 {
+        self.frame_us(request_len) + PROCESSING_TIME_US + 2 * self.max_ack_us
+    }
+
+    /// Return `reply_timeout_us` in ticks of a clock counting `tick_hz` ticks per second, rounded
+    /// up.
+    pub fn reply_timeout_ticks(&self, tick_hz: u32) -> u32 {
+        (u64::from(self.reply_timeout_us) * u64::from(tick_hz)).div_ceil(1_000_000) as u32
+    }
+}
+
+impl BaudRate {
+    /// Return the transmission times of this baudrate.
+    pub fn timings(self) -> Timings {
+        for_baud(self.bps())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use config::BaudRate;
+    use frame::MAX_ACK_LEN;
+    use timings;
+
+    #[test]
+    fn for_baud() {
+        let slow = BaudRate::Baud57600.timings();
+        assert_eq!(slow.byte_us, 174);
+        assert_eq!(slow.max_ack_us, 174 * MAX_ACK_LEN as u32);
+        assert_eq!(slow.reply_timeout_us, 174 * 9 + 1_000 + 2 * 174 * 13);
+
+        let fast = timings::for_baud(666_666);
+        assert_eq!(fast.byte_us, 16);
+        assert!(fast.reply_timeout_us < slow.reply_timeout_us);
+        assert_eq!(fast.reply_timeout_ticks(1_000), 2);
+        assert_eq!(fast.reply_timeout_ticks(1_000_000), fast.reply_timeout_us);
+    }
+}
@@ -0,0 +1,233 @@
+//! Velocity profiles of a move, independent from any transport.
+//!
+//! A [`Profile`](struct.Profile.html) gives the position and the velocity of a move at any time,
+//! limited by a maximum velocity and a maximum acceleration. It can be sampled in a control loop
+//! of the application, or sent as segments by a
+//! [`SmoothMover`](../smooth/struct.SmoothMover.html).
+//!
+//! Positions are in raw units, and times in seconds.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::trajectory::{Profile, Shape};
+//!
+//! // Move from 200 to 800 at 400 units/s at most, accelerating at 800 units/s².
+//! let profile = Profile::new(Shape::Trapezoid, 200.0, 800.0, 400.0, 800.0);
+//! assert_eq!(profile.duration(), 2.0);
+//! assert_eq!(profile.position(1.0), 500.0);
+//! assert_eq!(profile.velocity(1.0), 400.0);
+//! ```
+
+/// The shape of the acceleration and deceleration ramps of a [`Profile`](struct.Profile.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Shape {
+    /// The acceleration is constant during the ramps, the velocity is a trapezoid.
+    Trapezoid,
+    /// The acceleration rises and falls smoothly during the ramps, limiting the jerk.
+    SCurve,
+}
+
+impl Shape {
+    /// Return the ramp time for every unit of velocity reached at a maximum acceleration of 1.
+    fn ramp_factor(self) -> f32 {
+        match self {
+            Shape::Trapezoid => 1.0,
+            Shape::SCurve => 1.5,
+        }
+    }
+
+    /// Return the fraction of the peak velocity reached at the fraction `s` of a ramp.
+    fn velocity(self, s: f32) -> f32 {
+        match self {
+            Shape::Trapezoid => s,
+            Shape::SCurve => s * s * (3.0 - 2.0 * s),
+        }
+    }
+
+    /// Return the distance travelled at the fraction `s` of a ramp, for a ramp of duration 1 to
+    /// a peak velocity of 1. A whole ramp travels 0.5.
+    fn distance(self, s: f32) -> f32 {
+        match self {
+            Shape::Trapezoid => s * s / 2.0,
+            Shape::SCurve => s * s * s * (1.0 - s / 2.0),
+        }
+    }
+}
+
+/// Return the square root of `x`, which is not provided by `core`.
+fn sqrt(x: f32) -> f32 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    let mut root = if x > 1.0 { x } else { 1.0 };
+    for _ in 0..32 {
+        let next = (root + x / root) / 2.0;
+        if next >= root {
+            break;
+        }
+        root = next;
+    }
+    root
+}
+
+/// A move from a position to another, with a limited velocity and acceleration.
+///
+/// The move accelerates to its peak velocity, cruises, then decelerates symmetrically. Short
+/// moves do not reach the maximum velocity and do not cruise.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Profile {
+    shape: Shape,
+    from: f32,
+    to: f32,
+    peak_velocity: f32,
+    ramp_time: f32,
+    cruise_time: f32,
+}
+
+impl Profile {
+    /// Create the fastest move from `from` to `to` with a velocity up to `max_velocity` and an
+    /// acceleration up to `max_acceleration`. Both limits must be positive, otherwise the move
+    /// is instantaneous.
+    pub fn new(
+        shape: Shape,
+        from: f32,
+        to: f32,
+        max_velocity: f32,
+        max_acceleration: f32,
+    ) -> Profile {
+        let mut profile = Profile {
+            shape,
+            from,
+            to,
+            peak_velocity: 0.0,
+            ramp_time: 0.0,
+            cruise_time: 0.0,
+        };
+        let distance = (to - from).abs();
+        if distance == 0.0 || max_velocity <= 0.0 || max_acceleration <= 0.0 {
+            return profile;
+        }
+        let factor = shape.ramp_factor();
+        // Both ramps together travel `peak_velocity * ramp_time`.
+        let peak_velocity = max_velocity.min(sqrt(distance * max_acceleration / factor));
+        profile.peak_velocity = peak_velocity;
+        profile.ramp_time = factor * peak_velocity / max_acceleration;
+        profile.cruise_time = (distance / peak_velocity - profile.ramp_time).max(0.0);
+        profile
+    }
+
+    /// Return the shape of the ramps.
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    /// Return the start position.
+    pub fn from(&self) -> f32 {
+        self.from
+    }
+
+    /// Return the end position.
+    pub fn to(&self) -> f32 {
+        self.to
+    }
+
+    /// Return the duration of the move.
+    pub fn duration(&self) -> f32 {
+        2.0 * self.ramp_time + self.cruise_time
+    }
+
+    /// Return the highest speed reached during the move.
+    pub fn peak_velocity(&self) -> f32 {
+        self.peak_velocity
+    }
+
+    fn direction(&self) -> f32 {
+        if self.to < self.from {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Return the position at the time `t` since the start of the move.
+    pub fn position(&self, t: f32) -> f32 {
+        let duration = self.duration();
+        if t <= 0.0 {
+            return self.from;
+        } else if t >= duration {
+            return self.to;
+        }
+        let ramp = self.peak_velocity * self.ramp_time;
+        let travelled = if t < self.ramp_time {
+            ramp * self.shape.distance(t / self.ramp_time)
+        } else if t <= self.ramp_time + self.cruise_time {
+            ramp / 2.0 + self.peak_velocity * (t - self.ramp_time)
+        } else {
+            let distance = (self.to - self.from).abs();
+            distance - ramp * self.shape.distance((duration - t) / self.ramp_time)
+        };
+        self.from + self.direction() * travelled
+    }
+
+    /// Return the velocity at the time `t` since the start of the move, negative when the
+    /// position decreases.
+    pub fn velocity(&self, t: f32) -> f32 {
+        let duration = self.duration();
+        if t <= 0.0 || t >= duration {
+            return 0.0;
+        }
+        let fraction = if t < self.ramp_time {
+            self.shape.velocity(t / self.ramp_time)
+        } else if t <= self.ramp_time + self.cruise_time {
+            1.0
+        } else {
+            self.shape.velocity((duration - t) / self.ramp_time)
+        };
+        self.direction() * self.peak_velocity * fraction
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use trajectory::{sqrt, Profile, Shape};
+
+    #[test]
+    fn square_root() {
+        assert_eq!(sqrt(0.0), 0.0);
+        assert_eq!(sqrt(4.0), 2.0);
+        assert!((sqrt(0.25) - 0.5).abs() < 1e-6);
+        assert!((sqrt(600.0 * 800.0) - 692.820_3).abs() < 1e-2);
+    }
+
+    #[test]
+    fn profiles() {
+        let trapezoid = Profile::new(Shape::Trapezoid, 800.0, 200.0, 400.0, 800.0);
+        assert_eq!(trapezoid.duration(), 2.0);
+        assert_eq!(trapezoid.position(0.25), 775.0);
+        assert_eq!(trapezoid.position(0.5), 700.0);
+        assert_eq!(trapezoid.position(1.75), 225.0);
+        assert_eq!(trapezoid.position(3.0), 200.0);
+        assert_eq!(trapezoid.velocity(0.25), -200.0);
+        assert_eq!(trapezoid.velocity(1.0), -400.0);
+
+        // The S-curve ramps are longer, but travel the same distance.
+        let curve = Profile::new(Shape::SCurve, 200.0, 800.0, 400.0, 800.0);
+        assert_eq!(curve.duration(), 2.25);
+        assert_eq!(curve.position(0.75), 350.0);
+        assert_eq!(curve.velocity(0.375), 200.0);
+        assert_eq!(curve.velocity(0.75), 400.0);
+        assert_eq!(curve.position(2.25), 800.0);
+
+        // A short move does not reach the maximum velocity.
+        let short = Profile::new(Shape::Trapezoid, 0.0, 100.0, 400.0, 400.0);
+        assert_eq!(short.peak_velocity(), 200.0);
+        assert_eq!(short.duration(), 1.0);
+        assert_eq!(short.position(0.5), 50.0);
+
+        let still = Profile::new(Shape::SCurve, 512.0, 512.0, 400.0, 800.0);
+        assert_eq!(still.duration(), 0.0);
+        assert_eq!(still.position(1.0), 512.0);
+    }
+}
@@ -0,0 +1,694 @@
+//! Requests bundled with the description of their answer.
+//!
+//! A [`Transaction`](struct.Transaction.html) holds the message to send, the ACK expected in
+//! return and the function decoding this ACK into a typed value. The layer matching the received
+//! packets with the requests sent only needs the transaction to complete it.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! use drs_0x01::reader::ACKReader;
+//! use drs_0x01::Servo;
+//!
+//! let transaction = Servo::new(0xFD).request_position();
+//! // Send transaction.message() ...
+//! let mut reader = ACKReader::new();
+//! reader.parse(&[
+//!     0xFF, 0xFF, 0x0D, 0xFD, 0x44, 0x8E, 0x70, 0x3A, 0x02, 0x00, 0x02, 0x00, 0x01,
+//! ]);
+//! let packet = reader.pop_ack_packet().unwrap();
+//! assert_eq!(transaction.complete(&packet), Some(512));
+//! ```
+
+use addr::ReadableEEPAddr;
+use addr::{RamReadData, ReadableRamAddr};
+use builder::HerkulexMessage;
+#[cfg(feature = "runtime")]
+use clock::Clock;
+use config::AckPolicy;
+use frame::{AckCommand, ExpectedReply, FrameError, RequestCommand, ValidateFrame, MIN_FRAME_LEN};
+#[cfg(feature = "runtime")]
+use reader::ACKReader;
+use reader::{ACKPacket, Command};
+use reg::Register;
+use servo::BROADCAST_ID;
+#[cfg(feature = "runtime")]
+use transport::{bytes_read, Transport};
+use try_from::TryFrom;
+
+/// The command answered by an ACK.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AckKind {
+    /// The answer to a **EEP_WRITE**
+    EEPWrite,
+    /// The answer to a **EEP_READ** of `len` bytes at `addr`
+    EEPRead {
+        /// The address read
+        addr: ReadableEEPAddr,
+        /// The number of bytes read
+        len: u8,
+    },
+    /// The answer to a **RAM_WRITE**
+    RamWrite,
+    /// The answer to a **RAM_READ** of `len` bytes at `addr`
+    RamRead {
+        /// The address read
+        addr: ReadableRamAddr,
+        /// The number of bytes read
+        len: u8,
+    },
+    /// The answer to a **I_JOG**
+    IJog,
+    /// The answer to a **S_JOG**
+    SJog,
+    /// The answer to a **STAT**
+    Stat,
+    /// The answer to a **ROLLBACK**
+    Rollback,
+    /// The answer to a **REBOOT**
+    Reboot,
+}
+
+impl AckKind {
+    /// Return true if `cmd` answers this kind of command.
+    pub fn matches(self, cmd: &Command) -> bool {
+        match (self, *cmd) {
+            (AckKind::EEPRead { addr, len }, Command::EEPRead { data }) => {
+                data.addr == addr && data.data_len == len
+            }
+            (AckKind::RamRead { addr, len }, Command::RamRead { data }) => {
+                data.addr == addr && data.data_len == len
+            }
+            (AckKind::EEPWrite, Command::EEPWrite)
+            | (AckKind::RamWrite, Command::RamWrite)
+            | (AckKind::IJog, Command::IJog)
+            | (AckKind::SJog, Command::SJog)
+            | (AckKind::Stat, Command::Stat)
+            | (AckKind::Rollback, Command::Rollback)
+            | (AckKind::Reboot, Command::Reboot) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The ACK expected in return of a request.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ExpectedAck {
+    /// The ID of the servomotor which must answer
+    pub id: u8,
+    /// The command answered
+    pub kind: AckKind,
+}
+
+impl ExpectedAck {
+    /// Return true if `packet` is the expected ACK.
+    pub fn matches(&self, packet: &ACKPacket) -> bool {
+        packet.pid == self.id && self.kind.matches(&packet.cmd)
+    }
+}
+
+/// A request, the ACK it expects and how to decode this ACK into a `T`.
+#[derive(Clone, Debug)]
+pub struct Transaction<T> {
+    message: HerkulexMessage,
+    expected: Option<ExpectedAck>,
+    decode: fn(&ACKPacket) -> Option<T>,
+    context: u32,
+}
+
+/// The value decoded from the answer to a transaction, and the context given to the transaction
+/// by its sender.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Completion<T> {
+    /// The value decoded
+    pub value: T,
+    /// The context of the transaction
+    pub context: u32,
+}
+
+impl<T> Transaction<T> {
+    /// Create a transaction sending `message`, which expects `expected` in return.
+    /// `decode` is only called with packets matching `expected`.
+    pub fn new(
+        message: HerkulexMessage,
+        expected: Option<ExpectedAck>,
+        decode: fn(&ACKPacket) -> Option<T>,
+    ) -> Transaction<T> {
+        Transaction {
+            message,
+            expected,
+            decode,
+            context: 0,
+        }
+    }
+
+    /// Attach `context` to the transaction, to be returned with its completion : the ID of the
+    /// task waiting for the answer, an index in a table of callbacks ... It is 0 by default.
+    ///
+    /// ```
+    /// # extern crate drs_0x01;
+    /// use drs_0x01::reader::ACKReader;
+    /// use drs_0x01::Servo;
+    ///
+    /// const MOTION_TASK: u32 = 3;
+    /// let transaction = Servo::new(0xFD).request_position().with_context(MOTION_TASK);
+    /// let mut reader = ACKReader::new();
+    /// reader.parse(&[
+    ///     0xFF, 0xFF, 0x0D, 0xFD, 0x44, 0x8E, 0x70, 0x3A, 0x02, 0x00, 0x02, 0x00, 0x01,
+    /// ]);
+    /// let completion = transaction
+    ///     .complete_with_context(&reader.pop_ack_packet().unwrap())
+    ///     .unwrap();
+    /// assert_eq!((completion.value, completion.context), (512, MOTION_TASK));
+    /// ```
+    pub fn with_context(mut self, context: u32) -> Transaction<T> {
+        self.context = context;
+        self
+    }
+
+    /// Return the context attached to the transaction.
+    pub fn context(&self) -> u32 {
+        self.context
+    }
+
+    /// Return the message to send.
+    pub fn message(&self) -> &HerkulexMessage {
+        &self.message
+    }
+
+    /// Return the message to send, dropping the description of the answer.
+    pub fn into_message(self) -> HerkulexMessage {
+        self.message
+    }
+
+    /// Return the ACK expected in return, or `None` if the servomotor does not answer.
+    pub fn expected(&self) -> Option<ExpectedAck> {
+        self.expected
+    }
+
+    /// Return the length of the answer to this transaction by a servomotor whose `AckPolicy`
+    /// register is `policy`, see [`ExpectedReply`](../frame/trait.ExpectedReply.html).
+    pub fn expected_reply_len(&self, policy: AckPolicy) -> Option<usize> {
+        self.expected?;
+        self.message.expected_reply_len(policy)
+    }
+
+    /// Return true if `packet` is the answer to this transaction.
+    pub fn matches(&self, packet: &ACKPacket) -> bool {
+        self.expected
+            .is_some_and(|expected| expected.matches(packet))
+    }
+
+    /// Decode `packet` if it is the answer to this transaction.
+    pub fn complete(&self, packet: &ACKPacket) -> Option<T> {
+        if self.matches(packet) {
+            (self.decode)(packet)
+        } else {
+            None
+        }
+    }
+
+    /// Decode `packet` if it is the answer to this transaction, and return the value with the
+    /// context of the transaction.
+    pub fn complete_with_context(&self, packet: &ACKPacket) -> Option<Completion<T>> {
+        self.complete(packet).map(|value| Completion {
+            value,
+            context: self.context,
+        })
+    }
+}
+
+/// A write to the RAM followed by a read of the same register, created by
+/// [`Servo::ram_write_verified`](../struct.Servo.html#method.ram_write_verified).
+///
+/// The servomotor silently ignores some writes, of values out of range for example : the
+/// transaction is only completed by the read ACK, compared with the value written.
+#[derive(Clone, Debug)]
+pub struct VerifiedWrite {
+    write: HerkulexMessage,
+    read: Transaction<RamReadData>,
+    written: u16,
+}
+
+/// The value read back after a [`VerifiedWrite`](struct.VerifiedWrite.html) is not the value
+/// written.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RejectedWrite {
+    /// The register written
+    pub addr: ReadableRamAddr,
+    /// The value written
+    pub written: u16,
+    /// The value read back
+    pub read: u16,
+}
+
+impl VerifiedWrite {
+    pub(crate) fn new(
+        write: HerkulexMessage,
+        read: Transaction<RamReadData>,
+        written: u16,
+    ) -> VerifiedWrite {
+        VerifiedWrite {
+            write,
+            read,
+            written,
+        }
+    }
+
+    /// Return the message writing the register, to send first.
+    pub fn write_message(&self) -> &HerkulexMessage {
+        &self.write
+    }
+
+    /// Return the transaction reading the register back, to send after the write.
+    pub fn read(&self) -> &Transaction<RamReadData> {
+        &self.read
+    }
+
+    /// Compare the value carried by `packet` with the value written, if `packet` is the answer
+    /// to the read.
+    ///
+    /// # Errors
+    ///
+    /// Return the value read if it is not the value written.
+    pub fn complete(&self, packet: &ACKPacket) -> Option<Result<(), RejectedWrite>> {
+        let data = self.read.complete(packet)?;
+        let read = match data.data_len {
+            1 => u16::from(data.data[0]),
+            _ => data.as_u16()?,
+        };
+        if read == self.written {
+            Some(Ok(()))
+        } else {
+            Some(Err(RejectedWrite {
+                addr: data.addr,
+                written: self.written,
+                read,
+            }))
+        }
+    }
+}
+
+/// The error returned by [`write_verified`](fn.write_verified.html).
+#[cfg(feature = "runtime")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum VerifyError<E> {
+    /// The transport failed.
+    Transport(E),
+    /// The servomotor did not answer the read in time.
+    Timeout,
+    /// The servomotor did not keep the value written.
+    Rejected(RejectedWrite),
+}
+
+/// Send both messages of `write` and busy-wait the answer to the read for `timeout` ticks.
+///
+/// # Errors
+///
+/// Return `Rejected` if the value read back is not the value written.
+#[cfg(feature = "runtime")]
+pub fn write_verified<T, C>(
+    transport: &mut T,
+    clock: &C,
+    write: &VerifiedWrite,
+    timeout: u32,
+) -> Result<(), VerifyError<T::Error>>
+where
+    T: Transport + ?Sized,
+    C: Clock + ?Sized,
+{
+    let _span = span!("write_verified", id = write.write[3]);
+    let mut reader = ACKReader::new();
+    let mut buf = [0; 32];
+    let start = clock.now();
+    transport
+        .write(&write.write)
+        .map_err(VerifyError::Transport)?;
+    transport
+        .write(write.read.message())
+        .map_err(VerifyError::Transport)?;
+    loop {
+        let read = transport.read(&mut buf).map_err(VerifyError::Transport)?;
+        let mut result = None;
+        reader.parse_with(bytes_read(&buf, read), &mut |packet: &_| {
+            if result.is_none() {
+                result = write.complete(packet);
+            }
+        });
+        if let Some(result) = result {
+            return result.map_err(VerifyError::Rejected);
+        } else if clock.now().wrapping_sub(start) > timeout {
+            log_debug!(
+                "no answer from 0x{:02X} to the verified write",
+                write.write[3]
+            );
+            return Err(VerifyError::Timeout);
+        }
+    }
+}
+
+/// The reason why an ACK is not the answer to a request, returned by
+/// [`verify_reply`](fn.verify_reply.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MismatchError {
+    /// The request itself is not a valid frame.
+    InvalidRequest(FrameError),
+    /// The request was broadcast, so no servomotor answers it.
+    Unexpected,
+    /// The ACK was sent by another servomotor.
+    Id {
+        /// The ID of the servomotor the request was sent to
+        expected: u8,
+        /// The ID of the servomotor which sent the ACK
+        received: u8,
+    },
+    /// The ACK answers another command.
+    Command {
+        /// The command of the ACK answering the request (request command + 0x40)
+        expected: u8,
+        /// The command of the ACK
+        received: u8,
+    },
+    /// The ACK carries the data of another address.
+    Address {
+        /// The address read by the request
+        expected: u8,
+        /// The address of the data of the ACK
+        received: u8,
+    },
+    /// The ACK carries another number of bytes.
+    Length {
+        /// The number of bytes read by the request
+        expected: u8,
+        /// The number of bytes of the ACK
+        received: u8,
+    },
+}
+
+/// Check that `reply` is the answer to `request` : it must come from the servomotor the request
+/// was sent to, answer the same command and, for a read, carry the address and the number of
+/// bytes requested. This detects crossed wires and stale ACKs.
+///
+/// # Errors
+///
+/// Return the first mismatch found, in the order of the list above.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::reader::ACKReader;
+/// use drs_0x01::transaction::{verify_reply, MismatchError};
+/// use drs_0x01::Servo;
+///
+/// let mut reader = ACKReader::new();
+/// reader.parse(&[0xFF, 0xFF, 0x09, 0xFD, 0x47, 0xB2, 0x4C, 0x00, 0x01]);
+/// let reply = reader.pop_ack_packet().unwrap();
+/// assert_eq!(verify_reply(&Servo::new(0xFD).stat(), &reply), Ok(()));
+/// assert_eq!(
+///     verify_reply(&Servo::new(0x40).stat(), &reply),
+///     Err(MismatchError::Id { expected: 0x40, received: 0xFD })
+/// );
+/// ```
+pub fn verify_reply(request: &HerkulexMessage, reply: &ACKPacket) -> Result<(), MismatchError> {
+    request.validate().map_err(MismatchError::InvalidRequest)?;
+    let (pid, cmd) = (request[3], request[4]);
+    if pid == BROADCAST_ID {
+        return Err(MismatchError::Unexpected);
+    }
+    if reply.pid != pid {
+        return Err(MismatchError::Id {
+            expected: pid,
+            received: reply.pid,
+        });
+    }
+    let expected = RequestCommand::try_from(cmd)
+        .map_err(MismatchError::InvalidRequest)?
+        .ack();
+    let received = AckCommand::from(reply.cmd);
+    if received != expected {
+        return Err(MismatchError::Command {
+            expected: expected.into(),
+            received: received.into(),
+        });
+    }
+    let read = match reply.cmd {
+        Command::RamRead { data } => Some((u8::from(data.addr), data.data_len)),
+        Command::EEPRead { data } => Some((u8::from(data.addr), data.data_len)),
+        _ => None,
+    };
+    if let Some((addr, len)) = read {
+        let data = &request[MIN_FRAME_LEN..];
+        if addr != data[0] {
+            return Err(MismatchError::Address {
+                expected: data[0],
+                received: addr,
+            });
+        }
+        if len != data[1] {
+            return Err(MismatchError::Length {
+                expected: data[1],
+                received: len,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Decode a single byte read from the RAM.
+pub(crate) fn decode_ram_u8(packet: &ACKPacket) -> Option<u8> {
+    match packet.cmd {
+        Command::RamRead { data } if data.data_len == 1 => Some(data.data[0]),
+        _ => None,
+    }
+}
+
+/// Decode a little endian value of one or two bytes read from the RAM.
+pub(crate) fn decode_ram_u16(packet: &ACKPacket) -> Option<u16> {
+    match packet.cmd {
+        Command::RamRead { data } => match data.data_len {
+            1 => Some(u16::from(data.data[0])),
+            _ => data.as_u16(),
+        },
+        _ => None,
+    }
+}
+
+/// Decode the value of the register `R`, read from the RAM or from the EEP memory.
+pub(crate) fn decode_register<R: Register>(packet: &ACKPacket) -> Option<R::Value> {
+    let (data, len) = match packet.cmd {
+        Command::RamRead { data } => (data.data, data.data_len),
+        Command::EEPRead { data } => (data.data, data.data_len),
+        _ => return None,
+    };
+    R::decode(data.get(..len as usize)?)
+}
+
+#[cfg(test)]
+mod test {
+    use addr::{RamReadData, ReadableRamAddr};
+    use addr::{WritableRamAddr, RAM_MAP_SIZE};
+    use frame::FrameError;
+    use reader::{ACKPacket, Command, StatusDetail, StatusError};
+    use servo::Servo;
+    use test_support::AckFrameBuilder;
+    use transaction::{
+        verify_reply, write_verified, AckKind, Completion, ExpectedAck, MismatchError,
+        RejectedWrite, VerifyError,
+    };
+    use transport::Transport;
+
+    use core::cell::Cell;
+    use std::vec::Vec;
+
+    fn position(pid: u8, addr: ReadableRamAddr) -> ACKPacket {
+        ACKPacket {
+            pid,
+            cmd: Command::RamRead {
+                data: RamReadData {
+                    addr,
+                    data_len: 2,
+                    data: [0x00, 0x02],
+                },
+            },
+            error: StatusError::NoError,
+            detail: StatusDetail::NoDetail,
+            error_raw: 0x00,
+            detail_raw: 0x00,
+        }
+    }
+
+    #[test]
+    fn request_position() {
+        let transaction = Servo::new(0x40).request_position();
+        assert_eq!(
+            transaction.message(),
+            &Servo::new(0x40).ram_request(ReadableRamAddr::CalibratedPosition)
+        );
+        assert_eq!(
+            transaction.expected(),
+            Some(ExpectedAck {
+                id: 0x40,
+                kind: AckKind::RamRead {
+                    addr: ReadableRamAddr::CalibratedPosition,
+                    len: 2,
+                },
+            })
+        );
+        let answer = position(0x40, ReadableRamAddr::CalibratedPosition);
+        assert_eq!(transaction.complete(&answer), Some(512));
+        let other_servo = position(0x41, ReadableRamAddr::CalibratedPosition);
+        assert_eq!(transaction.complete(&other_servo), None);
+        let other_addr = position(0x40, ReadableRamAddr::AbsolutePosition);
+        assert_eq!(transaction.complete(&other_addr), None);
+
+        assert_eq!(transaction.context(), 0);
+        let transaction = transaction.with_context(7);
+        assert_eq!(
+            transaction.complete_with_context(&answer),
+            Some(Completion {
+                value: 512,
+                context: 7,
+            })
+        );
+        assert_eq!(transaction.complete_with_context(&other_servo), None);
+    }
+
+    #[test]
+    fn broadcast_is_not_answered() {
+        let transaction = Servo::new(0xFE).status();
+        assert_eq!(transaction.expected(), None);
+    }
+
+    #[test]
+    fn verify() {
+        let servo = Servo::new(0x40);
+        let request = servo.ram_request(ReadableRamAddr::CalibratedPosition);
+        let answer = position(0x40, ReadableRamAddr::CalibratedPosition);
+        assert_eq!(verify_reply(&request, &answer), Ok(()));
+        assert_eq!(
+            verify_reply(
+                &request,
+                &position(0x41, ReadableRamAddr::CalibratedPosition)
+            ),
+            Err(MismatchError::Id {
+                expected: 0x40,
+                received: 0x41,
+            })
+        );
+        assert_eq!(
+            verify_reply(&request, &position(0x40, ReadableRamAddr::AbsolutePosition)),
+            Err(MismatchError::Address {
+                expected: 58,
+                received: 60,
+            })
+        );
+        let request = servo.ram_request(ReadableRamAddr::Temperature);
+        assert_eq!(
+            verify_reply(&request, &position(0x40, ReadableRamAddr::Temperature)),
+            Err(MismatchError::Length {
+                expected: 1,
+                received: 2,
+            })
+        );
+        assert_eq!(
+            verify_reply(&servo.stat(), &answer),
+            Err(MismatchError::Command {
+                expected: 0x47,
+                received: 0x44,
+            })
+        );
+        assert_eq!(
+            verify_reply(&Servo::new(0xFE).stat(), &answer),
+            Err(MismatchError::Unexpected)
+        );
+        let mut corrupted = servo.stat();
+        corrupted[6] = 0;
+        assert_eq!(
+            verify_reply(&corrupted, &answer),
+            Err(MismatchError::InvalidRequest(FrameError::Checksum2))
+        );
+    }
+
+    /// A servomotor 0x40 ignoring the writes of positions above 1023.
+    struct Picky {
+        ram: [u8; RAM_MAP_SIZE as usize],
+        pending: Vec<u8>,
+    }
+
+    impl Transport for Picky {
+        type Error = ();
+
+        fn write(&mut self, frame: &[u8]) -> Result<(), ()> {
+            let (addr, len) = (usize::from(frame[7]), usize::from(frame[8]));
+            match frame[4] {
+                0x03 if len == 1 || u16::from(frame[10]) << 8 | u16::from(frame[9]) < 1024 => {
+                    self.ram[addr..addr + len].copy_from_slice(&frame[9..9 + len])
+                }
+                0x04 => {
+                    let data = &self.ram[addr..addr + len];
+                    self.pending = AckFrameBuilder::ram_read(0x40, frame[7], data)
+                        .build()
+                        .to_vec();
+                }
+                _ => (),
+            }
+            Ok(())
+        }
+
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+            let len = self.pending.len();
+            buf[..len].copy_from_slice(&self.pending);
+            self.pending.clear();
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn verified_writes() {
+        let tick = Cell::new(0u32);
+        let clock = || {
+            tick.set(tick.get() + 1);
+            tick.get()
+        };
+        let mut servo = Picky {
+            ram: [0; RAM_MAP_SIZE as usize],
+            pending: Vec::new(),
+        };
+        let servo_40 = Servo::new(0x40);
+        let write = servo_40
+            .ram_write_verified(WritableRamAddr::MaxPosition(0xFF, 0x02))
+            .unwrap();
+        assert_eq!(
+            write.write_message(),
+            &servo_40
+                .ram_write(WritableRamAddr::MaxPosition(0xFF, 0x02))
+                .unwrap()
+        );
+        assert_eq!(write_verified(&mut servo, &clock, &write, 10), Ok(()));
+
+        let write = servo_40
+            .ram_write_verified(WritableRamAddr::MaxPosition(0x00, 0x04))
+            .unwrap();
+        assert_eq!(
+            write_verified(&mut servo, &clock, &write, 10),
+            Err(VerifyError::Rejected(RejectedWrite {
+                addr: ReadableRamAddr::MaxPosition,
+                written: 1024,
+                read: 767,
+            }))
+        );
+
+        let write = Servo::new(0x41)
+            .ram_write_verified(WritableRamAddr::DeadZone(2))
+            .unwrap();
+        assert_eq!(
+            write_verified(&mut servo, &clock, &write, 10),
+            Err(VerifyError::Timeout)
+        );
+        assert!(Servo::new(0xFE)
+            .ram_write_verified(WritableRamAddr::DeadZone(2))
+            .is_none());
+    }
+}
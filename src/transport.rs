@@ -0,0 +1,297 @@
+//! The link used to exchange frames with the servomotors, usually a half-duplex UART.
+//!
+//! This crate does not drive any hardware : implement [`Transport`](trait.Transport.html) on top
+//! of the serial port of your platform to use the helpers which need to talk to the servomotors.
+
+#[cfg(any(test, feature = "test_support"))]
+use arrayvec::ArrayVec;
+
+/// A byte link with the servomotors.
+pub trait Transport {
+    /// The error returned by the underlying link
+    type Error;
+
+    /// Send every byte of `frame`.
+    fn write(&mut self, frame: &[u8]) -> Result<(), Self::Error>;
+
+    /// Read the bytes already received into `buf`, and return how many bytes were read, at most
+    /// `buf.len()`. This function must not wait for more bytes to arrive.
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+/// Return the `read` bytes received into `buf`, the whole buffer if a transport returned more
+/// than its length.
+#[cfg(feature = "runtime")]
+pub(crate) fn bytes_read(buf: &[u8], read: usize) -> &[u8] {
+    buf.get(..read).unwrap_or(buf)
+}
+
+impl<T: Transport + ?Sized> Transport for &mut T {
+    type Error = T::Error;
+
+    fn write(&mut self, frame: &[u8]) -> Result<(), Self::Error> {
+        (**self).write(frame)
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        (**self).read(buf)
+    }
+}
+
+/// The faults injected by a [`FaultyTransport`](struct.FaultyTransport.html) so far.
+#[cfg(any(test, feature = "test_support"))]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FaultStats {
+    /// The number of bytes dropped
+    pub dropped: u32,
+    /// The number of bytes sent or received twice
+    pub duplicated: u32,
+    /// The number of bytes with a flipped bit
+    pub corrupted: u32,
+    /// The number of reads which returned no byte while bytes were pending
+    pub delayed: u32,
+}
+
+/// A wrapper around a transport which drops, duplicates, corrupts or delays bytes, to check how
+/// an application recovers from a noisy bus.
+///
+/// The faults are drawn from a pseudo-random generator seeded by the application, so a test
+/// injects the same faults every time it runs. Every rate is a percentage, 0 by default : bytes
+/// are dropped, duplicated or corrupted in both directions, and a read returns no byte at the
+/// delay rate, leaving the bytes received for a later read.
+///
+/// This struct is available with the `test_support` feature.
+///
+/// # Examples
+///
+/// ```
+/// # extern crate drs_0x01;
+/// use drs_0x01::transport::{FaultyTransport, Transport};
+/// # struct Uart;
+/// # impl Transport for Uart {
+/// #     type Error = ();
+/// #     fn write(&mut self, _frame: &[u8]) -> Result<(), ()> { Ok(()) }
+/// #     fn read(&mut self, _buf: &mut [u8]) -> Result<usize, ()> { Ok(0) }
+/// # }
+///
+/// let mut uart = FaultyTransport::new(Uart, 42)
+///     .with_drop_rate(1)
+///     .with_corrupt_rate(2)
+///     .with_delay_rate(20);
+/// // Run the application on top of uart, then check the faults it went through ...
+/// let faults = uart.faults();
+/// ```
+#[cfg(any(test, feature = "test_support"))]
+#[derive(Clone, Debug)]
+pub struct FaultyTransport<T> {
+    inner: T,
+    state: u32,
+    drop_rate: u8,
+    duplicate_rate: u8,
+    corrupt_rate: u8,
+    delay_rate: u8,
+    pending: ArrayVec<[u8; 256]>,
+    stats: FaultStats,
+}
+
+#[cfg(any(test, feature = "test_support"))]
+impl<T: Transport> FaultyTransport<T> {
+    /// Wrap `inner`, drawing the faults from `seed`. No fault is injected until a rate is set.
+    pub fn new(inner: T, seed: u32) -> FaultyTransport<T> {
+        FaultyTransport {
+            inner,
+            // The generator never leaves 0.
+            state: if seed == 0 { 0x9E37_79B9 } else { seed },
+            drop_rate: 0,
+            duplicate_rate: 0,
+            corrupt_rate: 0,
+            delay_rate: 0,
+            pending: ArrayVec::new(),
+            stats: FaultStats::default(),
+        }
+    }
+
+    /// Drop `percent` of the bytes.
+    pub fn with_drop_rate(mut self, percent: u8) -> FaultyTransport<T> {
+        self.drop_rate = percent;
+        self
+    }
+
+    /// Duplicate `percent` of the bytes.
+    pub fn with_duplicate_rate(mut self, percent: u8) -> FaultyTransport<T> {
+        self.duplicate_rate = percent;
+        self
+    }
+
+    /// Flip a bit of `percent` of the bytes.
+    pub fn with_corrupt_rate(mut self, percent: u8) -> FaultyTransport<T> {
+        self.corrupt_rate = percent;
+        self
+    }
+
+    /// Return no byte for `percent` of the reads.
+    pub fn with_delay_rate(mut self, percent: u8) -> FaultyTransport<T> {
+        self.delay_rate = percent;
+        self
+    }
+
+    /// Return the faults injected so far.
+    pub fn faults(&self) -> FaultStats {
+        self.stats
+    }
+
+    /// Return the wrapped transport.
+    pub fn inner(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Return the wrapped transport, dropping the bytes received and not read yet.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    // xorshift32
+    fn next(&mut self) -> u32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state
+    }
+
+    fn chance(&mut self, percent: u8) -> bool {
+        percent > 0 && self.next() % 100 < u32::from(percent)
+    }
+
+    // Push `byte` through the faults into `out`, return false if `out` is full.
+    fn inject(&mut self, byte: u8, out: &mut ArrayVec<[u8; 256]>) -> bool {
+        if self.chance(self.drop_rate) {
+            self.stats.dropped += 1;
+            return true;
+        }
+        let byte = if self.chance(self.corrupt_rate) {
+            self.stats.corrupted += 1;
+            byte ^ 1 << (self.next() % 8)
+        } else {
+            byte
+        };
+        if self.chance(self.duplicate_rate) {
+            self.stats.duplicated += 1;
+            if out.try_push(byte).is_err() {
+                return false;
+            }
+        }
+        out.try_push(byte).is_ok()
+    }
+}
+
+#[cfg(any(test, feature = "test_support"))]
+impl<T: Transport> Transport for FaultyTransport<T> {
+    type Error = T::Error;
+
+    fn write(&mut self, frame: &[u8]) -> Result<(), Self::Error> {
+        let mut faulty = ArrayVec::new();
+        for &byte in frame {
+            if !self.inject(byte, &mut faulty) {
+                self.inner.write(&faulty)?;
+                faulty.clear();
+                self.inject(byte, &mut faulty);
+            }
+        }
+        self.inner.write(&faulty)
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let mut received = [0; 64];
+        let mut pending = ArrayVec::new();
+        core::mem::swap(&mut pending, &mut self.pending);
+        while pending.len() + 2 * received.len() <= pending.capacity() {
+            let read = self.inner.read(&mut received)?;
+            for &byte in bytes_read(&received, read) {
+                self.inject(byte, &mut pending);
+            }
+            if read < received.len() {
+                break;
+            }
+        }
+        self.pending = pending;
+        if self.pending.is_empty() {
+            return Ok(0);
+        } else if self.chance(self.delay_rate) {
+            self.stats.delayed += 1;
+            return Ok(0);
+        }
+        let len = buf.len().min(self.pending.len());
+        for (dst, src) in buf.iter_mut().zip(self.pending.drain(..len)) {
+            *dst = src;
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::vec::Vec;
+    use transport::{FaultStats, FaultyTransport, Transport};
+
+    /// A link echoing the bytes written.
+    #[derive(Default)]
+    struct Loopback(Vec<u8>);
+
+    impl Transport for Loopback {
+        type Error = ();
+
+        fn write(&mut self, frame: &[u8]) -> Result<(), ()> {
+            self.0.extend_from_slice(frame);
+            Ok(())
+        }
+
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, ()> {
+            let len = buf.len().min(self.0.len());
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0.drain(..len);
+            Ok(len)
+        }
+    }
+
+    fn exchange(link: &mut FaultyTransport<Loopback>, frame: &[u8]) -> Vec<u8> {
+        link.write(frame).unwrap();
+        let mut received = Vec::new();
+        let mut buf = [0; 16];
+        for _ in 0..100 {
+            let read = link.read(&mut buf).unwrap();
+            received.extend_from_slice(&buf[..read]);
+        }
+        received
+    }
+
+    #[test]
+    fn faults() {
+        let frame: Vec<u8> = (0..100).collect();
+        let mut clean = FaultyTransport::new(Loopback::default(), 1);
+        assert_eq!(exchange(&mut clean, &frame), frame);
+        assert_eq!(clean.faults(), FaultStats::default());
+
+        let faulty = || {
+            FaultyTransport::new(Loopback::default(), 7)
+                .with_drop_rate(5)
+                .with_duplicate_rate(5)
+                .with_corrupt_rate(5)
+                .with_delay_rate(50)
+        };
+        let (mut first, mut second) = (faulty(), faulty());
+        let received = exchange(&mut first, &frame);
+        assert_eq!(received, exchange(&mut second, &frame));
+        assert_ne!(received, frame);
+        let faults = first.faults();
+        assert!(faults.dropped > 0 && faults.duplicated > 0 && faults.corrupted > 0);
+        assert!(faults.delayed > 0);
+        assert_eq!(
+            received.len() as u32,
+            100 - faults.dropped + faults.duplicated
+        );
+
+        let mut mute = FaultyTransport::new(Loopback::default(), 7).with_drop_rate(100);
+        assert_eq!(exchange(&mut mute, &frame), []);
+        assert_eq!(mute.faults().dropped, 100);
+    }
+}
@@ -0,0 +1,184 @@
+//! Conversions between the raw values of the registers and [`uom`](https://docs.rs/uom)
+//! quantities, enabled by the `uom` feature.
+//!
+//! The angles are measured from the raw position 0, at 0.325° per raw value, and the velocities
+//! are derived from the `DifferentialPosition` register, which counts the raw positions travelled
+//! in 11.2 ms. The voltages are encoded at 0.074 V per raw value, and the durations of the
+//! `playtime` and period registers in units of 11.2 ms.
+//!
+//! The temperature registers are encoded through the non linear table of the datasheet, which is
+//! not part of this crate : they are kept raw.
+//!
+//! # Examples
+//!
+//! ```
+//! # extern crate drs_0x01;
+//! # extern crate uom;
+//! use drs_0x01::units;
+//! use uom::si::angle::degree;
+//! use uom::si::electric_potential::volt;
+//! use uom::si::f32::{Angle, ElectricPotential};
+//!
+//! assert_eq!(units::position(Angle::new::<degree>(166.4)), 512);
+//! assert_eq!(units::voltage_raw(ElectricPotential::new::<volt>(7.4)), 100);
+//! ```
+
+use uom::si::angle::degree;
+use uom::si::angular_velocity::degree_per_second;
+use uom::si::electric_potential::volt;
+use uom::si::f32::{Angle, AngularVelocity, ElectricPotential, Time};
+use uom::si::time::millisecond;
+
+use addr::WritableRamAddr;
+use builder::HerkulexMessage;
+use config::InpositionMargin;
+use model::{DEGREES_PER_POSITION, POSITION_RANGE};
+#[cfg(feature = "runtime")]
+use motion::{MultiTurnTracker, VelocityEstimator};
+use servo::Servo;
+
+/// The voltage of a raw value of the `Voltage`, `MinVoltage` and `MaxVoltage` registers.
+pub const VOLTS_PER_UNIT: f32 = 0.074;
+
+/// The duration of a raw value of the `playtime` and period registers, in milliseconds.
+const MILLISECONDS_PER_TICK: f32 = 11.2;
+
+// Round `value` to the closest integer between 0 and `max`.
+fn round(value: f32, max: u16) -> u16 {
+    (value + 0.5).clamp(0.0, f32::from(max)) as u16
+}
+
+/// Return the angle of the raw `position`.
+pub fn angle(position: u16) -> Angle {
+    Angle::new::<degree>(f32::from(position) * DEGREES_PER_POSITION)
+}
+
+/// Return the raw position closest to `angle`, clamped to the range of the position sensor.
+pub fn position(angle: Angle) -> u16 {
+    round(
+        angle.get::<degree>() / DEGREES_PER_POSITION,
+        POSITION_RANGE - 1,
+    )
+}
+
+/// Return the velocity read from the `DifferentialPosition` register.
+pub fn differential_velocity(raw: i16) -> AngularVelocity {
+    AngularVelocity::new::<degree_per_second>(
+        f32::from(raw) * DEGREES_PER_POSITION * 1000.0 / MILLISECONDS_PER_TICK,
+    )
+}
+
+/// Return the voltage read from the `Voltage`, `MinVoltage` or `MaxVoltage` register.
+pub fn voltage(raw: u8) -> ElectricPotential {
+    ElectricPotential::new::<volt>(f32::from(raw) * VOLTS_PER_UNIT)
+}
+
+/// Return the raw value of `voltage` for the `MinVoltage` and `MaxVoltage` registers, rounded and
+/// clamped between 0 and 255.
+pub fn voltage_raw(voltage: ElectricPotential) -> u8 {
+    round(voltage.get::<volt>() / VOLTS_PER_UNIT, 0xFF) as u8
+}
+
+/// Return the duration of a raw `playtime` or period.
+pub fn duration(ticks: u8) -> Time {
+    Time::new::<millisecond>(f32::from(ticks) * MILLISECONDS_PER_TICK)
+}
+
+/// Return the raw `playtime` or period closest to `duration`, clamped between 0 and 255.
+pub fn ticks(duration: Time) -> u8 {
+    round(duration.get::<millisecond>() / MILLISECONDS_PER_TICK, 0xFF) as u8
+}
+
+impl Servo {
+    /// Move the servo to the position closest to `angle`, see
+    /// [`set_position`](#method.set_position).
+    pub fn set_angle(self, angle: Angle) -> HerkulexMessage {
+        self.set_position(position(angle))
+    }
+
+    /// Set the range of input voltage out of which the servo reports an error.
+    pub fn set_voltage_limits(
+        self,
+        min: ElectricPotential,
+        max: ElectricPotential,
+    ) -> [HerkulexMessage; 2] {
+        [
+            self.ram_write_unchecked(WritableRamAddr::MinVoltage(voltage_raw(min))),
+            self.ram_write_unchecked(WritableRamAddr::MaxVoltage(voltage_raw(max))),
+        ]
+    }
+}
+
+impl InpositionMargin {
+    /// Create a margin of `angle`, see [`from_degrees`](#method.from_degrees).
+    pub fn from_angle(angle: Angle) -> InpositionMargin {
+        InpositionMargin::from_degrees(angle.get::<degree>())
+    }
+
+    /// Return the margin as an angle.
+    pub fn angle(self) -> Angle {
+        Angle::new::<degree>(self.degrees())
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl MultiTurnTracker {
+    /// Return the angle travelled by the tracked axis since the first sample.
+    pub fn angle(&self) -> Angle {
+        Angle::new::<degree>(self.angle_deg())
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl VelocityEstimator {
+    /// Return the last estimated velocity.
+    pub fn angular_velocity(&self) -> AngularVelocity {
+        AngularVelocity::new::<degree_per_second>(self.velocity())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uom::si::angle::degree;
+    use uom::si::angular_velocity::degree_per_second;
+    use uom::si::electric_potential::volt;
+    use uom::si::f32::{Angle, ElectricPotential, Time};
+    use uom::si::time::millisecond;
+
+    use addr::WritableRamAddr;
+    use servo::Servo;
+    use units;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(units::position(units::angle(1023)), 1023);
+        assert_eq!(units::position(Angle::new::<degree>(-10.0)), 0);
+        assert_eq!(units::position(Angle::new::<degree>(400.0)), 1023);
+        assert_eq!(units::voltage_raw(units::voltage(0x5B)), 0x5B);
+        assert_eq!(
+            units::voltage_raw(ElectricPotential::new::<volt>(100.0)),
+            0xFF
+        );
+        assert_eq!(units::ticks(Time::new::<millisecond>(504.0)), 0x2D);
+        assert_eq!(units::ticks(units::duration(60)), 60);
+        assert!((units::angle(512).get::<degree>() - 166.4).abs() < 1e-3);
+        let velocity = units::differential_velocity(-10).get::<degree_per_second>();
+        assert!((velocity + 290.178).abs() < 1e-3);
+
+        let servo = Servo::new(0x40);
+        assert_eq!(
+            servo.set_angle(Angle::new::<degree>(166.4)),
+            servo.set_position(512)
+        );
+        assert_eq!(
+            servo.set_voltage_limits(
+                ElectricPotential::new::<volt>(6.734),
+                ElectricPotential::new::<volt>(10.138)
+            ),
+            [
+                servo.ram_write(WritableRamAddr::MinVoltage(0x5B)).unwrap(),
+                servo.ram_write(WritableRamAddr::MaxVoltage(0x89)).unwrap()
+            ]
+        );
+    }
+}